@@ -0,0 +1,248 @@
+use crate::plugin::{Call, Error, Plugin, Return, Row, Signature, Values};
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::os::raw::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// Plugins can also be built as dynamic libraries that nu loads into its own process, which
+// saves starting a process and serializing every row. `export_plugin!` exports C functions that
+// pass nu's own values by pointer, each behind a `*mut c_void`:
+//
+//   nu_plugin_abi_version() -> u32
+//   nu_plugin_values_id() -> u64
+//   nu_plugin_new() -> *mut c_void
+//   nu_plugin_config(plugin, out: *mut Option<Result<Signature, Error>>)
+//   nu_plugin_begin_filter(plugin, call: *mut Option<Call>, out: *mut Vec<Return>)
+//   nu_plugin_filter(plugin, row: *mut Option<Row>, out: *mut Vec<Return>)
+//   nu_plugin_end_filter(plugin, out: *mut Vec<Return>)
+//   nu_plugin_sink(plugin, input: *mut Option<(Call, Vec<Row>)>, out: *mut Vec<Return>)
+//   nu_plugin_drop(plugin)
+//
+// The plugin takes its input out of the `Option` and pushes what it gives back, including
+// anything it emitted along the way, onto `out`. Both sides have to agree on how those values
+// are laid out, which only holds for a plugin built against the same nu by the same compiler.
+// `nu_plugin_values_id` is a hash of the `TypeId` of the plugin's `Values`, which Cargo makes
+// different for any other version of nu or of the compiler, so nu checks it against its own
+// before calling anything else. The version of the functions themselves is
+// `PLUGIN_ABI_VERSION`.
+//
+// Neither side may unwind into the other, so a panic in the plugin becomes an error, and
+// `nu_plugin_new` gives back null if creating the plugin panicked.
+
+thread_local! {
+    // While a call from nu is running in-process, the rows the plugin emits, which are otherwise
+    // sent to nu as messages
+    static CAPTURED: RefCell<Option<Vec<Box<dyn Any>>>> = RefCell::new(None);
+}
+
+/// Hold on to an emitted row when running in-process, or give it back to be sent
+pub(crate) fn capture<T: 'static>(value: T) -> Option<T> {
+    CAPTURED.with(|captured| match &mut *captured.borrow_mut() {
+        Some(values) => {
+            values.push(Box::new(value));
+            None
+        }
+        None => Some(value),
+    })
+}
+
+/// What `nu_plugin_values_id` gives back for a plugin using `V`
+pub fn values_id<V: Values>() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    TypeId::of::<V>().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Export a plugin's C functions from a `cdylib` crate, given an expression that creates it. The
+/// plugin has to be `Send`, as nu may move it between threads between calls.
+#[macro_export]
+macro_rules! export_plugin {
+    ($plugin:expr) => {
+        fn nu_plugin_create() -> impl $crate::Plugin + Send + 'static {
+            $plugin
+        }
+
+        #[no_mangle]
+        pub extern "C" fn nu_plugin_abi_version() -> u32 {
+            $crate::PLUGIN_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub extern "C" fn nu_plugin_values_id() -> u64 {
+            $crate::dylib_values_id(nu_plugin_create)
+        }
+
+        #[no_mangle]
+        pub extern "C" fn nu_plugin_new() -> *mut std::os::raw::c_void {
+            $crate::dylib_new(nu_plugin_create)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn nu_plugin_config(
+            plugin: *mut std::os::raw::c_void,
+            out: *mut std::os::raw::c_void,
+        ) {
+            $crate::dylib_config(nu_plugin_create, plugin, out)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn nu_plugin_begin_filter(
+            plugin: *mut std::os::raw::c_void,
+            call: *mut std::os::raw::c_void,
+            out: *mut std::os::raw::c_void,
+        ) {
+            $crate::dylib_begin_filter(nu_plugin_create, plugin, call, out)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn nu_plugin_filter(
+            plugin: *mut std::os::raw::c_void,
+            row: *mut std::os::raw::c_void,
+            out: *mut std::os::raw::c_void,
+        ) {
+            $crate::dylib_filter(nu_plugin_create, plugin, row, out)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn nu_plugin_end_filter(
+            plugin: *mut std::os::raw::c_void,
+            out: *mut std::os::raw::c_void,
+        ) {
+            $crate::dylib_end_filter(nu_plugin_create, plugin, out)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn nu_plugin_sink(
+            plugin: *mut std::os::raw::c_void,
+            input: *mut std::os::raw::c_void,
+            out: *mut std::os::raw::c_void,
+        ) {
+            $crate::dylib_sink(nu_plugin_create, plugin, input, out)
+        }
+
+        #[no_mangle]
+        pub unsafe extern "C" fn nu_plugin_drop(plugin: *mut std::os::raw::c_void) {
+            $crate::dylib_drop(nu_plugin_create, plugin)
+        }
+    };
+}
+
+// The functions below take the function that creates the plugin only for its type
+
+#[doc(hidden)]
+pub fn dylib_values_id<P: Plugin>(_: fn() -> P) -> u64 {
+    values_id::<P::Values>()
+}
+
+#[doc(hidden)]
+pub fn dylib_new<P: Plugin>(create: fn() -> P) -> *mut c_void {
+    match catch_unwind(create) {
+        Ok(plugin) => Box::into_raw(Box::new(plugin)) as *mut c_void,
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn dylib_config<P: Plugin>(_: fn() -> P, plugin: *mut c_void, out: *mut c_void) {
+    let plugin = &mut *(plugin as *mut P);
+    let out = &mut *(out as *mut Option<Result<Signature<P>, Error<P>>>);
+
+    *out = Some(
+        catch_unwind(AssertUnwindSafe(|| plugin.config())).unwrap_or_else(|_| {
+            Err(<P::Values as Values>::error(
+                "Plugin panicked while giving its signature".to_string(),
+            ))
+        }),
+    );
+}
+
+#[doc(hidden)]
+pub unsafe fn dylib_begin_filter<P: Plugin>(
+    _: fn() -> P,
+    plugin: *mut c_void,
+    call: *mut c_void,
+    out: *mut c_void,
+) {
+    if let Some(call) = take::<Call<P>>(call) {
+        answer::<P>(plugin, out, "begin_filter", |plugin| {
+            plugin.begin_filter(call)
+        })
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn dylib_filter<P: Plugin>(
+    _: fn() -> P,
+    plugin: *mut c_void,
+    row: *mut c_void,
+    out: *mut c_void,
+) {
+    if let Some(row) = take::<Row<P>>(row) {
+        answer::<P>(plugin, out, "filter", |plugin| plugin.filter(row))
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn dylib_end_filter<P: Plugin>(_: fn() -> P, plugin: *mut c_void, out: *mut c_void) {
+    answer::<P>(plugin, out, "end_filter", |plugin| plugin.end_filter())
+}
+
+#[doc(hidden)]
+pub unsafe fn dylib_sink<P: Plugin>(
+    _: fn() -> P,
+    plugin: *mut c_void,
+    input: *mut c_void,
+    out: *mut c_void,
+) {
+    if let Some((call, rows)) = take::<(Call<P>, Vec<Row<P>>)>(input) {
+        answer::<P>(plugin, out, "sink", |plugin| {
+            plugin.sink(call, rows);
+            Ok(vec![])
+        })
+    }
+}
+
+#[doc(hidden)]
+pub unsafe fn dylib_drop<P: Plugin>(_: fn() -> P, plugin: *mut c_void) {
+    if !plugin.is_null() {
+        drop(Box::from_raw(plugin as *mut P));
+    }
+}
+
+unsafe fn take<T>(input: *mut c_void) -> Option<T> {
+    (*(input as *mut Option<T>)).take()
+}
+
+/// Call one of the plugin's methods, pushing what it emitted and then what it gave back onto
+/// `out`
+unsafe fn answer<P: Plugin>(
+    plugin: *mut c_void,
+    out: *mut c_void,
+    method: &str,
+    call: impl FnOnce(&mut P) -> Result<Vec<Return<P>>, Error<P>>,
+) {
+    let plugin = &mut *(plugin as *mut P);
+    let out = &mut *(out as *mut Vec<Return<P>>);
+
+    CAPTURED.with(|captured| *captured.borrow_mut() = Some(vec![]));
+    let result = catch_unwind(AssertUnwindSafe(|| call(plugin)));
+    let emitted = CAPTURED
+        .with(|captured| captured.borrow_mut().take())
+        .unwrap_or_default();
+
+    for value in emitted {
+        if let Ok(value) = value.downcast::<Return<P>>() {
+            out.push(*value);
+        }
+    }
+
+    match result {
+        Ok(Ok(values)) => out.extend(values),
+        Ok(Err(err)) => out.push(<P::Values as Values>::failed(err)),
+        Err(_) => out.push(<P::Values as Values>::failed(<P::Values as Values>::error(
+            format!("Plugin panicked while handling {}", method),
+        ))),
+    }
+}
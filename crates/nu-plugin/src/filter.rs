@@ -0,0 +1,158 @@
+use crate::plugin::{serve_plugin, Plugin, Values};
+
+// Most plugins don't need all of `Plugin`. A filter turns each row of its input into zero or
+// more rows, and a sink takes all the rows at the end of a pipeline. Implementing `Filter` or
+// `Sink` and handing it to `serve_filter` or `serve_sink` from `main` takes care of the rest:
+//
+//   struct Len;
+//
+//   impl Filter for Len {
+//       type Values = NuValues;
+//
+//       fn signature(&self) -> Signature {
+//           Signature::build("len").desc("The length of each string")
+//       }
+//
+//       fn filter(&mut self, _: &CallInfo, input: Tagged<Value>) -> Result<Vec<Value>, ShellError> {
+//           Ok(vec![Value::int(input.as_string()?.chars().count() as i64)])
+//       }
+//   }
+//
+//   fn main() {
+//       serve_filter(Len);
+//   }
+//
+// In a `cdylib` crate, `export_filter!(Len)` or `export_sink!` goes in place of `main`.
+
+/// A plugin that turns each row of its input into zero or more rows
+pub trait Filter {
+    type Values: Values;
+
+    /// The name, flags and arguments of the command this plugin adds
+    fn signature(&self) -> <Self::Values as Values>::Signature;
+
+    /// Called for each row, with the arguments the command was called with. The rows given back
+    /// take the tag of the row they came from.
+    fn filter(
+        &mut self,
+        call: &<Self::Values as Values>::Call,
+        input: <Self::Values as Values>::Row,
+    ) -> Result<Vec<<Self::Values as Values>::Value>, <Self::Values as Values>::Error>;
+}
+
+/// A plugin at the end of a pipeline, which gets all of its rows at once
+pub trait Sink {
+    type Values: Values;
+
+    /// The name, flags and arguments of the command this plugin adds
+    fn signature(&self) -> <Self::Values as Values>::Signature;
+
+    fn sink(
+        &mut self,
+        call: <Self::Values as Values>::Call,
+        input: Vec<<Self::Values as Values>::Row>,
+    );
+}
+
+/// Answer nu's messages for a filter until nu is done with it
+pub fn serve_filter(filter: impl Filter + 'static) {
+    serve_plugin(&mut filter_plugin(filter));
+}
+
+/// Answer nu's messages for a sink until nu is done with it
+pub fn serve_sink(sink: impl Sink + 'static) {
+    serve_plugin(&mut sink_plugin(sink));
+}
+
+/// Export a filter from a `cdylib` crate, for nu to load into its own process instead of
+/// running it
+#[macro_export]
+macro_rules! export_filter {
+    ($filter:expr) => {
+        $crate::export_plugin!($crate::filter_plugin($filter));
+    };
+}
+
+/// Export a sink from a `cdylib` crate, for nu to load into its own process instead of running
+/// it
+#[macro_export]
+macro_rules! export_sink {
+    ($sink:expr) => {
+        $crate::export_plugin!($crate::sink_plugin($sink));
+    };
+}
+
+#[doc(hidden)]
+pub fn filter_plugin<F: Filter + 'static>(filter: F) -> impl Plugin<Values = F::Values> + 'static {
+    FilterPlugin { filter, call: None }
+}
+
+#[doc(hidden)]
+pub fn sink_plugin<S: Sink + 'static>(sink: S) -> impl Plugin<Values = S::Values> + 'static {
+    SinkPlugin { sink }
+}
+
+struct FilterPlugin<F: Filter> {
+    filter: F,
+    call: Option<<F::Values as Values>::Call>,
+}
+
+impl<F: Filter> Plugin for FilterPlugin<F> {
+    type Values = F::Values;
+
+    fn config(&mut self) -> Result<<F::Values as Values>::Signature, <F::Values as Values>::Error> {
+        Ok(<F::Values as Values>::filter_signature(
+            self.filter.signature(),
+        ))
+    }
+
+    fn begin_filter(
+        &mut self,
+        call_info: <F::Values as Values>::Call,
+    ) -> Result<Vec<<F::Values as Values>::Return>, <F::Values as Values>::Error> {
+        self.call = Some(call_info);
+        Ok(vec![])
+    }
+
+    fn filter(
+        &mut self,
+        input: <F::Values as Values>::Row,
+    ) -> Result<Vec<<F::Values as Values>::Return>, <F::Values as Values>::Error> {
+        let call = match &self.call {
+            Some(call) => call,
+            None => {
+                return Err(<F::Values as Values>::error(
+                    "filter called before begin_filter".to_string(),
+                ))
+            }
+        };
+
+        let tag = <F::Values as Values>::tag(&input);
+        let values = self.filter.filter(call, input)?;
+
+        Ok(values
+            .into_iter()
+            .map(|value| <F::Values as Values>::tagged(value, tag.clone()))
+            .collect())
+    }
+}
+
+struct SinkPlugin<S> {
+    sink: S,
+}
+
+impl<S: Sink> Plugin for SinkPlugin<S> {
+    type Values = S::Values;
+
+    fn config(&mut self) -> Result<<S::Values as Values>::Signature, <S::Values as Values>::Error> {
+        Ok(<S::Values as Values>::sink_signature(self.sink.signature()))
+    }
+
+    fn sink(
+        &mut self,
+        call_info: <S::Values as Values>::Call,
+        input: Vec<<S::Values as Values>::Row>,
+    ) {
+        self.sink.sink(call_info, input)
+    }
+}
@@ -0,0 +1,116 @@
+//! The messages exchanged between [nu](https://github.com/nushell/nushell) and its plugins, and
+//! the plugin side of the conversation.
+//!
+//! A plugin is an executable named `nu_plugin_<name>` somewhere nu looks for plugins, or a
+//! dynamic library of the same name. Each message is one line of JSON in the JSON-RPC 2.0 shape,
+//! described in docs/plugin-protocol.md. nu builds on these types for its side of the
+//! conversation, and a plugin implements `Plugin`, `Filter` or `Sink` and hands itself to
+//! `serve_plugin`, `serve_filter` or `serve_sink` for its side.
+//!
+//! The messages carry nu's own values, so they're generic over them here: `Call` is the call
+//! info a filter or sink is started with, `Row` a row of its input, and `Response` what a
+//! plugin answers a request with. The traits take them from `Values`, which nu implements as
+//! `nu::NuValues`.
+
+mod dylib;
+mod filter;
+mod plugin;
+
+pub use crate::dylib::values_id;
+#[doc(hidden)]
+pub use crate::dylib::{
+    dylib_begin_filter, dylib_config, dylib_drop, dylib_end_filter, dylib_filter, dylib_new,
+    dylib_sink, dylib_values_id,
+};
+#[doc(hidden)]
+pub use crate::filter::{filter_plugin, sink_plugin};
+pub use crate::filter::{serve_filter, serve_sink, Filter, Sink};
+pub use crate::plugin::{emit_value, serve_plugin, Plugin, Values};
+
+use serde::{Deserialize, Serialize};
+
+/// The version of the messages exchanged between nu and its plugins. It goes up whenever a change
+/// would confuse the other side.
+///
+/// Version 2 adds the `Duration` primitive, a number of nanoseconds, which a version 1 plugin
+/// can't read.
+pub const PROTOCOL_VERSION: u32 = 2;
+
+/// The version of the C functions a plugin built as a dynamic library exports. It goes up if
+/// their signatures or meaning change.
+///
+/// Version 2 passes nu's values by pointer in place of the JSON messages version 1 passed.
+pub const PLUGIN_ABI_VERSION: u32 = 2;
+
+/// Sent by nu as `hello` before asking a plugin for its signature, and sent back by the plugin
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Handshake {
+    pub protocol_version: u32,
+}
+
+/// A message, in the JSON-RPC 2.0 shape
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonRpc<T> {
+    jsonrpc: String,
+    pub method: String,
+    pub params: T,
+}
+
+impl<T> JsonRpc<T> {
+    pub fn new<U: Into<String>>(method: U, params: T) -> Self {
+        JsonRpc {
+            jsonrpc: "2.0".into(),
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// A message from nu to a plugin
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method")]
+#[allow(non_camel_case_types)]
+pub enum NuCommand<Call, Row> {
+    hello { params: Handshake },
+    config,
+    begin_filter { params: Call },
+    filter { params: Row },
+    end_filter,
+    sink { params: (Call, Vec<Row>) },
+    quit,
+}
+
+/// A message from a plugin to nu: the answer to a request, or a row sent ahead of it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "method")]
+#[allow(non_camel_case_types)]
+pub enum NuResult<Response, Row> {
+    response { params: Response },
+    value { params: Row },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_json_rpc() {
+        let hello = serde_json::to_string(&JsonRpc::new(
+            "hello",
+            Handshake {
+                protocol_version: PROTOCOL_VERSION,
+            },
+        ))
+        .unwrap();
+
+        assert_eq!(
+            hello,
+            r#"{"jsonrpc":"2.0","method":"hello","params":{"protocol_version":2}}"#
+        );
+
+        match serde_json::from_str::<NuCommand<(), i64>>(&hello).unwrap() {
+            NuCommand::hello { params } => assert_eq!(params.protocol_version, 2),
+            other => panic!("expected hello, got {:?}", other),
+        }
+    }
+}
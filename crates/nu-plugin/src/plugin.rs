@@ -0,0 +1,176 @@
+use crate::{Handshake, JsonRpc, NuCommand, PROTOCOL_VERSION};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::io;
+
+/// The types the messages carry, which are nu's own. nu implements this as `nu::NuValues`, for
+/// plugins to name as the `Values` of their `Plugin`, `Filter` or `Sink`.
+pub trait Values: 'static {
+    /// What a filter or sink is called with: its arguments and flags
+    type Call: Serialize + DeserializeOwned;
+    /// A row of a plugin's input
+    type Row: Serialize + DeserializeOwned;
+    /// What a plugin gives back: a row, an action for nu to take, or an error
+    type Return: Serialize + 'static;
+    /// The name, flags and arguments of the command a plugin adds
+    type Signature: Serialize;
+    type Error: Serialize;
+    /// A value without the tag a row has
+    type Value;
+    /// Where a row came from
+    type Tag: Clone;
+
+    fn error(message: String) -> Self::Error;
+
+    /// An error, given back in place of a row
+    fn failed(error: Self::Error) -> Self::Return;
+
+    fn tag(row: &Self::Row) -> Self::Tag;
+
+    /// A value a filter turned a row into, given the row's tag
+    fn tagged(value: Self::Value, tag: Self::Tag) -> Self::Return;
+
+    /// A signature for a command taking rows one at a time
+    fn filter_signature(signature: Self::Signature) -> Self::Signature;
+
+    /// A signature for a command taking all of its rows at once
+    fn sink_signature(signature: Self::Signature) -> Self::Signature;
+}
+
+pub(crate) type Call<P> = <<P as Plugin>::Values as Values>::Call;
+pub(crate) type Row<P> = <<P as Plugin>::Values as Values>::Row;
+pub(crate) type Return<P> = <<P as Plugin>::Values as Values>::Return;
+pub(crate) type Signature<P> = <<P as Plugin>::Values as Values>::Signature;
+pub(crate) type Error<P> = <<P as Plugin>::Values as Values>::Error;
+
+/// A plugin, answering each of the messages nu sends it
+pub trait Plugin {
+    type Values: Values;
+
+    fn config(&mut self) -> Result<Signature<Self>, Error<Self>>;
+
+    fn begin_filter(&mut self, _call_info: Call<Self>) -> Result<Vec<Return<Self>>, Error<Self>> {
+        Ok(vec![])
+    }
+
+    fn filter(&mut self, _input: Row<Self>) -> Result<Vec<Return<Self>>, Error<Self>> {
+        Ok(vec![])
+    }
+
+    fn end_filter(&mut self) -> Result<Vec<Return<Self>>, Error<Self>> {
+        Ok(vec![])
+    }
+
+    fn sink(&mut self, _call_info: Call<Self>, _input: Vec<Row<Self>>) {}
+
+    fn quit(&mut self) {}
+}
+
+/// Answer nu's messages until nu is done with the plugin
+pub fn serve_plugin<P: Plugin + ?Sized>(plugin: &mut P) {
+    let args = std::env::args();
+    if args.len() > 1 {
+        let input = args.skip(1).next();
+
+        let input = match input {
+            Some(arg) => std::fs::read_to_string(arg),
+            None => {
+                send_response(<P::Values as Values>::error(format!("No input given.")));
+                return;
+            }
+        };
+
+        if let Ok(input) = input {
+            handle_command(plugin, &input);
+        }
+    } else {
+        loop {
+            let mut input = String::new();
+            match io::stdin().read_line(&mut input) {
+                Ok(_) => {
+                    if !handle_command(plugin, &input) {
+                        break;
+                    }
+                }
+                e => {
+                    send_response(<P::Values as Values>::error(format!(
+                        "Could not handle plugin message: {:?}",
+                        e,
+                    )));
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Answer one message from nu. Returns whether nu has more to say after it.
+fn handle_command<P: Plugin + ?Sized>(plugin: &mut P, input: &str) -> bool {
+    let command = serde_json::from_str::<NuCommand<Call<P>, Row<P>>>(input);
+    match command {
+        Ok(NuCommand::hello { .. }) => {
+            send_response(handshake::<P::Values>());
+            true
+        }
+        Ok(NuCommand::config) => {
+            send_response(plugin.config());
+            false
+        }
+        Ok(NuCommand::begin_filter { params }) => {
+            send_response(plugin.begin_filter(params));
+            true
+        }
+        Ok(NuCommand::filter { params }) => {
+            send_response(plugin.filter(params));
+            true
+        }
+        Ok(NuCommand::end_filter) => {
+            send_response(plugin.end_filter());
+            false
+        }
+        Ok(NuCommand::sink { params }) => {
+            plugin.sink(params.0, params.1);
+            false
+        }
+        Ok(NuCommand::quit) => {
+            plugin.quit();
+            false
+        }
+        Err(e) => {
+            send_response(<P::Values as Values>::error(format!(
+                "Could not handle plugin message: {} {:?}",
+                input, e
+            )));
+            false
+        }
+    }
+}
+
+/// Send a row to nu straight away, ahead of the response to the request being handled. Filters
+/// that produce a lot of output from one row, or from `end_filter`, can use this to stream it.
+pub fn emit_value<V: Values>(value: V::Return) {
+    // A plugin loaded into nu's process hands its rows back with the reply instead
+    if let Some(value) = crate::dylib::capture(value) {
+        send_message("value", value);
+    }
+}
+
+fn handshake<V: Values>() -> Result<Handshake, V::Error> {
+    Ok(Handshake {
+        protocol_version: PROTOCOL_VERSION,
+    })
+}
+
+fn send_response<T: Serialize>(result: T) {
+    send_message("response", result);
+}
+
+fn send_message<T: Serialize>(method: &str, params: T) {
+    let response = JsonRpc::new(method, params);
+    let line = match serde_json::to_string(&response) {
+        Ok(response) => response,
+        Err(err) => err.to_string(),
+    };
+
+    println!("{}", line);
+}
@@ -0,0 +1,21 @@
+use nu::{CallInfo, NuValues, ShellError, Signature, Tagged, Value};
+use nu_plugin::{serve_filter, Filter};
+
+struct Len;
+
+impl Filter for Len {
+    type Values = NuValues;
+
+    fn signature(&self) -> Signature {
+        Signature::build("len").desc("The length of each string")
+    }
+
+    fn filter(&mut self, _call: &CallInfo, input: Tagged<Value>) -> Result<Vec<Value>, ShellError> {
+        let s = input.as_string()?;
+        Ok(vec![Value::int(s.chars().count() as i64)])
+    }
+}
+
+fn main() {
+    serve_filter(Len);
+}
@@ -1,30 +1,40 @@
 use crate::commands::autoview;
 use crate::commands::classified::{
-    ClassifiedCommand, ClassifiedInputStream, ClassifiedPipeline, ExternalCommand, InternalCommand,
-    StreamNext,
+    finish_pipeline, ClassifiedCommand, ClassifiedInputStream, ClassifiedPipeline, ExternalCommand,
+    InternalCommand, StreamNext,
 };
-use crate::commands::plugin::JsonRpc;
-use crate::commands::plugin::{PluginCommand, PluginSink};
+use crate::commands::plugin::{plugin_timeout, PluginCommand, PluginProcess, PluginSink};
+use crate::commands::plugin_dylib::{DylibPlugin, PluginLibrary};
+use crate::commands::plugins::SignatureCache;
 use crate::commands::whole_stream_command;
-use crate::context::Context;
+use crate::context::{Context, SpanSource};
+use crate::data::base::Block;
 use crate::data::config;
-use crate::data::Value;
+use crate::data::{TaggedDictBuilder, Value};
+use crate::env::terminal_integration;
 pub(crate) use crate::errors::ShellError;
+use crate::evaluate::forget_patterns;
+use crate::format::{RenderView, TableView};
 use crate::fuzzysearch::{interactive_fuzzy_search, SelectionResult};
 use crate::git::current_branch;
+use crate::parser::parse::flag::FlagKind;
 use crate::parser::registry::Signature;
-use crate::parser::{hir, CallNode, Pipeline, PipelineElement, TokenNode};
+use crate::parser::{
+    hir, CallNode, Flag, Operator, Pipeline, PipelineElement, RawToken, TokenNode,
+};
+use crate::plugin::{Handshake, PROTOCOL_VERSION};
 use crate::prelude::*;
 
+use futures::sink::SinkExt;
 use log::{debug, trace};
 use rustyline::error::ReadlineError;
 use rustyline::{self, config::Configurer, config::EditMode, ColorMode, Config, Editor};
 use std::env;
 use std::error::Error;
-use std::io::{BufRead, BufReader, Write};
+use std::io::Write;
 use std::iter::Iterator;
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::Ordering;
 
 #[derive(Debug)]
 pub enum MaybeOwned<'a, T> {
@@ -41,81 +51,153 @@ impl<T> MaybeOwned<'_, T> {
     }
 }
 
-fn load_plugin(path: &std::path::Path, context: &mut Context) -> Result<(), ShellError> {
-    let mut child = std::process::Command::new(path)
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .expect("Failed to spawn child process");
+pub(crate) fn load_plugin(
+    path: &std::path::Path,
+    registry: &CommandRegistry,
+) -> Result<(), ShellError> {
+    let path = dunce::canonicalize(path)?;
 
-    let stdin = child.stdin.as_mut().expect("Failed to open stdin");
-    let stdout = child.stdout.as_mut().expect("Failed to open stdout");
+    let library = if is_plugin_library(&path) {
+        Some(Arc::new(PluginLibrary::open(&path)?))
+    } else {
+        None
+    };
 
-    let mut reader = BufReader::new(stdout);
+    let cached = SignatureCache::load().get(&path);
+    let params = match &cached {
+        Some(params) => params.clone(),
+        None => match &library {
+            Some(library) => library.signature()?,
+            None => ask_plugin_signature(&path)?,
+        },
+    };
 
-    let request = JsonRpc::new("config", Vec::<Value>::new());
-    let request_raw = serde_json::to_string(&request)?;
-    stdin.write(format!("{}\n", request_raw).as_bytes())?;
-    let path = dunce::canonicalize(path)?;
+    trace!("processing {:?}", params);
 
-    let mut input = String::new();
-    let result = match reader.read_line(&mut input) {
-        Ok(count) => {
-            trace!("processing response ({} bytes)", count);
-            trace!("response: {}", input);
+    let name = params.name.clone();
+    let fname = path.to_string_lossy().to_string();
 
-            let response = serde_json::from_str::<JsonRpc<Result<Signature, ShellError>>>(&input);
-            match response {
-                Ok(jrpc) => match jrpc.params {
-                    Ok(params) => {
-                        let fname = path.to_string_lossy();
+    if registry.plugin_of(&name).as_ref() == Some(&path) {
+        trace!("plugin {:?} already loaded.", &name);
+        return Ok(());
+    }
 
-                        trace!("processing {:?}", params);
+    // A plugin can't take the place of one of nu's own commands, or of another plugin's
+    if registry.has(&name) {
+        eprintln!(
+            "nu: skipping the plugin {}, as there's already a command named {}",
+            path.display(),
+            name
+        );
 
-                        let name = params.name.clone();
-                        let fname = fname.to_string();
+        return Err(ShellError::string(format!(
+            "There's already a command named {}",
+            name
+        )));
+    }
 
-                        if context.has_command(&name) {
-                            trace!("plugin {:?} already loaded.", &name);
-                        } else {
-                            if params.is_filter {
-                                context.add_commands(vec![whole_stream_command(
-                                    PluginCommand::new(name, fname, params),
-                                )]);
-                            } else {
-                                context.add_commands(vec![whole_stream_command(PluginSink::new(
-                                    name, fname, params,
-                                ))]);
-                            };
-                        }
-                        Ok(())
-                    }
-                    Err(e) => Err(e),
-                },
-                Err(e) => {
-                    trace!("incompatible plugin {:?}", input);
-                    Err(ShellError::string(format!("Error: {:?}", e)))
-                }
-            }
-        }
-        Err(e) => Err(ShellError::string(format!("Error: {:?}", e))),
+    if cached.is_none() {
+        let mut cache = SignatureCache::load();
+        cache.insert(&path, params.clone());
+        let _ = cache.save();
+    }
+
+    let command = if let Some(library) = library {
+        whole_stream_command(DylibPlugin::new(name, library, params))
+    } else if params.is_filter {
+        whole_stream_command(PluginCommand::new(name, fname, params))
+    } else {
+        whole_stream_command(PluginSink::new(name, fname, params))
     };
 
-    let _ = child.wait();
+    registry.add_plugin_command(command, &path);
 
-    result
+    Ok(())
 }
 
-fn search_paths() -> Vec<std::path::PathBuf> {
-    let mut search_paths = Vec::new();
+/// Whether a plugin is a dynamic library to load into nu, rather than an executable to run
+fn is_plugin_library(path: &std::path::Path) -> bool {
+    path.extension()
+        .map(|ext| ext == std::env::consts::DLL_EXTENSION)
+        .unwrap_or(false)
+}
 
-    match env::var_os("PATH") {
-        Some(paths) => {
-            search_paths = env::split_paths(&paths).collect::<Vec<_>>();
+/// Start a plugin to ask it for its signature, checking first that it speaks our protocol
+fn ask_plugin_signature(path: &std::path::Path) -> Result<Signature, ShellError> {
+    // Plugins are asked before the first prompt, so one that never answers mustn't be waited on
+    // forever even when no timeout is configured
+    let timeout = plugin_timeout().unwrap_or(std::time::Duration::from_secs(10));
+
+    let mut plugin = PluginProcess::spawn(path)?;
+
+    let hello = plugin.with_timeout(timeout, |plugin| {
+        plugin.request::<Handshake>(
+            "hello",
+            Handshake {
+                protocol_version: PROTOCOL_VERSION,
+            },
+        )
+    });
+
+    match hello {
+        Ok(Handshake { protocol_version }) if protocol_version == PROTOCOL_VERSION => {}
+        Ok(Handshake { protocol_version }) => {
+            plugin.kill();
+            plugin.wait();
+            return Err(ShellError::string(format!(
+                "{} speaks version {} of the plugin protocol, but this nu speaks version {}",
+                path.display(),
+                protocol_version,
+                PROTOCOL_VERSION
+            )));
+        }
+        Err(_) => {
+            // Plugins from before the handshake reply to it with an error and exit. Apart from
+            // the handshake they speak the same protocol, so ask again without it.
+            trace!("{:?} doesn't know the handshake", path.display());
+            plugin.wait();
+            plugin = PluginProcess::spawn(path)?;
         }
-        None => println!("PATH is not defined in the environment."),
     }
 
+    let result = plugin.with_timeout(timeout, |plugin| {
+        plugin.request::<Signature>("config", Vec::<Value>::new())
+    });
+
+    plugin.wait();
+
+    result
+}
+
+// Plugins are looked for in the directories listed in the config, and otherwise next to the
+// nu executable, which is where `cargo install` puts them too:
+//
+//   plugin_dirs = ["~/.nu/plugins", "/opt/nu/plugins"]
+//
+// Plugins that live anywhere else can be registered one at a time with `plugin add <path>`,
+// which keeps them in the `plugins` list in the config.
+
+fn search_paths() -> Vec<std::path::PathBuf> {
+    let configured = config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("plugin_dirs").cloned());
+
+    let mut search_paths = match configured {
+        Some(Tagged {
+            item: Value::Table(dirs),
+            ..
+        }) => dirs
+            .iter()
+            .filter_map(|dir| dir.as_string().ok())
+            .map(|dir| PathBuf::from(shellexpand::tilde(&dir).as_ref()))
+            .collect(),
+        _ => env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()))
+            .into_iter()
+            .collect::<Vec<_>>(),
+    };
+
     #[cfg(debug_assertions)]
     {
         // Use our debug plugins in debug mode
@@ -146,7 +228,25 @@ fn search_paths() -> Vec<std::path::PathBuf> {
     search_paths
 }
 
-fn load_plugins(context: &mut Context) -> Result<(), ShellError> {
+/// The plugins registered with `plugin add`
+pub(crate) fn registered_plugins() -> Vec<PathBuf> {
+    match config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("plugins").cloned())
+    {
+        Some(Tagged {
+            item: Value::Table(plugins),
+            ..
+        }) => plugins
+            .iter()
+            .filter_map(|plugin| plugin.as_string().ok())
+            .map(PathBuf::from)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+pub(crate) fn load_plugins(registry: &CommandRegistry) -> Result<(), ShellError> {
     let opts = glob::MatchOptions {
         case_sensitive: false,
         require_literal_separator: false,
@@ -209,18 +309,83 @@ fn load_plugins(context: &mut Context) -> Result<(), ShellError> {
                         trace!("Trying {:?}", bin.display());
 
                         // we are ok if this plugin load fails
-                        let _ = load_plugin(&bin, context);
+                        let _ = load_plugin(&bin, registry);
                     }
                 }
             }
         }
     }
 
+    for path in search_paths() {
+        let mut pattern = path.to_path_buf();
+
+        pattern.push(std::path::Path::new(&format!(
+            "{}nu_plugin_[a-z]*{}",
+            std::env::consts::DLL_PREFIX,
+            std::env::consts::DLL_SUFFIX
+        )));
+
+        if let Ok(libraries) = glob::glob_with(&pattern.to_string_lossy(), opts) {
+            for library in libraries.filter_map(Result::ok) {
+                if library.is_file() {
+                    trace!("Trying {:?}", library.display());
+
+                    // we are ok if this plugin load fails
+                    let _ = load_plugin(&library, registry);
+                }
+            }
+        }
+    }
+
+    for plugin in registered_plugins() {
+        trace!("Trying {:?}", plugin.display());
+        let _ = load_plugin(&plugin, registry);
+    }
+
     Ok(())
 }
 
 pub struct History;
 
+/// How history is kept, from the `history_size`, `history_dedup` and `history_ignore_space`
+/// config settings
+pub struct HistoryConfig {
+    pub max_size: usize,
+    pub dedup: bool,
+    pub ignore_space: bool,
+}
+
+impl HistoryConfig {
+    pub fn from_config() -> HistoryConfig {
+        let mut history = HistoryConfig {
+            max_size: 10_000,
+            dedup: true,
+            ignore_space: true,
+        };
+
+        let config = match config::config(Tag::unknown()) {
+            Ok(config) => config,
+            Err(_) => return history,
+        };
+
+        if let Some(size) = config.get("history_size") {
+            if let Some(size) = size.as_string().ok().and_then(|s| s.parse().ok()) {
+                history.max_size = size;
+            }
+        }
+
+        if let Some(dedup) = config.get("history_dedup") {
+            history.dedup = dedup.is_true();
+        }
+
+        if let Some(ignore_space) = config.get("history_ignore_space") {
+            history.ignore_space = ignore_space.is_true();
+        }
+
+        history
+    }
+}
+
 impl History {
     pub fn path() -> PathBuf {
         const FNAME: &str = "history.txt";
@@ -231,9 +396,248 @@ impl History {
             })
             .unwrap_or(PathBuf::from(FNAME))
     }
+
+    /// Trim the history file down to the configured size, dropping consecutive duplicates, and
+    /// return what's left. The file is only rewritten when something was dropped, and then by
+    /// renaming a complete copy into place so a concurrent reader never sees half a file. The
+    /// history is locked throughout, so a line another session appends meanwhile isn't lost.
+    pub fn compact(settings: &HistoryConfig) -> std::io::Result<Vec<String>> {
+        let _lock = HistoryLock::acquire()?;
+        let path = History::path();
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        let original: Vec<&str> = contents.lines().collect();
+
+        let mut lines: Vec<String> = vec![];
+        for line in original.iter() {
+            if settings.dedup && lines.last().map(|l| l.as_str()) == Some(*line) {
+                continue;
+            }
+            lines.push(line.to_string());
+        }
+
+        if lines.len() > settings.max_size {
+            lines.drain(..lines.len() - settings.max_size);
+        }
+
+        if lines.len() != original.len() {
+            let mut tmp = path.clone();
+            tmp.set_extension(format!("txt.{}", std::process::id()));
+
+            {
+                let mut file = std::fs::File::create(&tmp)?;
+                for line in lines.iter() {
+                    writeln!(file, "{}", line)?;
+                }
+            }
+
+            std::fs::rename(&tmp, &path)?;
+        }
+
+        History::compact_details(settings.max_size)?;
+
+        Ok(lines)
+    }
+
+    /// Keep the details log to the same number of entries as the history itself
+    fn compact_details(max_size: usize) -> std::io::Result<()> {
+        let path = History::details_path();
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let entries: Vec<&str> = contents.lines().collect();
+        if entries.len() <= max_size {
+            return Ok(());
+        }
+
+        let mut tmp = path.clone();
+        tmp.set_extension(format!("txt.{}", std::process::id()));
+
+        {
+            let mut file = std::fs::File::create(&tmp)?;
+            for entry in &entries[entries.len() - max_size..] {
+                writeln!(file, "{}", entry)?;
+            }
+        }
+
+        std::fs::rename(&tmp, &path)
+    }
+
+    /// Add one entry to the end of the history file. Each session appends its own lines as
+    /// they're entered rather than saving its whole history on exit, so sessions running side
+    /// by side don't overwrite each other.
+    pub fn append(line: &str) -> std::io::Result<()> {
+        // Wait out a compaction in another session. If the lock can't be had, the line is still
+        // better appended than dropped.
+        let _lock = HistoryLock::acquire();
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(History::path())?;
+
+        // A single write keeps lines from concurrent sessions from interleaving
+        file.write_all(format!("{}\n", line).as_bytes())
+    }
+
+    /// Where each line that's run is logged along with when it ran, how long it took and how
+    /// it exited. This is what the `history` command lists.
+    pub fn details_path() -> PathBuf {
+        const FNAME: &str = "history-details.txt";
+        config::user_data()
+            .map(|mut p| {
+                p.push(FNAME);
+                p
+            })
+            .unwrap_or(PathBuf::from(FNAME))
+    }
+
+    /// Log a line that has finished running. Entries are tab-separated: start time in seconds
+    /// since the epoch, duration in milliseconds, exit code, then the line as a JSON string so
+    /// that multi-line entries stay on one line.
+    pub fn record(
+        line: &str,
+        started: std::time::SystemTime,
+        duration: std::time::Duration,
+        exit_code: i32,
+    ) -> std::io::Result<()> {
+        let started = started
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        let entry = format!(
+            "{}\t{}\t{}\t{}\n",
+            started,
+            duration.as_millis(),
+            exit_code,
+            serde_json::to_string(line)?
+        );
+
+        let _lock = HistoryLock::acquire();
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(History::details_path())?;
+
+        file.write_all(entry.as_bytes())
+    }
+
+    /// The entries logged by `record`, oldest first, skipping any that can't be read
+    pub fn details() -> std::io::Result<Vec<HistoryEntry>> {
+        let contents = match std::fs::read_to_string(History::details_path()) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+
+        Ok(contents
+            .lines()
+            .filter_map(|entry| {
+                let mut fields = entry.splitn(4, '\t');
+                Some(HistoryEntry {
+                    started: std::time::UNIX_EPOCH
+                        + std::time::Duration::from_secs(fields.next()?.parse().ok()?),
+                    duration: std::time::Duration::from_millis(fields.next()?.parse().ok()?),
+                    exit_code: fields.next()?.parse().ok()?,
+                    command: serde_json::from_str(fields.next()?).ok()?,
+                })
+            })
+            .collect())
+    }
+}
+
+/// Held while the history files are changed. It's a lock file next to the history, created
+/// only if it doesn't already exist, which works the same on every platform. One left behind by
+/// a session that crashed is taken over once it's a few seconds old.
+struct HistoryLock {
+    path: PathBuf,
+}
+
+impl HistoryLock {
+    fn acquire() -> std::io::Result<HistoryLock> {
+        let mut path = History::path();
+        path.set_extension("txt.lock");
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(2);
+
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(HistoryLock { path }),
+                Err(ref e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+
+            let stale = std::fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .map(|age| age > std::time::Duration::from_secs(10))
+                .unwrap_or(false);
+
+            if stale {
+                let _ = std::fs::remove_file(&path);
+                continue;
+            }
+
+            if std::time::Instant::now() > deadline {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "the history is locked by another session",
+                ));
+            }
+
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
 }
 
-pub async fn cli() -> Result<(), Box<dyn Error>> {
+impl Drop for HistoryLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub command: String,
+    pub started: std::time::SystemTime,
+    pub duration: std::time::Duration,
+    pub exit_code: i32,
+}
+
+/// How nu was started, from the command line
+#[derive(Debug, Default)]
+pub struct CliOptions {
+    pub login: bool,
+    /// Run this instead of starting the prompt (`nu -c`)
+    pub command: Option<String>,
+    /// What to read stdin as when running a command, like `json` for `from-json`
+    pub stdin_format: Option<String>,
+    /// Run this script instead of starting the prompt (`nu script.nu`)
+    pub script: Option<PathBuf>,
+    /// The arguments after the script's name, which it sees as `$args`
+    pub args: Vec<String>,
+    /// Run the `test_*` commands the script defines rather than the script (`nu --test`)
+    pub test: bool,
+}
+
+/// A context with the built-in commands, plugins and saved aliases loaded
+pub(crate) fn create_default_context() -> Result<Context, Box<dyn Error>> {
     let mut context = Context::basic()?;
 
     {
@@ -250,6 +654,11 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             whole_stream_command(Debug),
             whole_stream_command(Lines),
             whole_stream_command(Shells),
+            whole_stream_command(HistoryCommand),
+            whole_stream_command(Fg),
+            whole_stream_command(Bg),
+            whole_stream_command(Jobs),
+            whole_stream_command(Plugins),
             whole_stream_command(SplitColumn),
             whole_stream_command(SplitRow),
             whole_stream_command(Lines),
@@ -259,6 +668,7 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             whole_stream_command(ToBSON),
             whole_stream_command(ToCSV),
             whole_stream_command(ToJSON),
+            whole_stream_command(ToNu),
             whole_stream_command(ToSQLite),
             whole_stream_command(ToDB),
             whole_stream_command(ToTOML),
@@ -266,6 +676,8 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             whole_stream_command(ToURL),
             whole_stream_command(ToYAML),
             whole_stream_command(SortBy),
+            whole_stream_command(GroupBy),
+            whole_stream_command(Uniq),
             whole_stream_command(Tags),
             whole_stream_command(First),
             whole_stream_command(Last),
@@ -275,6 +687,7 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             whole_stream_command(FromINI),
             whole_stream_command(FromBSON),
             whole_stream_command(FromJSON),
+            whole_stream_command(FromNu),
             whole_stream_command(FromDB),
             whole_stream_command(FromSQLite),
             whole_stream_command(FromTOML),
@@ -289,22 +702,36 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             per_item_command(Open),
             per_item_command(Post),
             per_item_command(Where),
+            whole_stream_command(If),
             per_item_command(Echo),
+            whole_stream_command(ErrorCommand),
+            whole_stream_command(Assert),
             whole_stream_command(Config),
             whole_stream_command(SkipWhile),
+            whole_stream_command(TakeWhile),
             per_item_command(Enter),
             per_item_command(Help),
+            whole_stream_command(Hexdump),
+            whole_stream_command(Bytes),
+            whole_stream_command(HashCommand),
+            whole_stream_command(IntoCommand),
+            whole_stream_command(FormatCommand),
             whole_stream_command(Exit),
             whole_stream_command(Autoview),
             whole_stream_command(Pivot),
             per_item_command(Cpy),
             whole_stream_command(Date),
+            whole_stream_command(Benchmark),
             per_item_command(Mkdir),
             per_item_command(Move),
             whole_stream_command(Save),
             whole_stream_command(Table),
             whole_stream_command(Version),
             whole_stream_command(Which),
+            whole_stream_command(Alias),
+            whole_stream_command(Def),
+            whole_stream_command(For),
+            whole_stream_command(Source),
         ]);
 
         #[cfg(feature = "clipboard")]
@@ -314,9 +741,76 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             )]);
         }
     }
-    let _ = load_plugins(&mut context);
+    let _ = load_plugins(context.registry());
+    crate::commands::alias::load_aliases(context.registry());
+
+    Ok(context)
+}
+
+pub async fn cli(options: CliOptions) -> Result<(), Box<dyn Error>> {
+    let login = crate::env::login::is_login_shell(options.login);
+    crate::env::login::bootstrap(login);
+    crate::env::jobs::install_signal_handlers();
+
+    let mut context = create_default_context()?;
+    crate::env::jobs::inherit_variables(context.registry());
+
+    context.update_directory_env();
+    if login {
+        run_config_script(&mut context, "login.nu").await;
+    }
+
+    let stdin_format = options.stdin_format.as_ref().map(|f| f.as_str());
+
+    if let Some(command) = &options.command {
+        let exit_code = run_command(&mut context, command, stdin_format, None).await;
+        std::process::exit(exit_code);
+    }
 
-    let config = Config::builder().color_mode(ColorMode::Forced).build();
+    if let Some(script) = &options.script {
+        let text = match std::fs::read_to_string(script) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("nu: couldn't read {}: {}", script.display(), err);
+                std::process::exit(1);
+            }
+        };
+
+        let args = options
+            .args
+            .iter()
+            .map(|arg| Value::string(arg).tagged_unknown())
+            .collect();
+        context
+            .registry()
+            .set_variable("args", Value::Table(args).tagged_unknown());
+
+        if options.test {
+            let passed = run_tests(&mut context, &text).await;
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+
+        let exit_code =
+            run_command(&mut context, &text, stdin_format, Some(script.as_path())).await;
+        std::process::exit(exit_code);
+    }
+
+    // Piped in, like `echo version | nu`, the pipelines to run come from stdin, with no prompt
+    if !stdin_is_terminal() {
+        let exit_code = run_stdin(&mut context).await;
+        std::process::exit(exit_code);
+    }
+
+    run_config_script(&mut context, "startup.nu").await;
+
+    let history = HistoryConfig::from_config();
+
+    let config = Config::builder()
+        .color_mode(ColorMode::Forced)
+        .max_history_size(history.max_size)
+        .history_ignore_dups(history.dedup)
+        .history_ignore_space(history.ignore_space)
+        .build();
     let mut rl: Editor<_> = Editor::with_config(config);
 
     #[cfg(windows)]
@@ -325,9 +819,33 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
     }
 
     // we are ok if history does not exist
-    let _ = rl.load_history(&History::path());
+    if let Ok(lines) = History::compact(&history) {
+        for line in lines {
+            rl.add_history_entry(line);
+        }
+    }
+
+    // Register Ctrl-r for history fuzzy search
+    // rustyline doesn't support custom commands, so we override Ctrl-D (EOF)
+    // https://github.com/nushell/nushell/issues/689
+    #[cfg(all(not(windows), feature = "crossterm"))]
+    rl.bind_sequence(rustyline::KeyPress::Ctrl('R'), rustyline::Cmd::EndOfFile);
+    // Redefine Ctrl-D to same command as Ctrl-C
+    rl.bind_sequence(rustyline::KeyPress::Ctrl('D'), rustyline::Cmd::Interrupt);
+
+    // User bindings go last so they can replace the ones above
+    for binding in crate::shell::keybindings::keybindings() {
+        match binding {
+            Ok((key, action)) => {
+                rl.bind_sequence(key, action);
+            }
+            Err(reason) => context.with_host(|host| {
+                host.stderr(&format!("Ignoring key binding: {}", reason));
+            }),
+        }
+    }
 
-    let ctrl_c = Arc::new(AtomicBool::new(false));
+    let ctrl_c = context.ctrl_c.clone();
     let cc = ctrl_c.clone();
     ctrlc::set_handler(move || {
         cc.store(true, Ordering::SeqCst);
@@ -340,10 +858,17 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             continue;
         }
 
+        crate::env::jobs::report();
+
+        run_hooks(&mut context, "pre_prompt").await;
+
         let cwd = context.shell_manager.path();
+        let terminal_integration = terminal_integration::enabled();
 
         rl.set_helper(Some(crate::shell::Helper::new(
             context.shell_manager.clone(),
+            context.registry().clone(),
+            terminal_integration,
         )));
 
         let edit_mode = config::config(Tag::unknown())?
@@ -357,29 +882,40 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
 
         rl.set_edit_mode(edit_mode);
 
-        // Register Ctrl-r for history fuzzy search
-        // rustyline doesn't support custom commands, so we override Ctrl-D (EOF)
-        // https://github.com/nushell/nushell/issues/689
-        #[cfg(all(not(windows), feature = "crossterm"))]
-        rl.bind_sequence(rustyline::KeyPress::Ctrl('R'), rustyline::Cmd::EndOfFile);
-        // Redefine Ctrl-D to same command as Ctrl-C
-        rl.bind_sequence(rustyline::KeyPress::Ctrl('D'), rustyline::Cmd::Interrupt);
-
-        let prompt = &format!(
-            "{}{}> ",
-            cwd,
-            match current_branch() {
-                Some(s) => format!("({})", s),
-                None => "".to_string(),
-            }
-        );
+        // Pick up any changes to how file sizes are shown
+        crate::format::filesize::reload();
+
+        let prompt = match custom_prompt(&mut context).await {
+            Some(prompt) => prompt,
+            None => format!(
+                "{}{}> ",
+                cwd,
+                match current_branch() {
+                    Some(s) => format!("({})", s),
+                    None => "".to_string(),
+                }
+            ),
+        };
+        let prompt = &prompt;
+
+        if terminal_integration {
+            terminal_integration::prompt_start(&cwd);
+        }
+
         let mut initial_command = Some(String::new());
         let mut readline = Err(ReadlineError::Eof);
         while let Some(ref cmd) = initial_command {
             readline = rl.readline_with_initial(prompt, (&cmd, ""));
             if let Err(ReadlineError::Eof) = &readline {
-                // Fuzzy search in history
-                let lines = rl.history().iter().rev().map(|s| s.as_str()).collect();
+                // Fuzzy search in history, newest first and without repeats
+                let mut seen = std::collections::HashSet::new();
+                let lines = rl
+                    .history()
+                    .iter()
+                    .rev()
+                    .map(|s| s.as_str())
+                    .filter(|s| seen.insert(*s))
+                    .collect();
                 let selection = interactive_fuzzy_search(&lines, 5); // Clears last line with prompt
                 match selection {
                     SelectionResult::Selected(line) => {
@@ -400,14 +936,131 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
             }
         }
 
-        match process_line(readline, &mut context).await {
+        // Keep reading while a bracket or quote is still open, or the line ends with a `|` or
+        // `\`, rather than submitting a line that can only fail to parse
+        while let Ok(line) = &mut readline {
+            if !crate::parser::continue_line(line) {
+                break;
+            }
+
+            match rl.readline("::: ") {
+                Ok(more) => line.push_str(&more),
+                Err(ReadlineError::Eof) => readline = Ok("".to_string()),
+                Err(err) => readline = Err(err),
+            }
+        }
+
+        // The line editor's bracketed paste mode puts a paste into the line as it is, newlines
+        // and all, so nothing runs until it's submitted. A submitted block of several lines then
+        // runs a line at a time, as if each had been typed at the prompt.
+        let block = match &readline {
+            Ok(text) if text.contains('\n') => crate::parser::join_continued_lines(text),
+            _ => vec![],
+        };
+
+        if block.len() > 1 {
+            env::set_var("NU_COMMAND", block.join("\n"));
+            run_hooks(&mut context, "pre_execution").await;
+
+            if terminal_integration {
+                terminal_integration::command_start();
+            }
+
+            let mut exiting = false;
+
+            for line in block {
+                if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                    continue;
+                }
+
+                if rl.add_history_entry(line.clone()) {
+                    let _ = History::append(&line);
+                }
+
+                let started_at = std::time::SystemTime::now();
+                let started = std::time::Instant::now();
+                let result = process_line(Ok(line.clone()), &mut context).await;
+
+                if let LineResult::Error(..) = result {
+                    context.last_exit_code = 1;
+                }
+
+                if !(history.ignore_space && line.starts_with(' ')) {
+                    let _ = History::record(
+                        &line,
+                        started_at,
+                        started.elapsed(),
+                        context.last_exit_code,
+                    );
+                }
+
+                match result {
+                    LineResult::Success(_) => {}
+                    LineResult::Error(line, err) => print_err(err, line, &mut context),
+                    // Ctrl-C stops the rest of the block, the way it stops a single line
+                    LineResult::CtrlC => break,
+                    LineResult::Break => {
+                        exiting = true;
+                        break;
+                    }
+                }
+            }
+
+            if terminal_integration {
+                terminal_integration::command_finished(context.last_exit_code);
+            }
+
+            if exiting {
+                break;
+            }
+
+            ctrlcbreak = false;
+            continue;
+        }
+
+        if let Ok(line) = &readline {
+            if !line.trim().is_empty() {
+                env::set_var("NU_COMMAND", line);
+                run_hooks(&mut context, "pre_execution").await;
+            }
+        }
+
+        let submitted = readline.as_ref().ok().cloned().unwrap_or_default();
+
+        if terminal_integration && !submitted.trim().is_empty() {
+            terminal_integration::command_start();
+        }
+
+        let started_at = std::time::SystemTime::now();
+        let started = std::time::Instant::now();
+        let result = process_line(readline, &mut context).await;
+
+        let exit_code = match result {
+            LineResult::Success(_) => Some(context.last_exit_code),
+            LineResult::Error(..) => Some(1),
+            _ => None,
+        };
+
+        if let (Some(exit_code), false) = (exit_code, submitted.trim().is_empty()) {
+            if !(history.ignore_space && submitted.starts_with(' ')) {
+                let _ = History::record(&submitted, started_at, started.elapsed(), exit_code);
+            }
+            crate::env::notify::line_finished(&submitted, started.elapsed(), exit_code == 0);
+
+            if terminal_integration {
+                terminal_integration::command_finished(exit_code);
+            }
+        }
+
+        match result {
             LineResult::Success(line) => {
-                rl.add_history_entry(line.clone());
+                if rl.add_history_entry(line.clone()) {
+                    let _ = History::append(&line);
+                }
             }
 
             LineResult::CtrlC => {
                 if ctrlcbreak {
-                    let _ = rl.save_history(&History::path());
                     std::process::exit(0);
                 } else {
                     context.with_host(|host| host.stdout("CTRL-C pressed (again to quit)"));
@@ -416,22 +1069,12 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
                 }
             }
 
-            LineResult::Error(mut line, err) => {
-                rl.add_history_entry(line.clone());
-                let diag = err.to_diagnostic();
-                context.with_host(|host| {
-                    let writer = host.err_termcolor();
-                    line.push_str(" ");
-                    let files = crate::parser::Files::new(line);
-                    let _ = std::panic::catch_unwind(move || {
-                        let _ = language_reporting::emit(
-                            &mut writer.lock(),
-                            &files,
-                            &diag,
-                            &language_reporting::DefaultConfig,
-                        );
-                    });
-                })
+            LineResult::Error(line, err) => {
+                context.last_exit_code = 1;
+                if rl.add_history_entry(line.clone()) {
+                    let _ = History::append(&line);
+                }
+                print_err(err, line, &mut context);
             }
 
             LineResult::Break => {
@@ -441,12 +1084,576 @@ pub async fn cli() -> Result<(), Box<dyn Error>> {
         ctrlcbreak = false;
     }
 
-    // we are ok if we can not save history
-    let _ = rl.save_history(&History::path());
-
     Ok(())
 }
 
+/// Everything on stdin, as one string. Nothing is read until the first command asks for its
+/// input, so a command that doesn't take any won't wait on a stdin that never closes.
+fn stdin_input() -> ClassifiedInputStream {
+    let stream = async_stream_block! {
+        let mut text = String::new();
+
+        if std::io::Read::read_to_string(&mut std::io::stdin(), &mut text).is_ok() {
+            yield Value::string(text).tagged_unknown();
+        }
+    };
+
+    ClassifiedInputStream::from_input_stream(InputStream::from_stream(stream))
+}
+
+#[cfg(unix)]
+pub(crate) fn stdin_is_terminal() -> bool {
+    unsafe { libc::isatty(libc::STDIN_FILENO) == 1 }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn stdin_is_terminal() -> bool {
+    true
+}
+
+/// Whether nu's output is going to a terminal, rather than being captured as from `nu -c`
+#[cfg(unix)]
+pub(crate) fn stdout_is_terminal() -> bool {
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn stdout_is_terminal() -> bool {
+    true
+}
+
+fn print_err(err: ShellError, mut line: String, context: &mut Context) {
+    let help = err.help();
+    let mut diag = err.to_diagnostic();
+
+    // Labels can point into a file or page a value was read from rather than into the line.
+    // Those spans can't be underlined in the line, so they're listed with where they came from.
+    let mut origins = vec![];
+    let source_map = &context.source_map;
+    diag.labels.retain(|label| {
+        let origin = match source_map.get(&label.span.origin) {
+            Some(SpanSource::File(origin)) | Some(SpanSource::Url(origin)) => origin.clone(),
+            _ => return true,
+        };
+
+        origins.push(format!(
+            "{}from {}, bytes {}..{}",
+            label
+                .message
+                .as_ref()
+                .map(|message| format!("{}: ", message))
+                .unwrap_or_default(),
+            origin,
+            label.span.span.start,
+            label.span.span.end
+        ));
+        false
+    });
+
+    context.with_host(|host| {
+        let writer = host.err_termcolor();
+        line.push_str(" ");
+        let files = crate::parser::Files::new(line);
+        let _ = std::panic::catch_unwind(move || {
+            let mut writer = writer.lock();
+            let _ = language_reporting::emit(
+                &mut writer,
+                &files,
+                &diag,
+                &language_reporting::DefaultConfig,
+            );
+
+            for origin in origins {
+                let _ = writeln!(
+                    writer,
+                    "  {} {}",
+                    ansi_term::Color::Blue.bold().paint("= origin:"),
+                    origin
+                );
+            }
+
+            if let Some(help) = help {
+                let _ = writeln!(
+                    writer,
+                    "  {} {}",
+                    ansi_term::Color::Cyan.bold().paint("= help:"),
+                    help
+                );
+            }
+        });
+    })
+}
+
+/// Run a script from the config directory, if it's there. `login.nu` runs first in login
+/// shells, then `startup.nu` in every interactive session.
+async fn run_config_script(context: &mut Context, name: &str) {
+    if let Some(script) = config::default_path_for(&Some(PathBuf::from(name)))
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+    {
+        run_script(context, &script).await;
+    }
+}
+
+/// Run a script one line at a time in `context`, as if each line had been typed at the prompt,
+/// stopping at the first line that fails. Blank lines and lines starting with `#` are skipped.
+/// Returns the exit code the script leaves in `context`: 1 if a line failed, otherwise that of
+/// the last line.
+///
+/// Scripts can `source` other scripts, so this returns a boxed future to keep the async types
+/// from recursing.
+pub(crate) fn run_script<'a>(
+    context: &'a mut Context,
+    script: &'a str,
+) -> std::pin::Pin<Box<dyn Future<Output = i32> + Send + 'a>> {
+    Box::pin(async move {
+        for line in crate::parser::join_continued_lines(script) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let LineResult::Error(line, err) = process_line(Ok(line.to_string()), context).await
+            {
+                print_err(err, line, context);
+                context.last_exit_code = 1;
+                break;
+            }
+        }
+
+        context.last_exit_code
+    })
+}
+
+/// Run the lines of a `nu -c` command or a script, stopping at the first one that fails. Returns
+/// the exit code nu should leave with: 1 for an error in nu itself, otherwise that of the last
+/// command. Lines starting with `#`, like a shebang, are skipped.
+///
+/// When nu's stdin isn't a terminal, it's the input of the first line, as text or converted
+/// with `stdin_format`. An external command starting that line reads it directly instead. For a script, the failing line is reported by its number in `script`.
+async fn run_command(
+    context: &mut Context,
+    command: &str,
+    stdin_format: Option<&str>,
+    script: Option<&std::path::Path>,
+) -> i32 {
+    let mut stdin = if stdin_is_terminal() {
+        None
+    } else {
+        Some(stdin_input())
+    };
+
+    // A line carried on over several has the number of the first
+    for (first_line, line) in crate::parser::join_numbered_lines(command) {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let result = match stdin.take() {
+            Some(input) => {
+                process_line_with_input(Ok(line.to_string()), context, input, stdin_format).await
+            }
+            None => process_line(Ok(line.to_string()), context).await,
+        };
+
+        match result {
+            LineResult::Error(line, err) => {
+                print_err(err, line, context);
+                if let Some(script) = script {
+                    eprintln!("nu: stopped at line {} of {}", first_line, script.display());
+                }
+                return 1;
+            }
+            _ => {
+                if context.last_exit_code != 0 {
+                    return context.last_exit_code;
+                }
+            }
+        }
+    }
+
+    context.last_exit_code
+}
+
+/// Define the commands and aliases in a script without running the rest of it, then run each
+/// command named `test_*` and show a table of which passed. Returns whether they all did.
+async fn run_tests(context: &mut Context, script: &str) -> bool {
+    for line in crate::parser::join_continued_lines(script) {
+        let line = line.trim();
+        if !(line.starts_with("def ") || line.starts_with("alias ")) {
+            continue;
+        }
+
+        if let LineResult::Error(line, err) = process_line(Ok(line.to_string()), context).await {
+            print_err(err, line, context);
+            return false;
+        }
+    }
+
+    let tests: Vec<String> = context
+        .registry()
+        .names()
+        .into_iter()
+        .filter(|name| name.starts_with("test_"))
+        .collect();
+
+    let mut rows = vec![];
+    let mut passed = true;
+
+    for test in tests {
+        let (ok, error, label) = match collect_pipeline_output(context, &test).await {
+            Ok(_) => (true, String::new(), String::new()),
+            Err(err) => {
+                let diagnostic = err.to_diagnostic();
+                let labels: Vec<String> = diagnostic
+                    .labels
+                    .iter()
+                    .filter_map(|label| label.message.clone())
+                    .collect();
+
+                (false, diagnostic.message, labels.join("; "))
+            }
+        };
+
+        passed = passed && ok;
+
+        let mut row = TaggedDictBuilder::new(Tag::unknown());
+        row.insert("test", Value::string(&test));
+        row.insert("passed", Value::boolean(ok));
+        row.insert("error", Value::string(error));
+        row.insert("label", Value::string(label));
+        rows.push(row.into_tagged_value());
+    }
+
+    if let Some(view) = TableView::from_list(&rows) {
+        context.with_host(|host| {
+            let _ = view.render_view(host);
+        });
+    }
+
+    passed
+}
+
+/// Run the lines piped into nu as they arrive, the way they'd run if typed at the prompt: an
+/// error is reported and the next line runs anyway. Returns the exit code of the last line.
+async fn run_stdin(context: &mut Context) -> i32 {
+    use std::io::BufRead;
+
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut pending = String::new();
+
+    while let Some(Ok(line)) = lines.next() {
+        pending.push_str(&line);
+
+        // As at the prompt, a line with a bracket or quote left open or a trailing `|` or `\`
+        // carries on to the next
+        if crate::parser::continue_line(&mut pending) {
+            continue;
+        }
+
+        let line = std::mem::replace(&mut pending, String::new());
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        if let LineResult::Error(line, err) = process_line(Ok(line), context).await {
+            context.last_exit_code = 1;
+            print_err(err, line, context);
+        }
+    }
+
+    // The input ran out partway through a line
+    if !pending.trim().is_empty() {
+        let err = unfinished_line(&pending);
+        context.last_exit_code = 1;
+        print_err(err, pending, context);
+    }
+
+    context.last_exit_code
+}
+
+/// The error for a line still waiting on more when there's no more to read: one with a bracket
+/// or quote left open, or a trailing `|` or `\`
+fn unfinished_line(line: &str) -> ShellError {
+    let origin = uuid::Uuid::nil();
+
+    match crate::parser::unclosed_delimiter_at(line) {
+        Some((delimiter, at)) => ShellError::labeled_error(
+            "Parse Error: Unexpected end of input",
+            "this is never closed",
+            Tag::from((at, at + delimiter.len_utf8(), origin)),
+        ),
+        None => {
+            let end = line.trim_end().len();
+            ShellError::labeled_error(
+                "Parse Error: Unexpected end of input",
+                "the line goes on past the end of the input",
+                Tag::from((end.saturating_sub(1), end, origin)),
+            )
+        }
+    }
+}
+
+// Hooks are pipelines from the config that run at points in the prompt loop. Each event takes
+// a single pipeline or a list of them:
+//
+//   [hooks]
+//   pre_prompt = "ls"
+//   pre_execution = ["^log-command", "^update-terminal-title"]
+//
+// `pre_prompt` runs before each prompt is drawn and `pre_execution` before each submitted line,
+// with the line's text in NU_COMMAND.
+
+/// Run the hooks configured for `event`. They don't count as the last command run.
+async fn run_hooks(context: &mut Context, event: &str) {
+    let hooks = match config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("hooks").cloned())
+    {
+        Some(Tagged {
+            item: Value::Row(hooks),
+            ..
+        }) => match hooks.entries.get(event) {
+            Some(Tagged {
+                item: Value::Table(list),
+                ..
+            }) => list
+                .iter()
+                .filter_map(|hook| hook.as_string().ok())
+                .collect(),
+            Some(hook) => hook.as_string().ok().into_iter().collect(),
+            None => vec![],
+        },
+        _ => vec![],
+    };
+
+    let last_exit_code = context.last_exit_code;
+    for hook in hooks {
+        run_script(context, &hook).await;
+    }
+    context.last_exit_code = last_exit_code;
+}
+
+/// The prompt produced by running the `prompt` pipeline from the config, if one is set. The
+/// pipeline can read NU_CWD, NU_EXIT_CODE (of the last line run) and NU_SHELL_INDEX (the
+/// position in the shells ring) from the environment, and may end in an external command such
+/// as `^starship prompt`, whose output is captured rather than printed.
+async fn custom_prompt(context: &mut Context) -> Option<String> {
+    let pipeline = config::config(Tag::unknown())
+        .ok()?
+        .get("prompt")?
+        .as_string()
+        .ok()?;
+
+    env::set_var("NU_CWD", context.shell_manager.path());
+    env::set_var("NU_EXIT_CODE", context.last_exit_code.to_string());
+    env::set_var(
+        "NU_SHELL_INDEX",
+        context.shell_manager.current_shell.to_string(),
+    );
+
+    // Running the prompt shouldn't count as the last thing the user ran
+    let last_exit_code = context.last_exit_code;
+    let output = collect_pipeline_output(context, &pipeline).await;
+    context.last_exit_code = last_exit_code;
+
+    match output {
+        Ok(values) => {
+            let mut prompt = String::new();
+            for value in values {
+                prompt.push_str(&value.as_string().ok()?);
+            }
+            Some(prompt)
+        }
+        Err(err) => {
+            context.with_host(|host| host.stderr(&format!("Prompt failed: {}", err)));
+            None
+        }
+    }
+}
+
+/// Run `line` without viewing its output, handing back whatever the pipeline produced instead.
+/// A trailing external command has its output captured as lines rather than written out.
+pub(crate) async fn collect_pipeline_output(
+    ctx: &mut Context,
+    line: &str,
+) -> Result<Vec<Tagged<Value>>, ShellError> {
+    let source = Text::from(line);
+    let tokens = crate::parser::parse(line, uuid::Uuid::nil())?;
+    let output = match pipeline_output(ctx, &tokens, &source, ClassifiedInputStream::new()).await {
+        Ok(output) => output,
+        Err(err) => {
+            let _ = finish_pipeline(ctx).await;
+            return Err(err);
+        }
+    };
+    let values = output.into_vec().await;
+
+    finish_pipeline(ctx).await?;
+    Ok(values)
+}
+
+/// Run one pipeline with `input` going into its first command, handing back the stream of what
+/// its last command produces. A trailing external command has its output captured as lines.
+async fn pipeline_output(
+    ctx: &mut Context,
+    pipeline: &TokenNode,
+    source: &Text,
+    mut input: ClassifiedInputStream,
+) -> Result<InputStream, ShellError> {
+    let pipeline = classify_pipeline(pipeline, ctx, source)?;
+
+    let mut iter = pipeline.commands.into_iter().peekable();
+    let mut is_first_command = true;
+
+    while let Some(item) = iter.next() {
+        input = match (item, iter.peek()) {
+            (ClassifiedCommand::Expr(_), _) | (_, Some(ClassifiedCommand::Expr(_))) => {
+                return Err(ShellError::unimplemented("Expression-only commands"))
+            }
+
+            (ClassifiedCommand::Internal(left), _) => ClassifiedInputStream::from_input_stream(
+                left.run(ctx, input, source.clone(), is_first_command)
+                    .await?,
+            ),
+
+            (ClassifiedCommand::External(left), Some(ClassifiedCommand::External(_))) => {
+                left.run(ctx, input, StreamNext::External).await?
+            }
+
+            (ClassifiedCommand::External(left), _) => {
+                left.run(ctx, input, StreamNext::Internal).await?
+            }
+        };
+
+        is_first_command = false;
+    }
+
+    Ok(input.objects)
+}
+
+/// Run `pipeline` with `input` going into its first command, handing back what it produces as
+/// it's produced. This is how `def`, `if` and `for` run their blocks. Several pipelines can be
+/// separated by `;`, and their outputs follow one another.
+///
+/// Nothing runs until the stream is first asked for a value, and the pipeline stops wherever it
+/// was once the stream is dropped.
+pub(crate) fn stream_pipeline(
+    mut context: Context,
+    pipeline: String,
+    input: InputStream,
+) -> OutputStream {
+    let stream = async_stream_block! {
+        let pipeline = crate::commands::alias::expand_aliases(&pipeline, context.registry());
+        let source = Text::from(pipeline.as_str());
+
+        let statements = match crate::parser::parse_statements(&pipeline, uuid::Uuid::nil()) {
+            Ok(statements) => statements,
+            Err(err) => {
+                yield Err(err);
+                return;
+            }
+        };
+
+        let mut input = Some(input);
+
+        for statement in statements {
+            let input = match input.take() {
+                Some(input) => ClassifiedInputStream::from_input_stream(input),
+                None => ClassifiedInputStream::new(),
+            };
+
+            let mut values = match pipeline_output(&mut context, &statement, &source, input).await {
+                Ok(output) => output.values,
+                Err(err) => {
+                    let _ = finish_pipeline(&mut context).await;
+                    yield Err(err);
+                    return;
+                }
+            };
+
+            while let Some(value) = values.next().await {
+                yield ReturnSuccess::value(value);
+            }
+
+            if let Err(err) = finish_pipeline(&mut context).await {
+                yield Err(err);
+                return;
+            }
+        }
+    };
+
+    OutputStream::new(stream)
+}
+
+/// Run a block given to a command like `if` or `for`, for `value` if there is one. A block
+/// holding a pipeline, like `{ echo $it }`, runs it with the value as its input and as `$it`.
+/// Other blocks, like `{ $n * 2 }`, give what their expressions evaluate to.
+pub(crate) fn stream_block(
+    mut context: Context,
+    block: &Block,
+    value: Option<Tagged<Value>>,
+) -> OutputStream {
+    let pipeline = match block.pipeline() {
+        Some(pipeline) => pipeline.to_string(),
+        None => {
+            let value = value.unwrap_or_else(|| Value::nothing().tagged(block.tag));
+
+            return match block.invoke(&value) {
+                Ok(Tagged {
+                    item: Value::Table(rows),
+                    ..
+                }) => rows
+                    .into_iter()
+                    .map(ReturnSuccess::value)
+                    .collect::<VecDeque<_>>()
+                    .into(),
+                Ok(value) => OutputStream::one(ReturnSuccess::value(value)),
+                Err(err) => OutputStream::one(Err(err)),
+            };
+        }
+    };
+
+    for (name, captured) in block.captured.iter() {
+        context.bindings.vars.insert(name.clone(), captured.clone());
+    }
+
+    let input: InputStream = match value {
+        Some(value) => {
+            if let Some(param) = block.params.first() {
+                context.bindings.vars.insert(param.clone(), value.clone());
+            }
+
+            context.bindings.it = Some(value.clone());
+            vec![value].into()
+        }
+        None => VecDeque::new().into(),
+    };
+
+    stream_pipeline(context, pipeline, input)
+}
+
+/// Run the pipeline of a subexpression, like the `ls | length` of `echo (ls | length)`, for its
+/// value: what it produced if that's a single value, otherwise a table of it all.
+///
+/// The pipeline can hold subexpressions of its own, so this returns a boxed future.
+pub(crate) fn run_subexpression<'a>(
+    context: &'a mut Context,
+    pipeline: &'a str,
+    tag: Tag,
+) -> std::pin::Pin<Box<dyn Future<Output = Result<Tagged<Value>, ShellError>> + Send + 'a>> {
+    Box::pin(async move {
+        let mut values = collect_pipeline_output(context, pipeline).await?;
+
+        Ok(match values.len() {
+            0 => Value::nothing().tagged(tag),
+            1 => values.remove(0).item.tagged(tag),
+            _ => Value::Table(values).tagged(tag),
+        })
+    })
+}
+
 enum LineResult {
     Success(String),
     Error(String, ShellError),
@@ -455,11 +1662,53 @@ enum LineResult {
 }
 
 async fn process_line(readline: Result<String, ReadlineError>, ctx: &mut Context) -> LineResult {
+    process_line_with_input(readline, ctx, ClassifiedInputStream::new(), None).await
+}
+
+/// Run a line with `input` going into its first command. With a `convert_from` format, the
+/// input is read by the matching `from-*` command first.
+///
+/// A line can hold several pipelines separated by `;`. They run one after the other, and the
+/// first to fail stops the rest.
+async fn process_line_with_input(
+    readline: Result<String, ReadlineError>,
+    ctx: &mut Context,
+    input: ClassifiedInputStream,
+    convert_from: Option<&str>,
+) -> LineResult {
     match &readline {
         Ok(line) if line.trim() == "" => LineResult::Success(line.clone()),
 
-        Ok(line) => {
-            let result = match crate::parser::parse(&line, uuid::Uuid::nil()) {
+        Ok(original) => {
+            ctx.last_exit_code = 0;
+
+            if let Some(result) = crate::commands::alias::define_alias(original, ctx.registry()) {
+                return match result {
+                    Ok(()) => LineResult::Success(original.clone()),
+                    Err(err) => LineResult::Error(original.clone(), err),
+                };
+            }
+
+            // A definition can be followed by pipelines using it, after a `;`
+            let rest = match crate::commands::def::define_command(original, ctx.registry()) {
+                Some(Ok(rest)) if rest.trim().is_empty() => {
+                    return LineResult::Success(original.clone())
+                }
+                Some(Ok(rest)) => rest,
+                Some(Err(err)) => return LineResult::Error(original.clone(), err),
+                None => original,
+            };
+
+            let line = &crate::commands::alias::expand_aliases(rest, ctx.registry());
+
+            if let Some(pipeline) = crate::env::jobs::background_pipeline(line) {
+                return match crate::env::jobs::spawn(pipeline, ctx) {
+                    Ok(_) => LineResult::Success(original.clone()),
+                    Err(err) => LineResult::Error(line.clone(), err),
+                };
+            }
+
+            let statements = match crate::parser::parse_statements(&line, uuid::Uuid::nil()) {
                 Err(err) => {
                     return LineResult::Error(line.clone(), err);
                 }
@@ -468,112 +1717,21 @@ async fn process_line(readline: Result<String, ReadlineError>, ctx: &mut Context
             };
 
             debug!("=== Parsed ===");
-            debug!("{:#?}", result);
-
-            let mut pipeline = match classify_pipeline(&result, ctx, &Text::from(line)) {
-                Ok(pipeline) => pipeline,
-                Err(err) => return LineResult::Error(line.clone(), err),
-            };
+            debug!("{:#?}", statements);
 
-            match pipeline.commands.last() {
-                Some(ClassifiedCommand::External(_)) => {}
-                _ => pipeline
-                    .commands
-                    .push(ClassifiedCommand::Internal(InternalCommand {
-                        command: whole_stream_command(autoview::Autoview),
-                        name_tag: Tag::unknown(),
-                        args: hir::Call::new(
-                            Box::new(hir::Expression::synthetic_string("autoview")),
-                            None,
-                            None,
-                        ),
-                    })),
-            }
-
-            let mut input = ClassifiedInputStream::new();
-
-            let mut iter = pipeline.commands.into_iter().peekable();
-            let mut is_first_command = true;
-
-            loop {
-                let item: Option<ClassifiedCommand> = iter.next();
-                let next: Option<&ClassifiedCommand> = iter.peek();
-
-                input = match (item, next) {
-                    (None, _) => break,
-
-                    (Some(ClassifiedCommand::Expr(_)), _) => {
-                        return LineResult::Error(
-                            line.clone(),
-                            ShellError::unimplemented("Expression-only commands"),
-                        )
-                    }
+            let mut input = Some(input);
 
-                    (_, Some(ClassifiedCommand::Expr(_))) => {
-                        return LineResult::Error(
-                            line.clone(),
-                            ShellError::unimplemented("Expression-only commands"),
-                        )
-                    }
+            for statement in statements {
+                let input = input.take().unwrap_or_else(ClassifiedInputStream::new);
 
-                    (
-                        Some(ClassifiedCommand::Internal(left)),
-                        Some(ClassifiedCommand::External(_)),
-                    ) => match left
-                        .run(ctx, input, Text::from(line), is_first_command)
-                        .await
-                    {
-                        Ok(val) => ClassifiedInputStream::from_input_stream(val),
-                        Err(err) => return LineResult::Error(line.clone(), err),
-                    },
-
-                    (Some(ClassifiedCommand::Internal(left)), Some(_)) => {
-                        match left
-                            .run(ctx, input, Text::from(line), is_first_command)
-                            .await
-                        {
-                            Ok(val) => ClassifiedInputStream::from_input_stream(val),
-                            Err(err) => return LineResult::Error(line.clone(), err),
-                        }
-                    }
-
-                    (Some(ClassifiedCommand::Internal(left)), None) => {
-                        match left
-                            .run(ctx, input, Text::from(line), is_first_command)
-                            .await
-                        {
-                            Ok(val) => ClassifiedInputStream::from_input_stream(val),
-                            Err(err) => return LineResult::Error(line.clone(), err),
-                        }
-                    }
-
-                    (
-                        Some(ClassifiedCommand::External(left)),
-                        Some(ClassifiedCommand::External(_)),
-                    ) => match left.run(ctx, input, StreamNext::External).await {
-                        Ok(val) => val,
-                        Err(err) => return LineResult::Error(line.clone(), err),
-                    },
-
-                    (Some(ClassifiedCommand::External(left)), Some(_)) => {
-                        match left.run(ctx, input, StreamNext::Internal).await {
-                            Ok(val) => val,
-                            Err(err) => return LineResult::Error(line.clone(), err),
-                        }
-                    }
-
-                    (Some(ClassifiedCommand::External(left)), None) => {
-                        match left.run(ctx, input, StreamNext::Last).await {
-                            Ok(val) => val,
-                            Err(err) => return LineResult::Error(line.clone(), err),
-                        }
-                    }
-                };
-
-                is_first_command = false;
+                if let Err(err) = run_pipeline(ctx, &statement, line, input, convert_from).await {
+                    // This is the error reported, rather than any a command further up ran into
+                    let _ = finish_pipeline(ctx).await;
+                    return LineResult::Error(line.clone(), err);
+                }
             }
 
-            LineResult::Success(line.clone())
+            LineResult::Success(original.clone())
         }
         Err(ReadlineError::Interrupted) => LineResult::CtrlC,
         Err(ReadlineError::Eof) => LineResult::Break,
@@ -584,23 +1742,281 @@ async fn process_line(readline: Result<String, ReadlineError>, ctx: &mut Context
     }
 }
 
-fn classify_pipeline(
+/// Run one pipeline of `line`, viewing what it produces
+async fn run_pipeline(
+    ctx: &mut Context,
+    pipeline: &TokenNode,
+    line: &str,
+    mut input: ClassifiedInputStream,
+    convert_from: Option<&str>,
+) -> Result<(), ShellError> {
+    let mut pipeline = classify_pipeline(pipeline, ctx, &Text::from(line))?;
+
+    forget_patterns();
+
+    // Nothing but a comment
+    if pipeline.commands.is_empty() {
+        return Ok(());
+    }
+
+    // An external command at the head reads nu's stdin for itself, so it isn't taken as text
+    if convert_from.is_none() {
+        if let Some(ClassifiedCommand::External(_)) = pipeline.commands.first() {
+            input = ClassifiedInputStream::new();
+        }
+    }
+
+    if let Some(format) = convert_from {
+        let name = format!("from-{}", format);
+
+        if !ctx.has_command(&name) {
+            return Err(
+                ShellError::string(format!("nu can't read stdin as {}", format))
+                    .with_help(format!("there's no {} command to convert it", name)),
+            );
+        }
+
+        pipeline.commands.insert(
+            0,
+            ClassifiedCommand::Internal(InternalCommand {
+                command: ctx.get_command(&name),
+                name_tag: Tag::unknown(),
+                args: hir::Call::new(
+                    Box::new(hir::Expression::synthetic_string(name.clone())),
+                    None,
+                    None,
+                ),
+            }),
+        );
+    }
+
+    match pipeline.commands.last() {
+        Some(ClassifiedCommand::External(_)) => {}
+        _ => pipeline
+            .commands
+            .push(ClassifiedCommand::Internal(InternalCommand {
+                command: whole_stream_command(autoview::Autoview),
+                name_tag: Tag::unknown(),
+                args: hir::Call::new(
+                    Box::new(hir::Expression::synthetic_string("autoview")),
+                    None,
+                    None,
+                ),
+            })),
+    }
+
+    let mut iter = pipeline.commands.into_iter().peekable();
+    let mut is_first_command = true;
+
+    loop {
+        let item: Option<ClassifiedCommand> = iter.next();
+        let next: Option<&ClassifiedCommand> = iter.peek();
+
+        input = match (item, next) {
+            (None, _) => break,
+
+            (Some(ClassifiedCommand::Expr(_)), _) | (_, Some(ClassifiedCommand::Expr(_))) => {
+                return Err(ShellError::unimplemented("Expression-only commands"))
+            }
+
+            (Some(ClassifiedCommand::Internal(left)), _) => {
+                ClassifiedInputStream::from_input_stream(
+                    left.run(ctx, input, Text::from(line), is_first_command)
+                        .await?,
+                )
+            }
+
+            (Some(ClassifiedCommand::External(left)), Some(ClassifiedCommand::External(_))) => {
+                left.run(ctx, input, StreamNext::External).await?
+            }
+
+            (Some(ClassifiedCommand::External(left)), Some(_)) => {
+                left.run(ctx, input, StreamNext::Internal).await?
+            }
+
+            (Some(ClassifiedCommand::External(left)), None) => {
+                left.run(ctx, input, StreamNext::Last).await?
+            }
+        };
+
+        is_first_command = false;
+    }
+
+    // Whatever the last command still has to produce is produced before the next line runs
+    let _ = input.objects.into_vec().await;
+
+    finish_pipeline(ctx).await
+}
+
+pub(crate) fn classify_pipeline(
     pipeline: &TokenNode,
     context: &Context,
     source: &Text,
 ) -> Result<ClassifiedPipeline, ShellError> {
     let pipeline = pipeline.as_pipeline()?;
 
-    let Pipeline { parts, .. } = pipeline;
+    let Pipeline { mut parts, .. } = pipeline;
+
+    let redirect = match parts
+        .last()
+        .and_then(|last| split_redirect(last, context, source))
+    {
+        Some((last, redirect)) => {
+            parts.pop();
+            parts.push(last);
+            Some(redirect)
+        }
+        None => None,
+    };
 
-    let commands: Result<Vec<_>, ShellError> = parts
+    let mut commands = parts
         .iter()
         .map(|item| classify_command(&item, context, &source))
+        .collect::<Result<Vec<_>, ShellError>>()?;
+
+    if let Some(redirect) = redirect {
+        // An external command's output is already text, so it's written out as it came
+        let raw = match commands.last() {
+            Some(ClassifiedCommand::External(_)) => true,
+            _ => false,
+        };
+
+        commands.push(redirect.into_save(raw, context, source)?);
+    }
+
+    Ok(ClassifiedPipeline { commands })
+}
+
+/// Where a pipeline ending in `> path` or `>> path` writes its output
+struct Redirect {
+    op: Tagged<Operator>,
+    target: TokenNode,
+}
+
+impl Redirect {
+    /// The output is written by `save`, with `--append` for `>>`
+    fn into_save(
+        self,
+        raw: bool,
+        context: &Context,
+        source: &Text,
+    ) -> Result<ClassifiedCommand, ShellError> {
+        let path = hir::baseline_parse_next_expr(
+            &mut hir::TokensIterator::new(&[self.target]),
+            context,
+            source,
+            SyntaxShape::Path,
+        )?;
+
+        let switch = |on: bool| {
+            if on {
+                Some(Flag::new(FlagKind::Longhand, self.op.tag()))
+            } else {
+                None
+            }
+        };
+
+        let mut named = hir::NamedArguments::new();
+        named.insert_switch("raw", switch(raw));
+        named.insert_switch("append", switch(self.op.item == Operator::Append));
+
+        Ok(ClassifiedCommand::Internal(InternalCommand {
+            command: context.get_command("save"),
+            name_tag: self.op.tag(),
+            args: hir::Call::new(
+                Box::new(hir::Expression::synthetic_string("save")),
+                Some(vec![path]),
+                Some(named),
+            ),
+        }))
+    }
+}
+
+/// Take a trailing `> path` or `>> path` off the last command of a pipeline. `>` is a comparison
+/// when the command reads its arguments as a condition, like `where size > 10`, or when what
+/// follows isn't a word or a string, like `assert 4 > 5`. Inside a block it's always a comparison.
+fn split_redirect(
+    element: &PipelineElement,
+    context: &Context,
+    source: &Text,
+) -> Option<(PipelineElement, Redirect)> {
+    let call = element.call();
+    let children = call.children().as_ref()?;
+
+    let significant: Vec<usize> = children
+        .iter()
+        .enumerate()
+        .filter(|(_, token)| match token {
+            TokenNode::Whitespace(_) => false,
+            _ => true,
+        })
+        .map(|(idx, _)| idx)
         .collect();
 
-    Ok(ClassifiedPipeline {
-        commands: commands?,
-    })
+    if significant.len() < 2 {
+        return None;
+    }
+
+    let op_idx = significant[significant.len() - 2];
+    let target = &children[significant[significant.len() - 1]];
+
+    let op = match &children[op_idx] {
+        TokenNode::Operator(op) => op.clone(),
+        _ => return None,
+    };
+
+    match target {
+        TokenNode::Token(Tagged {
+            item: RawToken::Bare,
+            ..
+        })
+        | TokenNode::Token(Tagged {
+            item: RawToken::String(_),
+            ..
+        }) => {}
+        _ => return None,
+    }
+
+    match op.item {
+        Operator::Append => {}
+        Operator::GreaterThan if !takes_condition(call.head(), context, source) => {}
+        _ => return None,
+    }
+
+    let mut kept = children[..op_idx].to_vec();
+    while let Some(TokenNode::Whitespace(_)) = kept.last() {
+        kept.pop();
+    }
+
+    let end = kept
+        .last()
+        .map(|token| token.tag())
+        .unwrap_or(call.head().tag());
+    let shortened = CallNode::new(call.head().clone(), kept).tagged(call.tag().until(end));
+
+    Some((
+        PipelineElement::new(element.pipe, element.pre_ws, shortened, None),
+        Redirect {
+            op,
+            target: target.clone(),
+        },
+    ))
+}
+
+fn takes_condition(head: &TokenNode, context: &Context, source: &Text) -> bool {
+    if !head.is_bare() || !context.has_command(head.source(source)) {
+        return false;
+    }
+
+    context
+        .get_command(head.source(source))
+        .signature()
+        .positional
+        .iter()
+        .any(|arg| match arg.syntax_type() {
+            SyntaxShape::Block => true,
+            _ => false,
+        })
 }
 
 fn classify_command(
@@ -648,6 +2064,13 @@ fn classify_command(
             }
         }
 
+        // `null` is the nothing value, which could otherwise be mistaken for a command of that name
+        call if call.head().is_null() => Err(ShellError::labeled_error(
+            "Invalid command",
+            "null is a value, not a command",
+            call.head().tag(),
+        )),
+
         // If the command is something else (like a number or a variable), that is currently unsupported.
         // We might support `$somevar` as a curried command in the future.
         call => Err(ShellError::invalid_command(call.head().tag())),
@@ -662,16 +2085,38 @@ fn external_command(
     source: &Text,
     name: Tagged<&str>,
 ) -> ClassifiedCommand {
-    let arg_list_strings: Vec<Tagged<String>> = match call.children() {
-        Some(args) => args
-            .iter()
-            .filter_map(|i| match i {
-                TokenNode::Whitespace(_) => None,
-                other => Some(other.as_external_arg(source).tagged(other.tag())),
-            })
-            .collect(),
-        None => vec![],
-    };
+    let mut arg_list_strings: Vec<Tagged<String>> = vec![];
+
+    if let Some(args) = call.children() {
+        let mut args = args.iter().peekable();
+
+        while let Some(arg) = args.next() {
+            match arg {
+                TokenNode::Whitespace(_) => {}
+
+                // `--name=value` is read as a flag and its value, but an external command gets
+                // it back as the one argument it was written as
+                TokenNode::Flag(flag) => match args.peek() {
+                    Some(TokenNode::Whitespace(_)) | None => {
+                        arg_list_strings.push(arg.as_external_arg(source).tagged(arg.tag()))
+                    }
+                    Some(value) => {
+                        let tag = flag.tag().until(value.tag());
+                        let arg = format!(
+                            "{}={}",
+                            flag.tag().slice(source),
+                            value.as_external_arg(source)
+                        );
+
+                        arg_list_strings.push(arg.tagged(tag));
+                        args.next();
+                    }
+                },
+
+                other => arg_list_strings.push(other.as_external_arg(source).tagged(other.tag())),
+            }
+        }
+    }
 
     let (name, tag) = name.into_parts();
 
@@ -1,8 +1,13 @@
 #[macro_use]
 pub(crate) mod macros;
 
+pub(crate) mod alias;
 pub(crate) mod args;
+pub(crate) mod assert;
 pub(crate) mod autoview;
+pub(crate) mod benchmark;
+pub(crate) mod bg;
+pub(crate) mod bytes;
 pub(crate) mod cd;
 pub(crate) mod classified;
 pub(crate) mod clip;
@@ -11,16 +16,22 @@ pub(crate) mod config;
 pub(crate) mod cp;
 pub(crate) mod date;
 pub(crate) mod debug;
+pub(crate) mod def;
 pub(crate) mod echo;
 pub(crate) mod enter;
 pub(crate) mod env;
+pub(crate) mod error;
 pub(crate) mod exit;
 pub(crate) mod fetch;
+pub(crate) mod fg;
 pub(crate) mod first;
+pub(crate) mod for_;
+pub(crate) mod format_filesize;
 pub(crate) mod from_bson;
 pub(crate) mod from_csv;
 pub(crate) mod from_ini;
 pub(crate) mod from_json;
+pub(crate) mod from_nu;
 pub(crate) mod from_sqlite;
 pub(crate) mod from_toml;
 pub(crate) mod from_tsv;
@@ -28,7 +39,14 @@ pub(crate) mod from_url;
 pub(crate) mod from_xml;
 pub(crate) mod from_yaml;
 pub(crate) mod get;
+pub(crate) mod group_by;
+pub(crate) mod hash;
 pub(crate) mod help;
+pub(crate) mod hexdump;
+pub(crate) mod history;
+pub(crate) mod if_;
+pub(crate) mod into;
+pub(crate) mod jobs;
 pub(crate) mod last;
 pub(crate) mod lines;
 pub(crate) mod ls;
@@ -40,6 +58,8 @@ pub(crate) mod open;
 pub(crate) mod pick;
 pub(crate) mod pivot;
 pub(crate) mod plugin;
+pub(crate) mod plugin_dylib;
+pub(crate) mod plugins;
 pub(crate) mod post;
 pub(crate) mod prev;
 pub(crate) mod pwd;
@@ -51,24 +71,33 @@ pub(crate) mod shells;
 pub(crate) mod size;
 pub(crate) mod skip_while;
 pub(crate) mod sort_by;
+pub(crate) mod source;
 pub(crate) mod split_column;
 pub(crate) mod split_row;
 pub(crate) mod table;
 pub(crate) mod tags;
+pub(crate) mod take_while;
 pub(crate) mod to_bson;
 pub(crate) mod to_csv;
 pub(crate) mod to_json;
+pub(crate) mod to_nu;
 pub(crate) mod to_sqlite;
 pub(crate) mod to_toml;
 pub(crate) mod to_tsv;
 pub(crate) mod to_url;
 pub(crate) mod to_yaml;
 pub(crate) mod trim;
+pub(crate) mod uniq;
 pub(crate) mod version;
 pub(crate) mod where_;
 pub(crate) mod which_;
 
+pub(crate) use alias::Alias;
+pub(crate) use assert::Assert;
 pub(crate) use autoview::Autoview;
+pub(crate) use benchmark::Benchmark;
+pub(crate) use bg::Bg;
+pub(crate) use bytes::Bytes;
 pub(crate) use cd::CD;
 pub(crate) use command::{
     per_item_command, whole_stream_command, Command, PerItemCommand, RawCommandArgs,
@@ -79,16 +108,22 @@ pub(crate) use config::Config;
 pub(crate) use cp::Cpy;
 pub(crate) use date::Date;
 pub(crate) use debug::Debug;
+pub(crate) use def::Def;
 pub(crate) use echo::Echo;
 pub(crate) use enter::Enter;
 pub(crate) use env::Env;
+pub(crate) use error::ErrorCommand;
 pub(crate) use exit::Exit;
 pub(crate) use fetch::Fetch;
+pub(crate) use fg::Fg;
 pub(crate) use first::First;
+pub(crate) use for_::For;
+pub(crate) use format_filesize::FormatCommand;
 pub(crate) use from_bson::FromBSON;
 pub(crate) use from_csv::FromCSV;
 pub(crate) use from_ini::FromINI;
 pub(crate) use from_json::FromJSON;
+pub(crate) use from_nu::FromNu;
 pub(crate) use from_sqlite::FromDB;
 pub(crate) use from_sqlite::FromSQLite;
 pub(crate) use from_toml::FromTOML;
@@ -98,7 +133,14 @@ pub(crate) use from_xml::FromXML;
 pub(crate) use from_yaml::FromYAML;
 pub(crate) use from_yaml::FromYML;
 pub(crate) use get::Get;
+pub(crate) use group_by::GroupBy;
+pub(crate) use hash::HashCommand;
 pub(crate) use help::Help;
+pub(crate) use hexdump::Hexdump;
+pub(crate) use history::HistoryCommand;
+pub(crate) use if_::If;
+pub(crate) use into::IntoCommand;
+pub(crate) use jobs::Jobs;
 pub(crate) use last::Last;
 pub(crate) use lines::Lines;
 pub(crate) use ls::LS;
@@ -109,6 +151,7 @@ pub(crate) use nth::Nth;
 pub(crate) use open::Open;
 pub(crate) use pick::Pick;
 pub(crate) use pivot::Pivot;
+pub(crate) use plugins::Plugins;
 pub(crate) use post::Post;
 pub(crate) use prev::Previous;
 pub(crate) use pwd::PWD;
@@ -120,13 +163,16 @@ pub(crate) use shells::Shells;
 pub(crate) use size::Size;
 pub(crate) use skip_while::SkipWhile;
 pub(crate) use sort_by::SortBy;
+pub(crate) use source::Source;
 pub(crate) use split_column::SplitColumn;
 pub(crate) use split_row::SplitRow;
 pub(crate) use table::Table;
 pub(crate) use tags::Tags;
+pub(crate) use take_while::TakeWhile;
 pub(crate) use to_bson::ToBSON;
 pub(crate) use to_csv::ToCSV;
 pub(crate) use to_json::ToJSON;
+pub(crate) use to_nu::ToNu;
 pub(crate) use to_sqlite::ToDB;
 pub(crate) use to_sqlite::ToSQLite;
 pub(crate) use to_toml::ToTOML;
@@ -134,6 +180,7 @@ pub(crate) use to_tsv::ToTSV;
 pub(crate) use to_url::ToURL;
 pub(crate) use to_yaml::ToYAML;
 pub(crate) use trim::Trim;
+pub(crate) use uniq::Uniq;
 pub(crate) use version::Version;
 pub(crate) use where_::Where;
 pub(crate) use which_::Which;
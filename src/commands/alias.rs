@@ -0,0 +1,343 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::{config, Dictionary, TaggedDictBuilder, Value};
+use crate::errors::ShellError;
+use crate::parser::hir::SyntaxShape;
+use crate::prelude::*;
+
+pub struct Alias;
+
+#[derive(Deserialize)]
+pub struct AliasArgs {
+    name: Option<Tagged<String>>,
+    remove: bool,
+    save: bool,
+}
+
+impl WholeStreamCommand for Alias {
+    fn name(&self) -> &str {
+        "alias"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("alias")
+            .optional("name", SyntaxShape::String)
+            .switch("remove")
+            .switch("save")
+    }
+
+    fn usage(&self) -> &str {
+        "Define an alias with `alias name = pipeline`, using its arguments as `$1`, `$2`, ..., or list and remove aliases."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, alias)?.run()
+    }
+}
+
+pub fn alias(
+    AliasArgs { name, remove, save }: AliasArgs,
+    RunnableContext {
+        commands,
+        name: tag,
+        ..
+    }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    let aliases = commands.aliases();
+
+    match (name, remove) {
+        (Some(name), true) => {
+            if commands.remove_alias(name.item()).is_none() {
+                return Err(ShellError::labeled_error(
+                    "No such alias",
+                    "no alias with this name",
+                    name.tag(),
+                ));
+            }
+
+            if save {
+                save_alias(&name, None)?;
+            }
+
+            Ok(OutputStream::empty())
+        }
+        (Some(name), false) => match aliases.get(name.item()) {
+            Some(expansion) => Ok(OutputStream::one(alias_row(&name, expansion, tag))),
+            None => Err(ShellError::labeled_error(
+                "No such alias",
+                "no alias with this name",
+                name.tag(),
+            )),
+        },
+        (None, true) => Err(ShellError::labeled_error(
+            "Which alias should be removed?",
+            "needs an alias name",
+            tag,
+        )),
+        (None, false) => {
+            let rows: VecDeque<Tagged<Value>> = aliases
+                .iter()
+                .map(|(name, expansion)| alias_row(name, expansion, tag))
+                .collect();
+
+            Ok(rows.into())
+        }
+    }
+}
+
+fn alias_row(name: &str, expansion: &str, tag: Tag) -> Tagged<Value> {
+    let mut row = TaggedDictBuilder::new(tag);
+    row.insert("name", Value::string(name));
+    row.insert("expansion", Value::string(expansion));
+    row.into_tagged_value()
+}
+
+/// Handle `alias [--save] name = pipeline`. The right-hand side is a pipeline fragment that
+/// the parser would otherwise try to read as arguments to `alias`, so definitions are picked
+/// out of the line before it's parsed. Returns None for lines that aren't alias definitions.
+pub(crate) fn define_alias(
+    line: &str,
+    registry: &CommandRegistry,
+) -> Option<Result<(), ShellError>> {
+    let rest = line.trim_start();
+    if !rest.starts_with("alias") {
+        return None;
+    }
+    let rest = &rest["alias".len()..];
+    if !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let equals = rest.find('=')?;
+    let mut words: Vec<&str> = rest[..equals].split_whitespace().collect();
+    let expansion = rest[equals + 1..].trim();
+
+    let save = match words.iter().position(|w| *w == "--save") {
+        Some(idx) => {
+            words.remove(idx);
+            true
+        }
+        None => false,
+    };
+
+    let name = match words.as_slice() {
+        [name] => *name,
+        _ => return None,
+    };
+
+    if expansion.is_empty() {
+        return Some(Err(ShellError::string(&format!(
+            "The alias {} needs a pipeline to expand to",
+            name
+        ))));
+    }
+
+    registry.add_alias(name, expansion);
+
+    if save {
+        if let Err(err) = save_alias(name, Some(expansion)) {
+            return Some(Err(err));
+        }
+    }
+
+    Some(Ok(()))
+}
+
+/// Add an alias to (or with no expansion, remove it from) the `[aliases]` table in the config
+fn save_alias(name: &str, expansion: Option<&str>) -> Result<(), ShellError> {
+    let mut result = config::read(Tag::unknown(), &None)?;
+
+    let mut aliases = match result.get("aliases") {
+        Some(Tagged {
+            item: Value::Row(aliases),
+            ..
+        }) => aliases.clone(),
+        _ => Dictionary::default(),
+    };
+
+    match expansion {
+        Some(expansion) => {
+            aliases
+                .entries
+                .insert(name.to_string(), Value::string(expansion).tagged_unknown());
+        }
+        None => {
+            aliases.entries.swap_remove(name);
+        }
+    }
+
+    result.insert("aliases".to_string(), Value::Row(aliases).tagged_unknown());
+    config::write(&result, &None)
+}
+
+/// Register the aliases saved in the config
+pub(crate) fn load_aliases(registry: &CommandRegistry) {
+    let config = match config::config(Tag::unknown()) {
+        Ok(config) => config,
+        Err(_) => return,
+    };
+
+    if let Some(Tagged {
+        item: Value::Row(aliases),
+        ..
+    }) = config.get("aliases")
+    {
+        for (name, expansion) in aliases.entries.iter() {
+            if let Ok(expansion) = expansion.as_string() {
+                registry.add_alias(name.clone(), expansion);
+            }
+        }
+    }
+}
+
+/// Replace the command at the head of each pipeline element with its alias, if it has one.
+/// Aliases can refer to other aliases, but an alias is never expanded inside itself.
+pub(crate) fn expand_aliases(line: &str, registry: &CommandRegistry) -> String {
+    split_pipeline(line)
+        .into_iter()
+        .map(|(element, separator)| format!("{}{}", expand_element(element, registry), separator))
+        .collect()
+}
+
+fn expand_element(element: &str, registry: &CommandRegistry) -> String {
+    let mut element = element.to_string();
+    let mut expanded = vec![];
+
+    loop {
+        let start = element.len() - element.trim_start().len();
+        let end = element[start..]
+            .find(char::is_whitespace)
+            .map(|idx| start + idx)
+            .unwrap_or_else(|| element.len());
+        let head = element[start..end].to_string();
+
+        if expanded.contains(&head) {
+            break;
+        }
+
+        match registry.get_alias(&head) {
+            Some(expansion) => {
+                let rest = match substitute_args(&expansion, &element[end..]) {
+                    Some(substituted) => substituted,
+                    None => format!("{}{}", expansion, &element[end..]),
+                };
+
+                element = format!("{}{}", &element[..start], rest);
+                expanded.push(head);
+            }
+            None => break,
+        }
+    }
+
+    element
+}
+
+/// Fill in the `$1`, `$2`, ... of an alias's expansion with the words that followed the alias.
+/// Words the expansion doesn't refer to are passed along after it, like they are for an alias
+/// without placeholders. Returns None if the expansion has no placeholders.
+fn substitute_args(expansion: &str, args: &str) -> Option<String> {
+    let words = split_words(args);
+    let mut substituted = String::new();
+    let mut used = 0;
+    let mut chars = expansion.char_indices().peekable();
+
+    while let Some((idx, c)) = chars.next() {
+        let placeholder = c == '$'
+            && chars
+                .peek()
+                .map_or(false, |(_, next)| next.is_ascii_digit());
+
+        if !placeholder {
+            substituted.push(c);
+            continue;
+        }
+
+        let mut end = idx + 1;
+
+        while let Some(&(next_idx, next)) = chars.peek() {
+            if !next.is_ascii_digit() {
+                break;
+            }
+
+            end = next_idx + 1;
+            chars.next();
+        }
+
+        let position: usize = expansion[idx + 1..end].parse().unwrap_or(0);
+        if position > 0 {
+            if let Some(word) = words.get(position - 1) {
+                substituted.push_str(word);
+            }
+            used = std::cmp::max(used, position);
+        }
+    }
+
+    if used == 0 {
+        return None;
+    }
+
+    for word in words.iter().skip(used) {
+        substituted.push(' ');
+        substituted.push_str(word);
+    }
+
+    Some(substituted)
+}
+
+/// Split an alias's arguments at the whitespace that isn't inside quotes
+fn split_words(args: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut quote = None;
+
+    for (idx, c) in args.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                start = start.or(Some(idx));
+            }
+            (Some(q), c) if q == c => quote = None,
+            (None, c) if c.is_whitespace() => {
+                if let Some(start) = start.take() {
+                    words.push(&args[start..idx]);
+                }
+            }
+            _ => start = start.or(Some(idx)),
+        }
+    }
+
+    if let Some(start) = start {
+        words.push(&args[start..]);
+    }
+
+    words
+}
+
+/// Split a line at the pipes and semicolons that aren't inside quotes or blocks, keeping the
+/// separator that follows each part
+fn split_pipeline(line: &str) -> Vec<(&str, &str)> {
+    let mut elements = vec![];
+    let mut start = 0;
+    let mut quote = None;
+    let mut depth = 0;
+
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '(') | (None, '[') | (None, '{') => depth += 1,
+            (None, ')') | (None, ']') | (None, '}') => depth -= 1,
+            (None, '|') | (None, ';') if depth <= 0 => {
+                elements.push((&line[start..idx], &line[idx..idx + 1]));
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+
+    elements.push((&line[start..], ""));
+    elements
+}
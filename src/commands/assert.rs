@@ -0,0 +1,95 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::{Primitive, Value};
+use crate::errors::ShellError;
+use crate::parser::Operator;
+use crate::prelude::*;
+
+pub struct Assert;
+
+#[derive(Deserialize)]
+pub struct AssertArgs {
+    condition: Tagged<Value>,
+    rest: Vec<Tagged<Value>>,
+}
+
+impl WholeStreamCommand for Assert {
+    fn name(&self) -> &str {
+        "assert"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("assert")
+            .required("condition", SyntaxShape::Any)
+            .rest(SyntaxShape::Any)
+    }
+
+    fn usage(&self) -> &str {
+        "Fail unless a condition holds: `assert <condition>`, or `assert eq <actual> <expected>` to compare two values."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, assert)?.run()
+    }
+}
+
+pub fn assert(
+    AssertArgs { condition, rest }: AssertArgs,
+    _context: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    match (&condition.item, rest.as_slice()) {
+        (Value::Primitive(Primitive::String(action)), [actual, expected])
+            if action.as_str() == "eq" =>
+        {
+            assert_eq(actual, expected)?
+        }
+        (_, []) => assert_true(&condition)?,
+        (_, extra) => {
+            return Err(ShellError::labeled_error(
+                "Too many arguments to assert",
+                "unexpected argument",
+                extra[0].tag(),
+            )
+            .with_help("use assert eq <actual> <expected> to compare two values"))
+        }
+    }
+
+    Ok(OutputStream::empty())
+}
+
+fn assert_true(condition: &Tagged<Value>) -> Result<(), ShellError> {
+    match condition.item {
+        Value::Primitive(Primitive::Boolean(true)) => Ok(()),
+        Value::Primitive(Primitive::Boolean(false)) => Err(ShellError::labeled_error(
+            "Assertion failed",
+            "this is false",
+            condition.tag(),
+        )),
+        _ => Err(ShellError::type_error(
+            "Boolean",
+            condition.type_name().tagged(condition.tag()),
+        )),
+    }
+}
+
+fn assert_eq(actual: &Tagged<Value>, expected: &Tagged<Value>) -> Result<(), ShellError> {
+    // Numbers of different kinds, like 1 and 1.0, are still equal
+    let equal = actual
+        .compare(&Operator::Equal, &expected.item)
+        .unwrap_or_else(|_| actual.item == expected.item);
+
+    if equal {
+        return Ok(());
+    }
+
+    Err(ShellError::labeled_error_with_secondary(
+        "Assertion failed",
+        format!("this is {}", actual.format_leaf(None)),
+        actual.tag(),
+        format!("but {} was expected", expected.format_leaf(None)),
+        expected.tag(),
+    ))
+}
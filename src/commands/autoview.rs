@@ -1,5 +1,6 @@
 use crate::commands::{RawCommandArgs, WholeStreamCommand};
 use crate::errors::ShellError;
+use crate::format::progress::Progress;
 use crate::prelude::*;
 
 pub struct Autoview;
@@ -35,7 +36,13 @@ pub fn autoview(
     raw: RawCommandArgs,
 ) -> Result<OutputStream, ShellError> {
     Ok(OutputStream::new(async_stream_block! {
-        let input = context.input.drain_vec().await;
+        let mut progress = Progress::start();
+        let mut input = vec![];
+        while let Some(value) = context.input.values.next().await {
+            progress.record(&value);
+            input.push(value);
+        }
+        progress.finish();
 
         if input.len() > 0 {
             if let Tagged {
@@ -51,8 +58,7 @@ pub fn autoview(
                     for i in input {
                         match i.item {
                             Value::Primitive(Primitive::Binary(b)) => {
-                                use pretty_hex::*;
-                                println!("{:?}", b.hex_dump());
+                                println!("{}", crate::format::hex::hex_dump(&b, 0));
                             }
                             _ => {}
                         }
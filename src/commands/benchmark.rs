@@ -0,0 +1,43 @@
+use crate::commands::command::CommandAction;
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct Benchmark;
+
+#[derive(Deserialize)]
+pub struct BenchmarkArgs {
+    pipeline: Tagged<String>,
+}
+
+impl WholeStreamCommand for Benchmark {
+    fn name(&self) -> &str {
+        "benchmark"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("benchmark").required("pipeline", SyntaxShape::String)
+    }
+
+    fn usage(&self) -> &str {
+        "Run a pipeline, given as a string, and return how long it took as a duration."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, benchmark)?.run()
+    }
+}
+
+pub fn benchmark(
+    BenchmarkArgs { pipeline }: BenchmarkArgs,
+    _context: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    Ok(vec![Ok(ReturnSuccess::Action(CommandAction::Benchmark(
+        pipeline.item,
+    )))]
+    .into())
+}
@@ -0,0 +1,50 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct Bg;
+
+#[derive(Deserialize)]
+pub struct BgArgs {
+    id: Option<Tagged<i64>>,
+}
+
+impl WholeStreamCommand for Bg {
+    fn name(&self) -> &str {
+        "bg"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("bg").optional("id", SyntaxShape::Int)
+    }
+
+    fn usage(&self) -> &str {
+        "Let a job suspended with Ctrl-Z carry on in the background, the most recent one unless an id is given."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, bg)?.run()
+    }
+}
+
+pub fn bg(BgArgs { id }: BgArgs, context: RunnableContext) -> Result<OutputStream, ShellError> {
+    let id = match id {
+        Some(id) if id.item < 1 => {
+            return Err(ShellError::labeled_error(
+                "No such job",
+                "job ids start at 1",
+                id.tag(),
+            ))
+        }
+        Some(id) => Some(id.item as usize),
+        None => None,
+    };
+
+    crate::env::jobs::background(id, context.name)?;
+
+    Ok(OutputStream::empty())
+}
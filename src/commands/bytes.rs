@@ -0,0 +1,109 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct Bytes;
+
+#[derive(Deserialize)]
+pub struct BytesArgs {
+    action: Tagged<String>,
+    start: Option<Tagged<u64>>,
+    end: Option<Tagged<u64>>,
+}
+
+impl WholeStreamCommand for Bytes {
+    fn name(&self) -> &str {
+        "bytes"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("bytes")
+            .required("action", SyntaxShape::String)
+            .optional("start", SyntaxShape::Number)
+            .optional("end", SyntaxShape::Number)
+            .completions("action", &["length", "slice", "hex", "base64"])
+    }
+
+    fn usage(&self) -> &str {
+        "Work with binary data: `bytes length`, `bytes slice <start> <end>`, `bytes hex` and `bytes base64`."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, bytes)?.run()
+    }
+}
+
+/// The bytes of binary data, or of text as UTF-8
+pub(crate) fn bytes_of(value: &Tagged<Value>, name: Tag) -> Result<Vec<u8>, ShellError> {
+    match &value.item {
+        Value::Primitive(Primitive::Binary(b)) => Ok(b.clone()),
+        Value::Primitive(Primitive::String(s)) => Ok(s.as_bytes().to_vec()),
+        _ => Err(ShellError::labeled_error_with_secondary(
+            "Expected binary data from pipeline",
+            "requires binary input",
+            name,
+            "value originates from here",
+            value.tag(),
+        )),
+    }
+}
+
+fn bytes(
+    BytesArgs { action, start, end }: BytesArgs,
+    RunnableContext { input, name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    match action.item.as_str() {
+        "length" | "slice" | "hex" | "base64" => {}
+        _ => {
+            return Err(ShellError::labeled_error(
+                "Unknown bytes action",
+                "expected length, slice, hex or base64",
+                action.tag(),
+            ))
+        }
+    }
+
+    if action.item == "slice" && start.is_none() {
+        return Err(ShellError::labeled_error(
+            "Where should the slice start?",
+            "needs a start offset",
+            action.tag(),
+        ));
+    }
+
+    Ok(input
+        .values
+        .map(move |v| {
+            let bytes = bytes_of(&v, name)?;
+
+            let value = match action.item.as_str() {
+                "length" => Value::int(bytes.len()),
+                "slice" => {
+                    // Offsets past the end are clamped rather than failing
+                    let len = bytes.len();
+                    let start = start
+                        .as_ref()
+                        .map(|s| s.item as usize)
+                        .unwrap_or(0)
+                        .min(len);
+                    let end = end
+                        .as_ref()
+                        .map(|e| e.item as usize)
+                        .unwrap_or(len)
+                        .max(start)
+                        .min(len);
+
+                    Value::binary(bytes[start..end].to_vec())
+                }
+                "hex" => Value::string(hex::encode(&bytes)),
+                _ => Value::string(base64::encode(&bytes)),
+            };
+
+            ReturnSuccess::value(value.tagged(v.tag()))
+        })
+        .to_output_stream())
+}
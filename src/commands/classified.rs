@@ -104,6 +104,26 @@ impl InternalCommand {
             trace!(target: "nu::run::internal", "{}", self.args.debug(&source));
         }
 
+        // What each subexpression produces goes along with this run of the command alone
+        let mut subexpressions = indexmap::IndexMap::new();
+
+        // Unless the command runs it itself
+        let streamed = self
+            .command
+            .streamed_positional()
+            .and_then(|idx| self.args.positional.as_ref()?.get(idx))
+            .map(|expr| expr.tag);
+
+        for subexpression in self.args.subexpressions() {
+            if Some(subexpression.tag) == streamed {
+                continue;
+            }
+
+            let pipeline = subexpression.item.slice(&source);
+            let value = crate::cli::run_subexpression(context, pipeline, subexpression.tag).await?;
+            subexpressions.insert(subexpression.tag, value);
+        }
+
         let objects: InputStream =
             trace_stream!(target: "nu::trace_stream::internal", "input" = input.objects);
 
@@ -112,6 +132,7 @@ impl InternalCommand {
             self.name_tag.clone(),
             context.source_map.clone(),
             self.args,
+            subexpressions,
             &source,
             objects,
             is_first_command,
@@ -120,71 +141,157 @@ impl InternalCommand {
         let result = trace_out_stream!(target: "nu::trace_stream::internal", source: &source, "output" = result);
         let mut result = result.values;
 
+        // Actions change the context, so they're carried out here until the command produces its
+        // first value. From then on what it produces goes down the pipeline as it comes, so a
+        // command later on can stop an endless one, as `first` or `take-while` do.
         let mut stream = VecDeque::new();
         while let Some(item) = result.next().await {
             match item? {
-                ReturnSuccess::Action(action) => match action {
-                    CommandAction::ChangePath(path) => {
-                        context.shell_manager.set_path(path);
-                    }
-                    CommandAction::AddSpanSource(uuid, span_source) => {
-                        context.add_span_source(uuid, span_source);
-                    }
-                    CommandAction::Exit => std::process::exit(0), // TODO: save history.txt
-                    CommandAction::EnterHelpShell(value) => {
-                        match value {
-                            Tagged {
-                                item: Value::Primitive(Primitive::String(cmd)),
-                                tag,
-                            } => {
-                                context.shell_manager.insert_at_current(Box::new(
-                                    HelpShell::for_command(
-                                        Value::string(cmd).tagged(tag),
-                                        &context.registry(),
-                                    )?,
-                                ));
-                            }
-                            _ => {
-                                context.shell_manager.insert_at_current(Box::new(
-                                    HelpShell::index(&context.registry())?,
-                                ));
-                            }
-                        }
-                    }
-                    CommandAction::EnterValueShell(value) => {
-                        context
-                            .shell_manager
-                            .insert_at_current(Box::new(ValueShell::new(value)));
-                    }
-                    CommandAction::EnterShell(location) => {
-                        context.shell_manager.insert_at_current(Box::new(
-                            FilesystemShell::with_location(location, context.registry().clone())?,
-                        ));
-                    }
-                    CommandAction::PreviousShell => {
-                        context.shell_manager.prev();
-                    }
-                    CommandAction::NextShell => {
-                        context.shell_manager.next();
-                    }
-                    CommandAction::LeaveShell => {
-                        context.shell_manager.remove_at_current();
-                        if context.shell_manager.is_empty() {
-                            std::process::exit(0); // TODO: save history.txt
-                        }
-                    }
-                },
+                ReturnSuccess::Action(CommandAction::Source(script)) => {
+                    crate::cli::run_script(context, &script).await;
+                }
+                ReturnSuccess::Action(CommandAction::Benchmark(pipeline)) => {
+                    let started = std::time::Instant::now();
+                    crate::cli::run_script(context, &pipeline).await;
 
+                    stream.push_back(
+                        Value::duration(started.elapsed()).tagged(self.name_tag.clone()),
+                    );
+                }
+                ReturnSuccess::Action(action) => perform_action(context, action, &self.name_tag)?,
                 ReturnSuccess::Value(v) => {
                     stream.push_back(v);
+                    break;
+                }
+            }
+        }
+
+        // Any later actions are handed back to this context, which carries them out once the
+        // pipeline has finished
+        let context = context.clone();
+        let name_tag = self.name_tag.clone();
+
+        let rest = async_stream_block! {
+            while let Some(item) = result.next().await {
+                match item {
+                    Ok(ReturnSuccess::Value(v)) => yield v,
+                    Ok(ReturnSuccess::Action(action)) => context.defer_action(action, name_tag.clone()),
+                    Err(err) => {
+                        context.stream_failed(err);
+                        return;
+                    }
                 }
             }
+        };
+
+        Ok(InputStream::from_stream(
+            futures::stream::iter(stream).chain(rest),
+        ))
+    }
+}
+
+/// Once a pipeline has finished, carry out the actions its commands asked for after their output
+/// had started, then report the first error any of them ran into along the way
+pub(crate) async fn finish_pipeline(context: &mut Context) -> Result<(), ShellError> {
+    let unfinished = context.take_unfinished();
+
+    for (action, name_tag) in unfinished.actions {
+        match action {
+            // The output a benchmark would time has already gone down the pipeline
+            CommandAction::Source(script) | CommandAction::Benchmark(script) => {
+                crate::cli::run_script(context, &script).await;
+            }
+            action => perform_action(context, action, &name_tag)?,
         }
+    }
 
-        Ok(stream.into())
+    match unfinished.error {
+        Some(err) => Err(err),
+        None => Ok(()),
     }
 }
 
+/// Carry out an action a command asked for
+fn perform_action(
+    context: &mut Context,
+    action: CommandAction,
+    name_tag: &Tag,
+) -> Result<(), ShellError> {
+    match action {
+        CommandAction::ChangePath(path) => {
+            context.shell_manager.set_path(path);
+            context.update_directory_env();
+        }
+        CommandAction::AddSpanSource(uuid, span_source) => {
+            context.add_span_source(uuid, span_source);
+        }
+        CommandAction::Exit => std::process::exit(0), // TODO: save history.txt
+        CommandAction::EnterHelpShell(value) => match value {
+            Tagged {
+                item: Value::Primitive(Primitive::String(cmd)),
+                tag,
+            } => {
+                context
+                    .shell_manager
+                    .insert_at_current(Box::new(HelpShell::for_command(
+                        Value::string(cmd).tagged(tag),
+                        &context.registry(),
+                    )?));
+            }
+            _ => {
+                context
+                    .shell_manager
+                    .insert_at_current(Box::new(HelpShell::index(&context.registry())?));
+            }
+        },
+        CommandAction::EnterValueShell(value) => {
+            context
+                .shell_manager
+                .insert_at_current(Box::new(ValueShell::new(value, context.registry().clone())));
+        }
+        CommandAction::EnterShell(location) => {
+            context
+                .shell_manager
+                .insert_at_current(Box::new(FilesystemShell::with_location(
+                    location,
+                    context.registry().clone(),
+                )?));
+            context.update_directory_env();
+        }
+        CommandAction::PreviousShell => {
+            context.shell_manager.prev();
+            context.update_directory_env();
+        }
+        CommandAction::NextShell => {
+            context.shell_manager.next();
+            context.update_directory_env();
+        }
+        CommandAction::LeaveShell => {
+            context.shell_manager.remove_at_current();
+            if context.shell_manager.is_empty() {
+                std::process::exit(0); // TODO: save history.txt
+            }
+            context.update_directory_env();
+        }
+        CommandAction::SetEnv(key, value) => {
+            context.shell_manager.set_env(&key, &value);
+        }
+        CommandAction::Foreground(id) => {
+            context.last_exit_code = crate::env::jobs::foreground(id, name_tag.clone())?;
+        }
+        // These run a script, and so are carried out by the caller
+        CommandAction::Source(_) | CommandAction::Benchmark(_) => {
+            return Err(ShellError::labeled_error(
+                "Can't run a script here",
+                "this command ran a script after producing output",
+                name_tag.clone(),
+            ))
+        }
+    }
+
+    Ok(())
+}
+
 pub(crate) struct ExternalCommand {
     pub(crate) name: String,
 
@@ -288,21 +395,24 @@ impl ExternalCommand {
 
         let mut popen = process.popen()?;
 
+        let command_line = std::iter::once(self.name.as_str())
+            .chain(
+                self.args
+                    .iter()
+                    .map(|arg| arg.item.as_str())
+                    .filter(|arg| !arg.trim().is_empty()),
+            )
+            .collect::<Vec<_>>()
+            .join(" ");
+
         match stream_next {
             StreamNext::Last => {
                 let _ = popen.detach();
-                loop {
-                    match popen.poll() {
-                        None => {
-                            let _ = std::thread::sleep(std::time::Duration::new(0, 100000000));
-                        }
-                        _ => {
-                            let _ = popen.terminate();
-                            break;
-                        }
-                    }
+                context.last_exit_code = wait_in_foreground(popen, &command_line);
+                // Keep the prompt off the last line of the output, unless it's being captured
+                if crate::cli::stdout_is_terminal() {
+                    println!("");
                 }
-                println!("");
                 Ok(ClassifiedInputStream::new())
             }
             StreamNext::External => {
@@ -323,3 +433,31 @@ impl ExternalCommand {
         }
     }
 }
+
+/// Wait for the last command of a pipeline to finish, returning its exit code. On unix it can
+/// be suspended with Ctrl-Z instead, which puts it in the job table.
+#[cfg(unix)]
+fn wait_in_foreground(popen: subprocess::Popen, command_line: &str) -> i32 {
+    match popen.pid() {
+        Some(pid) => crate::env::jobs::wait_in_foreground(pid, command_line),
+        None => 1,
+    }
+}
+
+#[cfg(not(unix))]
+fn wait_in_foreground(mut popen: subprocess::Popen, _command_line: &str) -> i32 {
+    loop {
+        match popen.poll() {
+            None => {
+                let _ = std::thread::sleep(std::time::Duration::new(0, 100000000));
+            }
+            Some(status) => {
+                let _ = popen.terminate();
+                return match status {
+                    subprocess::ExitStatus::Exited(code) => code as i32,
+                    _ => 1,
+                };
+            }
+        }
+    }
+}
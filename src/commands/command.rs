@@ -1,7 +1,7 @@
 use crate::context::{SourceMap, SpanSource};
 use crate::data::Value;
 use crate::errors::ShellError;
-use crate::evaluate::Scope;
+use crate::evaluate::{Bindings, Scope};
 use crate::parser::hir;
 use crate::parser::{registry, ConfigDeserializer};
 use crate::prelude::*;
@@ -19,6 +19,10 @@ pub struct UnevaluatedCallInfo {
     pub source: Text,
     pub source_map: SourceMap,
     pub name_tag: Tag,
+    #[serde(skip)]
+    pub bindings: Bindings,
+    #[serde(skip)]
+    pub subexpressions: indexmap::IndexMap<Tag, Tagged<Value>>,
 }
 
 impl ToDebug for UnevaluatedCallInfo {
@@ -33,7 +37,8 @@ impl UnevaluatedCallInfo {
         registry: &registry::CommandRegistry,
         scope: &Scope,
     ) -> Result<CallInfo, ShellError> {
-        let args = self.args.evaluate(registry, scope, &self.source)?;
+        let scope = scope.for_call(&self.bindings, &self.subexpressions);
+        let args = self.args.evaluate(registry, &scope, &self.source)?;
 
         Ok(CallInfo {
             args,
@@ -73,15 +78,19 @@ impl CallInfo {
 #[get = "pub(crate)"]
 pub struct CommandArgs {
     pub host: Arc<Mutex<dyn Host>>,
+    pub ctrl_c: Arc<AtomicBool>,
     pub shell_manager: ShellManager,
     pub call_info: UnevaluatedCallInfo,
     pub input: InputStream,
+    // Whether the command starts its pipeline, rather than being given the output of another
+    pub is_first_command: bool,
 }
 
 #[derive(Getters, Clone)]
 #[get = "pub(crate)"]
 pub struct RawCommandArgs {
     pub host: Arc<Mutex<dyn Host>>,
+    pub ctrl_c: Arc<AtomicBool>,
     pub shell_manager: ShellManager,
     pub call_info: UnevaluatedCallInfo,
 }
@@ -90,9 +99,11 @@ impl RawCommandArgs {
     pub fn with_input(self, input: Vec<Tagged<Value>>) -> CommandArgs {
         CommandArgs {
             host: self.host,
+            ctrl_c: self.ctrl_c,
             shell_manager: self.shell_manager,
             call_info: self.call_info,
             input: input.into(),
+            is_first_command: false,
         }
     }
 }
@@ -111,7 +122,13 @@ impl CommandArgs {
         let host = self.host.clone();
         let shell_manager = self.shell_manager.clone();
         let input = self.input;
-        let call_info = self.call_info.evaluate(registry, &Scope::empty())?;
+
+        // In a block run once for each row, like a branch of `if`, `$it` is that row
+        let scope = match &self.call_info.bindings.it {
+            Some(it) => Scope::it_value(it.clone()),
+            None => Scope::empty(),
+        };
+        let call_info = self.call_info.evaluate(registry, &scope)?;
 
         Ok(EvaluatedWholeStreamCommandArgs::new(
             host,
@@ -155,6 +172,7 @@ impl CommandArgs {
     ) -> Result<RunnableRawArgs<T>, ShellError> {
         let raw_args = RawCommandArgs {
             host: self.host.clone(),
+            ctrl_c: self.ctrl_c.clone(),
             shell_manager: self.shell_manager.clone(),
             call_info: self.call_info.clone(),
         };
@@ -384,6 +402,10 @@ pub enum CommandAction {
     PreviousShell,
     NextShell,
     LeaveShell,
+    Source(String),
+    SetEnv(String, String),
+    Foreground(Option<usize>),
+    Benchmark(String),
 }
 
 impl ToDebug for CommandAction {
@@ -404,6 +426,10 @@ impl ToDebug for CommandAction {
             CommandAction::PreviousShell => write!(f, "action:previous-shell"),
             CommandAction::NextShell => write!(f, "action:next-shell"),
             CommandAction::LeaveShell => write!(f, "action:leave-shell"),
+            CommandAction::Source(_) => write!(f, "action:source"),
+            CommandAction::SetEnv(key, value) => write!(f, "action:set-env={}={}", key, value),
+            CommandAction::Foreground(id) => write!(f, "action:foreground={:?}", id),
+            CommandAction::Benchmark(pipeline) => write!(f, "action:benchmark={}", pipeline),
         }
     }
 }
@@ -457,6 +483,7 @@ pub trait WholeStreamCommand: Send + Sync {
             rest_positional: None,
             named: indexmap::IndexMap::new(),
             is_filter: true,
+            completions: indexmap::IndexMap::new(),
         }
     }
 
@@ -471,6 +498,13 @@ pub trait WholeStreamCommand: Send + Sync {
     fn is_binary(&self) -> bool {
         false
     }
+
+    /// The positional argument the command runs itself when it's a subexpression, like the
+    /// `(ls)` of `for f in (ls) { ... }`, to take its output as it's produced. Other
+    /// subexpressions are run to the end before the command starts.
+    fn streamed_positional(&self) -> Option<usize> {
+        None
+    }
 }
 
 pub trait PerItemCommand: Send + Sync {
@@ -484,6 +518,7 @@ pub trait PerItemCommand: Send + Sync {
             rest_positional: None,
             named: indexmap::IndexMap::new(),
             is_filter: true,
+            completions: indexmap::IndexMap::new(),
         }
     }
 
@@ -555,6 +590,7 @@ impl Command {
     ) -> OutputStream {
         let raw_args = RawCommandArgs {
             host: args.host,
+            ctrl_c: args.ctrl_c,
             shell_manager: args.shell_manager,
             call_info: args.call_info,
         };
@@ -578,17 +614,18 @@ impl Command {
 
             out.to_output_stream()
         } else {
-            let nothing = Value::nothing().tagged(Tag::unknown());
+            // Starting a block that runs for a row, like a branch of `if`, it runs for that row
+            let it = match &raw_args.call_info.bindings.it {
+                Some(it) => it.clone(),
+                None => Value::nothing().tagged(Tag::unknown()),
+            };
             let call_info = raw_args
                 .clone()
                 .call_info
-                .evaluate(&registry, &Scope::it_value(nothing.clone()))
+                .evaluate(&registry, &Scope::it_value(it.clone()))
                 .unwrap();
 
-            match command
-                .run(&call_info, &registry, &raw_args, nothing)
-                .into()
-            {
+            match command.run(&call_info, &registry, &raw_args, it).into() {
                 Ok(o) => o,
                 Err(e) => OutputStream::one(Err(e)),
             }
@@ -601,6 +638,13 @@ impl Command {
             Command::PerItem(command) => command.is_binary(),
         }
     }
+
+    pub fn streamed_positional(&self) -> Option<usize> {
+        match self {
+            Command::WholeStream(command) => command.streamed_positional(),
+            Command::PerItem(_) => None,
+        }
+    }
 }
 
 pub struct FnFilterCommand {
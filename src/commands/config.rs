@@ -11,6 +11,9 @@ pub struct Config;
 
 #[derive(Deserialize)]
 pub struct ConfigArgs {
+    action: Option<Tagged<String>>,
+    key: Option<Tagged<String>>,
+    value: Option<Tagged<Value>>,
     load: Option<Tagged<PathBuf>>,
     set: Option<(Tagged<String>, Tagged<Value>)>,
     get: Option<Tagged<String>>,
@@ -26,16 +29,20 @@ impl WholeStreamCommand for Config {
 
     fn signature(&self) -> Signature {
         Signature::build("config")
+            .optional("action", SyntaxShape::String)
+            .optional("key", SyntaxShape::Member)
+            .optional("value", SyntaxShape::Any)
             .named("load", SyntaxShape::Path)
             .named("set", SyntaxShape::Any)
             .named("get", SyntaxShape::Any)
             .named("remove", SyntaxShape::Any)
             .switch("clear")
+            .completions("action", &["get", "set", "remove", "merge"])
             .switch("path")
     }
 
     fn usage(&self) -> &str {
-        "Configuration management."
+        "Configuration management: `config get table.mode`, `config set table.mode light`, `config remove table.mode`, and `config merge <file>` to bring in the settings from another config file."
     }
 
     fn run(
@@ -49,6 +56,9 @@ impl WholeStreamCommand for Config {
 
 pub fn config(
     ConfigArgs {
+        action,
+        key,
+        value,
         load,
         set,
         get,
@@ -68,10 +78,58 @@ pub fn config(
 
     let mut result = crate::data::config::read(name_span, &configuration)?;
 
+    // `config get a.b` and friends are the same as `config --get a.b`
+    let (get, set, remove) = match action {
+        None => (get, set, remove),
+        Some(action) => match (action.item.as_str(), key, value) {
+            ("get", Some(key), _) => (Some(key), None, None),
+            ("set", Some(key), Some(value)) => (None, Some((key, value)), None),
+            ("remove", Some(key), _) => (None, None, Some(key)),
+            ("merge", Some(file), _) => {
+                if !PathBuf::from(&file.item).is_file() {
+                    return Err(ShellError::labeled_error(
+                        "Could not merge config",
+                        "file not found",
+                        file.tag(),
+                    ));
+                }
+
+                let other =
+                    crate::data::config::read(file.tag(), &Some(PathBuf::from(&file.item)))?;
+                config::merge(&mut result, &other);
+                config::write(&result, &configuration)?;
+
+                return Ok(
+                    stream![Value::Row(result.into()).tagged(file.tag())].from_input_stream()
+                );
+            }
+            ("get", None, _) | ("remove", None, _) | ("merge", None, _) | ("set", None, _) => {
+                return Err(ShellError::labeled_error(
+                    "Missing setting",
+                    "which setting? (like table.mode)",
+                    action.tag(),
+                ))
+            }
+            ("set", Some(key), None) => {
+                return Err(ShellError::labeled_error(
+                    "Missing value",
+                    format!("what should {} be set to?", key.item),
+                    key.tag(),
+                ))
+            }
+            _ => {
+                return Err(ShellError::labeled_error(
+                    "Unknown config action",
+                    "expected get, set, remove or merge",
+                    action.tag(),
+                ))
+            }
+        },
+    };
+
     if let Some(v) = get {
         let key = v.to_string();
-        let value = result
-            .get(&key)
+        let value = config::get_path(&result, &key)
             .ok_or_else(|| ShellError::string(&format!("Missing key {} in config", key)))?;
 
         let mut results = VecDeque::new();
@@ -92,7 +150,7 @@ pub fn config(
     }
 
     if let Some((key, value)) = set {
-        result.insert(key.to_string(), value.clone());
+        config::set_path(&mut result, &key.to_string(), value.clone());
 
         config::write(&result, &configuration)?;
 
@@ -116,8 +174,7 @@ pub fn config(
     if let Some(v) = remove {
         let key = v.to_string();
 
-        if result.contains_key(&key) {
-            result.swap_remove(&key);
+        if config::remove_path(&mut result, &key) {
             config::write(&result, &configuration)?;
         } else {
             return Err(ShellError::string(&format!(
@@ -0,0 +1,233 @@
+use crate::commands::command::WholeStreamCommand;
+use crate::commands::whole_stream_command;
+use crate::errors::ShellError;
+use crate::evaluate::Bindings;
+use crate::parser::hir::SyntaxShape;
+use crate::parser::registry::{CommandRegistry, NamedType, Signature};
+use crate::parser::{Delimiter, TokenNode};
+use crate::prelude::*;
+
+pub struct Def;
+
+impl WholeStreamCommand for Def {
+    fn name(&self) -> &str {
+        "def"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("def").rest(SyntaxShape::Any)
+    }
+
+    fn usage(&self) -> &str {
+        "Define a command with `def name [arg --flag: int] { pipeline }`."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        _registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        Err(ShellError::labeled_error(
+            "Incomplete definition",
+            "needs a name, parameters in [ ] and a body in { }",
+            args.call_info.name_tag,
+        ))
+    }
+}
+
+/// A command defined with `def`. When it runs, its parameters become variables of its own and
+/// its body runs as a pipeline, taking the command's input and giving its output.
+pub struct UserCommand {
+    signature: Signature,
+    body: String,
+}
+
+impl WholeStreamCommand for UserCommand {
+    fn name(&self) -> &str {
+        &self.signature.name
+    }
+
+    fn signature(&self) -> Signature {
+        self.signature.clone()
+    }
+
+    fn usage(&self) -> &str {
+        &self.signature.usage
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        // The body sees its parameters, but not the variables of wherever it's called from
+        let mut context = Context::for_block(&args, registry);
+        context.bindings = Bindings::default();
+
+        let args = args.evaluate_once(registry)?;
+        let tag = args.name_tag();
+
+        for (idx, param) in self.signature.positional.iter().enumerate() {
+            let value = args
+                .nth(idx)
+                .cloned()
+                .unwrap_or_else(|| Value::nothing().tagged(tag));
+
+            context
+                .bindings
+                .vars
+                .insert(param.name().to_string(), value);
+        }
+
+        for (name, ty) in self.signature.named.iter() {
+            let value = match ty {
+                NamedType::Switch => Value::boolean(args.has(name)).tagged(tag),
+                _ => args
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| Value::nothing().tagged(tag)),
+            };
+
+            context.bindings.vars.insert(name.clone(), value);
+        }
+
+        let (input, _) = args.split();
+
+        Ok(crate::cli::stream_pipeline(
+            context,
+            self.body.clone(),
+            input,
+        ))
+    }
+}
+
+/// Handle `def name [params] { pipeline }` at the start of a line. Like an alias's pipeline,
+/// the parameters and body aren't arguments to check against a signature, so the definition is
+/// picked out of the parsed line and registered before the line runs. Returns None for lines
+/// that don't start with one, and otherwise what's left of the line after the `;` following it,
+/// to run as usual.
+pub(crate) fn define_command<'line>(
+    line: &'line str,
+    registry: &CommandRegistry,
+) -> Option<Result<&'line str, ShellError>> {
+    let statements = crate::parser::parse_statements(line, uuid::Uuid::nil()).ok()?;
+    let first = statements.first()?;
+    let pipeline = first.as_pipeline().ok()?;
+
+    // A definition is a pipeline of its own
+    let call = match &pipeline.parts[..] {
+        [part] => part.call(),
+        _ => return None,
+    };
+
+    if !call.head().is_bare() || call.head().tag().slice(line) != "def" {
+        return None;
+    }
+
+    let args: Vec<&TokenNode> = call
+        .children()
+        .iter()
+        .flatten()
+        .filter(|arg| match arg {
+            TokenNode::Whitespace(_) => false,
+            _ => true,
+        })
+        .collect();
+
+    // Anything else is left for `def` itself to report as incomplete
+    let (name, params, body) = match &args[..] {
+        [name, TokenNode::Delimited(params), TokenNode::Delimited(body)]
+            if name.is_bare()
+                && *params.delimiter() == Delimiter::Square
+                && *body.delimiter() == Delimiter::Brace =>
+        {
+            (
+                name.tag().slice(line),
+                params.tag().slice(line),
+                body.tag().slice(line),
+            )
+        }
+        _ => return None,
+    };
+
+    let body = body[1..body.len() - 1].trim();
+
+    let after = line[first.tag().span.end..].trim_start();
+    let after = if after.starts_with(';') {
+        &after[1..]
+    } else {
+        after
+    };
+
+    let signature = match parse_signature(name, &params[1..params.len() - 1]) {
+        Ok(signature) => signature.desc(format!("def {} {}", name, params)),
+        Err(err) => return Some(Err(err)),
+    };
+
+    registry.add_definition(name, format!("def {} {} {{ {} }}", name, params, body));
+    registry.add_command(whole_stream_command(UserCommand {
+        signature,
+        body: body.to_string(),
+    }));
+
+    Some(Ok(after))
+}
+
+/// Read parameters like `path count: int name? --verbose --limit: int`. A `?` makes a
+/// positional optional, and a flag without a type is a switch.
+fn parse_signature(name: &str, params: &str) -> Result<Signature, ShellError> {
+    let params = params.replace(',', " ").replace(':', " : ");
+    let mut words = params.split_whitespace().peekable();
+    let mut signature = Signature::build(name);
+
+    while let Some(word) = words.next() {
+        let ty = match words.peek() {
+            Some(&":") => {
+                words.next();
+
+                match words.next() {
+                    Some(ty) => Some(shape(ty)?),
+                    None => {
+                        return Err(ShellError::string(&format!(
+                            "The parameter {} needs a type after the :",
+                            word
+                        )))
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        signature = if word.starts_with("--") {
+            match ty {
+                Some(ty) => signature.named(&word[2..], ty),
+                None => signature.switch(&word[2..]),
+            }
+        } else if word.ends_with('?') {
+            signature.optional(&word[..word.len() - 1], ty.unwrap_or(SyntaxShape::Any))
+        } else {
+            signature.required(word, ty.unwrap_or(SyntaxShape::Any))
+        };
+    }
+
+    Ok(signature)
+}
+
+fn shape(name: &str) -> Result<SyntaxShape, ShellError> {
+    Ok(match name {
+        "any" => SyntaxShape::Any,
+        "int" | "number" => SyntaxShape::Number,
+        "string" => SyntaxShape::String,
+        "path" => SyntaxShape::Path,
+        "pattern" => SyntaxShape::Pattern,
+        "bool" | "boolean" => SyntaxShape::Boolean,
+        "block" => SyntaxShape::Block,
+        "list" | "table" => SyntaxShape::List,
+        other => {
+            return Err(
+                ShellError::string(&format!("Unknown parameter type {}", other))
+                    .with_help("try any, int, number, string, path, pattern, bool, block or table"),
+            )
+        }
+    })
+}
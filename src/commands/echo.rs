@@ -42,6 +42,28 @@ fn run(
     let mut first = true;
 
     if let Some(ref positional) = call_info.args.positional {
+        // A list, like a script's `$args`, is echoed an item at a time
+        let positional: Vec<_> = positional
+            .iter()
+            .flat_map(|i| match &i.item {
+                Value::Table(list) => list.clone(),
+                _ => vec![i.clone()],
+            })
+            .collect();
+
+        // Rows, like those of a table literal, are passed along as they are
+        if positional.iter().any(|i| match i.item {
+            Value::Row(_) => true,
+            _ => false,
+        }) {
+            let stream: VecDeque<_> = positional
+                .into_iter()
+                .map(|i| Ok(ReturnSuccess::Value(i)))
+                .collect();
+
+            return Ok(stream.to_output_stream());
+        }
+
         for i in positional {
             match i.as_string() {
                 Ok(s) => {
@@ -15,7 +15,9 @@ impl PerItemCommand for Enter {
     }
 
     fn signature(&self) -> registry::Signature {
-        Signature::build("enter").required("location", SyntaxShape::Block)
+        Signature::build("enter")
+            .required("location", SyntaxShape::Block)
+            .named("env", SyntaxShape::String)
     }
 
     fn usage(&self) -> &str {
@@ -29,129 +31,169 @@ impl PerItemCommand for Enter {
         raw_args: &RawCommandArgs,
         _input: Tagged<Value>,
     ) -> Result<OutputStream, ShellError> {
-        let registry = registry.clone();
-        let raw_args = raw_args.clone();
-        match call_info.args.expect_nth(0)? {
-            Tagged {
-                item: Value::Primitive(Primitive::String(location)),
-                ..
-            } => {
-                let location = location.to_string();
-                let location_clone = location.to_string();
-
-                if location.starts_with("help") {
-                    let spec = location.split(":").collect::<Vec<&str>>();
-
-                    let (_, command) = (spec[0], spec[1]);
-
-                    if registry.has(command) {
-                        Ok(vec![Ok(ReturnSuccess::Action(CommandAction::EnterHelpShell(
-                            Value::string(command).tagged(Tag::unknown()),
-                        )))]
-                        .into())
-                    } else {
-                        Ok(vec![Ok(ReturnSuccess::Action(CommandAction::EnterHelpShell(
-                            Value::nothing().tagged(Tag::unknown()),
-                        )))]
-                        .into())
-                    }
-                } else if PathBuf::from(location).is_dir() {
-                    Ok(vec![Ok(ReturnSuccess::Action(CommandAction::EnterShell(
-                        location_clone,
+        // Variables given with `--env "KEY=value OTHER=value"` are set in the new shell only
+        let env: VecDeque<ReturnValue> = match call_info.args.get("env") {
+            Some(env) => env_assignments(env)?
+                .into_iter()
+                .map(|(key, value)| ReturnSuccess::action(CommandAction::SetEnv(key, value)))
+                .collect(),
+            None => VecDeque::new(),
+        };
+
+        let entered = enter(call_info, registry, raw_args)?;
+
+        Ok(entered.values.chain(env).to_output_stream())
+    }
+}
+
+fn env_assignments(env: &Tagged<Value>) -> Result<Vec<(String, String)>, ShellError> {
+    env.as_string()?
+        .split_whitespace()
+        .map(|assignment| {
+            let mut parts = assignment.splitn(2, '=');
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) if !key.is_empty() => {
+                    Ok((key.to_string(), value.to_string()))
+                }
+                _ => Err(ShellError::labeled_error(
+                    "Expected KEY=value",
+                    format!("{} is not an assignment", assignment),
+                    env.tag(),
+                )),
+            }
+        })
+        .collect()
+}
+
+fn enter(
+    call_info: &CallInfo,
+    registry: &registry::CommandRegistry,
+    raw_args: &RawCommandArgs,
+) -> Result<OutputStream, ShellError> {
+    let registry = registry.clone();
+    let raw_args = raw_args.clone();
+    match call_info.args.expect_nth(0)? {
+        Tagged {
+            item: Value::Primitive(Primitive::String(location)),
+            ..
+        } => {
+            let location = location.to_string();
+            let location_clone = location.to_string();
+
+            if location.starts_with("help") {
+                let spec = location.split(":").collect::<Vec<&str>>();
+
+                let (_, command) = (spec[0], spec[1]);
+
+                if registry.has(command) {
+                    Ok(vec![Ok(ReturnSuccess::Action(CommandAction::EnterHelpShell(
+                        Value::string(command).tagged(Tag::unknown()),
                     )))]
                     .into())
                 } else {
-                    let stream = async_stream_block! {
-                        // If it's a file, attempt to open the file as a value and enter it
-                        let cwd = raw_args.shell_manager.path();
-
-                        let full_path = std::path::PathBuf::from(cwd);
-
-                        let (file_extension, contents, contents_tag, span_source) =
-                            crate::commands::open::fetch(
-                                &full_path,
-                                &location_clone,
-                                Span::unknown(),
-                            )
-                            .await.unwrap();
-
-                        if contents_tag.origin != uuid::Uuid::nil() {
-                            // If we have loaded something, track its source
-                            yield ReturnSuccess::action(CommandAction::AddSpanSource(
-                                contents_tag.origin,
-                                span_source,
-                            ));
-                        }
+                    Ok(vec![Ok(ReturnSuccess::Action(CommandAction::EnterHelpShell(
+                        Value::nothing().tagged(Tag::unknown()),
+                    )))]
+                    .into())
+                }
+            } else if PathBuf::from(location).is_dir() {
+                Ok(vec![Ok(ReturnSuccess::Action(CommandAction::EnterShell(
+                    location_clone,
+                )))]
+                .into())
+            } else {
+                let stream = async_stream_block! {
+                    // If it's a file, attempt to open the file as a value and enter it
+                    let cwd = raw_args.shell_manager.path();
+
+                    let full_path = std::path::PathBuf::from(cwd);
+
+                    let (file_extension, contents, contents_tag, span_source) =
+                        crate::commands::open::fetch(
+                            &full_path,
+                            &location_clone,
+                            Span::unknown(),
+                        )
+                        .await.unwrap();
+
+                    if contents_tag.origin != uuid::Uuid::nil() {
+                        // If we have loaded something, track its source
+                        yield ReturnSuccess::action(CommandAction::AddSpanSource(
+                            contents_tag.origin,
+                            span_source,
+                        ));
+                    }
+
 
+                    match contents {
+                        Value::Primitive(Primitive::String(_)) => {
+                            let tagged_contents = contents.tagged(contents_tag);
 
-                        match contents {
-                            Value::Primitive(Primitive::String(_)) => {
-                                let tagged_contents = contents.tagged(contents_tag);
-
-                                if let Some(extension) = file_extension {
-                                    let command_name = format!("from-{}", extension);
-                                    if let Some(converter) =
-                                        registry.get_command(&command_name)
-                                    {
-                                        let new_args = RawCommandArgs {
-                                            host: raw_args.host,
-                                            shell_manager: raw_args.shell_manager,
-                                            call_info: UnevaluatedCallInfo {
-                                                args: crate::parser::hir::Call {
-                                                    head: raw_args.call_info.args.head,
-                                                    positional: None,
-                                                    named: None,
-                                                },
-                                                source: raw_args.call_info.source,
-                                                source_map: raw_args.call_info.source_map,
-                                                name_tag: raw_args.call_info.name_tag,
+                            if let Some(extension) = file_extension {
+                                let command_name = format!("from-{}", extension);
+                                if let Some(converter) =
+                                    registry.get_command(&command_name)
+                                {
+                                    let new_args = RawCommandArgs {
+                                        host: raw_args.host,
+                                        shell_manager: raw_args.shell_manager,
+                                        call_info: UnevaluatedCallInfo {
+                                            args: crate::parser::hir::Call {
+                                                head: raw_args.call_info.args.head,
+                                                positional: None,
+                                                named: None,
                                             },
-                                        };
-                                        let mut result = converter.run(
-                                            new_args.with_input(vec![tagged_contents]),
-                                            &registry,
-                                            false
-                                        );
-                                        let result_vec: Vec<Result<ReturnSuccess, ShellError>> =
-                                            result.drain_vec().await;
-                                        for res in result_vec {
-                                            match res {
-                                                Ok(ReturnSuccess::Value(Tagged {
-                                                    item,
-                                                    ..
-                                                })) => {
-                                                    yield Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(
-                                                        Tagged {
-                                                            item,
-                                                            tag: contents_tag,
-                                                        })));
-                                                }
-                                                x => yield x,
+                                            source: raw_args.call_info.source,
+                                            source_map: raw_args.call_info.source_map,
+                                            name_tag: raw_args.call_info.name_tag,
+                                            bindings: raw_args.call_info.bindings,
+                                            subexpressions: raw_args.call_info.subexpressions,
+                                        },
+                                    };
+                                    let mut result = converter.run(
+                                        new_args.with_input(vec![tagged_contents]),
+                                        &registry,
+                                        false
+                                    );
+                                    let result_vec: Vec<Result<ReturnSuccess, ShellError>> =
+                                        result.drain_vec().await;
+                                    for res in result_vec {
+                                        match res {
+                                            Ok(ReturnSuccess::Value(Tagged {
+                                                item,
+                                                ..
+                                            })) => {
+                                                yield Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(
+                                                    Tagged {
+                                                        item,
+                                                        tag: contents_tag,
+                                                    })));
                                             }
+                                            x => yield x,
                                         }
-                                    } else {
-                                        yield Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(tagged_contents)));
                                     }
                                 } else {
                                     yield Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(tagged_contents)));
                                 }
-                            }
-                            _ => {
-                                let tagged_contents = contents.tagged(contents_tag);
-
+                            } else {
                                 yield Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(tagged_contents)));
                             }
                         }
-                    };
-                    Ok(stream.to_output_stream())
-                }
+                        _ => {
+                            let tagged_contents = contents.tagged(contents_tag);
+
+                            yield Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(tagged_contents)));
+                        }
+                    }
+                };
+                Ok(stream.to_output_stream())
             }
-            x => Ok(
-                vec![Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(
-                    x.clone(),
-                )))]
-                .into(),
-            ),
         }
+        x => Ok(
+            vec![Ok(ReturnSuccess::Action(CommandAction::EnterValueShell(
+                x.clone(),
+            )))]
+            .into(),
+        ),
     }
 }
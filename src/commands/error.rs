@@ -0,0 +1,89 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::Value;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct ErrorCommand;
+
+#[derive(Deserialize)]
+pub struct ErrorArgs {
+    action: Tagged<String>,
+    message: Option<Tagged<String>>,
+    label: Option<Tagged<String>>,
+    at: Option<Tagged<Value>>,
+    help: Option<Tagged<String>>,
+}
+
+impl WholeStreamCommand for ErrorCommand {
+    fn name(&self) -> &str {
+        "error"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("error")
+            .required("action", SyntaxShape::String)
+            .optional("message", SyntaxShape::String)
+            .named("label", SyntaxShape::String)
+            .named("at", SyntaxShape::Any)
+            .named("help", SyntaxShape::String)
+            .completions("action", &["make"])
+    }
+
+    fn usage(&self) -> &str {
+        "Fail with an error of your own: `error make <message> --label <text> --at <value> --help <hint>` underlines where --at's value came from."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, error)?.run()
+    }
+}
+
+pub fn error(
+    ErrorArgs {
+        action,
+        message,
+        label,
+        at,
+        help,
+    }: ErrorArgs,
+    RunnableContext { name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    match action.item.as_str() {
+        "make" => {}
+        _ => {
+            return Err(ShellError::labeled_error(
+                "Unknown error action",
+                "expected make",
+                action.tag(),
+            ))
+        }
+    }
+
+    let message = match message {
+        Some(message) => message.item,
+        None => {
+            return Err(ShellError::labeled_error(
+                "What should the error say?",
+                "needs a message",
+                name,
+            ))
+        }
+    };
+
+    // Without --at, the error points at the `error make` that raised it
+    let tag = at.map(|at| at.tag()).unwrap_or(name);
+    let label = label
+        .map(|label| label.item)
+        .unwrap_or_else(|| message.clone());
+
+    let error = ShellError::labeled_error(message, label, tag);
+
+    Err(match help {
+        Some(help) => error.with_help(help.item),
+        None => error,
+    })
+}
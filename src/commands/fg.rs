@@ -0,0 +1,49 @@
+use crate::commands::command::CommandAction;
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct Fg;
+
+#[derive(Deserialize)]
+pub struct FgArgs {
+    id: Option<Tagged<i64>>,
+}
+
+impl WholeStreamCommand for Fg {
+    fn name(&self) -> &str {
+        "fg"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("fg").optional("id", SyntaxShape::Int)
+    }
+
+    fn usage(&self) -> &str {
+        "Bring a job to the foreground, the most recent one unless an id is given."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, fg)?.run()
+    }
+}
+
+pub fn fg(FgArgs { id }: FgArgs, _context: RunnableContext) -> Result<OutputStream, ShellError> {
+    let id = match id {
+        Some(id) if id.item < 1 => {
+            return Err(ShellError::labeled_error(
+                "No such job",
+                "job ids start at 1",
+                id.tag(),
+            ))
+        }
+        Some(id) => Some(id.item as usize),
+        None => None,
+    };
+
+    Ok(vec![Ok(ReturnSuccess::Action(CommandAction::Foreground(id)))].into())
+}
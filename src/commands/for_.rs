@@ -0,0 +1,128 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::parser::hir::{self, RawExpression};
+use crate::parser::ConfigDeserializer;
+use crate::prelude::*;
+
+pub struct For;
+
+#[derive(Deserialize)]
+pub struct ForArgs {
+    name: Tagged<String>,
+    #[serde(rename = "in")]
+    keyword: Tagged<String>,
+    values: Tagged<Value>,
+    block: value::Block,
+}
+
+impl WholeStreamCommand for For {
+    fn name(&self) -> &str {
+        "for"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("for")
+            .required("name", SyntaxShape::Member)
+            .required("in", SyntaxShape::Any)
+            .required("values", SyntaxShape::Any)
+            .required("block", SyntaxShape::Block)
+    }
+
+    fn usage(&self) -> &str {
+        "Run the block for each value, as in `for n in [1 2 3] { echo $n }`."
+    }
+
+    fn streamed_positional(&self) -> Option<usize> {
+        Some(2)
+    }
+
+    fn run(
+        &self,
+        mut args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        let context = Context::for_block(&args, registry);
+
+        // Values given by a subexpression are taken as it produces them, so the block can start
+        // on the first before the rest have been produced
+        let streamed = match args
+            .call_info
+            .args
+            .positional
+            .as_mut()
+            .and_then(|positional| positional.get_mut(2))
+        {
+            Some(expr) => match expr.item {
+                RawExpression::Subexpression(pipeline) => {
+                    let pipeline = pipeline.slice(&args.call_info.source).to_string();
+                    *expr = hir::Expression::synthetic_string(String::new());
+                    Some(crate::cli::stream_pipeline(
+                        context.clone(),
+                        pipeline,
+                        VecDeque::new().into(),
+                    ))
+                }
+                _ => None,
+            },
+            None => None,
+        };
+
+        let (_, args) = args.evaluate_once(registry)?.split();
+        let mut deserializer = ConfigDeserializer::from_call_info(args.call_info);
+
+        for_(ForArgs::deserialize(&mut deserializer)?, streamed, context)
+    }
+}
+
+pub fn for_(
+    ForArgs {
+        name,
+        keyword,
+        values,
+        mut block,
+    }: ForArgs,
+    streamed: Option<OutputStream>,
+    context: Context,
+) -> Result<OutputStream, ShellError> {
+    if keyword.item != "in" {
+        return Err(ShellError::labeled_error(
+            "Expected in",
+            "like for name in values",
+            keyword.tag(),
+        ));
+    }
+
+    let mut values = match (streamed, values) {
+        (Some(streamed), _) => streamed.values,
+        (
+            None,
+            Tagged {
+                item: Value::Table(rows),
+                ..
+            },
+        ) => OutputStream::from_input(futures::stream::iter(rows)).values,
+        (None, value) => OutputStream::one(ReturnSuccess::value(value)).values,
+    };
+
+    // The loop variable is the block's parameter, so each value is bound as it's reached
+    block.params = vec![name.item];
+
+    // Each value's run of the block starts once the one before has finished
+    let stream = async_stream_block! {
+        while let Some(value) = values.next().await {
+            match value {
+                Ok(ReturnSuccess::Value(value)) => {
+                    let mut output =
+                        crate::cli::stream_block(context.clone(), &block, Some(value)).values;
+
+                    while let Some(item) = output.next().await {
+                        yield item;
+                    }
+                }
+                other => yield other,
+            }
+        }
+    };
+
+    Ok(OutputStream::new(stream))
+}
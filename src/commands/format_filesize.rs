@@ -0,0 +1,112 @@
+use crate::commands::into::convert_columns;
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::format::filesize::{format_filesize_in, unit_named};
+use crate::prelude::*;
+use byte_unit::ByteUnit;
+
+pub struct FormatCommand;
+
+#[derive(Deserialize)]
+pub struct FormatArgs {
+    action: Tagged<String>,
+    unit: Tagged<String>,
+    rest: Vec<Tagged<String>>,
+}
+
+impl WholeStreamCommand for FormatCommand {
+    fn name(&self) -> &str {
+        "format"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("format")
+            .required("action", SyntaxShape::String)
+            .required("unit", SyntaxShape::String)
+            .rest(SyntaxShape::String)
+            .completions("action", &["filesize"])
+            .completions(
+                "unit",
+                &[
+                    "B", "KB", "KiB", "MB", "MiB", "GB", "GiB", "TB", "TiB", "PB", "PiB",
+                ],
+            )
+    }
+
+    fn usage(&self) -> &str {
+        "Render values as text: `format filesize MB` shows file sizes in the given unit (B, KB, KiB, MB, MiB, GB, GiB, TB, TiB, PB or PiB). Give column names to render only those columns of each row."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, format)?.run()
+    }
+}
+
+fn format(
+    FormatArgs { action, unit, rest }: FormatArgs,
+    RunnableContext { input, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    if action.item.as_str() != "filesize" {
+        return Err(ShellError::labeled_error(
+            "Unknown format action",
+            "expected filesize",
+            action.tag(),
+        ));
+    }
+
+    let byte_unit = match unit_named(&unit.item) {
+        Some(byte_unit) => byte_unit,
+        None => {
+            return Err(ShellError::labeled_error(
+                "Unknown file size unit",
+                "expected B, KB, KiB, MB, MiB, GB, GiB, TB, TiB, PB or PiB",
+                unit.tag(),
+            ))
+        }
+    };
+
+    Ok(input
+        .values
+        .map(move |v| {
+            let value = match &v.item {
+                Value::Row(dict) if rest.is_empty() => {
+                    // Without column names, every file size in the row is rendered
+                    let mut dict = dict.clone();
+
+                    for value in dict.entries.values_mut() {
+                        if let Value::Primitive(Primitive::Bytes(_)) = value.item {
+                            *value = format_size(value, byte_unit)?;
+                        }
+                    }
+
+                    Value::Row(dict).tagged(v.tag())
+                }
+                _ if rest.is_empty() => format_size(&v, byte_unit)?,
+                _ => convert_columns(&v, &rest, |value| format_size(value, byte_unit))?,
+            };
+
+            ReturnSuccess::value(value)
+        })
+        .to_output_stream())
+}
+
+fn format_size(value: &Tagged<Value>, unit: ByteUnit) -> Result<Tagged<Value>, ShellError> {
+    let bytes = match &value.item {
+        Value::Primitive(Primitive::Bytes(b)) => Some(*b),
+        Value::Primitive(Primitive::Int(i)) => i.to_u64(),
+        _ => None,
+    };
+
+    match bytes {
+        Some(bytes) => Ok(Value::string(format_filesize_in(bytes, unit)).tagged(value.tag())),
+        None => Err(ShellError::labeled_error(
+            "Expected a file size",
+            "this isn't a file size",
+            value.tag(),
+        )),
+    }
+}
@@ -0,0 +1,286 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::{Primitive, TaggedDictBuilder, Value};
+use crate::prelude::*;
+use chrono::{DateTime, Utc};
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub struct FromNu;
+
+impl WholeStreamCommand for FromNu {
+    fn name(&self) -> &str {
+        "from-nu"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("from-nu")
+    }
+
+    fn usage(&self) -> &str {
+        "Parse nu literal text (as written by to-nu) and create table."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        from_nu(args, registry)
+    }
+}
+
+struct NuReader<'a> {
+    chars: Peekable<Chars<'a>>,
+    tag: Tag,
+}
+
+impl<'a> NuReader<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if c.is_whitespace() || *c == ',' {
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("expected '{}', found '{}'", expected, c)),
+            None => Err(format!("expected '{}', found end of input", expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Tagged<Value>, String> {
+        self.skip_whitespace();
+
+        match self.chars.peek() {
+            Some('[') => {
+                self.chars.next();
+                let mut list = vec![];
+                loop {
+                    self.skip_whitespace();
+                    if let Some(']') = self.chars.peek() {
+                        self.chars.next();
+                        break;
+                    }
+                    list.push(self.value()?);
+                }
+                Ok(Value::Table(list).tagged(self.tag))
+            }
+            Some('{') => {
+                self.chars.next();
+                let mut row = TaggedDictBuilder::new(self.tag);
+                loop {
+                    self.skip_whitespace();
+                    if let Some('}') = self.chars.peek() {
+                        self.chars.next();
+                        break;
+                    }
+                    let key = self.key()?;
+                    self.skip_whitespace();
+                    self.expect(':')?;
+                    let value = self.value()?;
+                    row.insert_tagged(key, value);
+                }
+                Ok(row.into_tagged_value())
+            }
+            Some('"') => Ok(Value::string(self.string()?).tagged(self.tag)),
+            Some(_) => {
+                let word = self.word();
+                match word.as_str() {
+                    "path" => Ok(Value::path(self.string()?).tagged(self.tag)),
+                    "glob" => {
+                        Ok(Value::Primitive(Primitive::Pattern(self.string()?)).tagged(self.tag))
+                    }
+                    "0x" => Ok(Value::binary(self.binary()?).tagged(self.tag)),
+                    _ => Ok(parse_word(&word)?.tagged(self.tag)),
+                }
+            }
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+
+    fn key(&mut self) -> Result<String, String> {
+        if let Some('"') = self.chars.peek() {
+            return self.string();
+        }
+
+        let mut key = String::new();
+        while let Some(c) = self.chars.peek() {
+            if c.is_whitespace() || *c == ':' {
+                break;
+            }
+            key.push(*c);
+            self.chars.next();
+        }
+
+        if key.is_empty() {
+            Err("expected a column name".to_string())
+        } else {
+            Ok(key)
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.chars.peek() {
+            match c {
+                c if c.is_whitespace() => break,
+                ',' | ']' | '}' | '"' => break,
+                '[' if word == "0x" => break,
+                c => {
+                    word.push(*c);
+                    self.chars.next();
+                }
+            }
+        }
+        word
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some(c) => out.push(c),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some(c) => out.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn binary(&mut self) -> Result<Vec<u8>, String> {
+        self.expect('[')?;
+        let mut out = vec![];
+        loop {
+            self.skip_whitespace();
+            if let Some(']') = self.chars.peek() {
+                self.chars.next();
+                return Ok(out);
+            }
+            let byte = self.word();
+            out.push(
+                u8::from_str_radix(&byte, 16)
+                    .map_err(|_| format!("'{}' is not a hex byte", byte))?,
+            );
+        }
+    }
+}
+
+fn parse_word(word: &str) -> Result<Value, String> {
+    match word {
+        "null" => return Ok(Value::nothing()),
+        "true" => return Ok(Value::boolean(true)),
+        "false" => return Ok(Value::boolean(false)),
+        _ => {}
+    }
+
+    if word.ends_with("ns") {
+        if let Ok(nanos) = word[..word.len() - 2].parse::<i64>() {
+            return Ok(Value::Primitive(Primitive::Duration(nanos)));
+        }
+    }
+
+    if word.ends_with('b') {
+        if let Ok(bytes) = word[..word.len() - 1].parse::<u64>() {
+            return Ok(Value::bytes(bytes));
+        }
+    }
+
+    if let Ok(i) = word.parse::<BigInt>() {
+        return Ok(Value::int(i));
+    }
+
+    if word.contains('.') {
+        if let Ok(d) = word.parse::<BigDecimal>() {
+            return Ok(Value::decimal(d));
+        }
+    }
+
+    if let Ok(date) = DateTime::parse_from_rfc3339(word) {
+        return Ok(Value::Primitive(Primitive::Date(date.with_timezone(&Utc))));
+    }
+
+    Err(format!("'{}' is not a nu literal", word))
+}
+
+pub fn from_nu_string_to_value(s: &str, tag: impl Into<Tag>) -> Result<Tagged<Value>, String> {
+    let mut reader = NuReader {
+        chars: s.chars().peekable(),
+        tag: tag.into(),
+    };
+
+    let value = reader.value()?;
+    reader.skip_whitespace();
+
+    match reader.chars.next() {
+        None => Ok(value),
+        Some(c) => Err(format!("unexpected '{}' after value", c)),
+    }
+}
+
+fn from_nu(args: CommandArgs, registry: &CommandRegistry) -> Result<OutputStream, ShellError> {
+    let args = args.evaluate_once(registry)?;
+    let name_tag = args.name_tag();
+    let input = args.input;
+
+    let stream = async_stream_block! {
+        let values: Vec<Tagged<Value>> = input.values.collect().await;
+
+        let mut concat_string = String::new();
+        let mut latest_tag: Option<Tag> = None;
+
+        for value in values {
+            let value_tag = value.tag();
+            latest_tag = Some(value_tag);
+            match value.item {
+                Value::Primitive(Primitive::String(s)) => {
+                    concat_string.push_str(&s);
+                    concat_string.push_str("\n");
+                }
+                _ => yield Err(ShellError::labeled_error_with_secondary(
+                    "Expected a string from pipeline",
+                    "requires string input",
+                    name_tag,
+                    "value originates from here",
+                    value_tag,
+                )),
+
+            }
+        }
+
+        match from_nu_string_to_value(&concat_string, name_tag) {
+            Ok(x) =>
+                match x {
+                    Tagged { item: Value::Table(list), .. } => {
+                        for l in list {
+                            yield ReturnSuccess::value(l);
+                        }
+                    }
+                    x => yield ReturnSuccess::value(x),
+                }
+            Err(reason) => {
+                if let Some(last_tag) = latest_tag {
+                    yield Err(ShellError::labeled_error_with_secondary(
+                        format!("Could not parse as nu: {}", reason),
+                        "input cannot be parsed as nu",
+                        name_tag,
+                        "value originates from here",
+                        last_tag))
+                }
+            }
+        }
+    };
+
+    Ok(stream.to_output_stream())
+}
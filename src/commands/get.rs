@@ -51,18 +51,23 @@ fn get_member(path: &Tagged<String>, obj: &Tagged<Value>) -> Result<Tagged<Value
 
                             let mut possible_matches: Vec<_> = possibilities
                                 .iter()
-                                .map(|x| {
-                                    (natural::distance::levenshtein_distance(x, &path.item), x)
-                                })
+                                .map(|x| (natural::distance::levenshtein_distance(x, p), x))
                                 .collect();
 
                             possible_matches.sort();
 
-                            return Err(ShellError::labeled_error(
+                            let error = ShellError::labeled_error(
                                 "Unknown column",
-                                format!("did you mean '{}'?", possible_matches[0].1),
+                                format!("there is no column named '{}'", p),
                                 path.tag(),
-                            ));
+                            );
+
+                            return Err(match possible_matches.first() {
+                                Some((_, closest)) => {
+                                    error.with_help(format!("did you mean '{}'?", closest))
+                                }
+                                None => error.with_help("this value doesn't have any columns"),
+                            });
                         }
                     }
                 }
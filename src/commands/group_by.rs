@@ -0,0 +1,83 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+use crate::TaggedDictBuilder;
+
+pub struct GroupBy;
+
+#[derive(Deserialize)]
+pub struct GroupByArgs {
+    column: Tagged<String>,
+}
+
+impl WholeStreamCommand for GroupBy {
+    fn name(&self) -> &str {
+        "group-by"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("group-by").required("column", SyntaxShape::String)
+    }
+
+    fn usage(&self) -> &str {
+        "Creates a new row with a table of the matching rows for each value of the column."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, group_by)?.run()
+    }
+}
+
+pub fn group_by(
+    GroupByArgs { column }: GroupByArgs,
+    RunnableContext { input, name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    let stream = async_stream_block! {
+        let values: Vec<Tagged<Value>> = input.values.collect().await;
+
+        // Groups come out in the order their values were first seen
+        let mut groups: indexmap::IndexMap<String, Vec<Tagged<Value>>> = indexmap::IndexMap::new();
+
+        for value in values {
+            let key = match value.get_data_by_key(&column.item) {
+                Some(key) => key.as_string(),
+                None => {
+                    yield Err(ShellError::labeled_error(
+                        "Unknown column",
+                        "a row doesn't have this column",
+                        column.tag(),
+                    ));
+                    return;
+                }
+            };
+
+            let key = match key {
+                Ok(key) => key,
+                Err(_) => {
+                    yield Err(ShellError::labeled_error(
+                        "Can't group by this column",
+                        "its values need to be text or numbers",
+                        column.tag(),
+                    ));
+                    return;
+                }
+            };
+
+            groups.entry(key).or_insert_with(Vec::new).push(value);
+        }
+
+        let mut row = TaggedDictBuilder::new(name);
+
+        for (key, members) in groups {
+            row.insert(key, Value::Table(members));
+        }
+
+        yield ReturnSuccess::value(row.into_tagged_value());
+    };
+
+    Ok(OutputStream::new(stream))
+}
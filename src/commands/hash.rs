@@ -0,0 +1,66 @@
+use crate::commands::bytes::bytes_of;
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+use sha2::Digest;
+
+pub struct HashCommand;
+
+#[derive(Deserialize)]
+pub struct HashArgs {
+    algorithm: Tagged<String>,
+}
+
+impl WholeStreamCommand for HashCommand {
+    fn name(&self) -> &str {
+        "hash"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("hash")
+            .required("algorithm", SyntaxShape::String)
+            .completions("algorithm", &["md5", "sha256"])
+    }
+
+    fn usage(&self) -> &str {
+        "Hash binary data or text as hex: `hash md5` or `hash sha256`."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, hash)?.run()
+    }
+}
+
+fn hash(
+    HashArgs { algorithm }: HashArgs,
+    RunnableContext { input, name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    match algorithm.item.as_str() {
+        "md5" | "sha256" => {}
+        _ => {
+            return Err(ShellError::labeled_error(
+                "Unknown hash algorithm",
+                "expected md5 or sha256",
+                algorithm.tag(),
+            ))
+        }
+    }
+
+    Ok(input
+        .values
+        .map(move |v| {
+            let bytes = bytes_of(&v, name)?;
+
+            let digest = match algorithm.item.as_str() {
+                "md5" => format!("{:x}", md5::compute(&bytes)),
+                _ => hex::encode(sha2::Sha256::digest(&bytes)),
+            };
+
+            ReturnSuccess::value(Value::string(digest).tagged(v.tag()))
+        })
+        .to_output_stream())
+}
@@ -0,0 +1,71 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::format::hex::hex_dump;
+use crate::prelude::*;
+
+pub struct Hexdump;
+
+#[derive(Deserialize)]
+pub struct HexdumpArgs {
+    skip: Option<Tagged<u64>>,
+    length: Option<Tagged<u64>>,
+}
+
+impl WholeStreamCommand for Hexdump {
+    fn name(&self) -> &str {
+        "hexdump"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("hexdump")
+            .named("skip", SyntaxShape::Number)
+            .named("length", SyntaxShape::Number)
+    }
+
+    fn usage(&self) -> &str {
+        "View binary data as offsets, hex bytes, and ASCII text."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, hexdump)?.run()
+    }
+}
+
+fn hexdump(
+    HexdumpArgs { skip, length }: HexdumpArgs,
+    RunnableContext { input, name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    let skip = skip.map(|s| s.item as usize).unwrap_or(0);
+    let length = length.map(|l| l.item as usize);
+
+    Ok(input
+        .values
+        .map(move |v| {
+            let bytes = match &v.item {
+                Value::Primitive(Primitive::Binary(b)) => b.clone(),
+                Value::Primitive(Primitive::String(s)) => s.as_bytes().to_vec(),
+                _ => {
+                    return Err(ShellError::labeled_error_with_secondary(
+                        "Expected binary data from pipeline",
+                        "requires binary input",
+                        name,
+                        "value originates from here",
+                        v.tag(),
+                    ))
+                }
+            };
+
+            let start = std::cmp::min(skip, bytes.len());
+            let end = match length {
+                Some(length) => std::cmp::min(start.saturating_add(length), bytes.len()),
+                None => bytes.len(),
+            };
+
+            ReturnSuccess::value(Value::string(hex_dump(&bytes[start..end], start)).tagged(name))
+        })
+        .to_output_stream())
+}
@@ -0,0 +1,82 @@
+use crate::cli::History;
+use crate::commands::command::CommandAction;
+use crate::commands::WholeStreamCommand;
+use crate::data::{TaggedDictBuilder, Value};
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct HistoryCommand;
+
+#[derive(Deserialize)]
+pub struct HistoryArgs {
+    run: Option<Tagged<i64>>,
+}
+
+impl WholeStreamCommand for HistoryCommand {
+    fn name(&self) -> &str {
+        "history"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("history").named("run", SyntaxShape::Int)
+    }
+
+    fn usage(&self) -> &str {
+        "Show the lines run in past sessions as a table, or run one again with --run <index>."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, history)?.run()
+    }
+}
+
+pub fn history(
+    HistoryArgs { run }: HistoryArgs,
+    RunnableContext { name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    let entries = History::details().map_err(|err| {
+        ShellError::labeled_error(
+            "Could not read history",
+            format!("could not read history: {}", err),
+            name,
+        )
+    })?;
+
+    if let Some(index) = run {
+        let entry = match entries.get(index.item as usize) {
+            Some(entry) if index.item >= 0 => entry,
+            _ => {
+                return Err(ShellError::labeled_error(
+                    "No such history entry",
+                    format!("history has {} entries", entries.len()),
+                    index.tag(),
+                ))
+            }
+        };
+
+        return Ok(vec![Ok(ReturnSuccess::Action(CommandAction::Source(
+            entry.command.clone(),
+        )))]
+        .into());
+    }
+
+    let rows: VecDeque<Tagged<Value>> = entries
+        .into_iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let mut row = TaggedDictBuilder::new(name);
+            row.insert("index", Value::int(index as i64));
+            row.insert("command", Value::string(entry.command));
+            row.insert("timestamp", Value::system_date(entry.started));
+            row.insert("duration", Value::duration(entry.duration));
+            row.insert("exit_status", Value::int(entry.exit_code));
+            row.into_tagged_value()
+        })
+        .collect();
+
+    Ok(rows.into())
+}
@@ -0,0 +1,115 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::base::Block;
+use crate::errors::ShellError;
+use crate::parser::hir::SyntaxShape;
+use crate::parser::registry;
+use crate::prelude::*;
+
+pub struct If;
+
+impl WholeStreamCommand for If {
+    fn name(&self) -> &str {
+        "if"
+    }
+
+    fn signature(&self) -> registry::Signature {
+        Signature::build("if")
+            .required("condition", SyntaxShape::Block)
+            .required("then", SyntaxShape::Block)
+            .optional("else", SyntaxShape::Block)
+    }
+
+    fn usage(&self) -> &str {
+        "Run the first block if the condition holds, otherwise the second."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &registry::CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        let context = Context::for_block(&args, registry);
+        let starts_pipeline = args.is_first_command;
+        let (input, args) = args.evaluate_once(registry)?.split();
+
+        let condition = expect_block(args.expect_nth(0)?, "if needs a condition")?.clone();
+        let then = expect_block(args.expect_nth(1)?, "expected a block to run")?.clone();
+        let otherwise = match args.nth(2) {
+            Some(otherwise) => Some(expect_block(otherwise, "expected a block to run")?.clone()),
+            None => None,
+        };
+
+        // With input, the condition is checked and a branch run for each row. When `if` starts a
+        // pipeline that happens once, but not when the commands before it produced nothing.
+        let stream = async_stream_block! {
+            let mut input = input.values;
+            let mut first = true;
+
+            loop {
+                let row = input.next().await;
+                let standalone = row.is_none();
+
+                if standalone && !(first && starts_pipeline) {
+                    break;
+                }
+                first = false;
+
+                let it = row
+                    .clone()
+                    .unwrap_or_else(|| Value::nothing().tagged(condition.tag));
+
+                let holds = match holds(&condition, &it) {
+                    Ok(holds) => holds,
+                    Err(err) => {
+                        yield Err(err);
+                        break;
+                    }
+                };
+
+                // Only the branch that's taken is run
+                let branch = match (holds, &otherwise) {
+                    (true, _) => Some(&then),
+                    (false, Some(otherwise)) => Some(otherwise),
+                    (false, None) => None,
+                };
+
+                if let Some(branch) = branch {
+                    let mut output = crate::cli::stream_block(context.clone(), branch, row).values;
+
+                    while let Some(item) = output.next().await {
+                        yield item;
+                    }
+                }
+
+                if standalone {
+                    break;
+                }
+            }
+        };
+
+        Ok(OutputStream::new(stream))
+    }
+}
+
+fn holds(condition: &Block, it: &Tagged<Value>) -> Result<bool, ShellError> {
+    let holds = condition.invoke(it)?;
+
+    match holds.item {
+        Value::Primitive(Primitive::Boolean(b)) => Ok(b),
+        _ => Err(ShellError::labeled_error(
+            "Expected a condition",
+            format!("this is a {}", holds.type_name()),
+            condition.tag,
+        )),
+    }
+}
+
+fn expect_block<'a>(value: &'a Tagged<Value>, label: &str) -> Result<&'a Block, ShellError> {
+    match value {
+        Tagged {
+            item: Value::Block(block),
+            ..
+        } => Ok(block),
+        Tagged { tag, .. } => Err(ShellError::labeled_error("Expected a block", label, *tag)),
+    }
+}
@@ -0,0 +1,117 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::format::filesize::parse_filesize;
+use crate::prelude::*;
+
+pub struct IntoCommand;
+
+#[derive(Deserialize)]
+pub struct IntoArgs {
+    action: Tagged<String>,
+    rest: Vec<Tagged<String>>,
+}
+
+impl WholeStreamCommand for IntoCommand {
+    fn name(&self) -> &str {
+        "into"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("into")
+            .required("action", SyntaxShape::String)
+            .rest(SyntaxShape::String)
+            .completions("action", &["filesize"])
+    }
+
+    fn usage(&self) -> &str {
+        "Convert values: `into filesize` reads numbers and text like 5MB as file sizes. Give column names to convert those columns of each row."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, into)?.run()
+    }
+}
+
+fn into(
+    IntoArgs { action, rest }: IntoArgs,
+    RunnableContext { input, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    if action.item.as_str() != "filesize" {
+        return Err(ShellError::labeled_error(
+            "Unknown into action",
+            "expected filesize",
+            action.tag(),
+        ));
+    }
+
+    Ok(input
+        .values
+        .map(move |v| {
+            let value = if rest.is_empty() {
+                into_filesize(&v)?
+            } else {
+                convert_columns(&v, &rest, into_filesize)?
+            };
+
+            ReturnSuccess::value(value)
+        })
+        .to_output_stream())
+}
+
+/// Apply `convert` to the given columns of a row
+pub(crate) fn convert_columns(
+    row: &Tagged<Value>,
+    columns: &[Tagged<String>],
+    convert: impl Fn(&Tagged<Value>) -> Result<Tagged<Value>, ShellError>,
+) -> Result<Tagged<Value>, ShellError> {
+    let mut dict = match &row.item {
+        Value::Row(dict) => dict.clone(),
+        _ => {
+            return Err(ShellError::labeled_error(
+                "Expected rows from pipeline",
+                "columns can only be picked out of rows",
+                row.tag(),
+            ))
+        }
+    };
+
+    for column in columns {
+        let converted = match dict.entries.get(&column.item) {
+            Some(value) => convert(value)?,
+            None => {
+                return Err(ShellError::labeled_error(
+                    "Unknown column",
+                    format!("there is no column named '{}'", column.item),
+                    column.tag(),
+                ))
+            }
+        };
+
+        dict.entries.insert(column.item.clone(), converted);
+    }
+
+    Ok(Value::Row(dict).tagged(row.tag()))
+}
+
+fn into_filesize(value: &Tagged<Value>) -> Result<Tagged<Value>, ShellError> {
+    let bytes = match &value.item {
+        Value::Primitive(Primitive::Bytes(b)) => Some(*b),
+        Value::Primitive(Primitive::Int(i)) => i.to_u64(),
+        Value::Primitive(Primitive::Decimal(d)) => d.to_f64().map(|d| d.round() as u64),
+        Value::Primitive(Primitive::String(s)) => parse_filesize(s),
+        _ => None,
+    };
+
+    match bytes {
+        Some(bytes) => Ok(Value::bytes(bytes).tagged(value.tag())),
+        None => Err(ShellError::labeled_error(
+            "Could not convert to a file size",
+            "expected a number of bytes, or text like 5MB",
+            value.tag(),
+        )),
+    }
+}
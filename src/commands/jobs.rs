@@ -0,0 +1,46 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::TaggedDictBuilder;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct Jobs;
+
+impl WholeStreamCommand for Jobs {
+    fn name(&self) -> &str {
+        "jobs"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("jobs")
+    }
+
+    fn usage(&self) -> &str {
+        "List the jobs running in the background or suspended with Ctrl-Z."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        jobs(args, registry)
+    }
+}
+
+fn jobs(args: CommandArgs, _registry: &CommandRegistry) -> Result<OutputStream, ShellError> {
+    let tag = args.call_info.name_tag;
+    let mut jobs_out = VecDeque::new();
+
+    for (id, pid, status, command) in crate::env::jobs::list() {
+        let mut dict = TaggedDictBuilder::new(tag);
+
+        dict.insert("id", Value::int(id as i64));
+        dict.insert("pid", Value::int(pid as i64));
+        dict.insert("status", status);
+        dict.insert("command", command);
+
+        jobs_out.push_back(dict.into_tagged_value());
+    }
+
+    Ok(jobs_out.to_output_stream())
+}
@@ -19,10 +19,11 @@ impl PerItemCommand for Open {
         Signature::build(self.name())
             .required("path", SyntaxShape::Path)
             .switch("raw")
+            .switch("keep-errors")
     }
 
     fn usage(&self) -> &str {
-        "Load a file into a cell, convert to table if possible (avoid by appending '--raw')"
+        "Load a file into a cell, convert to table if possible (avoid by appending '--raw'). With '--keep-errors', a failure becomes a row with an error column instead of stopping the pipeline."
     }
 
     fn run(
@@ -55,7 +56,9 @@ fn run(
     let path_buf = path.as_path()?;
     let path_str = path_buf.display().to_string();
     let path_span = path.span();
+    let path_tag = path.tag();
     let has_raw = call_info.args.has("raw");
+    let keep_errors = call_info.args.has("keep-errors");
     let registry = registry.clone();
     let raw_args = raw_args.clone();
 
@@ -64,7 +67,11 @@ fn run(
         let result = fetch(&full_path, &path_str, path_span).await;
 
         if let Err(e) = result {
-            yield Err(e);
+            if keep_errors {
+                yield ReturnSuccess::value(e.into_failed_row(&path_str, path_tag));
+            } else {
+                yield Err(e);
+            }
             return;
         }
         let (file_extension, contents, contents_tag, span_source) = result.unwrap();
@@ -92,6 +99,7 @@ fn run(
             if let Some(converter) = registry.get_command(&command_name) {
                 let new_args = RawCommandArgs {
                     host: raw_args.host,
+                    ctrl_c: raw_args.ctrl_c,
                     shell_manager: raw_args.shell_manager,
                     call_info: UnevaluatedCallInfo {
                         args: crate::parser::hir::Call {
@@ -102,11 +110,18 @@ fn run(
                         source: raw_args.call_info.source,
                         source_map: raw_args.call_info.source_map,
                         name_tag: raw_args.call_info.name_tag,
+                        bindings: raw_args.call_info.bindings,
+                        subexpressions: raw_args.call_info.subexpressions,
                     }
                 };
                 let mut result = converter.run(new_args.with_input(vec![tagged_contents]), &registry, false);
                 let result_vec: Vec<Result<ReturnSuccess, ShellError>> = result.drain_vec().await;
                 for res in result_vec {
+                    if let (Err(e), true) = (&res, keep_errors) {
+                        yield ReturnSuccess::value(e.clone().into_failed_row(&path_str, path_tag));
+                        continue;
+                    }
+
                     match res {
                         Ok(ReturnSuccess::Value(Tagged { item: Value::Table(list), ..})) => {
                             for l in list {
@@ -4,35 +4,136 @@ use crate::parser::registry;
 use crate::prelude::*;
 use derive_new::new;
 use log::trace;
-use serde::{self, Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+pub use crate::plugin::JsonRpc;
+
+pub type NuResult = nu_plugin::NuResult<Result<VecDeque<ReturnValue>, ShellError>, ReturnValue>;
+
+/// How long a plugin gets to answer each request, from `plugin_timeout` in the config (in
+/// seconds). Without one, plugins are waited on until they answer or Ctrl-C is pressed.
+pub(crate) fn plugin_timeout() -> Option<Duration> {
+    let seconds = crate::data::config::config(Tag::unknown())
+        .ok()?
+        .get("plugin_timeout")?
+        .as_string()
+        .ok()?
+        .parse::<f64>()
+        .ok()?;
+
+    if seconds > 0.0 {
+        Some(Duration::from_millis((seconds * 1000.0) as u64))
+    } else {
+        None
+    }
+}
+
+fn timed_out(path: &Path, timeout: Duration) -> ShellError {
+    ShellError::string(format!(
+        "{} didn't answer within {:.1}s and was stopped (see plugin_timeout in the config)",
+        path.display(),
+        timeout.as_millis() as f64 / 1000.0
+    ))
+}
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct JsonRpc<T> {
-    jsonrpc: String,
-    pub method: String,
-    pub params: T,
+/// A plugin started to answer requests one at a time, as when it's asked for its signature
+pub(crate) struct PluginProcess {
+    path: PathBuf,
+    child: Arc<Mutex<std::process::Child>>,
+    stdin: std::process::ChildStdin,
+    stdout: BufReader<std::process::ChildStdout>,
 }
 
-impl<T> JsonRpc<T> {
-    pub fn new<U: Into<String>>(method: U, params: T) -> Self {
-        JsonRpc {
-            jsonrpc: "2.0".into(),
-            method: method.into(),
-            params,
+impl PluginProcess {
+    pub(crate) fn spawn(path: &Path) -> Result<PluginProcess, ShellError> {
+        let mut child = std::process::Command::new(path)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()?;
+
+        let stdin = child.stdin.take().expect("Failed to open stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("Failed to open stdout"));
+
+        Ok(PluginProcess {
+            path: path.to_path_buf(),
+            child: Arc::new(Mutex::new(child)),
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Send one request and read its response
+    pub(crate) fn request<T: DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: impl Serialize,
+    ) -> Result<T, ShellError> {
+        let request = JsonRpc::new(method, params);
+        let request_raw = serde_json::to_string(&request)?;
+        self.stdin
+            .write_all(format!("{}\n", request_raw).as_bytes())?;
+        self.stdin.flush()?;
+
+        let mut input = String::new();
+        match self.stdout.read_line(&mut input) {
+            Ok(count) => {
+                trace!("processing response ({} bytes)", count);
+                trace!("response: {}", input);
+
+                let response = serde_json::from_str::<JsonRpc<Result<T, ShellError>>>(&input);
+                match response {
+                    Ok(jrpc) => jrpc.params,
+                    Err(e) => {
+                        trace!("incompatible plugin {:?}", input);
+                        Err(ShellError::string(format!("Error: {:?}", e)))
+                    }
+                }
+            }
+            Err(e) => Err(ShellError::string(format!("Error: {:?}", e))),
         }
     }
-}
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(tag = "method")]
-#[allow(non_camel_case_types)]
-pub enum NuResult {
-    response {
-        params: Result<VecDeque<ReturnValue>, ShellError>,
-    },
+    /// Make requests, stopping the plugin if they haven't been answered after `timeout`
+    pub(crate) fn with_timeout<T>(
+        &mut self,
+        timeout: Duration,
+        requests: impl FnOnce(&mut PluginProcess) -> Result<T, ShellError>,
+    ) -> Result<T, ShellError> {
+        let (done, finished) = std::sync::mpsc::channel::<()>();
+        let child = self.child.clone();
+
+        let watchdog = std::thread::spawn(move || match finished.recv_timeout(timeout) {
+            Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                let _ = child.lock().unwrap().kill();
+                true
+            }
+            _ => false,
+        });
+
+        let result = requests(self);
+        let _ = done.send(());
+
+        // Killing the plugin is what made the request fail, so say why
+        match (result, watchdog.join()) {
+            (Err(_), Ok(true)) => Err(timed_out(&self.path, timeout)),
+            (result, _) => result,
+        }
+    }
+
+    pub(crate) fn kill(&mut self) {
+        let _ = self.child.lock().unwrap().kill();
+    }
+
+    pub(crate) fn wait(self) {
+        let _ = self.child.lock().unwrap().wait();
+    }
 }
 
 #[derive(new)]
@@ -64,6 +165,22 @@ impl WholeStreamCommand for PluginCommand {
     }
 }
 
+// Filter plugins are fed their input as it arrives and their output is passed on as it comes
+// back, so a plugin in the middle of a long pipeline doesn't hold it up. The host writes
+// `begin_filter`, a `filter` for each row and `end_filter` on one thread while another reads
+// what the plugin sends back:
+//
+//   {"jsonrpc": "2.0", "method": "value", "params": <return value>}
+//   {"jsonrpc": "2.0", "method": "response", "params": {"Ok": [<return value>, ...]}}
+//
+// `value` messages can be sent at any time to pass on a row straight away. Every request is
+// still answered with a `response`, which may carry more rows, so plugins that only ever send
+// a `response` per request keep working.
+//
+// A third thread stops the plugin when Ctrl-C is pressed, or when a request has gone
+// unanswered for longer than `plugin_timeout`. Time spent waiting on the rows coming into the
+// plugin doesn't count, only time the plugin spends on a request.
+
 pub fn filter_plugin(
     path: String,
     args: CommandArgs,
@@ -71,193 +188,140 @@ pub fn filter_plugin(
 ) -> Result<OutputStream, ShellError> {
     trace!("filter_plugin :: {}", path);
 
+    let ctrl_c = args.ctrl_c.clone();
     let args = args.evaluate_once(registry)?;
 
-    let mut child = std::process::Command::new(path)
+    let mut child = std::process::Command::new(&path)
         .stdin(std::process::Stdio::piped())
         .stdout(std::process::Stdio::piped())
-        .spawn()
-        .expect("Failed to spawn child process");
+        .spawn()?;
 
-    let mut bos: VecDeque<Tagged<Value>> = VecDeque::new();
-    bos.push_back(Value::Primitive(Primitive::BeginningOfStream).tagged_unknown());
-
-    let mut eos: VecDeque<Tagged<Value>> = VecDeque::new();
-    eos.push_back(Value::Primitive(Primitive::EndOfStream).tagged_unknown());
+    let mut stdin = child.stdin.take().expect("Failed to open stdin");
+    let stdout = child.stdout.take().expect("Failed to open stdout");
+    let child = Arc::new(Mutex::new(child));
 
     let call_info = args.call_info.clone();
+    let mut input = args.input.values;
 
     trace!("filtering :: {:?}", call_info);
 
-    let stream = bos
-        .chain(args.input.values)
-        .chain(eos)
-        .map(move |v| match v {
-            Tagged {
-                item: Value::Primitive(Primitive::BeginningOfStream),
-                ..
-            } => {
-                let stdin = child.stdin.as_mut().expect("Failed to open stdin");
-                let stdout = child.stdout.as_mut().expect("Failed to open stdout");
-
-                let mut reader = BufReader::new(stdout);
-
-                let request = JsonRpc::new("begin_filter", call_info.clone());
-                let request_raw = serde_json::to_string(&request).unwrap();
-                match stdin.write(format!("{}\n", request_raw).as_bytes()) {
-                    Ok(_) => {}
-                    Err(err) => {
-                        let mut result = VecDeque::new();
-                        result.push_back(Err(ShellError::unexpected(format!("{}", err))));
-                        return result;
-                    }
-                }
+    let (tx, rx) = futures::channel::mpsc::unbounded::<ReturnValue>();
 
-                let mut input = String::new();
-                match reader.read_line(&mut input) {
-                    Ok(_) => {
-                        let response = serde_json::from_str::<NuResult>(&input);
-                        match response {
-                            Ok(NuResult::response { params }) => match params {
-                                Ok(params) => params,
-                                Err(e) => {
-                                    let mut result = VecDeque::new();
-                                    result.push_back(ReturnValue::Err(e));
-                                    result
-                                }
-                            },
-                            Err(e) => {
-                                let mut result = VecDeque::new();
-                                result.push_back(Err(ShellError::string(format!(
-                                    "Error while processing begin_filter response: {:?} {}",
-                                    e, input
-                                ))));
-                                result
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let mut result = VecDeque::new();
-                        result.push_back(Err(ShellError::string(format!(
-                            "Error while reading begin_filter response: {:?}",
-                            e
-                        ))));
-                        result
-                    }
+    // When each request that hasn't been answered yet was sent
+    let pending = Arc::new(Mutex::new(VecDeque::<Instant>::new()));
+    let finished = Arc::new(AtomicBool::new(false));
+
+    {
+        let pending = pending.clone();
+
+        std::thread::spawn(move || {
+            let mut send = |request: String| -> Result<(), ShellError> {
+                pending.lock().unwrap().push_back(Instant::now());
+                stdin.write_all(format!("{}\n", request).as_bytes())?;
+                stdin.flush()?;
+                Ok(())
+            };
+
+            let result: Result<(), ShellError> = futures::executor::block_on(async {
+                send(serde_json::to_string(&JsonRpc::new(
+                    "begin_filter",
+                    call_info,
+                ))?)?;
+
+                while let Some(value) = input.next().await {
+                    send(serde_json::to_string(&JsonRpc::new("filter", value))?)?;
                 }
+
+                let end: JsonRpc<Vec<Value>> = JsonRpc::new("end_filter", vec![]);
+                send(serde_json::to_string(&end)?)
+            });
+
+            // A plugin that stops reading early closes its end of the pipe, which isn't worth
+            // reporting on top of whatever it said about why it stopped
+            if let Err(err) = result {
+                trace!("filter_plugin :: stopped writing: {:?}", err);
             }
-            Tagged {
-                item: Value::Primitive(Primitive::EndOfStream),
-                ..
-            } => {
-                let stdin = child.stdin.as_mut().expect("Failed to open stdin");
-                let stdout = child.stdout.as_mut().expect("Failed to open stdout");
-
-                let mut reader = BufReader::new(stdout);
-
-                let request: JsonRpc<std::vec::Vec<Value>> = JsonRpc::new("end_filter", vec![]);
-                let request_raw = match serde_json::to_string(&request) {
-                    Ok(req) => req,
-                    Err(err) => {
-                        let mut result = VecDeque::new();
-                        result.push_back(Err(ShellError::unexpected(format!("{}", err))));
-                        return result;
-                    }
-                };
+        });
+    }
 
-                let _ = stdin.write(format!("{}\n", request_raw).as_bytes()); // TODO: Handle error
-
-                let mut input = String::new();
-                let result = match reader.read_line(&mut input) {
-                    Ok(_) => {
-                        let response = serde_json::from_str::<NuResult>(&input);
-                        match response {
-                            Ok(NuResult::response { params }) => match params {
-                                Ok(params) => {
-                                    let request: JsonRpc<std::vec::Vec<Value>> =
-                                        JsonRpc::new("quit", vec![]);
-                                    let request_raw = serde_json::to_string(&request).unwrap();
-                                    let _ = stdin.write(format!("{}\n", request_raw).as_bytes()); // TODO: Handle error
-
-                                    params
-                                }
-                                Err(e) => {
-                                    let mut result = VecDeque::new();
-                                    result.push_back(ReturnValue::Err(e));
-                                    result
-                                }
-                            },
-                            Err(e) => {
-                                let mut result = VecDeque::new();
-                                result.push_back(Err(ShellError::string(format!(
-                                    "Error while processing end_filter response: {:?} {}",
-                                    e, input
-                                ))));
-                                result
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let mut result = VecDeque::new();
-                        result.push_back(Err(ShellError::string(format!(
-                            "Error while reading end_filter: {:?}",
-                            e
-                        ))));
-                        result
-                    }
+    {
+        let child = child.clone();
+        let pending = pending.clone();
+        let finished = finished.clone();
+        let tx = tx.clone();
+        let timeout = plugin_timeout();
+        let path = PathBuf::from(&path);
+
+        std::thread::spawn(move || {
+            while !finished.load(Ordering::SeqCst) {
+                std::thread::sleep(Duration::from_millis(50));
+
+                let overdue = match timeout {
+                    Some(timeout) => pending
+                        .lock()
+                        .unwrap()
+                        .front()
+                        .map(|sent| sent.elapsed() > timeout)
+                        .unwrap_or(false),
+                    None => false,
                 };
 
-                let _ = child.wait();
+                if ctrl_c.load(Ordering::SeqCst) || overdue {
+                    let _ = child.lock().unwrap().kill();
 
-                result
-            }
-            _ => {
-                let stdin = child.stdin.as_mut().expect("Failed to open stdin");
-                let stdout = child.stdout.as_mut().expect("Failed to open stdout");
-
-                let mut reader = BufReader::new(stdout);
-
-                let request = JsonRpc::new("filter", v);
-                let request_raw = serde_json::to_string(&request).unwrap();
-                let _ = stdin.write(format!("{}\n", request_raw).as_bytes()); // TODO: Handle error
-
-                let mut input = String::new();
-                match reader.read_line(&mut input) {
-                    Ok(_) => {
-                        let response = serde_json::from_str::<NuResult>(&input);
-                        match response {
-                            Ok(NuResult::response { params }) => match params {
-                                Ok(params) => params,
-                                Err(e) => {
-                                    let mut result = VecDeque::new();
-                                    result.push_back(ReturnValue::Err(e));
-                                    result
-                                }
-                            },
-                            Err(e) => {
-                                let mut result = VecDeque::new();
-                                result.push_back(Err(ShellError::string(format!(
-                                    "Error while processing filter response: {:?} {}",
-                                    e, input
-                                ))));
-                                result
-                            }
-                        }
+                    if let (true, Some(timeout)) = (overdue, timeout) {
+                        let _ = tx.unbounded_send(Err(timed_out(&path, timeout)));
                     }
-                    Err(e) => {
-                        let mut result = VecDeque::new();
-                        result.push_back(Err(ShellError::string(format!(
-                            "Error while reading filter response: {:?}",
-                            e
-                        ))));
-                        result
+                    break;
+                }
+            }
+        });
+    }
+
+    std::thread::spawn(move || {
+        let reader = BufReader::new(stdout);
+
+        for line in reader.lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    let _ = tx.unbounded_send(Err(ShellError::string(format!(
+                        "Error while reading filter response: {:?}",
+                        e
+                    ))));
+                    break;
+                }
+            };
+
+            match serde_json::from_str::<NuResult>(&line) {
+                Ok(NuResult::value { params }) => {
+                    let _ = tx.unbounded_send(params);
+                }
+                Ok(NuResult::response { params: Ok(params) }) => {
+                    pending.lock().unwrap().pop_front();
+                    for value in params {
+                        let _ = tx.unbounded_send(value);
                     }
                 }
+                Ok(NuResult::response { params: Err(e) }) => {
+                    pending.lock().unwrap().pop_front();
+                    let _ = tx.unbounded_send(Err(e));
+                }
+                Err(e) => {
+                    let _ = tx.unbounded_send(Err(ShellError::string(format!(
+                        "Error while processing filter response: {:?} {}",
+                        e, line
+                    ))));
+                }
             }
-        })
-        .flatten();
+        }
+
+        // The plugin has closed its output, so it's exiting or already gone
+        finished.store(true, Ordering::SeqCst);
+        let _ = child.lock().unwrap().wait();
+    });
 
-    Ok(stream.to_output_stream())
+    Ok(rx.to_output_stream())
 }
 
 #[derive(new)]
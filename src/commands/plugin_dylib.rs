@@ -0,0 +1,280 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::parser::registry;
+use crate::plugin::{NuValues, PLUGIN_ABI_VERSION};
+use crate::prelude::*;
+use derive_new::new;
+use log::trace;
+use std::os::raw::c_void;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+// Plugins built as dynamic libraries run inside nu. They're handed nu's own values by pointer,
+// through the C functions `nu_plugin::export_plugin!` exports (see crates/nu-plugin/src/dylib.rs),
+// instead of the messages a plugin running as its own process is sent.
+
+type AbiVersionFn = unsafe extern "C" fn() -> u32;
+type ValuesIdFn = unsafe extern "C" fn() -> u64;
+type NewFn = unsafe extern "C" fn() -> *mut c_void;
+type ConfigFn = unsafe extern "C" fn(*mut c_void, *mut c_void);
+type InputFn = unsafe extern "C" fn(*mut c_void, *mut c_void, *mut c_void);
+type EndFilterFn = unsafe extern "C" fn(*mut c_void, *mut c_void);
+type DropFn = unsafe extern "C" fn(*mut c_void);
+
+pub struct PluginLibrary {
+    path: PathBuf,
+    new: NewFn,
+    config: ConfigFn,
+    begin_filter: InputFn,
+    filter: InputFn,
+    end_filter: EndFilterFn,
+    sink: InputFn,
+    drop: DropFn,
+    // The functions above live in the library, so it's kept open for as long as they are
+    _library: libloading::Library,
+}
+
+impl PluginLibrary {
+    pub fn open(path: &Path) -> Result<PluginLibrary, ShellError> {
+        let library = libloading::Library::new(path).map_err(|e| {
+            ShellError::string(format!("Could not load plugin {}: {}", path.display(), e))
+        })?;
+
+        let missing = |e: std::io::Error| {
+            ShellError::string(format!("{} isn't a nu plugin: {}", path.display(), e))
+        };
+
+        unsafe {
+            let abi_version = *library
+                .get::<AbiVersionFn>(b"nu_plugin_abi_version\0")
+                .map_err(missing)?;
+
+            let abi_version = abi_version();
+            if abi_version != PLUGIN_ABI_VERSION {
+                return Err(ShellError::string(format!(
+                    "{} was built for version {} of the plugin library interface, but this nu uses version {}",
+                    path.display(),
+                    abi_version,
+                    PLUGIN_ABI_VERSION
+                )));
+            }
+
+            // Values are passed as they are, so the plugin has to lay them out as nu does
+            let values_id = *library
+                .get::<ValuesIdFn>(b"nu_plugin_values_id\0")
+                .map_err(missing)?;
+
+            if values_id() != nu_plugin::values_id::<NuValues>() {
+                return Err(ShellError::string(format!(
+                    "{} was built against a different nu, or by a different compiler, than this nu was",
+                    path.display()
+                )));
+            }
+
+            let new = *library.get::<NewFn>(b"nu_plugin_new\0").map_err(missing)?;
+            let config = *library
+                .get::<ConfigFn>(b"nu_plugin_config\0")
+                .map_err(missing)?;
+            let begin_filter = *library
+                .get::<InputFn>(b"nu_plugin_begin_filter\0")
+                .map_err(missing)?;
+            let filter = *library
+                .get::<InputFn>(b"nu_plugin_filter\0")
+                .map_err(missing)?;
+            let end_filter = *library
+                .get::<EndFilterFn>(b"nu_plugin_end_filter\0")
+                .map_err(missing)?;
+            let sink = *library
+                .get::<InputFn>(b"nu_plugin_sink\0")
+                .map_err(missing)?;
+            let drop = *library
+                .get::<DropFn>(b"nu_plugin_drop\0")
+                .map_err(missing)?;
+
+            Ok(PluginLibrary {
+                path: path.to_path_buf(),
+                new,
+                config,
+                begin_filter,
+                filter,
+                end_filter,
+                sink,
+                drop,
+                _library: library,
+            })
+        }
+    }
+
+    /// A fresh copy of the plugin, as starting its executable again would give
+    fn instance(self: &Arc<Self>) -> Result<Instance, ShellError> {
+        let handle = unsafe { (self.new)() };
+
+        if handle.is_null() {
+            return Err(ShellError::string(format!(
+                "{} panicked while starting",
+                self.path.display()
+            )));
+        }
+
+        Ok(Instance {
+            library: self.clone(),
+            handle,
+        })
+    }
+
+    /// Ask the plugin for its signature
+    pub fn signature(self: &Arc<Self>) -> Result<registry::Signature, ShellError> {
+        let plugin = self.instance()?;
+        let mut out: Option<Result<registry::Signature, ShellError>> = None;
+
+        unsafe {
+            (self.config)(plugin.handle, &mut out as *mut _ as *mut c_void);
+        }
+
+        match out {
+            Some(signature) => signature,
+            None => Err(ShellError::string(format!(
+                "{} didn't give its signature",
+                self.path.display()
+            ))),
+        }
+    }
+}
+
+struct Instance {
+    library: Arc<PluginLibrary>,
+    handle: *mut c_void,
+}
+
+// `export_plugin!` only accepts plugins that are `Send`, and the handle is only ever used
+// through `&mut self`, so the instance can move to whichever thread polls the stream next
+unsafe impl Send for Instance {}
+
+impl Instance {
+    /// Hand the plugin an input through one of its functions, giving back the rows it returned
+    fn call<T>(&mut self, function: InputFn, input: T) -> Vec<ReturnValue> {
+        let mut input = Some(input);
+        let mut out: Vec<ReturnValue> = vec![];
+
+        unsafe {
+            function(
+                self.handle,
+                &mut input as *mut Option<T> as *mut c_void,
+                &mut out as *mut Vec<ReturnValue> as *mut c_void,
+            );
+        }
+
+        out
+    }
+
+    fn begin_filter(&mut self, call_info: CallInfo) -> Vec<ReturnValue> {
+        let begin_filter = self.library.begin_filter;
+        self.call(begin_filter, call_info)
+    }
+
+    fn filter(&mut self, row: Tagged<Value>) -> Vec<ReturnValue> {
+        let filter = self.library.filter;
+        self.call(filter, row)
+    }
+
+    fn end_filter(&mut self) -> Vec<ReturnValue> {
+        let mut out: Vec<ReturnValue> = vec![];
+
+        unsafe {
+            (self.library.end_filter)(
+                self.handle,
+                &mut out as *mut Vec<ReturnValue> as *mut c_void,
+            );
+        }
+
+        out
+    }
+
+    fn sink(&mut self, call_info: CallInfo, input: Vec<Tagged<Value>>) -> Vec<ReturnValue> {
+        let sink = self.library.sink;
+        self.call(sink, (call_info, input))
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        unsafe { (self.library.drop)(self.handle) }
+    }
+}
+
+#[derive(new)]
+pub struct DylibPlugin {
+    name: String,
+    library: Arc<PluginLibrary>,
+    config: registry::Signature,
+}
+
+impl WholeStreamCommand for DylibPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn signature(&self) -> registry::Signature {
+        self.config.clone()
+    }
+
+    fn usage(&self) -> &str {
+        &self.config.usage
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        trace!("dylib plugin :: {}", self.library.path.display());
+
+        let ctrl_c = args.ctrl_c.clone();
+        let args = args.evaluate_once(registry)?;
+        let call_info = args.call_info.clone();
+        let mut input = args.input.values;
+        let library = self.library.clone();
+        let is_filter = self.config.is_filter;
+
+        let stream = async_stream_block! {
+            let mut plugin = match library.instance() {
+                Ok(plugin) => plugin,
+                Err(e) => {
+                    yield Err(e);
+                    return;
+                }
+            };
+
+            if is_filter {
+                for value in plugin.begin_filter(call_info) {
+                    yield value;
+                }
+
+                // Plugins in nu's own process can't be stopped partway through a row, so Ctrl-C
+                // is checked between them, and `plugin_timeout` doesn't apply
+                while let Some(row) = input.next().await {
+                    if ctrl_c.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    for value in plugin.filter(row) {
+                        yield value;
+                    }
+                }
+
+                for value in plugin.end_filter() {
+                    yield value;
+                }
+            } else {
+                let input: Vec<Tagged<Value>> = input.collect().await;
+
+                for value in plugin.sink(call_info, input) {
+                    yield value;
+                }
+            }
+        };
+
+        Ok(OutputStream::new(stream))
+    }
+}
@@ -0,0 +1,195 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::{config, Value};
+use crate::errors::ShellError;
+use crate::parser::registry::Signature;
+use crate::prelude::*;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub struct Plugins;
+
+#[derive(Deserialize)]
+pub struct PluginsArgs {
+    action: Tagged<String>,
+    path: Option<Tagged<PathBuf>>,
+}
+
+impl WholeStreamCommand for Plugins {
+    fn name(&self) -> &str {
+        "plugin"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("plugin")
+            .required("action", SyntaxShape::String)
+            .optional("path", SyntaxShape::Path)
+            .completions("action", &["add", "refresh"])
+    }
+
+    fn usage(&self) -> &str {
+        "Manage plugins: `plugin add <path>` loads a plugin now and in future sessions, `plugin refresh` asks every plugin for its signature again."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, plugins)?.run()
+    }
+}
+
+pub fn plugins(
+    PluginsArgs { action, path }: PluginsArgs,
+    RunnableContext {
+        commands,
+        shell_manager,
+        name,
+        ..
+    }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    match (action.item.as_str(), path) {
+        ("add", Some(path)) => {
+            let full_path = PathBuf::from(shell_manager.path()).join(&path.item);
+            let full_path = dunce::canonicalize(&full_path).map_err(|_| {
+                ShellError::labeled_error("Could not find plugin", "no such file", path.tag())
+            })?;
+
+            crate::cli::load_plugin(&full_path, &commands).map_err(|_| {
+                ShellError::labeled_error(
+                    "Could not load plugin",
+                    "not a plugin nu can talk to",
+                    path.tag(),
+                )
+            })?;
+
+            register(&full_path)?;
+
+            Ok(OutputStream::empty())
+        }
+        ("add", None) => Err(ShellError::labeled_error(
+            "Which plugin should be added?",
+            "needs a path",
+            name,
+        )),
+        ("refresh", _) => {
+            // Take out the commands plugins gave us, so they come back with whatever the
+            // plugins say now
+            commands.remove_plugin_commands();
+
+            SignatureCache::clear()?;
+            crate::cli::load_plugins(&commands)?;
+
+            Ok(OutputStream::empty())
+        }
+        _ => Err(ShellError::labeled_error(
+            "Unknown plugin action",
+            "expected add or refresh",
+            action.tag(),
+        )),
+    }
+}
+
+/// Add a plugin to the `plugins` list in the config, so it's loaded at startup
+fn register(plugin: &PathBuf) -> Result<(), ShellError> {
+    let mut result = config::read(Tag::unknown(), &None)?;
+
+    let mut plugins = match result.get("plugins") {
+        Some(Tagged {
+            item: Value::Table(plugins),
+            ..
+        }) => plugins.clone(),
+        _ => vec![],
+    };
+
+    let entry = plugin.to_string_lossy().to_string();
+    if plugins
+        .iter()
+        .any(|p| p.as_string().ok().as_ref() == Some(&entry))
+    {
+        return Ok(());
+    }
+
+    plugins.push(Value::string(entry).tagged_unknown());
+    result.insert(
+        "plugins".to_string(),
+        Value::Table(plugins).tagged_unknown(),
+    );
+
+    config::write(&result, &None)
+}
+
+/// The signatures plugins have reported, so they don't all have to be started each time nu
+/// is. An entry is only used while the plugin binary has the modification time it had when
+/// it was asked.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct SignatureCache {
+    plugins: IndexMap<String, CachedSignature>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedSignature {
+    modified: u64,
+    signature: Signature,
+}
+
+impl SignatureCache {
+    fn path() -> Result<PathBuf, ShellError> {
+        let mut path = config::user_data()?;
+        path.push("plugin-signatures.json");
+        Ok(path)
+    }
+
+    /// The cache as saved, or an empty one if there isn't one that can be read
+    pub(crate) fn load() -> SignatureCache {
+        SignatureCache::path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self) -> Result<(), ShellError> {
+        std::fs::write(SignatureCache::path()?, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub(crate) fn clear() -> Result<(), ShellError> {
+        match std::fs::remove_file(SignatureCache::path()?) {
+            Err(ref e) if e.kind() != std::io::ErrorKind::NotFound => Err(ShellError::string(
+                format!("Could not clear plugin cache: {}", e),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn get(&self, plugin: &Path) -> Option<Signature> {
+        let cached = self.plugins.get(&plugin.to_string_lossy().to_string())?;
+
+        if Some(cached.modified) == modified(plugin) {
+            Some(cached.signature.clone())
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn insert(&mut self, plugin: &Path, signature: Signature) {
+        if let Some(modified) = modified(plugin) {
+            self.plugins.insert(
+                plugin.to_string_lossy().to_string(),
+                CachedSignature {
+                    modified,
+                    signature,
+                },
+            );
+        }
+    }
+}
+
+/// When a file was last modified, in milliseconds since the epoch
+fn modified(file: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(file).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(std::time::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_millis() as u64)
+}
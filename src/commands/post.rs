@@ -100,6 +100,7 @@ fn run(
             if let Some(converter) = registry.get_command(&command_name) {
                 let new_args = RawCommandArgs {
                     host: raw_args.host,
+                    ctrl_c: raw_args.ctrl_c,
                     shell_manager: raw_args.shell_manager,
                     call_info: UnevaluatedCallInfo {
                         args: crate::parser::hir::Call {
@@ -110,6 +111,8 @@ fn run(
                         source: raw_args.call_info.source,
                         source_map: raw_args.call_info.source_map,
                         name_tag: raw_args.call_info.name_tag,
+                        bindings: raw_args.call_info.bindings,
+                        subexpressions: raw_args.call_info.subexpressions,
                     }
                 };
                 let mut result = converter.run(new_args.with_input(vec![tagged_contents]), &registry, false);
@@ -180,6 +183,7 @@ pub async fn post(
                 if let Some(converter) = registry.get_command("to-json") {
                     let new_args = RawCommandArgs {
                         host: raw_args.host,
+                        ctrl_c: raw_args.ctrl_c,
                         shell_manager: raw_args.shell_manager,
                         call_info: UnevaluatedCallInfo {
                             args: crate::parser::hir::Call {
@@ -190,6 +194,8 @@ pub async fn post(
                             source: raw_args.call_info.source,
                             source_map: raw_args.call_info.source_map,
                             name_tag: raw_args.call_info.name_tag,
+                            bindings: raw_args.call_info.bindings,
+                            subexpressions: raw_args.call_info.subexpressions,
                         },
                     };
                     let mut result = converter.run(
@@ -2,20 +2,28 @@ use crate::commands::{UnevaluatedCallInfo, WholeStreamCommand};
 use crate::data::Value;
 use crate::errors::ShellError;
 use crate::prelude::*;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
 
 pub struct Save;
 
 macro_rules! process_string {
     ($input:ident, $name_tag:ident) => {{
-        let mut result_string = String::new();
+        let mut result_bytes: Vec<u8> = Vec::new();
         for res in $input {
             match res {
                 Tagged {
                     item: Value::Primitive(Primitive::String(s)),
                     ..
                 } => {
-                    result_string.push_str(&s);
+                    result_bytes.extend(s.into_bytes());
+                }
+                // Binary data, like a file opened with --raw, is written back unchanged
+                Tagged {
+                    item: Value::Primitive(Primitive::Binary(b)),
+                    ..
+                } => {
+                    result_bytes.extend(b);
                 }
                 _ => {
                     yield core::task::Poll::Ready(Err(ShellError::labeled_error(
@@ -26,7 +34,7 @@ macro_rules! process_string {
                 }
             }
         }
-        Ok(result_string.into_bytes())
+        Ok(result_bytes)
     }};
 }
 
@@ -84,6 +92,7 @@ macro_rules! process_binary_return_success {
 pub struct SaveArgs {
     path: Option<Tagged<PathBuf>>,
     raw: bool,
+    append: bool,
 }
 
 impl WholeStreamCommand for Save {
@@ -95,6 +104,7 @@ impl WholeStreamCommand for Save {
         Signature::build("save")
             .optional("path", SyntaxShape::Path)
             .switch("raw")
+            .switch("append")
     }
 
     fn usage(&self) -> &str {
@@ -114,6 +124,7 @@ fn save(
     SaveArgs {
         path,
         raw: save_raw,
+        append,
     }: SaveArgs,
     RunnableContext {
         input,
@@ -170,6 +181,19 @@ fn save(
             }
         }
 
+        // Another document appended to a structured file would leave it unreadable, so only text
+        // can be appended to one, as lines of their own
+        let appending_text = append && !save_raw && is_structured(&full_path, &registry);
+        if appending_text && !input.iter().all(is_text) {
+            yield Err(ShellError::labeled_error(
+                "Can't append to a structured file",
+                "only text can be appended to this file",
+                name_tag,
+            ));
+            return;
+        }
+        let save_raw = save_raw || appending_text;
+
         let content : Result<Vec<u8>, ShellError> = if !save_raw {
             if let Some(extension) = full_path.extension() {
                 let command_name = format!("to-{}", extension.to_str().unwrap());
@@ -186,6 +210,8 @@ fn save(
                             source: raw_args.call_info.source,
                             source_map: raw_args.call_info.source_map,
                             name_tag: raw_args.call_info.name_tag,
+                            bindings: raw_args.call_info.bindings,
+                            subexpressions: raw_args.call_info.subexpressions,
                         }
                     };
                     let mut result = converter.run(new_args.with_input(input), &registry, false);
@@ -202,11 +228,11 @@ fn save(
                 process_string!(input, name_tag)
             }
         } else {
-            Ok(string_from(&input).into_bytes())
+            Ok(bytes_from(&input))
         };
 
         match content {
-            Ok(save_data) => match std::fs::write(full_path, save_data) {
+            Ok(save_data) => match write(&full_path, save_data, append, save_raw) {
                 Ok(o) => o,
                 Err(e) => yield Err(ShellError::string(e.to_string())),
             },
@@ -218,19 +244,66 @@ fn save(
     Ok(OutputStream::new(stream))
 }
 
-fn string_from(input: &Vec<Tagged<Value>>) -> String {
-    let mut save_data = String::new();
+/// Whether a file is in a format that `save` converts its input to, like JSON or CSV
+fn is_structured(path: &Path, registry: &CommandRegistry) -> bool {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some(extension) => registry.has(&format!("to-{}", extension)),
+        None => false,
+    }
+}
+
+fn is_text(value: &Tagged<Value>) -> bool {
+    match value.item {
+        Value::Primitive(Primitive::String(_)) | Value::Primitive(Primitive::Binary(_)) => true,
+        _ => false,
+    }
+}
+
+/// Write out the saved data. Raw text appended to a file starts on a line of its own, the
+/// same way `--raw` puts each value on its own line.
+fn write(path: &Path, mut data: Vec<u8>, append: bool, raw: bool) -> std::io::Result<()> {
+    if !append {
+        return std::fs::write(path, data);
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+
+    let len = file.metadata()?.len();
+    if raw && !data.is_empty() && len > 0 {
+        let mut last = [0u8];
+        file.seek(SeekFrom::Start(len - 1))?;
+        file.read_exact(&mut last)?;
+
+        if last[0] != b'\n' {
+            data.insert(0, b'\n');
+        }
+    }
+
+    file.write_all(&data)
+}
+
+fn bytes_from(input: &Vec<Tagged<Value>>) -> Vec<u8> {
+    let mut save_data = Vec::new();
 
     if input.len() > 0 {
         let mut first = true;
         for i in input.iter() {
             if !first {
-                save_data.push_str("\n");
+                save_data.push(b'\n');
             } else {
                 first = false;
             }
-            if let Ok(data) = &i.as_string() {
-                save_data.push_str(data);
+            match &i.item {
+                Value::Primitive(Primitive::Binary(b)) => save_data.extend(b),
+                _ => {
+                    if let Ok(data) = &i.as_string() {
+                        save_data.extend(data.as_bytes());
+                    }
+                }
             }
         }
     }
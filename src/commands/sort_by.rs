@@ -7,6 +7,8 @@ pub struct SortBy;
 #[derive(Deserialize)]
 pub struct SortByArgs {
     rest: Vec<Tagged<String>>,
+    reverse: Option<Tagged<String>>,
+    insensitive: bool,
 }
 
 impl WholeStreamCommand for SortBy {
@@ -15,11 +17,14 @@ impl WholeStreamCommand for SortBy {
     }
 
     fn signature(&self) -> Signature {
-        Signature::build("sort-by").rest(SyntaxShape::String)
+        Signature::build("sort-by")
+            .named("reverse", SyntaxShape::String)
+            .switch("insensitive")
+            .rest(SyntaxShape::String)
     }
 
     fn usage(&self) -> &str {
-        "Sort by the given columns."
+        "Sort by the given columns. The one given with --reverse sorts largest first."
     }
 
     fn run(
@@ -32,21 +37,67 @@ impl WholeStreamCommand for SortBy {
 }
 
 fn sort_by(
-    SortByArgs { rest }: SortByArgs,
+    SortByArgs {
+        rest,
+        reverse,
+        insensitive,
+    }: SortByArgs,
     mut context: RunnableContext,
 ) -> Result<OutputStream, ShellError> {
+    // `sort-by type --reverse name` sorts by type and then by name, largest first, wherever the
+    // flag goes. A column given with `--reverse` and among the others keeps its place there.
+    let reversed = reverse.map(|column| column.item);
+
+    let mut columns: Vec<(String, bool)> = rest
+        .into_iter()
+        .map(|column| {
+            let descending = reversed.as_ref() == Some(&column.item);
+            (column.item, descending)
+        })
+        .collect();
+
+    if let Some(reversed) = reversed {
+        if !columns.iter().any(|(column, _)| *column == reversed) {
+            columns.push((reversed, true));
+        }
+    }
+
     Ok(OutputStream::new(async_stream_block! {
-        let mut vec = context.input.drain_vec().await;
+        let vec = context.input.drain_vec().await;
 
-        let calc_key = |item: &Tagged<Value>| {
-            rest.iter()
-                .map(|f| item.get_data_by_key(f).map(|i| i.clone()))
-                .collect::<Vec<Option<Tagged<Value>>>>()
-        };
-        vec.sort_by_cached_key(calc_key);
+        let mut keyed: Vec<(Vec<Option<Value>>, Tagged<Value>)> = vec
+            .into_iter()
+            .map(|item| {
+                let key = columns
+                    .iter()
+                    .map(|(column, _)| sort_key(item.get_data_by_key(column), insensitive))
+                    .collect();
+                (key, item)
+            })
+            .collect();
 
-        for item in vec {
+        keyed.sort_by(|(left, _), (right, _)| {
+            for ((left, right), (_, descending)) in left.iter().zip(right).zip(&columns) {
+                let ordering = if *descending { right.cmp(left) } else { left.cmp(right) };
+
+                if ordering != std::cmp::Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            std::cmp::Ordering::Equal
+        });
+
+        for (_, item) in keyed {
             yield item.into();
         }
     }))
 }
+
+/// What a cell is sorted by: its value, without its tag, and in lowercase if case shouldn't count
+fn sort_key(cell: Option<&Tagged<Value>>, insensitive: bool) -> Option<Value> {
+    cell.map(|cell| match &cell.item {
+        Value::Primitive(Primitive::String(s)) if insensitive => Value::string(s.to_lowercase()),
+        other => other.clone(),
+    })
+}
@@ -0,0 +1,57 @@
+use crate::commands::command::{CommandAction, WholeStreamCommand};
+use crate::errors::ShellError;
+use crate::parser::hir::SyntaxShape;
+use crate::parser::registry::{CommandRegistry, Signature};
+use crate::prelude::*;
+use std::path::PathBuf;
+
+pub struct Source;
+
+impl WholeStreamCommand for Source {
+    fn name(&self) -> &str {
+        "source"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("source").required("path", SyntaxShape::Path)
+    }
+
+    fn usage(&self) -> &str {
+        "Run a script of nu commands in the current session, keeping the aliases, commands and environment it defines"
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        source(args, registry)
+    }
+}
+
+pub fn source(args: CommandArgs, registry: &CommandRegistry) -> Result<OutputStream, ShellError> {
+    let args = args.evaluate_once(registry)?;
+
+    let file = match args.nth(0) {
+        Some(file) => file,
+        None => {
+            return Err(ShellError::labeled_error(
+                "Which script should be run?",
+                "needs a path",
+                args.name_tag(),
+            ))
+        }
+    };
+
+    let path = PathBuf::from(args.shell_manager.path()).join(file.as_path()?);
+
+    let script = std::fs::read_to_string(&path).map_err(|_| {
+        ShellError::labeled_error(
+            "Could not read script",
+            format!("could not read {}", path.display()),
+            file.tag(),
+        )
+    })?;
+
+    Ok(vec![Ok(ReturnSuccess::Action(CommandAction::Source(script)))].into())
+}
@@ -1,5 +1,6 @@
 use crate::commands::WholeStreamCommand;
 use crate::errors::ShellError;
+use crate::format::table::TableOptions;
 use crate::format::TableView;
 use crate::prelude::*;
 use futures_async_stream::async_stream_block;
@@ -7,7 +8,11 @@ use futures_async_stream::async_stream_block;
 pub struct Table;
 
 #[derive(Deserialize)]
-pub struct TableArgs {}
+pub struct TableArgs {
+    expand: bool,
+    width: Option<Tagged<u64>>,
+    truncate: bool,
+}
 
 impl WholeStreamCommand for Table {
     fn name(&self) -> &str {
@@ -16,6 +21,9 @@ impl WholeStreamCommand for Table {
 
     fn signature(&self) -> Signature {
         Signature::build("table")
+            .switch("expand")
+            .named("width", SyntaxShape::Number)
+            .switch("truncate")
     }
 
     fn usage(&self) -> &str {
@@ -31,12 +39,25 @@ impl WholeStreamCommand for Table {
     }
 }
 
-pub fn table(_args: TableArgs, context: RunnableContext) -> Result<OutputStream, ShellError> {
+pub fn table(
+    TableArgs {
+        expand,
+        width,
+        truncate,
+    }: TableArgs,
+    context: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    let options = TableOptions {
+        expand,
+        width: width.map(|w| w.item as usize),
+        truncate,
+    };
+
     let stream = async_stream_block! {
         let input: Vec<Tagged<Value>> = context.input.into_vec().await;
         if input.len() > 0 {
             let mut host = context.host.lock().unwrap();
-            let view = TableView::from_list(&input);
+            let view = TableView::from_list_with_options(&input, &options);
             if let Some(view) = view {
                 handle_unexpected(&mut *host, |host| crate::format::print_view(&view, host));
             }
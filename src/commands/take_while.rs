@@ -0,0 +1,52 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+
+pub struct TakeWhile;
+
+#[derive(Deserialize)]
+pub struct TakeWhileArgs {
+    condition: value::Block,
+}
+
+impl WholeStreamCommand for TakeWhile {
+    fn name(&self) -> &str {
+        "take-while"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("take-while")
+            .required("condition", SyntaxShape::Block)
+            .filter()
+    }
+
+    fn usage(&self) -> &str {
+        "Returns rows while the condition matches, and stops at the first that doesn't."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, take_while)?.run()
+    }
+}
+
+pub fn take_while(
+    TakeWhileArgs { condition }: TakeWhileArgs,
+    RunnableContext { input, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    let objects = input.values.take_while(move |item| {
+        let result = condition.invoke(&item);
+
+        let return_value = match result {
+            Ok(ref v) if v.is_true() => true,
+            _ => false,
+        };
+
+        futures::future::ready(return_value)
+    });
+
+    Ok(objects.from_input_stream())
+}
@@ -33,6 +33,9 @@ pub fn value_to_json_value(v: &Tagged<Value>) -> Result<serde_json::Value, Shell
             serde_json::Number::from(b.to_u64().expect("What about really big numbers")),
         ),
         Value::Primitive(Primitive::Date(d)) => serde_json::Value::String(d.to_string()),
+        Value::Primitive(Primitive::Duration(nanos)) => {
+            serde_json::Value::Number(serde_json::Number::from(*nanos))
+        }
         Value::Primitive(Primitive::EndOfStream) => serde_json::Value::Null,
         Value::Primitive(Primitive::BeginningOfStream) => serde_json::Value::Null,
         Value::Primitive(Primitive::Decimal(f)) => serde_json::Value::Number(
@@ -0,0 +1,142 @@
+use crate::commands::WholeStreamCommand;
+use crate::data::{Primitive, Value};
+use crate::prelude::*;
+
+pub struct ToNu;
+
+impl WholeStreamCommand for ToNu {
+    fn name(&self) -> &str {
+        "to-nu"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("to-nu")
+    }
+
+    fn usage(&self) -> &str {
+        "Convert table into nu literal text that from-nu can read back"
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        to_nu(args, registry)
+    }
+}
+
+pub fn value_to_nu_string(v: &Tagged<Value>) -> Result<String, ShellError> {
+    Ok(match v.item() {
+        Value::Primitive(Primitive::Nothing) => "null".to_string(),
+        Value::Primitive(Primitive::Boolean(b)) => format!("{}", b),
+        Value::Primitive(Primitive::Int(i)) => i.to_string(),
+        Value::Primitive(Primitive::Decimal(d)) => {
+            // Decimals always carry a fraction so they don't come back as ints
+            let d = d.to_string();
+            if d.contains('.') {
+                d
+            } else {
+                format!("{}.0", d)
+            }
+        }
+        Value::Primitive(Primitive::Bytes(b)) => format!("{}b", b),
+        Value::Primitive(Primitive::Date(d)) => d.to_rfc3339(),
+        Value::Primitive(Primitive::Duration(nanos)) => format!("{}ns", nanos),
+        Value::Primitive(Primitive::String(s)) => quote_nu_string(s),
+        Value::Primitive(Primitive::Pattern(s)) => format!("glob{}", quote_nu_string(s)),
+        Value::Primitive(Primitive::Path(p)) => {
+            format!("path{}", quote_nu_string(&p.to_string_lossy()))
+        }
+        Value::Primitive(Primitive::Binary(b)) => {
+            let bytes: Vec<String> = b.iter().map(|x| format!("{:02x}", x)).collect();
+            format!("0x[{}]", bytes.join(" "))
+        }
+        Value::Primitive(Primitive::BeginningOfStream)
+        | Value::Primitive(Primitive::EndOfStream) => {
+            return Err(ShellError::labeled_error(
+                "Can not convert stream markers to nu",
+                "unexpected stream marker",
+                v.tag(),
+            ))
+        }
+        Value::Block(_) => {
+            return Err(ShellError::labeled_error(
+                "Can not convert blocks to nu",
+                "blocks can not be serialized",
+                v.tag(),
+            ))
+        }
+        Value::Table(l) => {
+            let mut out = vec![];
+            for value in l {
+                out.push(value_to_nu_string(value)?);
+            }
+            format!("[{}]", out.join(" "))
+        }
+        Value::Row(o) => {
+            let mut out = vec![];
+            for (k, v) in o.entries.iter() {
+                out.push(format!("{}: {}", quote_nu_key(k), value_to_nu_string(v)?));
+            }
+            format!("{{{}}}", out.join(", "))
+        }
+    })
+}
+
+fn quote_nu_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn quote_nu_key(k: &str) -> String {
+    let is_bare = !k.is_empty()
+        && k.chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+
+    if is_bare {
+        k.to_string()
+    } else {
+        quote_nu_string(k)
+    }
+}
+
+fn to_nu(args: CommandArgs, registry: &CommandRegistry) -> Result<OutputStream, ShellError> {
+    let args = args.evaluate_once(registry)?;
+    let name_tag = args.name_tag();
+    let stream = async_stream_block! {
+        let input: Vec<Tagged<Value>> = args.input.values.collect().await;
+
+        let to_process_input = if input.len() > 1 {
+            let tag = input[0].tag;
+            vec![Tagged { item: Value::Table(input), tag } ]
+        } else if input.len() == 1 {
+            input
+        } else {
+            vec![]
+        };
+
+        for value in to_process_input {
+            match value_to_nu_string(&value) {
+                Ok(x) => yield ReturnSuccess::value(
+                    Value::Primitive(Primitive::String(x)).tagged(name_tag),
+                ),
+                Err(e) => yield Err(e),
+            }
+        }
+    };
+
+    Ok(stream.to_output_stream())
+}
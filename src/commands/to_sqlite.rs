@@ -95,6 +95,7 @@ fn nu_value_to_sqlite_string(v: Value) -> String {
             Primitive::Boolean(true) => "1".into(),
             Primitive::Boolean(_) => "0".into(),
             Primitive::Date(d) => format!("'{}'", d),
+            Primitive::Duration(nanos) => format!("{}", nanos),
             Primitive::Path(p) => format!("'{}'", p.display().to_string().replace("'", "''")),
             Primitive::Binary(u) => format!("x'{}'", encode(u)),
             Primitive::BeginningOfStream => "NULL".into(),
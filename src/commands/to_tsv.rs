@@ -42,6 +42,9 @@ pub fn value_to_tsv_value(v: &Value) -> Value {
         Value::Primitive(Primitive::Path(x)) => Value::Primitive(Primitive::Path(x.clone())),
         Value::Primitive(Primitive::Bytes(b)) => Value::Primitive(Primitive::Bytes(b.clone())),
         Value::Primitive(Primitive::Date(d)) => Value::Primitive(Primitive::Date(d.clone())),
+        Value::Primitive(Primitive::Duration(nanos)) => {
+            Value::Primitive(Primitive::Duration(*nanos))
+        }
         Value::Row(o) => Value::Row(o.clone()),
         Value::Table(l) => Value::Table(l.clone()),
         Value::Block(_) => Value::Primitive(Primitive::Nothing),
@@ -52,6 +55,7 @@ pub fn value_to_tsv_value(v: &Value) -> Value {
 fn to_string_helper(v: &Value) -> Result<String, ShellError> {
     match v {
         Value::Primitive(Primitive::Date(d)) => Ok(d.to_string()),
+        Value::Primitive(Primitive::Duration(nanos)) => Ok(format!("{}", nanos)),
         Value::Primitive(Primitive::Bytes(b)) => Ok(format!("{}", b)),
         Value::Primitive(Primitive::Boolean(_)) => Ok(v.as_string()?),
         Value::Primitive(Primitive::Decimal(_)) => Ok(v.as_string()?),
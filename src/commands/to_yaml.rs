@@ -33,6 +33,9 @@ pub fn value_to_yaml_value(v: &Tagged<Value>) -> Result<serde_yaml::Value, Shell
             serde_yaml::Value::Number(serde_yaml::Number::from(b.to_f64().unwrap()))
         }
         Value::Primitive(Primitive::Date(d)) => serde_yaml::Value::String(d.to_string()),
+        Value::Primitive(Primitive::Duration(nanos)) => {
+            serde_yaml::Value::Number(serde_yaml::Number::from(*nanos))
+        }
         Value::Primitive(Primitive::EndOfStream) => serde_yaml::Value::Null,
         Value::Primitive(Primitive::BeginningOfStream) => serde_yaml::Value::Null,
         Value::Primitive(Primitive::Decimal(f)) => {
@@ -0,0 +1,79 @@
+use crate::commands::WholeStreamCommand;
+use crate::errors::ShellError;
+use crate::prelude::*;
+use crate::TaggedDictBuilder;
+use std::collections::{BTreeMap, BTreeSet};
+
+pub struct Uniq;
+
+#[derive(Deserialize)]
+pub struct UniqArgs {
+    count: bool,
+}
+
+impl WholeStreamCommand for Uniq {
+    fn name(&self) -> &str {
+        "uniq"
+    }
+
+    fn signature(&self) -> Signature {
+        Signature::build("uniq").switch("count")
+    }
+
+    fn usage(&self) -> &str {
+        "Return the unique rows, optionally with how many times each was seen."
+    }
+
+    fn run(
+        &self,
+        args: CommandArgs,
+        registry: &CommandRegistry,
+    ) -> Result<OutputStream, ShellError> {
+        args.process(registry, uniq)?.run()
+    }
+}
+
+fn uniq(
+    UniqArgs { count }: UniqArgs,
+    RunnableContext { input, name, .. }: RunnableContext,
+) -> Result<OutputStream, ShellError> {
+    if !count {
+        // Each value goes on as soon as it's first seen, so a long stream doesn't have to end
+        // before anything comes out
+        let mut seen = BTreeSet::new();
+
+        let objects = input
+            .values
+            .filter(move |item| futures::future::ready(seen.insert(item.item.clone())));
+
+        return Ok(objects.from_input_stream());
+    }
+
+    let stream = async_stream_block! {
+        let values: Vec<Tagged<Value>> = input.values.collect().await;
+
+        // The index of each value's row in `counts`, which keeps the order values were first seen
+        let mut index: BTreeMap<Value, usize> = BTreeMap::new();
+        let mut counts: Vec<(Tagged<Value>, u64)> = vec![];
+
+        for value in values {
+            match index.get(&value.item) {
+                Some(position) => counts[*position].1 += 1,
+                None => {
+                    index.insert(value.item.clone(), counts.len());
+                    counts.push((value, 1));
+                }
+            }
+        }
+
+        for (value, count) in counts {
+            let mut row = TaggedDictBuilder::new(name);
+            row.insert_tagged("value", value);
+            row.insert("count", Value::int(count));
+
+            yield ReturnSuccess::value(row.into_tagged_value());
+        }
+    };
+
+    Ok(OutputStream::new(stream))
+}
@@ -1,4 +1,5 @@
 use crate::commands::{Command, UnevaluatedCallInfo};
+use crate::evaluate::Bindings;
 use crate::parser::hir;
 use crate::prelude::*;
 
@@ -7,6 +8,7 @@ use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -38,12 +40,26 @@ impl SourceMap {
 pub struct CommandRegistry {
     #[new(value = "Arc::new(Mutex::new(IndexMap::default()))")]
     registry: Arc<Mutex<IndexMap<String, Arc<Command>>>>,
+    #[new(value = "Arc::new(Mutex::new(IndexMap::default()))")]
+    aliases: Arc<Mutex<IndexMap<String, String>>>,
+    #[new(value = "Arc::new(Mutex::new(IndexMap::default()))")]
+    variables: Arc<Mutex<IndexMap<String, Tagged<Value>>>>,
+    // The source of each command defined with `def`, by name
+    #[new(value = "Arc::new(Mutex::new(IndexMap::default()))")]
+    definitions: Arc<Mutex<IndexMap<String, String>>>,
+    // The commands plugins added, by name, with the plugin each came from
+    #[new(value = "Arc::new(Mutex::new(IndexMap::default()))")]
+    plugins: Arc<Mutex<IndexMap<String, PathBuf>>>,
 }
 
 impl CommandRegistry {
     pub(crate) fn empty() -> CommandRegistry {
         CommandRegistry {
             registry: Arc::new(Mutex::new(IndexMap::default())),
+            aliases: Arc::new(Mutex::new(IndexMap::default())),
+            variables: Arc::new(Mutex::new(IndexMap::default())),
+            definitions: Arc::new(Mutex::new(IndexMap::default())),
+            plugins: Arc::new(Mutex::new(IndexMap::default())),
         }
     }
 
@@ -64,18 +80,109 @@ impl CommandRegistry {
         registry.insert(name.into(), command);
     }
 
+    pub(crate) fn add_command(&self, command: Arc<Command>) {
+        let mut registry = self.registry.lock().unwrap();
+        registry.insert(command.name().to_string(), command);
+    }
+
+    pub(crate) fn remove_command(&self, name: &str) -> Option<Arc<Command>> {
+        let mut registry = self.registry.lock().unwrap();
+        registry.swap_remove(name)
+    }
+
+    /// Add a command a plugin gives, remembering which plugin it came from
+    pub(crate) fn add_plugin_command(&self, command: Arc<Command>, plugin: &Path) {
+        let mut plugins = self.plugins.lock().unwrap();
+        plugins.insert(command.name().to_string(), plugin.to_path_buf());
+
+        self.add_command(command);
+    }
+
+    /// The plugin a command came from, if it came from one
+    pub(crate) fn plugin_of(&self, name: &str) -> Option<PathBuf> {
+        let plugins = self.plugins.lock().unwrap();
+        plugins.get(name).cloned()
+    }
+
+    /// Take out the commands plugins added, leaving nu's own
+    pub(crate) fn remove_plugin_commands(&self) {
+        let mut plugins = self.plugins.lock().unwrap();
+
+        for (name, _) in plugins.drain(..) {
+            self.remove_command(&name);
+        }
+    }
+
     pub(crate) fn names(&self) -> Vec<String> {
         let registry = self.registry.lock().unwrap();
         registry.keys().cloned().collect()
     }
+
+    pub(crate) fn add_alias(&self, name: impl Into<String>, expansion: impl Into<String>) {
+        let mut aliases = self.aliases.lock().unwrap();
+        aliases.insert(name.into(), expansion.into());
+    }
+
+    pub(crate) fn remove_alias(&self, name: &str) -> Option<String> {
+        let mut aliases = self.aliases.lock().unwrap();
+        aliases.swap_remove(name)
+    }
+
+    pub(crate) fn get_alias(&self, name: &str) -> Option<String> {
+        let aliases = self.aliases.lock().unwrap();
+        aliases.get(name).cloned()
+    }
+
+    pub(crate) fn aliases(&self) -> IndexMap<String, String> {
+        self.aliases.lock().unwrap().clone()
+    }
+
+    /// Set a variable for the rest of the session, like `$args` for a script
+    pub(crate) fn set_variable(&self, name: impl Into<String>, value: Tagged<Value>) {
+        let mut variables = self.variables.lock().unwrap();
+        variables.insert(name.into(), value);
+    }
+
+    pub(crate) fn get_variable(&self, name: &str) -> Option<Tagged<Value>> {
+        let variables = self.variables.lock().unwrap();
+        variables.get(name).cloned()
+    }
+
+    pub(crate) fn variables(&self) -> IndexMap<String, Tagged<Value>> {
+        self.variables.lock().unwrap().clone()
+    }
+
+    /// Remember the `def` a command came from, so it can be defined again elsewhere
+    pub(crate) fn add_definition(&self, name: impl Into<String>, source: impl Into<String>) {
+        let mut definitions = self.definitions.lock().unwrap();
+        definitions.insert(name.into(), source.into());
+    }
+
+    pub(crate) fn definitions(&self) -> IndexMap<String, String> {
+        self.definitions.lock().unwrap().clone()
+    }
 }
 
 #[derive(Clone)]
 pub struct Context {
     registry: CommandRegistry,
     pub(crate) source_map: SourceMap,
-    host: Arc<Mutex<dyn Host + Send>>,
+    host: Arc<Mutex<dyn Host>>,
+    pub(crate) ctrl_c: Arc<AtomicBool>,
     pub(crate) shell_manager: ShellManager,
+    pub(crate) last_exit_code: i32,
+    pub(crate) bindings: Bindings,
+    // What commands whose output had already started on its way down the pipeline leave to be
+    // done once it has finished, shared with the clones that drive the rest of their output
+    unfinished: Arc<Mutex<Unfinished>>,
+}
+
+/// The actions a command asked for after it produced its first value, and the first error one
+/// ran into after that point
+#[derive(Default)]
+pub(crate) struct Unfinished {
+    pub(crate) actions: Vec<(CommandAction, Tag)>,
+    pub(crate) error: Option<ShellError>,
 }
 
 impl Context {
@@ -84,15 +191,50 @@ impl Context {
     }
 
     pub(crate) fn basic() -> Result<Context, Box<dyn Error>> {
-        let registry = CommandRegistry::new();
+        Context::with_registry(CommandRegistry::new())
+    }
+
+    pub(crate) fn with_registry(registry: CommandRegistry) -> Result<Context, Box<dyn Error>> {
         Ok(Context {
             registry: registry.clone(),
             source_map: SourceMap::new(),
             host: Arc::new(Mutex::new(crate::env::host::BasicHost)),
+            ctrl_c: Arc::new(AtomicBool::new(false)),
             shell_manager: ShellManager::basic(registry)?,
+            last_exit_code: 0,
+            bindings: Bindings::default(),
+            unfinished: Arc::new(Mutex::new(Unfinished::default())),
         })
     }
 
+    /// A context for running the pipelines of a command's block, like the body of a `def`, in
+    /// the shell the command runs in and with the variables it was run with
+    pub(crate) fn for_block(args: &CommandArgs, registry: &CommandRegistry) -> Context {
+        Context {
+            registry: registry.clone(),
+            source_map: args.call_info.source_map.clone(),
+            host: args.host.clone(),
+            ctrl_c: args.ctrl_c.clone(),
+            shell_manager: args.shell_manager.clone(),
+            last_exit_code: 0,
+            bindings: args.call_info.bindings.clone(),
+            unfinished: Arc::new(Mutex::new(Unfinished::default())),
+        }
+    }
+
+    /// Load or unload `.nu-env` files to match the current shell's directory
+    pub(crate) fn update_directory_env(&mut self) {
+        let cwd = PathBuf::from(self.shell_manager.path());
+
+        // Value shells have paths too, but not ones on disk
+        if !cwd.is_dir() {
+            return;
+        }
+
+        let mut host = self.host.lock().unwrap();
+        self.shell_manager.update_directory_env(&cwd, &mut *host);
+    }
+
     pub(crate) fn with_host(&mut self, block: impl FnOnce(&mut dyn Host)) {
         let mut host = self.host.lock().unwrap();
 
@@ -109,6 +251,30 @@ impl Context {
         self.source_map.insert(uuid, span_source);
     }
 
+    /// Hold on to an action a command asked for after it had produced some of its output, to be
+    /// carried out by this context once the pipeline has finished
+    pub(crate) fn defer_action(&self, action: CommandAction, name_tag: Tag) {
+        self.unfinished
+            .lock()
+            .unwrap()
+            .actions
+            .push((action, name_tag));
+    }
+
+    /// Hold on to an error from a command that had already produced some of its output, to be
+    /// reported once the pipeline has finished. Only the first is kept.
+    pub(crate) fn stream_failed(&self, err: ShellError) {
+        let mut unfinished = self.unfinished.lock().unwrap();
+
+        if unfinished.error.is_none() {
+            unfinished.error = Some(err);
+        }
+    }
+
+    pub(crate) fn take_unfinished(&self) -> Unfinished {
+        std::mem::replace(&mut *self.unfinished.lock().unwrap(), Unfinished::default())
+    }
+
     pub(crate) fn has_command(&self, name: &str) -> bool {
         self.registry.has(name)
     }
@@ -123,17 +289,27 @@ impl Context {
         name_tag: Tag,
         source_map: SourceMap,
         args: hir::Call,
+        subexpressions: IndexMap<Tag, Tagged<Value>>,
         source: &Text,
         input: InputStream,
         is_first_command: bool,
     ) -> OutputStream {
-        let command_args = self.command_args(args, input, source, source_map, name_tag);
+        let command_args = self.command_args(
+            args,
+            subexpressions,
+            input,
+            source,
+            source_map,
+            name_tag,
+            is_first_command,
+        );
         command.run(command_args, self.registry(), is_first_command)
     }
 
     fn call_info(
         &self,
         args: hir::Call,
+        subexpressions: IndexMap<Tag, Tagged<Value>>,
         source: &Text,
         source_map: SourceMap,
         name_tag: Tag,
@@ -143,22 +319,28 @@ impl Context {
             source: source.clone(),
             source_map,
             name_tag,
+            bindings: self.bindings.clone(),
+            subexpressions,
         }
     }
 
     fn command_args(
         &self,
         args: hir::Call,
+        subexpressions: IndexMap<Tag, Tagged<Value>>,
         input: InputStream,
         source: &Text,
         source_map: SourceMap,
         name_tag: Tag,
+        is_first_command: bool,
     ) -> CommandArgs {
         CommandArgs {
             host: self.host.clone(),
+            ctrl_c: self.ctrl_c.clone(),
             shell_manager: self.shell_manager.clone(),
-            call_info: self.call_info(args, source, source_map, name_tag),
+            call_info: self.call_info(args, subexpressions, source, source_map, name_tag),
             input,
+            is_first_command,
         }
     }
 }
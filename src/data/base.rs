@@ -77,6 +77,8 @@ pub enum Primitive {
     Pattern(String),
     Boolean(bool),
     Date(DateTime<Utc>),
+    /// A length of time, in nanoseconds
+    Duration(i64),
     Path(PathBuf),
     #[serde(with = "serde_bytes")]
     Binary(Vec<u8>),
@@ -114,6 +116,7 @@ impl Primitive {
             String(_) => "string",
             Boolean(_) => "boolean",
             Date(_) => "date",
+            Duration(_) => "duration",
             Binary(_) => "binary",
         }
         .to_string()
@@ -134,6 +137,7 @@ impl Primitive {
             String(string) => write!(f, "{:?}", string),
             Boolean(boolean) => write!(f, "{}", boolean),
             Date(date) => write!(f, "{}", date),
+            Duration(nanos) => write!(f, "{}ns", nanos),
             Binary(binary) => write!(f, "{:?}", binary),
         }
     }
@@ -153,20 +157,7 @@ impl Primitive {
             Primitive::BeginningOfStream => String::new(),
             Primitive::EndOfStream => String::new(),
             Primitive::Path(p) => format!("{}", p.display()),
-            Primitive::Bytes(b) => {
-                let byte = byte_unit::Byte::from_bytes(*b as u128);
-
-                if byte.get_bytes() == 0u128 {
-                    return "—".to_string();
-                }
-
-                let byte = byte.get_appropriate_unit(false);
-
-                match byte.get_unit() {
-                    byte_unit::ByteUnit::B => format!("{} B ", byte.get_value()),
-                    _ => format!("{}", byte.format(1)),
-                }
-            }
+            Primitive::Bytes(b) => crate::format::filesize::format_filesize(*b),
             Primitive::Int(i) => format!("{}", i),
             Primitive::Decimal(decimal) => format!("{}", decimal),
             Primitive::Pattern(s) => format!("{}", s),
@@ -181,18 +172,59 @@ impl Primitive {
             },
             Primitive::Binary(_) => format!("<binary>"),
             Primitive::Date(d) => format!("{}", d.humanize()),
+            Primitive::Duration(nanos) => format_duration(*nanos),
         }
     }
 
     pub fn style(&self) -> &'static str {
         match self {
             Primitive::Bytes(0) => "c", // centre 'missing' indicator
-            Primitive::Int(_) | Primitive::Bytes(_) | Primitive::Decimal(_) => "r",
+            Primitive::Int(_)
+            | Primitive::Bytes(_)
+            | Primitive::Decimal(_)
+            | Primitive::Duration(_) => "r",
             _ => "",
         }
     }
 }
 
+/// A duration the way people read one: its two largest units, like `2min 3s` or `1s 250ms`
+pub(crate) fn format_duration(nanos: i64) -> String {
+    const UNITS: &[(&str, i64)] = &[
+        ("day", 86_400_000_000_000),
+        ("hr", 3_600_000_000_000),
+        ("min", 60_000_000_000),
+        ("s", 1_000_000_000),
+        ("ms", 1_000_000),
+        ("µs", 1_000),
+        ("ns", 1),
+    ];
+
+    if nanos == 0 {
+        return "0s".to_string();
+    }
+
+    let sign = if nanos < 0 { "-" } else { "" };
+    let mut remaining = nanos.checked_abs().unwrap_or(std::i64::MAX);
+
+    let parts: Vec<String> = UNITS
+        .iter()
+        .filter_map(|(unit, size)| {
+            let count = remaining / size;
+            remaining %= size;
+
+            if count > 0 {
+                Some(format!("{}{}", count, unit))
+            } else {
+                None
+            }
+        })
+        .take(2)
+        .collect();
+
+    format!("{}{}", sign, parts.join(" "))
+}
+
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone, new, Serialize)]
 pub struct Operation {
     pub(crate) left: Value,
@@ -202,14 +234,38 @@ pub struct Operation {
 
 #[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone, Serialize, Deserialize, new)]
 pub struct Block {
+    pub(crate) params: Vec<String>,
     pub(crate) expressions: Vec<hir::Expression>,
     pub(crate) source: Text,
     pub(crate) tag: Tag,
+    #[new(default)]
+    pub(crate) captured: Vec<(String, Tagged<Value>)>,
 }
 
 impl Block {
+    /// The pipeline this block holds, if it's one to run rather than expressions to evaluate,
+    /// like the `echo big` of `{ echo big }`
+    pub(crate) fn pipeline(&self) -> Option<&str> {
+        match &self.expressions[..] {
+            [Tagged {
+                item: hir::RawExpression::Subexpression(pipeline),
+                ..
+            }] => Some(pipeline.slice(&self.source)),
+            _ => None,
+        }
+    }
+
     pub fn invoke(&self, value: &Tagged<Value>) -> Result<Tagged<Value>, ShellError> {
-        let scope = Scope::new(value.clone());
+        let mut scope = Scope::new(value.clone());
+
+        for (name, value) in self.captured.iter() {
+            scope.add_var(name.clone(), value.clone());
+        }
+
+        // A block's first parameter names the value it's invoked with, alongside `$it`
+        if let Some(param) = self.params.first() {
+            scope.add_var(param.clone(), value.clone());
+        }
 
         if self.expressions.len() == 0 {
             return Ok(Value::nothing().tagged(self.tag));
@@ -390,6 +446,15 @@ impl std::convert::TryFrom<Option<&Tagged<Value>>> for Switch {
     }
 }
 
+/// Why an arithmetic operator has no value for its operands
+#[derive(Debug)]
+pub(crate) enum ComputeError {
+    /// The types of the operands, which it can't combine
+    Mismatch(String, String),
+    /// The kind of value the result would be, which can't hold anything that large
+    OutOfRange(&'static str),
+}
+
 impl Tagged<Value> {
     pub(crate) fn debug(&self) -> ValueDebug<'_> {
         ValueDebug { value: self }
@@ -583,6 +648,12 @@ impl Value {
         other: &Value,
     ) -> Result<bool, (String, String)> {
         match operator {
+            // Nothing only equals nothing, and can't be ordered against anything
+            Operator::Equal | Operator::NotEqual if self.is_nothing() || other.is_nothing() => {
+                let equal = self.is_nothing() && other.is_nothing();
+
+                Ok(equal == (*operator == Operator::Equal))
+            }
             _ => {
                 let coerced = coerce_compare(self, other)?;
                 let ordering = coerced.compare();
@@ -607,6 +678,114 @@ impl Value {
         }
     }
 
+    /// The result of an arithmetic operator, or why it doesn't have one
+    pub(crate) fn compute(
+        &self,
+        operator: &Operator,
+        other: &Value,
+    ) -> Result<Value, ComputeError> {
+        let mismatch = || ComputeError::Mismatch(self.type_name(), other.type_name());
+        let out_of_range = || ComputeError::OutOfRange("duration");
+
+        let (left, right) = match (self, other) {
+            (Value::Primitive(left), Value::Primitive(right)) => (left, right),
+            _ => return Err(mismatch()),
+        };
+
+        use Primitive::*;
+
+        let result = match (left, right, operator) {
+            (Date(left), Date(right), Operator::Minus) => Duration(
+                left.signed_duration_since(*right)
+                    .num_nanoseconds()
+                    .ok_or_else(out_of_range)?,
+            ),
+            (Date(date), Duration(nanos), Operator::Plus) => Date(
+                date.checked_add_signed(chrono::Duration::nanoseconds(*nanos))
+                    .ok_or(ComputeError::OutOfRange("date"))?,
+            ),
+            (Date(date), Duration(nanos), Operator::Minus) => Date(
+                date.checked_sub_signed(chrono::Duration::nanoseconds(*nanos))
+                    .ok_or(ComputeError::OutOfRange("date"))?,
+            ),
+            (Duration(left), Duration(right), Operator::Plus) => {
+                Duration(left.checked_add(*right).ok_or_else(out_of_range)?)
+            }
+            (Duration(left), Duration(right), Operator::Minus) => {
+                Duration(left.checked_sub(*right).ok_or_else(out_of_range)?)
+            }
+            (Duration(nanos), other, Operator::Multiply)
+            | (other, Duration(nanos), Operator::Multiply) => {
+                let factor = as_number(other).ok_or_else(mismatch)?;
+                Duration(checked_nanos(Number::from(nanos) * factor).ok_or_else(out_of_range)?)
+            }
+            (Duration(nanos), other, Operator::Divide) => {
+                let divisor = as_number(other).ok_or_else(mismatch)?;
+                Duration(checked_nanos(Number::from(nanos) / divisor).ok_or_else(out_of_range)?)
+            }
+            _ => {
+                let (l, r) = match (as_number(left), as_number(right)) {
+                    (Some(l), Some(r)) => (l, r),
+                    _ => return Err(mismatch()),
+                };
+
+                let number = match operator {
+                    Operator::Plus => l + r,
+                    Operator::Minus => l - r,
+                    Operator::Multiply => l * r,
+                    Operator::Divide => l / r,
+                    Operator::Modulo => l % r,
+                    Operator::Pow if l.pow_is_too_large(&r) => {
+                        return Err(ComputeError::OutOfRange("number"))
+                    }
+                    Operator::Pow => l.pow(r).ok_or_else(mismatch)?,
+                    _ => return Err(mismatch()),
+                };
+
+                // A size added to or scaled by a number is still a size, but two sizes divide
+                // into a plain ratio and a power of a size isn't one
+                let is_size = match (left, right, operator) {
+                    (Bytes(_), Bytes(_), Operator::Divide) => false,
+                    (_, _, Operator::Pow) => false,
+                    (Bytes(_), _, _) | (_, Bytes(_), _) => true,
+                    _ => false,
+                };
+
+                let bytes = match &number {
+                    Number::Int(int) if is_size => int.to_u64(),
+                    Number::Decimal(decimal) if is_size => decimal
+                        .to_f64()
+                        .filter(|d| *d >= 0.0)
+                        .map(|d| d.round() as u64),
+                    _ => None,
+                };
+
+                match bytes {
+                    Some(bytes) => Bytes(bytes),
+                    None => Primitive::number(number),
+                }
+            }
+        };
+
+        Ok(Value::Primitive(result))
+    }
+
+    pub(crate) fn is_nothing(&self) -> bool {
+        match self {
+            Value::Primitive(Primitive::Nothing) => true,
+            _ => false,
+        }
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        match self {
+            Value::Primitive(Primitive::Int(int)) => int.is_zero(),
+            Value::Primitive(Primitive::Decimal(decimal)) => decimal.is_zero(),
+            Value::Primitive(Primitive::Bytes(bytes)) => *bytes == 0,
+            _ => false,
+        }
+    }
+
     pub(crate) fn as_string(&self) -> Result<String, ShellError> {
         match self {
             Value::Primitive(Primitive::String(s)) => Ok(s.clone()),
@@ -615,6 +794,7 @@ impl Value {
             Value::Primitive(Primitive::Int(x)) => Ok(format!("{}", x)),
             Value::Primitive(Primitive::Bytes(x)) => Ok(format!("{}", x)),
             Value::Primitive(Primitive::Path(x)) => Ok(format!("{}", x.display())),
+            Value::Primitive(Primitive::Duration(x)) => Ok(format_duration(*x)),
             // TODO: this should definitely be more general with better errors
             other => Err(ShellError::string(format!(
                 "Expected string, got {:?}",
@@ -646,6 +826,10 @@ impl Value {
         Value::Primitive(Primitive::Bytes(s.into()))
     }
 
+    pub fn duration(duration: std::time::Duration) -> Value {
+        Value::Primitive(Primitive::Duration(duration.as_nanos() as i64))
+    }
+
     pub fn int(s: impl Into<BigInt>) -> Value {
         Value::Primitive(Primitive::Int(s.into()))
     }
@@ -736,6 +920,7 @@ enum CompareValues {
     Ints(BigInt, BigInt),
     Decimals(BigDecimal, BigDecimal),
     String(String, String),
+    Booleans(bool, bool),
 }
 
 impl CompareValues {
@@ -744,10 +929,31 @@ impl CompareValues {
             CompareValues::Ints(left, right) => left.cmp(right),
             CompareValues::Decimals(left, right) => left.cmp(right),
             CompareValues::String(left, right) => left.cmp(right),
+            CompareValues::Booleans(left, right) => left.cmp(right),
         }
     }
 }
 
+/// The number as a count of nanoseconds, if a duration can hold it
+pub(crate) fn checked_nanos(number: Number) -> Option<i64> {
+    match number {
+        Number::Int(int) => int.to_i64(),
+        Number::Decimal(decimal) => decimal
+            .to_f64()
+            .filter(|nanos| nanos.abs() < std::i64::MAX as f64)
+            .map(|nanos| nanos.round() as i64),
+    }
+}
+
+fn as_number(primitive: &Primitive) -> Option<Number> {
+    match primitive {
+        Primitive::Int(int) => Some(Number::Int(int.clone())),
+        Primitive::Decimal(decimal) => Some(Number::Decimal(decimal.clone())),
+        Primitive::Bytes(bytes) => Some(Number::Int(BigInt::from(*bytes))),
+        _ => None,
+    }
+}
+
 fn coerce_compare(left: &Value, right: &Value) -> Result<CompareValues, (String, String)> {
     match (left, right) {
         (Value::Primitive(left), Value::Primitive(right)) => coerce_compare_primitive(left, right),
@@ -756,6 +962,10 @@ fn coerce_compare(left: &Value, right: &Value) -> Result<CompareValues, (String,
     }
 }
 
+fn is_boolean_text(text: &str) -> bool {
+    text == "true" || text == "false"
+}
+
 fn coerce_compare_primitive(
     left: &Primitive,
     right: &Primitive,
@@ -780,6 +990,17 @@ fn coerce_compare_primitive(
             CompareValues::Decimals(BigDecimal::from(*left), right.clone())
         }
         (String(left), String(right)) => CompareValues::String(left.clone(), right.clone()),
+        (Boolean(left), Boolean(right)) => CompareValues::Booleans(*left, *right),
+        // Text read from files often spells booleans out
+        (Boolean(left), String(right)) if is_boolean_text(right) => {
+            CompareValues::Booleans(*left, right.as_str() == "true")
+        }
+        (String(left), Boolean(right)) if is_boolean_text(left) => {
+            CompareValues::Booleans(left.as_str() == "true", *right)
+        }
+        (Duration(left), Duration(right)) => {
+            CompareValues::Ints(BigInt::from(*left), BigInt::from(*right))
+        }
         _ => return Err((left.type_name(), right.type_name())),
     })
 }
@@ -5,11 +5,19 @@ use crate::errors::ShellError;
 use crate::prelude::*;
 use app_dirs::*;
 use indexmap::IndexMap;
+use lazy_static::lazy_static;
 use log::trace;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, OpenOptions};
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+lazy_static! {
+    // Settings layered over the config file for the time being, like those a directory's
+    // .nu-env brings in while we're inside it
+    static ref OVERLAY: Mutex<IndexMap<String, Tagged<Value>>> = Mutex::new(IndexMap::new());
+}
 
 #[derive(Deserialize, Serialize)]
 struct Config {
@@ -92,7 +100,105 @@ pub fn read(
 }
 
 pub(crate) fn config(tag: impl Into<Tag>) -> Result<IndexMap<String, Tagged<Value>>, ShellError> {
-    read(tag, &None)
+    let mut config = read(tag, &None)?;
+
+    merge(&mut config, &OVERLAY.lock().unwrap());
+
+    Ok(config)
+}
+
+/// Layer `settings` over `config`. Sections are merged key by key, so `[table] mode = "light"`
+/// leaves the table's other settings alone.
+pub(crate) fn merge(
+    config: &mut IndexMap<String, Tagged<Value>>,
+    settings: &IndexMap<String, Tagged<Value>>,
+) {
+    for (key, value) in settings.iter() {
+        match (config.get_mut(key), &value.item) {
+            (
+                Some(Tagged {
+                    item: Value::Row(section),
+                    ..
+                }),
+                Value::Row(over),
+            ) => merge(&mut section.entries, &over.entries),
+            _ => {
+                config.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// The setting at a path like `table.mode`
+pub(crate) fn get_path<'a>(
+    config: &'a IndexMap<String, Tagged<Value>>,
+    path: &str,
+) -> Option<&'a Tagged<Value>> {
+    let mut keys = path.split('.');
+    let mut current = config.get(keys.next()?)?;
+
+    for key in keys {
+        current = match &current.item {
+            Value::Row(section) => section.entries.get(key)?,
+            _ => return None,
+        };
+    }
+
+    Some(current)
+}
+
+/// Set the setting at a path like `table.mode`, making whatever sections it needs
+pub(crate) fn set_path(
+    config: &mut IndexMap<String, Tagged<Value>>,
+    path: &str,
+    value: Tagged<Value>,
+) {
+    match path.find('.') {
+        None => {
+            config.insert(path.to_string(), value);
+        }
+        Some(dot) => {
+            let (key, rest) = (&path[..dot], &path[dot + 1..]);
+            let tag = value.tag();
+
+            let section = config
+                .entry(key.to_string())
+                .or_insert_with(|| Value::Row(Dictionary::default()).tagged(tag));
+
+            // A plain setting in the way is replaced by the section
+            match section.item {
+                Value::Row(_) => {}
+                _ => *section = Value::Row(Dictionary::default()).tagged(tag),
+            }
+
+            if let Value::Row(section) = &mut section.item {
+                set_path(&mut section.entries, rest, value);
+            }
+        }
+    }
+}
+
+/// Remove the setting at a path like `table.mode`, returning whether it was there
+pub(crate) fn remove_path(config: &mut IndexMap<String, Tagged<Value>>, path: &str) -> bool {
+    match path.find('.') {
+        None => config.swap_remove(path).is_some(),
+        Some(dot) => {
+            let (key, rest) = (&path[..dot], &path[dot + 1..]);
+
+            match config.get_mut(key) {
+                Some(Tagged {
+                    item: Value::Row(section),
+                    ..
+                }) => remove_path(&mut section.entries, rest),
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Replace the settings layered over the config file. They're never written back to it.
+pub(crate) fn set_overlay(overlay: IndexMap<String, Tagged<Value>>) {
+    *OVERLAY.lock().unwrap() = overlay;
 }
 
 pub fn write(
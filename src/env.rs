@@ -1,3 +1,9 @@
+pub(crate) mod directory_env;
 pub(crate) mod host;
+pub(crate) mod jobs;
+pub(crate) mod login;
+pub(crate) mod notify;
+pub(crate) mod terminal_integration;
 
+pub(crate) use self::directory_env::DirectoryEnv;
 pub(crate) use self::host::Host;
@@ -0,0 +1,185 @@
+use crate::commands::from_toml::convert_toml_value_to_nu_value;
+use crate::data::config;
+use crate::prelude::*;
+use crate::shell::shell_manager::ShellManager;
+use indexmap::IndexMap;
+use sha2::Digest;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+// A directory can carry its own environment in a `.nu-env` file. It applies while the current
+// directory is at or below it, and is undone on the way out:
+//
+//   [env]
+//   DATABASE_URL = "postgres://localhost/dev"
+//
+//   [config]
+//   table_mode = "light"
+//
+//   [config.filesize]
+//   binary = true
+//
+// The `[config]` section is the project's own config. It's merged over the user's, section by
+// section, so the example above changes how file sizes are shown without touching the rest of
+// the `[filesize]` settings.
+//
+// Nothing is loaded from a `.nu-env` until the user has said they trust it. Trust is tied to
+// the file's contents, so an edited file has to be trusted again.
+
+const FILE_NAME: &str = ".nu-env";
+
+#[derive(Debug, Clone, Default)]
+pub struct DirectoryEnv {
+    loaded: Option<LoadedEnv>,
+}
+
+#[derive(Debug, Clone)]
+struct LoadedEnv {
+    file: PathBuf,
+    // The variables the .nu-env set, so leaving can take them back off
+    vars: Vec<String>,
+}
+
+impl DirectoryEnv {
+    /// Bring the environment in line with `cwd`: leave the `.nu-env` we were using if it no
+    /// longer applies, and load the nearest one that does. Whatever is loaded belongs to the
+    /// current shell in `shells`.
+    pub(crate) fn enter(&mut self, cwd: &Path, host: &mut dyn Host, shells: &mut ShellManager) {
+        let file = nearest_env_file(cwd);
+
+        if self.loaded.as_ref().map(|l| &l.file) == file.as_ref() {
+            return;
+        }
+
+        if let Some(loaded) = self.loaded.take() {
+            for key in loaded.vars {
+                shells.unset_env(&key);
+            }
+            shells.set_config_overlay(IndexMap::new());
+            host.stdout(&format!("nu-env: unloaded {}", loaded.file.display()));
+        }
+
+        let file = match file {
+            Some(file) => file,
+            None => return,
+        };
+
+        match load(&file, host, shells) {
+            Ok(Some(loaded)) => {
+                host.stdout(&format!("nu-env: loaded {}", file.display()));
+                self.loaded = Some(loaded);
+            }
+            Ok(None) => {}
+            Err(err) => host.stderr(&format!("nu-env: {}", err)),
+        }
+    }
+}
+
+fn nearest_env_file(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .map(|dir| dir.join(FILE_NAME))
+        .find(|file| file.is_file())
+}
+
+fn load(
+    file: &Path,
+    host: &mut dyn Host,
+    shells: &mut ShellManager,
+) -> Result<Option<LoadedEnv>, ShellError> {
+    let contents = std::fs::read_to_string(file)?;
+
+    if !is_trusted(file, &contents)? {
+        if !ask_to_trust(file, host) {
+            return Ok(None);
+        }
+        trust(file, &contents)?;
+    }
+
+    let parsed: toml::Value = toml::from_str(&contents).map_err(|err| {
+        ShellError::string(&format!("Couldn't parse {}:\n{}", file.display(), err))
+    })?;
+
+    let mut vars = vec![];
+
+    if let Some(toml::Value::Table(env)) = parsed.get("env") {
+        for (key, value) in env {
+            let value = match value {
+                toml::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            shells.set_env(key, &value);
+            vars.push(key.clone());
+        }
+    }
+
+    if let Some(settings) = parsed.get("config") {
+        if let Value::Row(settings) = convert_toml_value_to_nu_value(settings, Tag::unknown()).item
+        {
+            shells.set_config_overlay(settings.entries);
+        }
+    }
+
+    Ok(Some(LoadedEnv {
+        file: file.to_path_buf(),
+        vars,
+    }))
+}
+
+fn ask_to_trust(file: &Path, host: &mut dyn Host) -> bool {
+    // With no one at a terminal to answer, as when a script is piped in, stdin isn't ours to
+    // read and nothing is trusted
+    if !crate::cli::stdin_is_terminal() {
+        host.stderr(&format!(
+            "nu-env: not loading {}, as it isn't trusted yet",
+            file.display()
+        ));
+        return false;
+    }
+
+    host.stdout(&format!(
+        "nu-env: {} wants to set environment variables and settings.",
+        file.display()
+    ));
+    print!("Trust it? [y/N] ");
+    let _ = std::io::stdout().flush();
+
+    let mut answer = String::new();
+    match std::io::stdin().read_line(&mut answer) {
+        Ok(_) => {
+            let answer = answer.trim();
+            answer == "y" || answer == "Y" || answer == "yes"
+        }
+        Err(_) => false,
+    }
+}
+
+fn trust_file() -> Result<PathBuf, ShellError> {
+    let mut path = config::user_data()?;
+    path.push("nu-env-trusted.txt");
+    Ok(path)
+}
+
+fn fingerprint(file: &Path, contents: &str) -> String {
+    let digest = sha2::Sha256::digest(contents.as_bytes());
+    format!("{} {}", hex::encode(digest), file.display())
+}
+
+fn is_trusted(file: &Path, contents: &str) -> Result<bool, ShellError> {
+    let trusted = match std::fs::read_to_string(trust_file()?) {
+        Ok(trusted) => trusted,
+        Err(_) => return Ok(false),
+    };
+
+    let fingerprint = fingerprint(file, contents);
+    Ok(trusted.lines().any(|line| line == fingerprint))
+}
+
+fn trust(file: &Path, contents: &str) -> Result<(), ShellError> {
+    let mut trusted = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(trust_file()?)?;
+
+    writeln!(trusted, "{}", fingerprint(file, contents))?;
+    Ok(())
+}
@@ -0,0 +1,473 @@
+use crate::prelude::*;
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+// Commands that run apart from the prompt. An external command suspended with Ctrl-Z is one:
+// the terminal sends SIGTSTP to the whole foreground process group, which nu shares with the
+// commands it runs, so nu catches the signal for itself and lets the command stop. A pipeline
+// ending in `&` is the other, run by a nu of its own in a process group of its own so Ctrl-Z
+// and Ctrl-C at the prompt leave it be. That nu is given the commands defined with `def`, the
+// aliases and the variables of the one that started it. Both kinds are put in this table,
+// where `jobs` lists them, `fg` picks one back up, handing it the terminal, and `bg` lets a
+// stopped one carry on in the background.
+
+/// Set in the environment of a nu running a background job, to the variables it inherits
+const JOB_VARIABLES: &str = "NU_JOB_VARIABLES";
+
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum JobStatus {
+    Running,
+    Stopped,
+    Done(i32),
+}
+
+#[cfg(unix)]
+impl JobStatus {
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            JobStatus::Running => "Running".to_string(),
+            JobStatus::Stopped => "Stopped".to_string(),
+            JobStatus::Done(0) => "Done".to_string(),
+            JobStatus::Done(code) => format!("Exit {}", code),
+        }
+    }
+}
+
+#[cfg(unix)]
+#[derive(Debug)]
+pub(crate) struct Job {
+    pub(crate) id: usize,
+    pub(crate) pid: u32,
+    // Whether the job leads a process group of its own, as one started with `&` does
+    own_group: bool,
+    pub(crate) command: String,
+    pub(crate) status: JobStatus,
+}
+
+#[cfg(unix)]
+impl Job {
+    fn new(pid: u32, command: &str) -> Job {
+        Job {
+            id: 0,
+            pid,
+            own_group: false,
+            command: command.to_string(),
+            status: JobStatus::Running,
+        }
+    }
+}
+
+#[cfg(unix)]
+lazy_static! {
+    static ref JOBS: Mutex<Vec<Job>> = Mutex::new(vec![]);
+}
+
+/// How a command we were waiting on stopped running in the foreground
+#[cfg(unix)]
+enum WaitStatus {
+    Exited(i32),
+    Stopped,
+}
+
+#[cfg(unix)]
+fn add(mut job: Job) -> usize {
+    let mut jobs = JOBS.lock().unwrap();
+
+    if job.id == 0 {
+        job.id = jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+    }
+
+    let id = job.id;
+    jobs.push(job);
+    jobs.sort_by_key(|job| job.id);
+
+    id
+}
+
+/// Take a job out of the table: the one with the given id, or the most recent
+#[cfg(unix)]
+fn take(id: Option<usize>) -> Option<Job> {
+    let mut jobs = JOBS.lock().unwrap();
+
+    let idx = match id {
+        Some(id) => jobs.iter().position(|job| job.id == id)?,
+        None => jobs.len().checked_sub(1)?,
+    };
+
+    Some(jobs.remove(idx))
+}
+
+#[cfg(unix)]
+extern "C" fn ignore_signal(_: libc::c_int) {}
+
+/// Keep Ctrl-Z from stopping nu itself. A handler is installed rather than ignoring the signal
+/// outright, because an ignored signal stays ignored in the commands we start. The nu running a
+/// background job is left to stop along with the rest of the job.
+#[cfg(unix)]
+pub(crate) fn install_signal_handlers() {
+    if std::env::var_os(JOB_VARIABLES).is_some() {
+        return;
+    }
+
+    unsafe {
+        libc::signal(
+            libc::SIGTSTP,
+            ignore_signal as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn install_signal_handlers() {}
+
+#[cfg(unix)]
+fn wait_status(status: libc::c_int) -> Option<WaitStatus> {
+    unsafe {
+        if libc::WIFSTOPPED(status) {
+            Some(WaitStatus::Stopped)
+        } else if libc::WIFEXITED(status) {
+            Some(WaitStatus::Exited(libc::WEXITSTATUS(status)))
+        } else if libc::WIFSIGNALED(status) {
+            Some(WaitStatus::Exited(128 + libc::WTERMSIG(status)))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(unix)]
+fn wait(pid: u32) -> WaitStatus {
+    loop {
+        let mut status: libc::c_int = 0;
+        let result = unsafe { libc::waitpid(pid as libc::pid_t, &mut status, libc::WUNTRACED) };
+
+        if result == -1 {
+            match std::io::Error::last_os_error().raw_os_error() {
+                Some(libc::EINTR) => continue,
+                _ => return WaitStatus::Exited(1),
+            }
+        }
+
+        if let Some(status) = wait_status(status) {
+            return status;
+        }
+    }
+}
+
+/// Check on a job without waiting for it
+#[cfg(unix)]
+fn poll(job: &mut Job) {
+    if let JobStatus::Done(_) = job.status {
+        return;
+    }
+
+    let mut status: libc::c_int = 0;
+    let result = unsafe {
+        libc::waitpid(
+            job.pid as libc::pid_t,
+            &mut status,
+            libc::WUNTRACED | libc::WNOHANG,
+        )
+    };
+
+    job.status = match result {
+        0 => job.status,
+        -1 => JobStatus::Done(1),
+        _ => match wait_status(status) {
+            Some(WaitStatus::Stopped) => JobStatus::Stopped,
+            Some(WaitStatus::Exited(code)) => JobStatus::Done(code),
+            None => job.status,
+        },
+    };
+}
+
+/// Wait for a command running in the foreground, moving it to the job table if it's suspended.
+/// Returns the exit code, which for a suspended command is the one shells use for SIGTSTP.
+#[cfg(unix)]
+pub(crate) fn wait_in_foreground(pid: u32, command: &str) -> i32 {
+    finish_in_foreground(Job::new(pid, command))
+}
+
+#[cfg(unix)]
+fn finish_in_foreground(mut job: Job) -> i32 {
+    let status = wait(job.pid);
+
+    if job.own_group {
+        take_terminal_back();
+    }
+
+    match status {
+        WaitStatus::Exited(code) => code,
+        WaitStatus::Stopped => {
+            job.status = JobStatus::Stopped;
+
+            let command = job.command.clone();
+            let id = add(job);
+            println!();
+            println!("[{}]+  Stopped    {}", id, command);
+            128 + libc::SIGTSTP
+        }
+    }
+}
+
+#[cfg(unix)]
+fn no_such_job(id: Option<usize>, tag: Tag) -> ShellError {
+    ShellError::labeled_error(
+        "No such job",
+        match id {
+            Some(_) => "no job with this id",
+            None => "there are no jobs",
+        },
+        tag,
+    )
+}
+
+/// Make `pgid` the terminal's foreground process group, so that it's the one reading from it
+/// and getting Ctrl-C and Ctrl-Z
+#[cfg(unix)]
+fn give_terminal(pgid: libc::pid_t) {
+    if !crate::cli::stdin_is_terminal() {
+        return;
+    }
+
+    // Once nu isn't in the foreground itself, changing it again would stop nu with SIGTTOU
+    unsafe {
+        let mut block: libc::sigset_t = std::mem::zeroed();
+        let mut previous: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut block);
+        libc::sigaddset(&mut block, libc::SIGTTOU);
+
+        libc::pthread_sigmask(libc::SIG_BLOCK, &block, &mut previous);
+        libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+        libc::pthread_sigmask(libc::SIG_SETMASK, &previous, std::ptr::null_mut());
+    }
+}
+
+#[cfg(unix)]
+fn take_terminal_back() {
+    give_terminal(unsafe { libc::getpgrp() });
+}
+
+/// Let a job carry on. One with a process group of its own is continued as a whole.
+#[cfg(unix)]
+fn resume(job: &Job, tag: Tag) -> Result<(), ShellError> {
+    let target = if job.own_group {
+        -(job.pid as libc::pid_t)
+    } else {
+        job.pid as libc::pid_t
+    };
+
+    if unsafe { libc::kill(target, libc::SIGCONT) } == -1 {
+        return Err(ShellError::labeled_error(
+            "Could not resume job",
+            format!("could not resume {}", job.command),
+            tag,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Bring a job to the foreground, resuming it if it's stopped, and wait for it again
+#[cfg(unix)]
+pub(crate) fn foreground(id: Option<usize>, tag: Tag) -> Result<i32, ShellError> {
+    let mut job = take(id).ok_or_else(|| no_such_job(id, tag))?;
+
+    println!("{}", job.command);
+
+    poll(&mut job);
+    if let JobStatus::Done(code) = job.status {
+        return Ok(code);
+    }
+
+    if job.own_group {
+        give_terminal(job.pid as libc::pid_t);
+    }
+
+    if let Err(err) = resume(&job, tag) {
+        if job.own_group {
+            take_terminal_back();
+        }
+        add(job);
+        return Err(err);
+    }
+    job.status = JobStatus::Running;
+
+    Ok(finish_in_foreground(job))
+}
+
+/// Let a stopped job carry on without waiting for it
+#[cfg(unix)]
+pub(crate) fn background(id: Option<usize>, tag: Tag) -> Result<(), ShellError> {
+    let mut jobs = JOBS.lock().unwrap();
+
+    let job = match id {
+        Some(id) => jobs.iter_mut().find(|job| job.id == id),
+        None => jobs
+            .iter_mut()
+            .rev()
+            .find(|job| job.status == JobStatus::Stopped),
+    }
+    .ok_or_else(|| no_such_job(id, tag))?;
+
+    if job.status == JobStatus::Stopped {
+        resume(job, tag)?;
+        job.status = JobStatus::Running;
+    }
+
+    println!("[{}]+  {} &", job.id, job.command);
+    Ok(())
+}
+
+/// Run `pipeline` in the background with a nu of its own, writing to the terminal as it goes.
+/// That nu starts out with the commands, aliases and variables of `context`.
+#[cfg(unix)]
+pub(crate) fn spawn(pipeline: &str, context: &Context) -> Result<usize, ShellError> {
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    let nu = std::env::current_exe().map_err(|e| ShellError::string(e.to_string()))?;
+    let registry = context.registry();
+
+    let mut script: Vec<String> = registry
+        .aliases()
+        .into_iter()
+        .map(|(name, expansion)| format!("alias {} = {}", name, expansion))
+        .collect();
+    script.extend(registry.definitions().into_iter().map(|(_, def)| def));
+    script.push(pipeline.to_string());
+
+    let variables: indexmap::IndexMap<String, Value> = registry
+        .variables()
+        .into_iter()
+        .map(|(name, value)| (name, value.item))
+        .collect();
+    let variables =
+        serde_json::to_string(&variables).map_err(|e| ShellError::string(e.to_string()))?;
+
+    let mut command = Command::new(nu);
+    command
+        .arg("-c")
+        .arg(script.join("\n"))
+        .current_dir(context.shell_manager.path())
+        .env(JOB_VARIABLES, variables);
+
+    unsafe {
+        command.pre_exec(|| {
+            libc::setpgid(0, 0);
+            Ok(())
+        });
+    }
+
+    let child = command
+        .spawn()
+        .map_err(|e| ShellError::string(format!("Could not start {}: {}", pipeline, e)))?;
+
+    let mut job = Job::new(child.id(), pipeline);
+    job.own_group = true;
+
+    let pid = job.pid;
+    let id = add(job);
+    println!("[{}] {}", id, pid);
+
+    Ok(id)
+}
+
+/// In the nu running a background job, take on the variables of the nu that started it
+pub(crate) fn inherit_variables(registry: &CommandRegistry) {
+    let variables = match std::env::var(JOB_VARIABLES) {
+        Ok(variables) => variables,
+        Err(_) => return,
+    };
+
+    // Anything the job starts in turn gets its own
+    std::env::remove_var(JOB_VARIABLES);
+
+    if let Ok(variables) = serde_json::from_str::<indexmap::IndexMap<String, Value>>(&variables) {
+        for (name, value) in variables {
+            registry.set_variable(name, value.tagged_unknown());
+        }
+    }
+}
+
+/// The jobs in the table, checking first on how each is doing
+#[cfg(unix)]
+pub(crate) fn list() -> Vec<(usize, u32, String, String)> {
+    let mut jobs = JOBS.lock().unwrap();
+
+    jobs.iter_mut()
+        .map(|job| {
+            poll(job);
+            (job.id, job.pid, job.status.describe(), job.command.clone())
+        })
+        .collect()
+}
+
+/// Before the prompt, show which background jobs have finished
+#[cfg(unix)]
+pub(crate) fn report() {
+    let mut jobs = JOBS.lock().unwrap();
+
+    for job in jobs.iter_mut() {
+        poll(job);
+
+        if let JobStatus::Done(_) = job.status {
+            println!(
+                "[{}]+  {}    {}",
+                job.id,
+                job.status.describe(),
+                job.command
+            );
+        }
+    }
+
+    jobs.retain(|job| match job.status {
+        JobStatus::Done(_) => false,
+        _ => true,
+    });
+}
+
+/// A line ending in a single `&`, ahead of any comment, runs in the background. Returns the
+/// pipeline without it.
+pub(crate) fn background_pipeline(line: &str) -> Option<&str> {
+    let line = crate::parser::without_comment(line).trim_end();
+
+    if line.ends_with('&') && !line.ends_with("&&") {
+        Some(line[..line.len() - 1].trim_end())
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn unavailable(tag: Tag) -> ShellError {
+    ShellError::labeled_error(
+        "Job control isn't available on this platform",
+        "no job control",
+        tag,
+    )
+}
+
+#[cfg(not(unix))]
+pub(crate) fn foreground(_id: Option<usize>, tag: Tag) -> Result<i32, ShellError> {
+    Err(unavailable(tag))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn background(_id: Option<usize>, tag: Tag) -> Result<(), ShellError> {
+    Err(unavailable(tag))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn spawn(_pipeline: &str, _context: &Context) -> Result<usize, ShellError> {
+    Err(unavailable(Tag::unknown()))
+}
+
+#[cfg(not(unix))]
+pub(crate) fn list() -> Vec<(usize, u32, String, String)> {
+    vec![]
+}
+
+#[cfg(not(unix))]
+pub(crate) fn report() {}
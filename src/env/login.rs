@@ -0,0 +1,80 @@
+use crate::data::config;
+use crate::prelude::*;
+use std::path::PathBuf;
+
+// Environment setup that happens once as nu starts. Directories to add to PATH can be listed
+// in the config:
+//
+//   path = ["~/.cargo/bin", "/usr/local/bin"]
+//
+// They go in front of the inherited PATH, skipping any that are already on it.
+
+/// Whether we were started the way login(1) and terminal emulators start a login shell: with
+/// `--login`, or with a leading `-` on the program name
+pub(crate) fn is_login_shell(login_flag: bool) -> bool {
+    login_flag
+        || std::env::args_os()
+            .next()
+            .map(|arg0| arg0.to_string_lossy().starts_with('-'))
+            .unwrap_or(false)
+}
+
+pub(crate) fn bootstrap(login: bool) {
+    let level = std::env::var("SHLVL")
+        .ok()
+        .and_then(|level| level.parse::<u32>().ok())
+        .unwrap_or(0);
+    std::env::set_var("SHLVL", (level + 1).to_string());
+
+    if login {
+        if let Ok(exe) = std::env::current_exe() {
+            std::env::set_var("SHELL", exe);
+        }
+
+        // Login shells can't rely on a parent to have set these up
+        if std::env::var_os("HOME").is_none() {
+            if let Some(home) = dirs::home_dir() {
+                std::env::set_var("HOME", home);
+            }
+        }
+        if std::env::var_os("USER").is_none() {
+            if let Ok(user) = std::env::var("LOGNAME") {
+                std::env::set_var("USER", user);
+            }
+        }
+    }
+
+    add_configured_paths();
+}
+
+fn add_configured_paths() {
+    let additions: Vec<PathBuf> = match config::config(Tag::unknown()) {
+        Ok(config) => match config.get("path") {
+            Some(Tagged {
+                item: Value::Table(list),
+                ..
+            }) => list
+                .iter()
+                .filter_map(|path| path.as_string().ok())
+                .map(|path| PathBuf::from(shellexpand::tilde(&path).as_ref()))
+                .collect(),
+            _ => return,
+        },
+        Err(_) => return,
+    };
+
+    let current: Vec<PathBuf> = match std::env::var_os("PATH") {
+        Some(path) => std::env::split_paths(&path).collect(),
+        None => vec![],
+    };
+
+    let mut paths: Vec<PathBuf> = additions
+        .into_iter()
+        .filter(|path| !current.contains(path))
+        .collect();
+    paths.extend(current);
+
+    if let Ok(joined) = std::env::join_paths(paths) {
+        std::env::set_var("PATH", joined);
+    }
+}
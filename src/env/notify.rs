@@ -0,0 +1,137 @@
+use crate::data::config;
+use crate::prelude::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+// Long-running lines can announce that they've finished. This is off unless a threshold is set
+// in the config:
+//
+//   [notify]
+//   after = 10          # seconds a line has to run for
+//   with = "desktop"    # or "bell", the default
+//
+// A bell is left to the terminal, which normally only flags it for windows and tabs that aren't
+// focused. Desktop notifications go through notify-send or osascript, and are skipped when we
+// can tell the terminal window is focused.
+
+#[derive(Debug, Clone, PartialEq)]
+enum NotifyWith {
+    Bell,
+    Desktop,
+}
+
+#[derive(Debug, Clone)]
+struct NotifySettings {
+    after: Duration,
+    with: NotifyWith,
+}
+
+impl NotifySettings {
+    fn from_config() -> Option<NotifySettings> {
+        let config = config::config(Tag::unknown()).ok()?;
+
+        let settings = match config.get("notify") {
+            Some(Tagged {
+                item: Value::Row(settings),
+                ..
+            }) => settings.clone(),
+            _ => return None,
+        };
+
+        let after = settings
+            .entries
+            .get("after")?
+            .as_string()
+            .ok()?
+            .parse::<f64>()
+            .ok()?;
+
+        let with = match settings
+            .entries
+            .get("with")
+            .and_then(|with| with.as_string().ok())
+        {
+            Some(ref with) if with == "desktop" => NotifyWith::Desktop,
+            _ => NotifyWith::Bell,
+        };
+
+        Some(NotifySettings {
+            after: Duration::from_millis((after * 1000.0) as u64),
+            with,
+        })
+    }
+}
+
+/// Called after each line has run, with how long it took
+pub(crate) fn line_finished(line: &str, elapsed: Duration, succeeded: bool) {
+    let settings = match NotifySettings::from_config() {
+        Some(settings) => settings,
+        None => return,
+    };
+
+    if elapsed < settings.after {
+        return;
+    }
+
+    match settings.with {
+        NotifyWith::Bell => {
+            print!("\x07");
+            let _ = std::io::stdout().flush();
+        }
+        NotifyWith::Desktop => {
+            if terminal_is_focused() == Some(true) {
+                return;
+            }
+
+            let title = if succeeded {
+                "nu: finished"
+            } else {
+                "nu: failed"
+            };
+            let body = format!("{} ({}s)", line, elapsed.as_secs());
+
+            desktop_notification(title, &body);
+        }
+    }
+}
+
+/// Whether the window we're running in has focus, where that can be found out. Only X11
+/// terminals that set WINDOWID can be checked, with xdotool.
+fn terminal_is_focused() -> Option<bool> {
+    let window = std::env::var("WINDOWID").ok()?;
+
+    let output = Command::new("xdotool")
+        .arg("getactivewindow")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).trim() == window.trim())
+}
+
+fn desktop_notification(title: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            body, title
+        ));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(body);
+        command
+    };
+
+    // The notification is best effort; a missing notifier shouldn't get in the way of the prompt
+    let _ = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
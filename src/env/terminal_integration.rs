@@ -0,0 +1,76 @@
+use crate::prelude::*;
+use std::io::Write;
+use std::path::Path;
+
+// Escape sequences that tell the terminal what the shell is doing: OSC 7 reports the current
+// directory (so new tabs and splits can open in it) and OSC 133 marks where each prompt,
+// command and its output begin (so the terminal can jump between prompts). Terminals that
+// don't understand them ignore them. They can be turned off with
+// `terminal_integration = false`, and are never written when the output isn't a terminal.
+
+pub(crate) fn enabled() -> bool {
+    if !crate::cli::stdout_is_terminal() {
+        return false;
+    }
+
+    crate::data::config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("terminal_integration").map(|t| t.is_true()))
+        .unwrap_or(true)
+}
+
+fn emit(sequence: &str) {
+    print!("{}", sequence);
+    let _ = std::io::stdout().flush();
+}
+
+/// Just before the prompt is drawn
+pub(crate) fn prompt_start(cwd: &str) {
+    let cwd = Path::new(cwd);
+
+    // Value and help shells have paths that aren't on disk
+    if cwd.is_dir() {
+        if let Ok(mut url) = url::Url::from_file_path(cwd) {
+            let _ = url.set_host(hostname().as_ref().map(|h| h.as_str()));
+            emit(&format!("\x1b]7;{}\x1b\\", url));
+        }
+
+        // Windows Terminal has its own sequence for the working directory
+        if cfg!(windows) {
+            emit(&format!("\x1b]9;9;{}\x1b\\", cwd.display()));
+        }
+    }
+
+    emit("\x1b]133;A\x1b\\");
+}
+
+/// Written at the end of the prompt, where the command line starts. The line editor draws the
+/// prompt itself, so this goes into the highlighted prompt rather than being emitted.
+pub(crate) const PROMPT_END: &str = "\x1b]133;B\x1b\\";
+
+/// Once a line has been entered, before it runs
+pub(crate) fn command_start() {
+    emit("\x1b]133;C\x1b\\");
+}
+
+/// When the line has finished running
+pub(crate) fn command_finished(exit_code: i32) {
+    emit(&format!("\x1b]133;D;{}\x1b\\", exit_code));
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+
+    if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+        return None;
+    }
+
+    let len = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    Some(String::from_utf8_lossy(&buf[..len]).to_string())
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
@@ -1,3 +1,4 @@
+use crate::data::TaggedDictBuilder;
 use crate::prelude::*;
 
 use ansi_term::Color;
@@ -30,9 +31,73 @@ pub enum ArgumentError {
 }
 
 #[derive(Debug, Eq, PartialEq, Clone, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(from = "SerializedShellError")]
 pub struct ShellError {
     error: ProximateShellError,
     cause: Option<Box<ProximateShellError>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<String>,
+}
+
+// Errors come back from plugins either as nu wrote them out, or in a simpler form that plugins
+// not written with nu's own types can produce:
+//
+//   {"message": "Unknown unit", "labels": [{"label": "expected b, kb or mb", "tag": <tag>}],
+//    "help": "units are case sensitive"}
+//
+// where each tag is one a value or argument arrived with, so the error points back into the
+// command line the same way an internal error does. The first label is the primary one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedShellError {
+    Full {
+        error: ProximateShellError,
+        cause: Option<Box<ProximateShellError>>,
+        #[serde(default)]
+        help: Option<String>,
+    },
+    Labeled {
+        message: String,
+        #[serde(default)]
+        labels: Vec<SerializedLabel>,
+        #[serde(default)]
+        help: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct SerializedLabel {
+    label: String,
+    tag: Tag,
+}
+
+impl From<SerializedShellError> for ShellError {
+    fn from(error: SerializedShellError) -> ShellError {
+        match error {
+            SerializedShellError::Full { error, cause, help } => ShellError { error, cause, help },
+            SerializedShellError::Labeled {
+                message,
+                labels,
+                help,
+            } => {
+                let mut diagnostic = Diagnostic::new(Severity::Error, message);
+
+                for (i, SerializedLabel { label, tag }) in labels.into_iter().enumerate() {
+                    let span = if i == 0 {
+                        Label::new_primary(tag)
+                    } else {
+                        Label::new_secondary(tag)
+                    };
+                    diagnostic = diagnostic.with_label(span.with_message(label));
+                }
+
+                ShellError {
+                    help,
+                    ..ShellError::diagnostic(diagnostic)
+                }
+            }
+        }
+    }
 }
 
 impl ToDebug for ShellError {
@@ -151,13 +216,73 @@ impl ShellError {
             }
             nom::Err::Failure(span) | nom::Err::Error(span) => {
                 let diagnostic = Diagnostic::new(Severity::Error, format!("Parse Error"))
-                    .with_label(Label::new_primary(Tag::from(span.0)));
+                    .with_label(
+                        Label::new_primary(Tag::from(span.0))
+                            .with_message("nu couldn't make sense of the line from here"),
+                    );
 
                 ShellError::diagnostic(diagnostic)
             }
         }
     }
 
+    /// Add a hint about how to fix the problem, shown under the error
+    pub fn with_help(self, help: impl Into<String>) -> ShellError {
+        ShellError {
+            help: Some(help.into()),
+            ..self
+        }
+    }
+
+    /// The hint to show under the error: the one it was given, or one that fits its kind
+    pub(crate) fn help(&self) -> Option<String> {
+        if let Some(help) = &self.help {
+            return Some(help.clone());
+        }
+
+        match &self.error {
+            ProximateShellError::InvalidCommand { .. } => Some(format!(
+                "{} lists the commands nu knows",
+                Color::Cyan.paint("help commands")
+            )),
+            ProximateShellError::ArgumentError { command, error, .. } => match error {
+                ArgumentError::InvalidExternalWord => Some(format!(
+                    "prefix it with {} to run the external command of that name",
+                    Color::Cyan.paint("^")
+                )),
+                _ => Some(format!(
+                    "{} shows the flags and parameters it takes",
+                    Color::Cyan.paint(format!("help {}", command))
+                )),
+            },
+            _ => None,
+        }
+    }
+
+    /// The error as a row, for commands that can pass a failure on in their output instead of
+    /// stopping the pipeline (like `open --keep-errors`)
+    pub(crate) fn into_row(self, tag: impl Into<Tag>) -> TaggedDictBuilder {
+        let diagnostic = self.to_diagnostic();
+
+        let labels: Vec<String> = diagnostic
+            .labels
+            .iter()
+            .filter_map(|label| label.message.clone())
+            .collect();
+
+        let mut row = TaggedDictBuilder::new(tag);
+        row.insert("error", Value::string(diagnostic.message));
+        row.insert("label", Value::string(labels.join("; ")));
+        row
+    }
+
+    /// With --keep-errors, a file or url that `open` or `fetch` fails on becomes this row instead
+    pub(crate) fn into_failed_row(self, path: &str, tag: impl Into<Tag>) -> Tagged<Value> {
+        let mut row = self.into_row(tag);
+        row.insert("path", Value::string(path));
+        row.into_tagged_value()
+    }
+
     pub(crate) fn diagnostic(diagnostic: Diagnostic<Tag>) -> ShellError {
         ProximateShellError::Diagnostic(ShellDiagnostic { diagnostic }).start()
     }
@@ -169,7 +294,7 @@ impl ShellError {
             }
             ProximateShellError::InvalidCommand { command } => {
                 Diagnostic::new(Severity::Error, "Invalid command")
-                    .with_label(Label::new_primary(command))
+                    .with_label(Label::new_primary(command).with_message("not a command nu knows"))
             }
             ProximateShellError::MissingValue { tag, reason } => {
                 let mut d = Diagnostic::new(
@@ -198,10 +323,10 @@ impl ShellError {
                         "{} requires {}{}",
                         Color::Cyan.paint(command),
                         Color::Black.bold().paint("--"),
-                        Color::Black.bold().paint(name)
+                        Color::Black.bold().paint(name.clone())
                     ),
                 )
-                .with_label(Label::new_primary(tag)),
+                .with_label(Label::new_primary(tag).with_message(format!("requires --{}", name))),
                 ArgumentError::MissingMandatoryPositional(name) => Diagnostic::new(
                     Severity::Error,
                     format!(
@@ -219,10 +344,12 @@ impl ShellError {
                         "{} is missing value for flag {}{}",
                         Color::Cyan.paint(command),
                         Color::Black.bold().paint("--"),
-                        Color::Black.bold().paint(name)
+                        Color::Black.bold().paint(name.clone())
                     ),
                 )
-                .with_label(Label::new_primary(tag)),
+                .with_label(
+                    Label::new_primary(tag).with_message(format!("--{} needs a value after it", name)),
+                ),
             },
             ProximateShellError::TypeError {
                 expected,
@@ -231,10 +358,11 @@ impl ShellError {
                         item: Some(actual),
                         tag,
                     },
-            } => Diagnostic::new(Severity::Error, "Type Error").with_label(
-                Label::new_primary(tag)
-                    .with_message(format!("Expected {}, found {}", expected, actual)),
-            ),
+            } => Diagnostic::new(Severity::Error, format!("Type Error: expected {}", expected))
+                .with_label(
+                    Label::new_primary(tag)
+                        .with_message(format!("{} found here, but {} was expected", actual, expected)),
+                ),
 
             ProximateShellError::TypeError {
                 expected,
@@ -267,10 +395,10 @@ impl ShellError {
                 problem:
                     Tagged {
                         tag,
-                        ..
+                        item
                     },
             } => Diagnostic::new(Severity::Error, "Syntax Error")
-                .with_label(Label::new_primary(tag).with_message("Unexpected external command")),
+                .with_label(Label::new_primary(tag).with_message(item)),
 
             ProximateShellError::MissingProperty { subpath, expr } => {
                 let subpath = subpath.into_label();
@@ -424,6 +552,7 @@ impl ProximateShellError {
         ShellError {
             cause: None,
             error: self,
+            help: None,
         }
     }
 }
@@ -1,18 +1,33 @@
-use crate::data::base::Block;
+use crate::data::base::{Block, ComputeError};
+use crate::data::TaggedDictBuilder;
 use crate::errors::ArgumentError;
 use crate::parser::{
     hir::{self, Expression, RawExpression},
-    CommandRegistry, Text,
+    unescape_bare, CommandRegistry, Operator, Text,
 };
 use crate::prelude::*;
 use derive_new::new;
 use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref PATTERNS: Mutex<IndexMap<String, Regex>> = Mutex::new(IndexMap::new());
+}
+
+/// Forget the patterns compiled for `=~` and `!~`, so each pipeline compiles its own just once
+pub(crate) fn forget_patterns() {
+    PATTERNS.lock().unwrap().clear();
+}
 
 #[derive(new)]
 pub struct Scope {
     it: Tagged<Value>,
     #[new(default)]
     vars: IndexMap<String, Tagged<Value>>,
+    #[new(default)]
+    subexpressions: IndexMap<Tag, Tagged<Value>>,
 }
 
 impl Scope {
@@ -20,6 +35,7 @@ impl Scope {
         Scope {
             it: Value::nothing().tagged_unknown(),
             vars: IndexMap::new(),
+            subexpressions: IndexMap::new(),
         }
     }
 
@@ -27,10 +43,44 @@ impl Scope {
         Scope {
             it: value,
             vars: IndexMap::new(),
+            subexpressions: IndexMap::new(),
+        }
+    }
+
+    pub(crate) fn add_var(&mut self, name: impl Into<String>, value: Tagged<Value>) {
+        self.vars.insert(name.into(), value);
+    }
+
+    /// This scope for evaluating a command's arguments: with the variables it was run with,
+    /// for those the scope doesn't set itself, and what the subexpressions in its arguments
+    /// produced, by where each is
+    pub(crate) fn for_call(
+        &self,
+        bindings: &Bindings,
+        subexpressions: &IndexMap<Tag, Tagged<Value>>,
+    ) -> Scope {
+        let mut vars = bindings.vars.clone();
+
+        for (name, value) in self.vars.iter() {
+            vars.insert(name.clone(), value.clone());
+        }
+
+        Scope {
+            it: self.it.clone(),
+            vars,
+            subexpressions: subexpressions.clone(),
         }
     }
 }
 
+/// What the commands in a block run by `def`, `if` or `for` are evaluated with, on top of their
+/// own arguments: the variables bound for the block, and the `$it` it runs for, if any
+#[derive(Debug, Clone, Default)]
+pub struct Bindings {
+    pub(crate) it: Option<Tagged<Value>>,
+    pub(crate) vars: IndexMap<String, Tagged<Value>>,
+}
+
 pub(crate) fn evaluate_baseline_expr(
     expr: &Expression,
     registry: &CommandRegistry,
@@ -38,7 +88,7 @@ pub(crate) fn evaluate_baseline_expr(
     source: &Text,
 ) -> Result<Tagged<Value>, ShellError> {
     match &expr.item {
-        RawExpression::Literal(literal) => Ok(evaluate_literal(expr.copy_tag(literal), source)),
+        RawExpression::Literal(literal) => evaluate_literal(expr.copy_tag(literal), source),
         RawExpression::ExternalWord => Err(ShellError::argument_error(
             "Invalid external word",
             ArgumentError::InvalidExternalWord,
@@ -48,17 +98,105 @@ pub(crate) fn evaluate_baseline_expr(
         RawExpression::Synthetic(hir::Synthetic::String(s)) => {
             Ok(Value::string(s).tagged_unknown())
         }
-        RawExpression::Variable(var) => evaluate_reference(var, scope, source),
+        RawExpression::Variable(var) => evaluate_reference(var, registry, scope, source),
+        RawExpression::Subexpression(_) => scope
+            .subexpressions
+            .get(&expr.tag())
+            .cloned()
+            .ok_or_else(|| {
+                ShellError::labeled_error(
+                    "Subexpression not run",
+                    "only a command's arguments can run a pipeline",
+                    expr.tag(),
+                )
+            }),
         RawExpression::ExternalCommand(external) => evaluate_external(external, scope, source),
-        RawExpression::Binary(binary) => {
+        RawExpression::Binary(binary) if binary.op().is_pattern_match() => {
+            let left = evaluate_baseline_expr(binary.left(), registry, scope, source)?;
+            let right = evaluate_baseline_expr(binary.right(), registry, scope, source)?;
+
+            let pattern = compiled_pattern(&right, binary.right().tag())?;
+            let text = left.as_string().map_err(|_| {
+                ShellError::type_error("String", left.type_name().tagged(binary.left().tag()))
+            })?;
+
+            let matched = pattern.is_match(&text);
+
+            Ok(Value::boolean(matched == (binary.op().item == Operator::Match)).tagged(expr.tag()))
+        }
+        RawExpression::Binary(binary) if binary.op().is_membership() => {
             let left = evaluate_baseline_expr(binary.left(), registry, scope, source)?;
             let right = evaluate_baseline_expr(binary.right(), registry, scope, source)?;
 
-            match left.compare(binary.op(), &*right) {
-                Ok(result) => Ok(Value::boolean(result).tagged(expr.tag())),
-                Err((left_type, right_type)) => Err(ShellError::coerce_error(
-                    binary.left().copy_tag(left_type),
-                    binary.right().copy_tag(right_type),
+            let list = match &right.item {
+                Value::Table(list) => list,
+                other => {
+                    return Err(ShellError::type_error(
+                        "List",
+                        other.type_name().tagged(binary.right().tag()),
+                    ))
+                }
+            };
+
+            let found = list
+                .iter()
+                .any(|item| left.compare(&Operator::Equal, item).unwrap_or(false));
+
+            Ok(Value::boolean(found == (binary.op().item == Operator::In)).tagged(expr.tag()))
+        }
+        RawExpression::Not(operand) => {
+            let value = evaluate_condition(operand, registry, scope, source)?;
+            Ok(Value::boolean(!value).tagged(expr.tag()))
+        }
+        RawExpression::Binary(binary) if binary.op().is_logical() => {
+            let left = evaluate_condition(binary.left(), registry, scope, source)?;
+
+            // The right side is only looked at when it could change the answer
+            let result = match binary.op().item {
+                Operator::And if !left => false,
+                Operator::Or if left => true,
+                _ => evaluate_condition(binary.right(), registry, scope, source)?,
+            };
+
+            Ok(Value::boolean(result).tagged(expr.tag()))
+        }
+        RawExpression::Binary(binary) => {
+            let left = evaluate_operand(binary.left(), binary.right(), registry, scope, source)?;
+            let right = evaluate_operand(binary.right(), binary.left(), registry, scope, source)?;
+
+            let result = if binary.op().is_comparison() {
+                left.compare(binary.op(), &*right)
+                    .map(Value::boolean)
+                    .map_err(|(left, right)| ComputeError::Mismatch(left, right))
+            } else {
+                let divides = match binary.op().item {
+                    Operator::Divide | Operator::Modulo => true,
+                    _ => false,
+                };
+
+                if divides && right.is_zero() {
+                    return Err(ShellError::labeled_error(
+                        "Division by zero",
+                        "this is zero",
+                        binary.right().tag(),
+                    ));
+                }
+
+                left.compute(binary.op(), &*right)
+            };
+
+            match result {
+                Ok(value) => Ok(value.tagged(expr.tag())),
+                Err(ComputeError::Mismatch(left_type, right_type)) => {
+                    Err(ShellError::coerce_error(
+                        binary.left().copy_tag(left_type),
+                        binary.right().copy_tag(right_type),
+                    ))
+                }
+                Err(ComputeError::OutOfRange(kind)) => Err(ShellError::labeled_error(
+                    "Result out of range",
+                    format!("the {} this gives is too large", kind),
+                    expr.tag(),
                 )),
             }
         }
@@ -72,11 +210,53 @@ pub(crate) fn evaluate_baseline_expr(
 
             Ok(Value::Table(exprs).tagged(expr.tag()))
         }
-        RawExpression::Block(block) => {
-            Ok(
-                Value::Block(Block::new(block.clone(), source.clone(), expr.tag()))
-                    .tagged(expr.tag()),
-            )
+        RawExpression::Table(headers, rows) => {
+            let mut keys = vec![];
+
+            for header in headers {
+                let value = evaluate_baseline_expr(header, registry, scope, source)?;
+                keys.push(value.as_string()?);
+            }
+
+            let mut table = vec![];
+
+            for row in rows {
+                let mut dict = TaggedDictBuilder::new(expr.tag());
+
+                for (key, cell) in keys.iter().zip(row) {
+                    let value = evaluate_baseline_expr(cell, registry, scope, source)?;
+                    dict.insert_tagged(key.clone(), value);
+                }
+
+                table.push(dict.into_tagged_value());
+            }
+
+            Ok(Value::Table(table).tagged(expr.tag()))
+        }
+        RawExpression::Record(fields) => {
+            let mut dict = TaggedDictBuilder::new(expr.tag());
+
+            for (key, value) in fields {
+                let value = evaluate_baseline_expr(value, registry, scope, source)?;
+                dict.insert_tagged(key.item.clone(), value);
+            }
+
+            Ok(dict.into_tagged_value())
+        }
+        RawExpression::Block(params, block) => {
+            let params = params.iter().map(|p| p.item.clone()).collect();
+
+            let mut block = Block::new(params, block.clone(), source.clone(), expr.tag());
+
+            // A block can be invoked after the variables it mentions have gone, as by a command
+            // in a `for` body, so it keeps them
+            block.captured = scope
+                .vars
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+
+            Ok(Value::Block(block).tagged(expr.tag()))
         }
         RawExpression::Path(path) => {
             let value = evaluate_baseline_expr(path.head(), registry, scope, source)?;
@@ -96,11 +276,18 @@ pub(crate) fn evaluate_baseline_expr(
 
                         possible_matches.sort();
 
-                        return Err(ShellError::labeled_error(
+                        let error = ShellError::labeled_error(
                             "Unknown column",
-                            format!("did you mean '{}'?", possible_matches[0].1),
+                            format!("there is no column named '{}'", name),
                             expr.tag(),
-                        ));
+                        );
+
+                        return Err(match possible_matches.first() {
+                            Some((_, closest)) => {
+                                error.with_help(format!("did you mean '{}'?", closest))
+                            }
+                            None => error.with_help("this value doesn't have any columns"),
+                        });
                     }
                     Some(next) => {
                         item = next.clone().item.tagged(expr.tag());
@@ -110,24 +297,133 @@ pub(crate) fn evaluate_baseline_expr(
 
             Ok(item.item().clone().tagged(expr.tag()))
         }
+        RawExpression::Interpolation(parts) => {
+            let mut string = String::new();
+
+            for part in parts {
+                let value = evaluate_baseline_expr(part, registry, scope, source)?;
+
+                match value.as_string() {
+                    Ok(s) => string.push_str(&s),
+                    Err(_) => {
+                        return Err(ShellError::labeled_error(
+                            "Can't put this in a string",
+                            format!("this is a {}", value.type_name()),
+                            part.tag(),
+                        ))
+                    }
+                }
+            }
+
+            Ok(Value::string(string).tagged(expr.tag()))
+        }
         RawExpression::Boolean(_boolean) => unimplemented!(),
     }
 }
 
-fn evaluate_literal(literal: Tagged<&hir::Literal>, source: &Text) -> Tagged<Value> {
+fn compiled_pattern(pattern: &Value, tag: Tag) -> Result<Regex, ShellError> {
+    let text = pattern
+        .as_string()
+        .map_err(|_| ShellError::type_error("String", pattern.type_name().tagged(tag)))?;
+
+    let mut patterns = PATTERNS.lock().unwrap();
+
+    if let Some(regex) = patterns.get(&text) {
+        return Ok(regex.clone());
+    }
+
+    let regex = Regex::new(&text).map_err(|err| {
+        ShellError::labeled_error("Invalid regular expression", err.to_string(), tag)
+    })?;
+
+    patterns.insert(text, regex.clone());
+
+    Ok(regex)
+}
+
+fn evaluate_condition(
+    expr: &Expression,
+    registry: &CommandRegistry,
+    scope: &Scope,
+    source: &Text,
+) -> Result<bool, ShellError> {
+    let value = evaluate_baseline_expr(expr, registry, scope, source)?;
+
+    match value.item {
+        Value::Primitive(Primitive::Boolean(b)) => Ok(b),
+        other => Err(ShellError::type_error(
+            "Boolean",
+            other.type_name().tagged(expr.tag()),
+        )),
+    }
+}
+
+/// One side of a binary expression. A missing cell is nothing when it's compared with `null`, so
+/// `where parent == null` finds the rows without one.
+fn evaluate_operand(
+    expr: &Expression,
+    other: &Expression,
+    registry: &CommandRegistry,
+    scope: &Scope,
+    source: &Text,
+) -> Result<Tagged<Value>, ShellError> {
+    match expr.item() {
+        RawExpression::Path(path) if is_null(other) => {
+            let mut item = evaluate_baseline_expr(path.head(), registry, scope, source)?;
+
+            for name in path.tail() {
+                let next = item.get_data_by_key(name);
+
+                match next {
+                    None => return Ok(Value::nothing().tagged(expr.tag())),
+                    Some(next) => {
+                        item = next.clone().item.tagged(expr.tag());
+                    }
+                }
+            }
+
+            Ok(item.item().clone().tagged(expr.tag()))
+        }
+        _ => evaluate_baseline_expr(expr, registry, scope, source),
+    }
+}
+
+fn is_null(expr: &Expression) -> bool {
+    match expr.item() {
+        RawExpression::Literal(hir::Literal::Nothing) => true,
+        _ => false,
+    }
+}
+
+fn evaluate_literal(
+    literal: Tagged<&hir::Literal>,
+    source: &Text,
+) -> Result<Tagged<Value>, ShellError> {
     let result = match literal.item {
         hir::Literal::Number(int) => int.into(),
-        hir::Literal::Size(int, unit) => unit.compute(int),
+        hir::Literal::Size(int, unit) => match unit.compute(int) {
+            Some(value) => value,
+            None => {
+                return Err(ShellError::labeled_error(
+                    "Duration out of range",
+                    "a duration can be at most about 292 years",
+                    literal.tag(),
+                ))
+            }
+        },
+        hir::Literal::Boolean(b) => Value::boolean(*b),
+        hir::Literal::Nothing => Value::nothing(),
         hir::Literal::String(tag) => Value::string(tag.slice(source)),
         hir::Literal::GlobPattern => Value::pattern(literal.tag().slice(source)),
-        hir::Literal::Bare => Value::string(literal.tag().slice(source)),
+        hir::Literal::Bare => Value::string(unescape_bare(literal.tag().slice(source))),
     };
 
-    literal.map(|_| result)
+    Ok(literal.map(|_| result))
 }
 
 fn evaluate_reference(
     name: &hir::Variable,
+    registry: &CommandRegistry,
     scope: &Scope,
     source: &Text,
 ) -> Result<Tagged<Value>, ShellError> {
@@ -137,10 +433,40 @@ fn evaluate_reference(
             .vars
             .get(tag.slice(source))
             .map(|v| v.clone())
+            .or_else(|| registry.get_variable(tag.slice(source)))
+            .or_else(|| builtin_variable(tag.slice(source), *tag))
             .unwrap_or_else(|| Value::nothing().tagged(*tag))),
     }
 }
 
+/// `$env` is the environment nu is running in, and `$nu` holds it as `$nu.env`
+fn builtin_variable(name: &str, tag: Tag) -> Option<Tagged<Value>> {
+    match name {
+        "env" => Some(environment(tag)),
+        "nu" => {
+            let mut nu = TaggedDictBuilder::new(tag);
+            nu.insert_tagged("env", environment(tag));
+            Some(nu.into_tagged_value())
+        }
+        _ => None,
+    }
+}
+
+fn environment(tag: Tag) -> Tagged<Value> {
+    let mut env = TaggedDictBuilder::new(tag);
+
+    // A name or value that isn't valid UTF-8 gets replacement characters, since `vars()` would
+    // panic on it
+    for (key, value) in std::env::vars_os() {
+        env.insert(
+            key.to_string_lossy().into_owned(),
+            Value::string(value.to_string_lossy()),
+        );
+    }
+
+    env.into_tagged_value()
+}
+
 fn evaluate_external(
     external: &hir::ExternalCommand,
     _scope: &Scope,
@@ -1,3 +1,3 @@
 pub(crate) mod evaluator;
 
-pub(crate) use evaluator::{evaluate_baseline_expr, Scope};
+pub(crate) use evaluator::{evaluate_baseline_expr, forget_patterns, Bindings, Scope};
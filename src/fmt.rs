@@ -0,0 +1,259 @@
+use crate::parser::parse::call_node::CallNode;
+use crate::parser::{
+    join_numbered_lines, parse_with_recovery, unclosed_delimiter, without_comment, Delimiter,
+    Pipeline, TokenNode,
+};
+use std::error::Error;
+use std::path::Path;
+
+// `nu --fmt <file>` rewrites a script with its spacing made regular: one space between the
+// arguments of a command, ` | ` between the commands of a pipeline, `; ` between pipelines, and
+// nothing just inside `( )` and `[ ]` but a space inside `{ }`. Everything else, like the words
+// and strings themselves, stays as it was written. A line that doesn't parse is left alone.
+//
+// Lines are read the way `source` reads them. A string that runs on over several lines is
+// formatted along with the lines it spans, so its contents are copied as they are. Lines carried
+// on by an open bracket, a `|` or a `\` are each formatted on their own, keeping their layout.
+
+/// Format the script at `path` in place. Returns whether anything changed.
+pub fn fmt_file(path: &Path) -> Result<bool, Box<dyn Error>> {
+    let script = std::fs::read_to_string(path)?;
+    let formatted = format_script(&script);
+
+    if formatted == script {
+        return Ok(false);
+    }
+
+    std::fs::write(path, formatted)?;
+    Ok(true)
+}
+
+/// The script with the spacing of each line made regular
+pub fn format_script(script: &str) -> String {
+    let lines: Vec<&str> = script.split('\n').collect();
+    let logical = join_numbered_lines(script);
+    let mut out = vec![];
+
+    for (idx, (first, joined)) in logical.iter().enumerate() {
+        let next = logical
+            .get(idx + 1)
+            .map_or(lines.len() + 1, |(next, _)| *next);
+        let physical = &lines[first - 1..next - 1];
+        let spanned = joined.matches('\n').count() + 1;
+
+        // The joined line is exactly the lines it came from when only strings carry it on
+        if spanned > 1
+            && spanned <= physical.len()
+            && physical[..spanned].join("\n") == *joined
+            && string_spans_lines(joined)
+        {
+            out.push(format_line(joined));
+            out.extend(physical[spanned..].iter().map(|line| format_line(line)));
+        } else {
+            out.extend(physical.iter().map(|line| format_line(line)));
+        }
+    }
+
+    out.join("\n")
+}
+
+/// Whether a string in a line joined from several runs on from one of them to the next
+fn string_spans_lines(joined: &str) -> bool {
+    joined.match_indices('\n').any(|(idx, _)| {
+        match unclosed_delimiter(without_comment(&joined[..idx])) {
+            Some('"') | Some('\'') | Some('`') => true,
+            _ => false,
+        }
+    })
+}
+
+/// A line of a script, formatted. Its indentation is kept, so a pipeline carried on over
+/// several lines still lines up.
+pub fn format_line(line: &str) -> String {
+    let line = trim_end(line);
+    let body = line.trim_start();
+    let indent = &line[..line.len() - body.len()];
+
+    let formatted = match format_statements(body) {
+        Some(formatted) => formatted,
+        None => return line.to_string(),
+    };
+
+    // Only spacing should have changed. If the line now reads differently, keep it as it was.
+    if outline(&formatted) != outline(body) {
+        return line.to_string();
+    }
+
+    match format_statements(&formatted) {
+        Some(ref again) if *again == formatted => format!("{}{}", indent, formatted),
+        _ => line.to_string(),
+    }
+}
+
+/// The line without the whitespace at its end, apart from a space escaped with `\`, like the
+/// one ending `a\ `
+fn trim_end(line: &str) -> &str {
+    let trimmed = line.trim_end();
+    let backslashes = trimmed.chars().rev().take_while(|c| *c == '\\').count();
+
+    match line[trimmed.len()..].chars().next() {
+        Some(escaped) if backslashes % 2 == 1 => &line[..trimmed.len() + escaped.len_utf8()],
+        _ => trimmed,
+    }
+}
+
+/// What a line reads as, leaving out its spacing: each of its words and symbols with its kind,
+/// and what it's nested in
+fn outline(line: &str) -> Option<Vec<String>> {
+    let statements = match parse_with_recovery(line, uuid::Uuid::nil()) {
+        TokenNode::Statements(statements) => statements.item,
+        _ => return None,
+    };
+
+    let mut out = vec![];
+
+    for statement in &statements {
+        out.push(";".to_string());
+        outline_node(statement, line, &mut out);
+    }
+
+    Some(out)
+}
+
+fn outline_node(node: &TokenNode, source: &str, out: &mut Vec<String>) {
+    match node {
+        TokenNode::Whitespace(_) => {}
+        TokenNode::Pipeline(pipeline) => {
+            for part in &pipeline.parts {
+                if part.pipe.is_some() {
+                    out.push("|".to_string());
+                }
+
+                outline_node(&TokenNode::Call(part.call().clone()), source, out);
+            }
+
+            if let Some(comment) = pipeline.comment {
+                out.push(comment.slice(source).trim_end().to_string());
+            }
+        }
+        TokenNode::Call(call) => {
+            outline_node(call.head(), source, out);
+
+            for child in call.children().iter().flat_map(|children| children.iter()) {
+                outline_node(child, source, out);
+            }
+        }
+        TokenNode::Delimited(delimited) => {
+            out.push(delimited.item.type_name().to_string());
+
+            for child in delimited.item.children() {
+                outline_node(child, source, out);
+            }
+
+            out.push("end".to_string());
+        }
+        other => out.push(format!(
+            "{} {}",
+            other.type_name(),
+            other.tag().slice(source)
+        )),
+    }
+}
+
+fn format_statements(line: &str) -> Option<String> {
+    let statements = match parse_with_recovery(line, uuid::Uuid::nil()) {
+        TokenNode::Statements(statements) => statements.item,
+        _ => return None,
+    };
+
+    let mut pipelines = vec![];
+
+    for statement in &statements {
+        match statement {
+            TokenNode::Pipeline(pipeline) => pipelines.push(format_pipeline(&pipeline.item, line)),
+            _ => return None,
+        }
+    }
+
+    Some(pipelines.join("; "))
+}
+
+fn format_pipeline(pipeline: &Pipeline, source: &str) -> String {
+    let calls = pipeline
+        .parts
+        .iter()
+        .map(|part| format_call(&part.call().item, source))
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    // A pipeline carried on from the line before starts with its `|`
+    let mut out = match pipeline.parts.first() {
+        Some(first) if first.pipe.is_some() => format!("| {}", calls),
+        _ => calls,
+    };
+
+    if let Some(comment) = pipeline.comment {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        out.push_str(comment.slice(source).trim_end());
+    }
+
+    out
+}
+
+fn format_call(call: &CallNode, source: &str) -> String {
+    let mut nodes = vec![*call.head().clone()];
+
+    if let Some(children) = call.children() {
+        nodes.extend(children.iter().cloned());
+    }
+
+    format_nodes(&nodes, source)
+}
+
+/// Nodes as they'd be written next to each other, with any run of whitespace between them made
+/// a single space and the whitespace at either end dropped
+fn format_nodes(nodes: &[TokenNode], source: &str) -> String {
+    let mut out = String::new();
+    let mut space = false;
+
+    for node in nodes {
+        match node {
+            TokenNode::Whitespace(_) => space = !out.is_empty(),
+            _ => {
+                if space {
+                    out.push(' ');
+                    space = false;
+                }
+                out.push_str(&format_node(node, source));
+
+                // A block's parameters are read without the space after them, like `|row|`
+                space = match node {
+                    TokenNode::BlockParams(_) => true,
+                    _ => false,
+                };
+            }
+        }
+    }
+
+    out
+}
+
+fn format_node(node: &TokenNode, source: &str) -> String {
+    match node {
+        TokenNode::Call(call) => format_call(&call.item, source),
+        TokenNode::Pipeline(pipeline) => format_pipeline(&pipeline.item, source),
+        TokenNode::Delimited(delimited) => {
+            let inner = format_nodes(delimited.item.children(), source);
+
+            match delimited.item.delimiter() {
+                Delimiter::Paren => format!("({})", inner),
+                Delimiter::Square => format!("[{}]", inner),
+                Delimiter::Brace if inner.is_empty() => "{}".to_string(),
+                Delimiter::Brace => format!("{{ {} }}", inner),
+            }
+        }
+        other => other.tag().slice(source).to_string(),
+    }
+}
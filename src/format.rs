@@ -1,7 +1,13 @@
 pub(crate) mod entries;
+pub(crate) mod filesize;
 pub(crate) mod generic;
+pub(crate) mod hex;
 pub(crate) mod list;
+pub(crate) mod ls_colors;
+pub(crate) mod pager;
+pub(crate) mod progress;
 pub(crate) mod table;
+pub(crate) mod theme;
 
 use crate::prelude::*;
 
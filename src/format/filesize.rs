@@ -0,0 +1,145 @@
+use crate::data::config;
+use crate::prelude::*;
+use byte_unit::{Byte, ByteUnit};
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+
+// File sizes are shown in decimal units (KB, MB, powers of 1000) with one decimal place, unless
+// the config asks otherwise:
+//
+//   [filesize]
+//   binary = true    # KiB, MiB, powers of 1024
+//   precision = 2    # decimal places
+
+lazy_static! {
+    static ref SETTINGS: Mutex<Option<FilesizeSettings>> = Mutex::new(None);
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct FilesizeSettings {
+    pub(crate) binary: bool,
+    pub(crate) precision: usize,
+}
+
+impl Default for FilesizeSettings {
+    fn default() -> FilesizeSettings {
+        FilesizeSettings {
+            binary: false,
+            precision: 1,
+        }
+    }
+}
+
+impl FilesizeSettings {
+    fn from_config() -> FilesizeSettings {
+        let mut settings = FilesizeSettings::default();
+
+        let config = match config::config(Tag::unknown()) {
+            Ok(config) => config,
+            Err(_) => return settings,
+        };
+
+        if let Some(Tagged {
+            item: Value::Row(section),
+            ..
+        }) = config.get("filesize")
+        {
+            if let Some(binary) = section.entries.get("binary") {
+                settings.binary = binary.is_true();
+            }
+
+            if let Some(precision) = section
+                .entries
+                .get("precision")
+                .and_then(|p| p.as_string().ok())
+                .and_then(|p| p.parse::<usize>().ok())
+            {
+                settings.precision = precision;
+            }
+        }
+
+        settings
+    }
+}
+
+/// The settings in use, read from the config the first time they're needed after a reload
+pub(crate) fn settings() -> FilesizeSettings {
+    let mut settings = SETTINGS.lock().unwrap();
+
+    *settings.get_or_insert_with(FilesizeSettings::from_config)
+}
+
+/// Forget the settings, so the next size shown picks up changes to the config
+pub(crate) fn reload() {
+    *SETTINGS.lock().unwrap() = None;
+}
+
+/// A size in whichever unit suits it best, as the config asks
+pub(crate) fn format_filesize(bytes: u64) -> String {
+    if bytes == 0 {
+        return "—".to_string();
+    }
+
+    let settings = settings();
+    let byte = Byte::from_bytes(bytes as u128).get_appropriate_unit(settings.binary);
+
+    match byte.get_unit() {
+        ByteUnit::B => format!("{} B ", byte.get_value()),
+        _ => byte.format(settings.precision),
+    }
+}
+
+/// A size in the given unit, like `MB` or `GiB`
+pub(crate) fn format_filesize_in(bytes: u64, unit: ByteUnit) -> String {
+    let byte = Byte::from_bytes(bytes as u128).get_adjusted_unit(unit);
+
+    match unit {
+        ByteUnit::B => format!("{} B", bytes),
+        _ => byte.format(settings().precision),
+    }
+}
+
+// The units a size can be given in, with how many bytes each stands for
+const UNITS: [(&str, ByteUnit, f64); 11] = [
+    ("b", ByteUnit::B, 1.0),
+    ("kb", ByteUnit::KB, 1e3),
+    ("kib", ByteUnit::KiB, 1024.0),
+    ("mb", ByteUnit::MB, 1e6),
+    ("mib", ByteUnit::MiB, 1_048_576.0),
+    ("gb", ByteUnit::GB, 1e9),
+    ("gib", ByteUnit::GiB, 1_073_741_824.0),
+    ("tb", ByteUnit::TB, 1e12),
+    ("tib", ByteUnit::TiB, 1_099_511_627_776.0),
+    ("pb", ByteUnit::PB, 1e15),
+    ("pib", ByteUnit::PiB, 1_125_899_906_842_624.0),
+];
+
+/// The unit with the given name. Case doesn't matter, so `kb` is `KB` and `mib` is `MiB`.
+pub(crate) fn unit_named(name: &str) -> Option<ByteUnit> {
+    let name = name.to_lowercase();
+
+    UNITS
+        .iter()
+        .find(|(unit_name, _, _)| *unit_name == name.as_str())
+        .map(|(_, unit, _)| *unit)
+}
+
+/// Read a size like `5MB`, `1.5 GiB` or `1024`, which is taken to be bytes
+pub(crate) fn parse_filesize(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+
+    let number = text[..split].parse::<f64>().ok()?;
+    let unit = match text[split..].trim() {
+        "" => "b".to_string(),
+        unit => unit.to_lowercase(),
+    };
+
+    let (_, _, size) = UNITS
+        .iter()
+        .find(|(unit_name, _, _)| *unit_name == unit.as_str())?;
+
+    Some((number * size).round() as u64)
+}
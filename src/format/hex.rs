@@ -0,0 +1,39 @@
+// Bytes are printed in the style of `hexdump -C`: an offset column, sixteen bytes split into
+// two groups of eight, and a gutter showing the printable ASCII characters
+
+const BYTES_PER_LINE: usize = 16;
+
+fn hex_line(offset: usize, chunk: &[u8]) -> String {
+    let mut hex = String::with_capacity(BYTES_PER_LINE * 3 + 1);
+
+    for idx in 0..BYTES_PER_LINE {
+        if idx == BYTES_PER_LINE / 2 {
+            hex.push(' ');
+        }
+
+        match chunk.get(idx) {
+            Some(byte) => hex.push_str(&format!("{:02x} ", byte)),
+            None => hex.push_str("   "),
+        }
+    }
+
+    let ascii: String = chunk
+        .iter()
+        .map(|byte| match *byte {
+            0x20..=0x7e => *byte as char,
+            _ => '.',
+        })
+        .collect();
+
+    format!("{:08x}  {} |{}|", offset, hex, ascii)
+}
+
+/// Render `bytes` as `hexdump -C` style text, numbering lines starting at `offset`
+pub fn hex_dump(bytes: &[u8], offset: usize) -> String {
+    bytes
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .map(|(idx, chunk)| hex_line(offset + idx * BYTES_PER_LINE, chunk))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,150 @@
+use crate::data::Value;
+use crate::format::theme::color_code;
+use crate::prelude::*;
+use indexmap::IndexMap;
+
+// File names in listings are colored by what kind of file they are. The colors come from
+// LS_COLORS, with a palette in the config taking precedence:
+//
+//   [ls_colors]
+//   directory = "blue"
+//   symlink = "cyan"
+//   executable = "green"
+//   archive = "red"
+//
+// Only the rendered cell is styled; the name itself stays a plain string.
+
+const ARCHIVES: &[&str] = &[
+    "7z", "bz2", "deb", "gz", "jar", "rar", "rpm", "tar", "tbz2", "tgz", "xz", "zip", "zst",
+];
+
+#[derive(Debug, Clone)]
+pub struct LsColors {
+    // Style specs by LS_COLORS key: `di`, `ln`, `ex`, `fi`, or `*.ext` for extensions
+    styles: IndexMap<String, String>,
+}
+
+impl LsColors {
+    pub fn from_env_and_config() -> LsColors {
+        let mut styles = IndexMap::new();
+
+        styles.insert("di".to_string(), "bFb".to_string());
+        styles.insert("ln".to_string(), "bFc".to_string());
+        styles.insert("ex".to_string(), "bFg".to_string());
+        for ext in ARCHIVES {
+            styles.insert(format!("*.{}", ext), "bFr".to_string());
+        }
+
+        if let Ok(ls_colors) = std::env::var("LS_COLORS") {
+            for entry in ls_colors.split(':') {
+                let mut parts = entry.splitn(2, '=');
+                if let (Some(key), Some(codes)) = (parts.next(), parts.next()) {
+                    if let Some(style) = sgr_to_style(codes) {
+                        styles.insert(key.to_string(), style);
+                    }
+                }
+            }
+        }
+
+        if let Ok(config) = crate::data::config::config(Tag::unknown()) {
+            if let Some(Tagged {
+                item: Value::Row(palette),
+                ..
+            }) = config.get("ls_colors")
+            {
+                for (kind, color) in palette.entries.iter() {
+                    let color = match color.as_string().ok().and_then(|c| color_code(&c)) {
+                        Some(color) => color,
+                        None => continue,
+                    };
+
+                    let keys: Vec<String> = match kind.as_str() {
+                        "directory" => vec!["di".to_string()],
+                        "symlink" => vec!["ln".to_string()],
+                        "executable" => vec!["ex".to_string()],
+                        "file" => vec!["fi".to_string()],
+                        "archive" => ARCHIVES.iter().map(|ext| format!("*.{}", ext)).collect(),
+                        _ => continue,
+                    };
+
+                    for key in keys {
+                        styles.insert(key, format!("F{}", color));
+                    }
+                }
+            }
+        }
+
+        LsColors { styles }
+    }
+
+    /// The style spec for the `name` cell of a row that looks like a directory listing (has
+    /// both `name` and `type` columns), or None for anything else
+    pub fn style_for_row(&self, row: &Value) -> Option<&str> {
+        let name = row.get_data_by_key("name")?.as_string().ok()?;
+        let kind = row.get_data_by_key("type")?.as_string().ok()?;
+
+        let key = match kind.as_str() {
+            "Directory" => "di".to_string(),
+            "Symlink" => "ln".to_string(),
+            _ => {
+                let ext = std::path::Path::new(&name)
+                    .extension()
+                    .map(|ext| format!("*.{}", ext.to_string_lossy()));
+
+                match ext {
+                    Some(ext) if self.styles.contains_key(&ext) => ext,
+                    _ if is_executable(&name) => "ex".to_string(),
+                    _ => "fi".to_string(),
+                }
+            }
+        };
+
+        self.styles.get(&key).map(|style| style.as_str())
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(name: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(name)
+        .map(|metadata| metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(name: &str) -> bool {
+    match std::path::Path::new(name).extension() {
+        Some(ext) => {
+            let ext = ext.to_string_lossy().to_lowercase();
+            ext == "exe" || ext == "bat" || ext == "cmd"
+        }
+        None => false,
+    }
+}
+
+/// Turn the SGR codes of an LS_COLORS entry (like `01;34`) into a prettytable style spec.
+/// Codes that have no style spec equivalent are dropped.
+fn sgr_to_style(codes: &str) -> Option<String> {
+    const COLORS: &[char] = &['d', 'r', 'g', 'y', 'b', 'm', 'c', 'w'];
+
+    let mut style = String::new();
+
+    for code in codes.split(';') {
+        match code.parse::<usize>() {
+            Ok(1) => style.push('b'),
+            Ok(3) => style.push('i'),
+            Ok(4) => style.push('u'),
+            Ok(n @ 30..=37) => style.push_str(&format!("F{}", COLORS[n - 30])),
+            Ok(n @ 40..=47) => style.push_str(&format!("B{}", COLORS[n - 40])),
+            Ok(n @ 90..=97) => style.push_str(&format!("F{}", COLORS[n - 90].to_ascii_uppercase())),
+            _ => {}
+        }
+    }
+
+    if style.is_empty() {
+        None
+    } else {
+        Some(style)
+    }
+}
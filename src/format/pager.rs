@@ -0,0 +1,58 @@
+use crate::prelude::*;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Output taller than the terminal is sent through a pager instead of scrolling off the top.
+// The pager is taken from the `pager` config setting, then `$PAGER`, and falls back to `less`.
+// Paging can be turned off entirely with `table_paging = false`.
+
+pub(crate) fn should_page(num_lines: usize) -> bool {
+    let enabled = crate::data::config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("table_paging").map(|p| p.is_true()))
+        .unwrap_or(true);
+
+    if !enabled {
+        return false;
+    }
+
+    match term_size::dimensions_stdout() {
+        Some((_, height)) => num_lines > height,
+        None => false,
+    }
+}
+
+fn pager_command() -> String {
+    let configured = crate::data::config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("pager").and_then(|p| p.as_string().ok()));
+
+    match configured {
+        Some(pager) => pager,
+        None => std::env::var("PAGER").unwrap_or_else(|_| "less -R".to_string()),
+    }
+}
+
+pub(crate) fn page(contents: &[u8]) -> Result<(), ShellError> {
+    let pager = pager_command();
+    let mut parts = pager.split_whitespace();
+
+    let program = match parts.next() {
+        Some(program) => program,
+        None => return Err(ShellError::string("No pager configured")),
+    };
+
+    let mut child = Command::new(program)
+        .args(parts)
+        .stdin(Stdio::piped())
+        .spawn()?;
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        // The pager may be quit before reading everything, which closes the pipe early
+        let _ = stdin.write_all(contents);
+    }
+
+    child.wait()?;
+
+    Ok(())
+}
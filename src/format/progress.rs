@@ -0,0 +1,123 @@
+use crate::prelude::*;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// While a pipeline is still producing rows and nothing has been printed yet, a spinner with the
+// number of rows (and bytes of text or binary data) seen so far is drawn on stderr. It only
+// appears once the wait passes `progress_delay` milliseconds (1000 by default), and can be
+// turned off with `progress = false`. The thread drawing it is started by the first row to come
+// after that, so output that comes quickly doesn't pay for it.
+
+const SPINNER: &[char] = &['|', '/', '-', '\\'];
+
+#[derive(Default)]
+struct Counts {
+    rows: AtomicUsize,
+    bytes: AtomicU64,
+    done: AtomicBool,
+}
+
+pub(crate) struct Progress {
+    counts: Arc<Counts>,
+    started: Instant,
+    // How long to wait before drawing, or None if the spinner isn't drawn at all
+    delay: Option<Duration>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Progress {
+    pub(crate) fn start() -> Progress {
+        let config = crate::data::config::config(Tag::unknown()).ok();
+        let enabled = config
+            .as_ref()
+            .and_then(|config| config.get("progress").map(|p| p.is_true()))
+            .unwrap_or(true);
+        let delay = config
+            .as_ref()
+            .and_then(|config| config.get("progress_delay"))
+            .and_then(|delay| delay.as_string().ok())
+            .and_then(|delay| delay.parse::<u64>().ok())
+            .unwrap_or(1000);
+
+        // Nobody is watching stderr if it isn't a terminal
+        let delay = match (enabled, term_size::dimensions_stderr()) {
+            (true, Some(_)) => Some(Duration::from_millis(delay)),
+            _ => None,
+        };
+
+        Progress {
+            counts: Arc::new(Counts::default()),
+            started: Instant::now(),
+            delay,
+            thread: None,
+        }
+    }
+
+    pub(crate) fn record(&mut self, value: &Value) {
+        self.counts.rows.fetch_add(1, Ordering::Relaxed);
+
+        let bytes = match value {
+            Value::Primitive(Primitive::String(s)) => s.len() as u64,
+            Value::Primitive(Primitive::Binary(b)) => b.len() as u64,
+            _ => 0,
+        };
+        self.counts.bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        if self.thread.is_none() {
+            if let Some(delay) = self.delay {
+                if self.started.elapsed() >= delay {
+                    let counts = self.counts.clone();
+                    self.thread = Some(std::thread::spawn(move || draw(&counts)));
+                }
+            }
+        }
+    }
+
+    /// Stop the spinner, clearing it from the line so output can start there
+    pub(crate) fn finish(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.counts.done.store(true, Ordering::SeqCst);
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn draw(counts: &Counts) {
+    let mut frame = 0;
+
+    while !counts.done.load(Ordering::SeqCst) {
+        let rows = counts.rows.load(Ordering::Relaxed);
+        let bytes = counts.bytes.load(Ordering::Relaxed);
+
+        let mut status = format!("{} {} rows", SPINNER[frame % SPINNER.len()], rows);
+        if bytes > 0 {
+            status.push_str(&format!(
+                ", {}",
+                Primitive::Bytes(bytes).format(None).trim()
+            ));
+        }
+
+        eprint!("\r{}\x1b[K", status);
+        let _ = std::io::stderr().flush();
+
+        frame += 1;
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    eprint!("\r\x1b[K");
+    let _ = std::io::stderr().flush();
+}
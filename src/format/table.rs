@@ -1,11 +1,14 @@
 use crate::data::Value;
+use crate::format::ls_colors::LsColors;
+use crate::format::pager;
+use crate::format::theme::TableTheme;
 use crate::format::RenderView;
 use crate::prelude::*;
 use derive_new::new;
 use textwrap::fill;
 
-use prettytable::format::{FormatBuilder, LinePosition, LineSeparator};
-use prettytable::{color, Attr, Cell, Row, Table};
+use prettytable::{Cell, Row, Table};
+use term::Terminal;
 
 #[derive(Debug, new)]
 pub struct TableView {
@@ -13,12 +16,25 @@ pub struct TableView {
     headers: Vec<String>,
 
     // List of rows of cells, each containing value and prettytable style-string:
-    entries: Vec<Vec<(String, &'static str)>>,
+    entries: Vec<Vec<(String, String)>>,
+
+    // Borders and colors to render with, as read from the config:
+    theme: TableTheme,
+
+    // Number of columns dropped to fit the table into the terminal:
+    hidden_columns: usize,
 }
 
-enum TableMode {
-    Light,
-    Normal,
+#[derive(Debug, Default, Clone)]
+pub struct TableOptions {
+    // Show every column at its full width, even if the table overflows the terminal
+    pub expand: bool,
+
+    // Render for this many columns of output instead of the terminal's width
+    pub width: Option<usize>,
+
+    // Cut overly wide cells off with an ellipsis instead of wrapping them
+    pub truncate: bool,
 }
 
 impl TableView {
@@ -35,6 +51,13 @@ impl TableView {
     }
 
     pub fn from_list(values: &[Tagged<Value>]) -> Option<TableView> {
+        TableView::from_list_with_options(values, &TableOptions::default())
+    }
+
+    pub fn from_list_with_options(
+        values: &[Tagged<Value>],
+        options: &TableOptions,
+    ) -> Option<TableView> {
         if values.len() == 0 {
             return None;
         }
@@ -45,10 +68,12 @@ impl TableView {
             headers.push("value".to_string());
         }
 
+        let theme = TableTheme::from_config();
+        let ls_colors = LsColors::from_env_and_config();
         let mut entries = vec![];
 
         for (idx, value) in values.iter().enumerate() {
-            let mut row: Vec<(String, &'static str)> = match value {
+            let mut row: Vec<(String, String)> = match value {
                 Tagged {
                     item: Value::Row(..),
                     ..
@@ -57,18 +82,28 @@ impl TableView {
                     .enumerate()
                     .map(|(i, d)| {
                         let data = value.get_data(d);
-                        return (
-                            data.borrow().format_leaf(Some(&headers[i])),
-                            data.borrow().style_leaf(),
-                        );
+
+                        let file_style = match d.as_str() {
+                            "name" => ls_colors.style_for_row(value),
+                            _ => None,
+                        };
+
+                        let style = match file_style {
+                            Some(file_style) => {
+                                format!("{}{}", data.borrow().style_leaf(), file_style)
+                            }
+                            None => theme.cell_style(data.borrow(), data.borrow().style_leaf()),
+                        };
+
+                        return (data.borrow().format_leaf(Some(&headers[i])), style);
                     })
                     .collect(),
-                x => vec![(x.format_leaf(None), x.style_leaf())],
+                x => vec![(x.format_leaf(None), theme.cell_style(x, x.style_leaf()))],
             };
 
             if values.len() > 1 {
                 // Indices are black, bold, right-aligned:
-                row.insert(0, (format!("{}", idx.to_string()), "Fdbr"));
+                row.insert(0, (format!("{}", idx.to_string()), "Fdbr".to_string()));
             }
 
             entries.push(row);
@@ -95,14 +130,26 @@ impl TableView {
             ));
         }
 
+        if options.expand {
+            return Some(TableView {
+                headers,
+                entries,
+                theme,
+                hidden_columns: 0,
+            });
+        }
+
         // Different platforms want different amounts of buffer, not sure why
-        let termwidth = std::cmp::max(textwrap::termwidth(), 20);
+        let termwidth = std::cmp::max(options.width.unwrap_or_else(textwrap::termwidth), 20);
 
         // Make sure we have enough space for the columns we have
         let max_num_of_columns = termwidth / 10;
+        let mut hidden_columns = 0;
 
         // If we have too many columns, truncate the table
         if max_num_of_columns < headers.len() {
+            hidden_columns = headers.len() - max_num_of_columns;
+
             headers.truncate(max_num_of_columns);
             for row in 0..entries.len() {
                 entries[row].truncate(max_num_of_columns);
@@ -110,7 +157,7 @@ impl TableView {
 
             headers.push("...".to_string());
             for row in 0..entries.len() {
-                entries[row].push(("...".to_string(), "c")); // ellipsis is centred
+                entries[row].push(("...".to_string(), "c".to_string())); // ellipsis is centred
             }
         }
 
@@ -182,18 +229,37 @@ impl TableView {
             99999
         };
 
-        // Wrap cells as needed
+        // Wrap (or truncate) cells as needed
         for head in 0..headers.len() {
             if max_per_column[head] > max_naive_column_width {
                 headers[head] = fill(&headers[head], max_column_width);
                 for row in 0..entries.len() {
-                    entries[row][head].0 = fill(&entries[row][head].0, max_column_width);
+                    entries[row][head].0 = if options.truncate {
+                        truncate(&entries[row][head].0, max_column_width)
+                    } else {
+                        fill(&entries[row][head].0, max_column_width)
+                    };
                 }
             }
         }
 
-        Some(TableView { headers, entries })
+        Some(TableView {
+            headers,
+            entries,
+            theme,
+            hidden_columns,
+        })
+    }
+}
+
+fn truncate(contents: &str, width: usize) -> String {
+    if contents.chars().count() <= width {
+        return contents.to_string();
     }
+
+    let mut truncated: String = contents.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
 }
 
 impl RenderView for TableView {
@@ -204,58 +270,54 @@ impl RenderView for TableView {
 
         let mut table = Table::new();
 
-        let table_mode = crate::data::config::config(Tag::unknown())?
-            .get("table_mode")
-            .map(|s| match s.as_string().unwrap().as_ref() {
-                "light" => TableMode::Light,
-                _ => TableMode::Normal,
-            })
-            .unwrap_or(TableMode::Normal);
-
-        match table_mode {
-            TableMode::Light => {
-                table.set_format(
-                    FormatBuilder::new()
-                        .separator(LinePosition::Title, LineSeparator::new('─', '─', ' ', ' '))
-                        .padding(1, 1)
-                        .build(),
-                );
-            }
-            _ => {
-                table.set_format(
-                    FormatBuilder::new()
-                        .column_separator('│')
-                        .separator(LinePosition::Top, LineSeparator::new('━', '┯', ' ', ' '))
-                        .separator(LinePosition::Title, LineSeparator::new('─', '┼', ' ', ' '))
-                        .separator(LinePosition::Bottom, LineSeparator::new('━', '┷', ' ', ' '))
-                        .padding(1, 1)
-                        .build(),
-                );
-            }
-        }
+        table.set_format(self.theme.format());
 
         let header: Vec<Cell> = self
             .headers
             .iter()
             .map(|h| {
-                Cell::new(h)
-                    .with_style(Attr::ForegroundColor(color::GREEN))
-                    .with_style(Attr::Bold)
+                self.theme
+                    .header_attrs()
+                    .into_iter()
+                    .fold(Cell::new(h), |cell, attr| cell.with_style(attr))
             })
             .collect();
 
         table.set_titles(Row::new(header));
 
-        for row in &self.entries {
+        for (idx, row) in self.entries.iter().enumerate() {
+            let row_style = self.theme.row_style(idx);
             table.add_row(Row::new(
                 row.iter()
-                    .map(|(v, s)| Cell::new(v).style_spec(s))
+                    .map(|(v, s)| Cell::new(v).style_spec(&format!("{}{}", s, row_style)))
                     .collect(),
             ));
         }
 
+        if pager::should_page(table.to_string().lines().count()) {
+            if let Some(mut buffer) = term::terminfo::TerminfoTerminal::new(vec![]) {
+                table.print_term(&mut buffer).unwrap();
+
+                if pager::page(&buffer.into_inner()).is_ok() {
+                    return Ok(());
+                }
+            }
+        }
+
         table.print_term(&mut *host.out_terminal()).unwrap();
 
+        if self.hidden_columns > 0 {
+            host.stdout(&format!(
+                "{} more {} hidden, use `table --expand` to show all columns",
+                self.hidden_columns,
+                if self.hidden_columns == 1 {
+                    "column"
+                } else {
+                    "columns"
+                }
+            ));
+        }
+
         Ok(())
     }
 }
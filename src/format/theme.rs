@@ -0,0 +1,221 @@
+use crate::data::{Primitive, Value};
+use crate::prelude::*;
+use indexmap::IndexMap;
+
+use prettytable::format::{FormatBuilder, LinePosition, LineSeparator, TableFormat};
+use prettytable::{color, Attr};
+
+// The table theme is read from the config file. A config might look like:
+//
+//   table_mode = "rounded"
+//   header_color = "cyan"
+//   table_stripes = true
+//
+//   [table_colors]
+//   path = "blue"
+//   number = "yellow"
+//   date = "magenta"
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableMode {
+    Light,
+    Normal,
+    Rounded,
+    Heavy,
+    Compact,
+    None,
+}
+
+impl TableMode {
+    fn from_str(s: &str) -> TableMode {
+        match s {
+            "light" => TableMode::Light,
+            "rounded" => TableMode::Rounded,
+            "heavy" => TableMode::Heavy,
+            "compact" => TableMode::Compact,
+            "none" => TableMode::None,
+            _ => TableMode::Normal,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TableTheme {
+    pub mode: TableMode,
+    pub header_color: color::Color,
+    pub stripes: bool,
+    colors: IndexMap<String, char>,
+}
+
+impl Default for TableTheme {
+    fn default() -> TableTheme {
+        TableTheme {
+            mode: TableMode::Normal,
+            header_color: color::GREEN,
+            stripes: false,
+            colors: IndexMap::new(),
+        }
+    }
+}
+
+impl TableTheme {
+    pub fn from_config() -> TableTheme {
+        let mut theme = TableTheme::default();
+
+        let config = match crate::data::config::config(Tag::unknown()) {
+            Ok(config) => config,
+            Err(_) => return theme,
+        };
+
+        if let Some(mode) = config.get("table_mode") {
+            if let Ok(mode) = mode.as_string() {
+                theme.mode = TableMode::from_str(&mode);
+            }
+        }
+
+        if let Some(header_color) = config.get("header_color") {
+            if let Some(c) = header_color.as_string().ok().and_then(|c| color_code(&c)) {
+                theme.header_color = term_color(c);
+            }
+        }
+
+        if let Some(stripes) = config.get("table_stripes") {
+            theme.stripes = stripes.is_true();
+        }
+
+        if let Some(Tagged {
+            item: Value::Row(colors),
+            ..
+        }) = config.get("table_colors")
+        {
+            for (kind, value) in colors.entries.iter() {
+                if let Some(c) = value.as_string().ok().and_then(|c| color_code(&c)) {
+                    theme.colors.insert(kind.clone(), c);
+                }
+            }
+        }
+
+        theme
+    }
+
+    pub fn format(&self) -> TableFormat {
+        match self.mode {
+            TableMode::Light => FormatBuilder::new()
+                .separator(LinePosition::Title, LineSeparator::new('─', '─', ' ', ' '))
+                .padding(1, 1)
+                .build(),
+            TableMode::Rounded => FormatBuilder::new()
+                .column_separator('│')
+                .borders('│')
+                .separator(LinePosition::Top, LineSeparator::new('─', '┬', '╭', '╮'))
+                .separator(LinePosition::Title, LineSeparator::new('─', '┼', '├', '┤'))
+                .separator(LinePosition::Bottom, LineSeparator::new('─', '┴', '╰', '╯'))
+                .padding(1, 1)
+                .build(),
+            TableMode::Heavy => FormatBuilder::new()
+                .column_separator('┃')
+                .borders('┃')
+                .separator(LinePosition::Top, LineSeparator::new('━', '┳', '┏', '┓'))
+                .separator(LinePosition::Title, LineSeparator::new('━', '╋', '┣', '┫'))
+                .separator(LinePosition::Bottom, LineSeparator::new('━', '┻', '┗', '┛'))
+                .padding(1, 1)
+                .build(),
+            TableMode::Compact => FormatBuilder::new()
+                .column_separator('│')
+                .separator(LinePosition::Title, LineSeparator::new('─', '┼', ' ', ' '))
+                .padding(0, 0)
+                .build(),
+            TableMode::None => FormatBuilder::new().padding(0, 2).build(),
+            TableMode::Normal => FormatBuilder::new()
+                .column_separator('│')
+                .separator(LinePosition::Top, LineSeparator::new('━', '┯', ' ', ' '))
+                .separator(LinePosition::Title, LineSeparator::new('─', '┼', ' ', ' '))
+                .separator(LinePosition::Bottom, LineSeparator::new('━', '┷', ' ', ' '))
+                .padding(1, 1)
+                .build(),
+        }
+    }
+
+    pub fn header_attrs(&self) -> Vec<Attr> {
+        vec![Attr::ForegroundColor(self.header_color), Attr::Bold]
+    }
+
+    /// The prettytable style spec for a cell, combining its alignment with the configured
+    /// color for its type
+    pub fn cell_style(&self, value: &Value, alignment: &str) -> String {
+        let color = match value {
+            Value::Primitive(p) => self.color_for(p),
+            _ => None,
+        };
+
+        match color {
+            Some(c) => format!("{}F{}", alignment, c),
+            None => alignment.to_string(),
+        }
+    }
+
+    /// The style spec to append to every cell of the given row
+    pub fn row_style(&self, row: usize) -> &'static str {
+        if self.stripes && row % 2 == 1 {
+            "BD"
+        } else {
+            ""
+        }
+    }
+
+    fn color_for(&self, primitive: &Primitive) -> Option<char> {
+        if let Some(c) = self.colors.get(&primitive.type_name()) {
+            return Some(*c);
+        }
+
+        match primitive {
+            Primitive::Int(_) | Primitive::Decimal(_) | Primitive::Bytes(_) => {
+                self.colors.get("number").cloned()
+            }
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn color_code(name: &str) -> Option<char> {
+    Some(match name {
+        "black" => 'd',
+        "red" => 'r',
+        "green" => 'g',
+        "yellow" => 'y',
+        "blue" => 'b',
+        "magenta" | "purple" => 'm',
+        "cyan" => 'c',
+        "white" => 'w',
+        "bright_black" | "gray" | "grey" => 'D',
+        "bright_red" => 'R',
+        "bright_green" => 'G',
+        "bright_yellow" => 'Y',
+        "bright_blue" => 'B',
+        "bright_magenta" | "bright_purple" => 'M',
+        "bright_cyan" => 'C',
+        "bright_white" => 'W',
+        _ => return None,
+    })
+}
+
+fn term_color(code: char) -> color::Color {
+    match code {
+        'd' => color::BLACK,
+        'r' => color::RED,
+        'g' => color::GREEN,
+        'y' => color::YELLOW,
+        'b' => color::BLUE,
+        'm' => color::MAGENTA,
+        'c' => color::CYAN,
+        'w' => color::WHITE,
+        'D' => color::BRIGHT_BLACK,
+        'R' => color::BRIGHT_RED,
+        'G' => color::BRIGHT_GREEN,
+        'Y' => color::BRIGHT_YELLOW,
+        'B' => color::BRIGHT_BLUE,
+        'M' => color::BRIGHT_MAGENTA,
+        'C' => color::BRIGHT_CYAN,
+        _ => color::BRIGHT_WHITE,
+    }
+}
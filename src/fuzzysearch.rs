@@ -25,6 +25,8 @@ pub fn interactive_fuzzy_search(lines: &Vec<&str>, max_results: usize) -> Select
             // User input for search
             let mut searchinput = String::new();
             let mut selected = 0;
+            // The first match shown, once Ctrl-R has walked the selection past the bottom
+            let mut offset = 0;
 
             let mut cursor = cursor();
             let _ = cursor.hide();
@@ -32,9 +34,18 @@ pub fn interactive_fuzzy_search(lines: &Vec<&str>, max_results: usize) -> Select
             let mut sync_stdin = input.read_sync();
 
             while state == State::Selecting {
-                let mut selected_lines = fuzzy_search(&searchinput, &lines, max_results);
-                let num_lines = selected_lines.len();
-                paint_selection_list(&selected_lines, selected);
+                let mut selected_lines = fuzzy_search(&searchinput, &lines, lines.len());
+
+                if selected < offset {
+                    offset = selected;
+                } else if selected >= offset + max_results {
+                    offset = selected + 1 - max_results;
+                }
+
+                let visible =
+                    &selected_lines[offset..selected_lines.len().min(offset + max_results)];
+                let num_lines = visible.len();
+                paint_selection_list(visible, selected - offset);
                 if let Some(ev) = sync_stdin.next() {
                     match ev {
                         InputEvent::Keyboard(k) => match k {
@@ -46,7 +57,7 @@ pub fn interactive_fuzzy_search(lines: &Vec<&str>, max_results: usize) -> Select
                                     selected -= 1;
                                 }
                             }
-                            KeyEvent::Down => {
+                            KeyEvent::Down | KeyEvent::Ctrl('r') => {
                                 if selected + 1 < selected_lines.len() {
                                     selected += 1;
                                 }
@@ -68,10 +79,12 @@ pub fn interactive_fuzzy_search(lines: &Vec<&str>, max_results: usize) -> Select
                             KeyEvent::Char(ch) => {
                                 searchinput.push(ch);
                                 selected = 0;
+                                offset = 0;
                             }
                             KeyEvent::Backspace => {
                                 searchinput.pop();
                                 selected = 0;
+                                offset = 0;
                             }
                             _ => {
                                 // println!("OTHER InputEvent: {:?}", k);
@@ -152,7 +165,7 @@ fn highlight(textmatch: &Match, normal: Style, highlighted: Style) -> Vec<ANSISt
 }
 
 #[cfg(feature = "crossterm")]
-fn paint_selection_list(lines: &Vec<Match>, selected: usize) {
+fn paint_selection_list(lines: &[Match], selected: usize) {
     let terminal = terminal();
     let size = terminal.terminal_size();
     let width = size.0 as usize;
@@ -174,7 +187,8 @@ fn paint_selection_list(lines: &Vec<Match>, selected: usize) {
     let _ = cursor.goto(0, y + (lines.len() as u16));
     print!(
         "{}",
-        Colour::Blue.paint("[ESC to quit, Enter to execute, Tab to edit]")
+        Colour::Blue
+            .paint("[ESC to quit, Enter to execute, Tab to edit, Ctrl-R for older matches]")
     );
 
     let _ = std::io::stdout().flush();
@@ -0,0 +1,359 @@
+use crate::cli::{classify_pipeline, create_default_context};
+use crate::context::{CommandRegistry, Context};
+use crate::data::meta::Span;
+use crate::errors::ShellError;
+use crate::parser::parse::call_node::CallNode;
+use crate::parser::parse::text::Text;
+use crate::parser::{
+    continue_line, join_numbered_lines, parse_with_recovery, Pipeline, RawToken, TokenNode,
+};
+use crate::Tagged;
+use language_reporting::{LabelStyle, Severity};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+// `nu --ide-check <file>` parses a script and checks each line against the signatures of the
+// commands it calls, without running anything. Every problem found is printed to stdout as a
+// line of JSON, so editor plugins can lint scripts on save:
+//
+//   {"file":"build.nu","line":3,"column":10,"end_line":3,"end_column":15,"start":42,"end":47,
+//    "severity":"error","message":"Unknown flag","label":"open has no flag named rwa",
+//    "help":"did you mean --raw?"}
+//
+// Lines and columns count from 1, columns in characters; `start` and `end` are byte offsets
+// into the file.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptDiagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub start: usize,
+    pub end: usize,
+    pub severity: String,
+    pub message: String,
+    pub label: Option<String>,
+    pub help: Option<String>,
+}
+
+/// Check the script at `path`, printing what's wrong with it. Returns whether it was clean.
+pub fn ide_check(path: &Path) -> Result<bool, Box<dyn Error>> {
+    let script = std::fs::read_to_string(path)?;
+    let context = create_default_context()?;
+
+    let diagnostics = check_script(&context, &path.display().to_string(), &script);
+
+    for diagnostic in &diagnostics {
+        println!("{}", serde_json::to_string(diagnostic)?);
+    }
+
+    Ok(diagnostics.is_empty())
+}
+
+/// The problems in `script`, a line at a time, the way `source` would read it
+pub(crate) fn check_script(context: &Context, file: &str, script: &str) -> Vec<ScriptDiagnostic> {
+    let physical = physical_lines(script);
+    let mut diagnostics = vec![];
+
+    for (first, line) in join_numbered_lines(script) {
+        let trimmed = line.trim();
+
+        // Aliases are left alone: defining one could save it to the config, and a line calling
+        // one that isn't known is taken to be an external command, which is never an error
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("alias ") {
+            continue;
+        }
+
+        if let Err(err) = check_line(context, &line) {
+            diagnostics.push(to_script_diagnostic(
+                err,
+                file,
+                first,
+                &line,
+                &physical[first - 1..],
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Each line of `script`, with the byte offset it starts at
+fn physical_lines(script: &str) -> Vec<(usize, &str)> {
+    let mut offset = 0;
+
+    script
+        .split('\n')
+        .map(|line| {
+            let start = offset;
+            offset += line.len() + 1;
+            (start, line.trim_end_matches('\r'))
+        })
+        .collect()
+}
+
+/// Where each of the lines a logical line was joined from starts in it. `continue_line` keeps
+/// the start of each line and puts one character after it, so the joined line is made of
+/// pieces that each begin a line of the script.
+fn piece_starts(physical: &[(usize, &str)]) -> Vec<usize> {
+    let mut pending = String::new();
+    let mut starts = vec![];
+
+    for (_, line) in physical {
+        starts.push(pending.len());
+        pending.push_str(line);
+
+        if !continue_line(&mut pending) {
+            break;
+        }
+    }
+
+    starts
+}
+
+fn check_line(context: &Context, line: &str) -> Result<(), ShellError> {
+    let line = &crate::commands::alias::expand_aliases(line, context.registry());
+    let statements = crate::parser::parse_statements(line, uuid::Uuid::nil())?;
+
+    for pipeline in statements {
+        classify_pipeline(&pipeline, context, &Text::from(line.as_str()))?;
+    }
+
+    Ok(())
+}
+
+fn to_script_diagnostic(
+    err: ShellError,
+    file: &str,
+    line_number: usize,
+    line: &str,
+    physical: &[(usize, &str)],
+) -> ScriptDiagnostic {
+    let help = err.help();
+    let diagnostic = err.to_diagnostic();
+
+    let primary = diagnostic
+        .labels
+        .iter()
+        .find(|label| match label.style {
+            LabelStyle::Primary => true,
+            _ => false,
+        })
+        .or_else(|| diagnostic.labels.first());
+
+    // An error that doesn't point anywhere covers the whole line
+    let (start, end) = match primary {
+        Some(label) => (
+            label.span.span.start.min(line.len()),
+            label.span.span.end.min(line.len()),
+        ),
+        None => (0, line.len()),
+    };
+
+    // Offsets into the joined line, found again in the lines of the script it came from
+    let starts = piece_starts(physical);
+    let locate = |offset: usize| {
+        let piece = starts
+            .iter()
+            .rposition(|start| *start <= offset)
+            .unwrap_or(0);
+        let (line_start, text) = physical[piece];
+        let within = (offset - starts[piece]).min(text.len());
+
+        let column = match text.get(..within) {
+            Some(before) => before.chars().count() + 1,
+            None => within + 1,
+        };
+
+        (piece, column, line_start + within)
+    };
+
+    let (start_piece, column, start) = locate(start);
+    let (end_piece, end_column, end) = locate(end);
+
+    ScriptDiagnostic {
+        file: file.to_string(),
+        line: line_number + start_piece,
+        column,
+        end_line: line_number + end_piece,
+        end_column,
+        start,
+        end,
+        severity: match diagnostic.severity {
+            Severity::Bug | Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note | Severity::Help => "info",
+        }
+        .to_string(),
+        message: diagnostic.message.clone(),
+        label: primary.and_then(|label| label.message.clone()),
+        help,
+    }
+}
+
+// Highlighting works from the token tree alone, which `parse_with_recovery` gives even for a
+// line that's half typed, and asks the registry only whether a command's name is one of nu's.
+// The prompt colours the line from it as it's typed, and editors can colour scripts the same way.
+
+/// What a piece of a line is, for colouring it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HighlightKind {
+    InternalCommand,
+    ExternalCommand,
+    Flag,
+    String,
+    Number,
+    Literal,
+    Variable,
+    Member,
+    Operator,
+    Pipe,
+    Bare,
+    Comment,
+    Error,
+}
+
+/// nu's commands, along with those of the plugins it finds, for `highlight` to tell them apart
+/// from external ones. Loading plugins takes a while, so this is done once for many lines.
+pub fn command_registry() -> Result<CommandRegistry, Box<dyn Error>> {
+    Ok(create_default_context()?.registry().clone())
+}
+
+/// The pieces of `line` worth colouring, in order, with what each is. The spaces between them,
+/// and the brackets and `;` around them, aren't included.
+pub fn highlight(line: &str, registry: &CommandRegistry) -> Vec<(Span, HighlightKind)> {
+    let tree = parse_with_recovery(line, uuid::Uuid::nil());
+
+    highlight_tree(&tree, line, registry)
+}
+
+pub(crate) fn highlight_tree(
+    tree: &TokenNode,
+    line: &str,
+    registry: &CommandRegistry,
+) -> Vec<(Span, HighlightKind)> {
+    let mut highlights = vec![];
+    highlight_node(tree, line, registry, &mut highlights);
+    highlights.sort_by_key(|(span, _)| span.start);
+    highlights
+}
+
+fn highlight_pipeline(
+    pipeline: &Pipeline,
+    line: &str,
+    registry: &CommandRegistry,
+    out: &mut Vec<(Span, HighlightKind)>,
+) {
+    for part in &pipeline.parts {
+        if let Some(pipe) = part.pipe {
+            out.push((pipe.span, HighlightKind::Pipe));
+        }
+
+        highlight_call(&part.call().item, line, registry, out);
+    }
+
+    if let Some(comment) = pipeline.comment {
+        out.push((comment.span, HighlightKind::Comment));
+    }
+}
+
+fn highlight_call(
+    call: &CallNode,
+    line: &str,
+    registry: &CommandRegistry,
+    out: &mut Vec<(Span, HighlightKind)>,
+) {
+    match call.head().as_ref() {
+        TokenNode::Token(Tagged {
+            item: RawToken::Bare,
+            tag,
+        }) => {
+            let name = tag.slice(line);
+            let kind = if registry.has(name) || registry.get_alias(name).is_some() {
+                HighlightKind::InternalCommand
+            } else {
+                HighlightKind::ExternalCommand
+            };
+
+            out.push((tag.span, kind));
+        }
+        head => highlight_node(head, line, registry, out),
+    }
+
+    if let Some(children) = call.children() {
+        for child in children {
+            highlight_node(child, line, registry, out);
+        }
+    }
+}
+
+fn highlight_node(
+    node: &TokenNode,
+    line: &str,
+    registry: &CommandRegistry,
+    out: &mut Vec<(Span, HighlightKind)>,
+) {
+    let tag = node.tag();
+
+    match node {
+        TokenNode::Token(token) => {
+            let kind = match token.item {
+                RawToken::Number(_) | RawToken::Size(..) => HighlightKind::Number,
+                RawToken::Boolean(_) | RawToken::Null => HighlightKind::Literal,
+                RawToken::String(_) => HighlightKind::String,
+                RawToken::Variable(_) => HighlightKind::Variable,
+                RawToken::ExternalCommand(_) => HighlightKind::ExternalCommand,
+                RawToken::ExternalWord | RawToken::GlobPattern | RawToken::Bare => {
+                    HighlightKind::Bare
+                }
+            };
+
+            out.push((tag.span, kind));
+        }
+        TokenNode::Call(call) => highlight_call(&call.item, line, registry, out),
+        TokenNode::Pipeline(pipeline) => highlight_pipeline(&pipeline.item, line, registry, out),
+        TokenNode::Statements(statements) => {
+            for statement in &statements.item {
+                highlight_node(statement, line, registry, out);
+            }
+        }
+        TokenNode::Delimited(delimited) => {
+            for child in delimited.item.children() {
+                highlight_node(child, line, registry, out);
+            }
+        }
+        TokenNode::Path(path) => {
+            highlight_node(path.item.head(), line, registry, out);
+
+            for member in path.item.tail() {
+                out.push((member.tag().span, HighlightKind::Member));
+            }
+        }
+        TokenNode::Table(items) => {
+            for item in &items.item {
+                highlight_node(item, line, registry, out);
+            }
+        }
+        TokenNode::Record(fields) => {
+            for (key, value) in &fields.item {
+                out.push((key.tag().span, HighlightKind::Member));
+                highlight_node(value, line, registry, out);
+            }
+        }
+        TokenNode::BlockParams(params) => {
+            for param in &params.item {
+                out.push((param.span, HighlightKind::Variable));
+            }
+        }
+        TokenNode::Interpolation(_) => out.push((tag.span, HighlightKind::String)),
+        TokenNode::Operator(_) => out.push((tag.span, HighlightKind::Operator)),
+        TokenNode::Flag(_) => out.push((tag.span, HighlightKind::Flag)),
+        TokenNode::Member(_) => out.push((tag.span, HighlightKind::Member)),
+        TokenNode::Error(_) => out.push((tag.span, HighlightKind::Error)),
+        TokenNode::Whitespace(_) => {}
+    }
+}
@@ -11,9 +11,12 @@ mod data;
 mod env;
 mod errors;
 mod evaluate;
+mod fmt;
 mod format;
 mod fuzzysearch;
 mod git;
+mod ide;
+mod lsp;
 mod parser;
 mod plugin;
 mod shell;
@@ -22,18 +25,24 @@ mod traits;
 mod utils;
 
 pub use crate::commands::command::{CallInfo, ReturnSuccess, ReturnValue};
-pub use crate::context::{SourceMap, SpanSource};
+pub use crate::context::{CommandRegistry, SourceMap, SpanSource};
 pub use crate::env::host::BasicHost;
 pub use crate::parser::hir::SyntaxShape;
+pub use crate::parser::parse::reparse::{reparse, Edit};
 pub use crate::parser::parse::token_tree_builder::TokenTreeBuilder;
-pub use crate::plugin::{serve_plugin, Plugin};
+pub use crate::parser::parse_with_recovery;
+pub use crate::plugin::{NuValues, PLUGIN_ABI_VERSION, PROTOCOL_VERSION};
 pub use crate::utils::{AbsoluteFile, AbsolutePath, RelativePath};
-pub use cli::cli;
+pub use cli::{cli, CliOptions};
 pub use data::base::{Primitive, Value};
-pub use data::config::{config_path, APP_INFO};
+pub use data::config::{config, config_path, APP_INFO};
 pub use data::dict::{Dictionary, TaggedDictBuilder};
-pub use data::meta::{Tag, Tagged, TaggedItem};
+pub use data::meta::{Span, Tag, Tagged, TaggedItem};
 pub use errors::{CoerceInto, ShellError};
+pub use fmt::{fmt_file, format_script};
+pub use format::hex::hex_dump;
+pub use ide::{command_registry, highlight, ide_check, HighlightKind, ScriptDiagnostic};
+pub use lsp::lsp;
 pub use num_traits::cast::ToPrimitive;
 pub use parser::parse::text::Text;
 pub use parser::registry::{EvaluatedArgs, NamedType, PositionalType, Signature};
@@ -0,0 +1,388 @@
+use crate::cli::create_default_context;
+use crate::context::Context;
+use crate::ide::check_script;
+use crate::parser::registry::{NamedType, PositionalType};
+use crate::shell::completer::{filesystem_completions, NuCompleter};
+use serde_json::{json, Value as Json};
+use std::collections::HashMap;
+use std::error::Error;
+use std::io::{BufRead, Read, Write};
+use std::path::PathBuf;
+
+// `nu --lsp` speaks the Language Server Protocol over stdin and stdout, so editors can give
+// feedback on `.nu` scripts as they're written. It offers:
+//
+// - diagnostics, the same ones `--ide-check` finds, each time a script is opened or changed
+// - hover, showing a command's usage and signature
+// - completion, the same as at the prompt
+// - document symbols, for the commands a script defines with `def` and `alias`
+//
+// Documents are synced whole. Positions count UTF-16 code units along a line, as the protocol
+// has them, and documents are found on disk from their `file://` URIs.
+
+/// Serve the Language Server Protocol on stdin and stdout until the client asks to exit
+pub fn lsp() -> Result<(), Box<dyn Error>> {
+    let mut server = Server {
+        context: create_default_context()?,
+        documents: HashMap::new(),
+    };
+
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+
+    while let Some(body) = read_message(&mut input)? {
+        // A message that can't be read is answered with an error, and the server carries on
+        let message: Json = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(e) => {
+                send(&json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": {"code": -32700, "message": format!("Parse error: {}", e)}
+                }))?;
+                continue;
+            }
+        };
+
+        let method = message
+            .get("method")
+            .and_then(Json::as_str)
+            .unwrap_or("")
+            .to_string();
+        let params = message.get("params").cloned().unwrap_or(Json::Null);
+
+        match message.get("id") {
+            Some(id) => match server.request(&method, &params) {
+                Some(result) => send(&json!({"jsonrpc": "2.0", "id": id, "result": result}))?,
+                None => send(&json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": {"code": -32601, "message": format!("Unknown method {}", method)}
+                }))?,
+            },
+            None if method == "exit" => return Ok(()),
+            None => server.notification(&method, &params)?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a message: headers, a blank line, then as many bytes of JSON as `Content-Length` says.
+/// Returns the JSON unparsed, or None once the client has gone.
+fn read_message(input: &mut impl BufRead) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+
+        if header.to_ascii_lowercase().starts_with("content-length:") {
+            length = header["content-length:".len()..]
+                .trim()
+                .parse::<usize>()
+                .ok();
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+
+    Ok(Some(body))
+}
+
+fn send(message: &Json) -> Result<(), Box<dyn Error>> {
+    let body = serde_json::to_string(message)?;
+
+    let stdout = std::io::stdout();
+    let mut stdout = stdout.lock();
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    stdout.flush()?;
+
+    Ok(())
+}
+
+struct Server {
+    context: Context,
+    // The text of each open document, by URI
+    documents: HashMap<String, String>,
+}
+
+impl Server {
+    /// Answer a request, or None if it isn't one we know
+    fn request(&self, method: &str, params: &Json) -> Option<Json> {
+        match method {
+            "initialize" => Some(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "completionProvider": {"triggerCharacters": ["-", "/"]},
+                    "documentSymbolProvider": true
+                },
+                "serverInfo": {"name": "nu"}
+            })),
+            "shutdown" => Some(Json::Null),
+            "textDocument/hover" => Some(self.hover(params).unwrap_or(Json::Null)),
+            "textDocument/completion" => Some(self.completion(params).unwrap_or_else(|| json!([]))),
+            "textDocument/documentSymbol" => {
+                Some(self.symbols(params).unwrap_or_else(|| json!([])))
+            }
+            _ => None,
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Result<(), Box<dyn Error>> {
+        let uri = match params["textDocument"]["uri"].as_str() {
+            Some(uri) => uri.to_string(),
+            None => return Ok(()),
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("");
+                self.documents.insert(uri.clone(), text.to_string());
+                self.publish_diagnostics(&uri)
+            }
+            "textDocument/didChange" => {
+                // With whole-document sync, the last change is the document as it now is
+                let changes = params["contentChanges"].as_array();
+                if let Some(text) = changes
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                {
+                    self.documents.insert(uri.clone(), text.to_string());
+                }
+                self.publish_diagnostics(&uri)
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                send(&json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": {"uri": uri, "diagnostics": []}
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Result<(), Box<dyn Error>> {
+        let text = match self.documents.get(uri) {
+            Some(text) => text,
+            None => return Ok(()),
+        };
+
+        let diagnostics: Vec<Json> = check_script(&self.context, uri, text)
+            .into_iter()
+            .map(|diagnostic| {
+                let mut message = match &diagnostic.label {
+                    Some(label) => format!("{}: {}", diagnostic.message, label),
+                    None => diagnostic.message.clone(),
+                };
+                if let Some(help) = &diagnostic.help {
+                    message.push_str(&format!("\n{}", help));
+                }
+
+                let severity = match diagnostic.severity.as_str() {
+                    "error" => 1,
+                    "warning" => 2,
+                    _ => 3,
+                };
+
+                let (start_line, start_character) = utf16_position(text, diagnostic.start);
+                let (end_line, end_character) = utf16_position(text, diagnostic.end);
+
+                json!({
+                    "range": range(start_line, start_character, end_line, end_character),
+                    "severity": severity,
+                    "source": "nu",
+                    "message": message
+                })
+            })
+            .collect();
+
+        send(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {"uri": uri, "diagnostics": diagnostics}
+        }))
+    }
+
+    /// The line the request's position is on, and the byte offset of the position in it
+    fn position<'a>(&'a self, params: &Json) -> Option<(&'a str, usize, usize)> {
+        let text = self
+            .documents
+            .get(params["textDocument"]["uri"].as_str()?)?;
+        let line_number = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+
+        let line = text.split('\n').nth(line_number)?.trim_end_matches('\r');
+
+        // The character is counted in UTF-16 code units
+        let mut units = 0;
+        let offset = line
+            .char_indices()
+            .find(|(_, c)| {
+                let found = units >= character;
+                units += c.len_utf16();
+                found
+            })
+            .map(|(idx, _)| idx)
+            .unwrap_or_else(|| line.len());
+
+        Some((line, line_number, offset))
+    }
+
+    fn hover(&self, params: &Json) -> Option<Json> {
+        let (line, _, offset) = self.position(params)?;
+
+        let is_word = |c: char| !c.is_whitespace() && !"|;()[]{}".contains(c);
+        let start = line[..offset]
+            .rfind(|c: char| !is_word(c))
+            .map(|idx| idx + 1)
+            .unwrap_or(0);
+        let end = line[offset..]
+            .find(|c: char| !is_word(c))
+            .map(|idx| idx + offset)
+            .unwrap_or_else(|| line.len());
+
+        let command = self.context.registry().get_command(&line[start..end])?;
+        let signature = command.signature();
+
+        let mut usage = signature.name.clone();
+        for positional in &signature.positional {
+            match positional {
+                PositionalType::Mandatory(name, _) => usage.push_str(&format!(" <{}>", name)),
+                PositionalType::Optional(name, _) => usage.push_str(&format!(" ({})", name)),
+            }
+        }
+        if signature.rest_positional.is_some() {
+            usage.push_str(" ...args");
+        }
+
+        let mut help = format!("```\n{}\n```\n\n{}\n", usage, command.usage());
+        for (name, ty) in &signature.named {
+            help.push_str(&match ty {
+                NamedType::Switch => format!("\n- `--{}`", name),
+                NamedType::Mandatory(shape) | NamedType::Optional(shape) => {
+                    format!("\n- `--{} <{}>`", name, shape)
+                }
+            });
+        }
+
+        Some(json!({"contents": {"kind": "markdown", "value": help}}))
+    }
+
+    fn completion(&self, params: &Json) -> Option<Json> {
+        let (line, line_number, offset) = self.position(params)?;
+
+        // Paths complete from the script's own directory
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let cwd = uri_to_path(uri)
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let completer = NuCompleter::new(self.context.registry().clone());
+        let (start, pairs) = completer
+            .complete(line, offset, &|partial| {
+                filesystem_completions(&cwd, partial)
+            })
+            .ok()?;
+
+        let start = line[..start].encode_utf16().count();
+        let end = line[..offset].encode_utf16().count();
+
+        let items: Vec<Json> = pairs
+            .into_iter()
+            .map(|pair| {
+                json!({
+                    "label": pair.display,
+                    "textEdit": {
+                        "range": range(line_number, start, line_number, end),
+                        "newText": pair.replacement
+                    }
+                })
+            })
+            .collect();
+
+        Some(json!(items))
+    }
+
+    fn symbols(&self, params: &Json) -> Option<Json> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let text = self.documents.get(uri)?;
+
+        let symbols: Vec<Json> = text
+            .split('\n')
+            .enumerate()
+            .filter_map(|(line_number, line)| {
+                let line = line.trim_end_matches('\r');
+                let trimmed = line.trim_start();
+
+                let rest = if trimmed.starts_with("def ") {
+                    &trimmed["def ".len()..]
+                } else if trimmed.starts_with("alias ") {
+                    &trimmed["alias ".len()..]
+                } else {
+                    return None;
+                };
+
+                // The name runs up to the parameters of a `def` or the `=` of an alias
+                let name = rest
+                    .split(|c: char| c.is_whitespace() || c == '[' || c == '=')
+                    .find(|word| !word.is_empty())?;
+
+                Some(json!({
+                    "name": name,
+                    "kind": 12,
+                    "location": {
+                        "uri": uri,
+                        "range": range(line_number, 0, line_number, line.encode_utf16().count())
+                    }
+                }))
+            })
+            .collect();
+
+        Some(json!(symbols))
+    }
+}
+
+fn range(start_line: usize, start_character: usize, end_line: usize, end_character: usize) -> Json {
+    json!({
+        "start": {"line": start_line, "character": start_character},
+        "end": {"line": end_line, "character": end_character}
+    })
+}
+
+/// The line a byte offset into `text` is on, and how many UTF-16 code units into it the offset is
+fn utf16_position(text: &str, offset: usize) -> (usize, usize) {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+
+    (
+        before.matches('\n').count(),
+        before[line_start..].encode_utf16().count(),
+    )
+}
+
+/// The file a `file://` URI names, with any escaped characters in it decoded
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    url::Url::parse(uri).ok()?.to_file_path().ok()
+}
@@ -1,10 +1,11 @@
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg};
 use log::LevelFilter;
 use std::error::Error;
 
 fn main() -> Result<(), Box<dyn Error>> {
     let matches = App::new("nushell")
         .version(clap::crate_version!())
+        .setting(AppSettings::TrailingVarArg)
         .arg(
             Arg::with_name("loglevel")
                 .short("l")
@@ -13,12 +14,72 @@ fn main() -> Result<(), Box<dyn Error>> {
                 .possible_values(&["error", "warn", "info", "debug", "trace"])
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("login")
+                .long("login")
+                .help("Start as a login shell, running login.nu and setting up the environment"),
+        )
+        .arg(
+            Arg::with_name("commands")
+                .short("c")
+                .long("commands")
+                .value_name("COMMANDS")
+                .takes_value(true)
+                .help("Run the given pipeline and exit with its exit code"),
+        )
+        .arg(
+            Arg::with_name("stdin-format")
+                .long("stdin-format")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .requires("commands")
+                .help("Convert stdin from this format (like json or csv) before the pipeline"),
+        )
+        .arg(
+            Arg::with_name("ide-check")
+                .long("ide-check")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Check a script for errors without running it, printing them as JSON"),
+        )
+        .arg(
+            Arg::with_name("lsp")
+                .long("lsp")
+                .help("Serve the Language Server Protocol on stdin and stdout, for editors"),
+        )
+        .arg(
+            Arg::with_name("fmt")
+                .long("fmt")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Rewrite a script with its spacing made regular"),
+        )
+        .arg(
+            Arg::with_name("test")
+                .long("test")
+                .requires("script")
+                .help("Run the test_ commands the script defines instead of the script itself"),
+        )
         .arg(
             Arg::with_name("develop")
                 .long("develop")
                 .multiple(true)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("script")
+                .value_name("SCRIPT")
+                .index(1)
+                .help("Run a script instead of starting the prompt"),
+        )
+        .arg(
+            Arg::with_name("args")
+                .value_name("ARGS")
+                .index(2)
+                .multiple(true)
+                .allow_hyphen_values(true)
+                .help("Arguments for the script, which it sees as $args"),
+        )
         .get_matches();
 
     let loglevel = match matches.value_of("loglevel") {
@@ -50,6 +111,32 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     builder.try_init()?;
 
-    futures::executor::block_on(nu::cli())?;
+    if let Some(file) = matches.value_of("ide-check") {
+        let clean = nu::ide_check(std::path::Path::new(file))?;
+        std::process::exit(if clean { 0 } else { 1 });
+    }
+
+    if matches.is_present("lsp") {
+        return nu::lsp();
+    }
+
+    if let Some(file) = matches.value_of("fmt") {
+        nu::fmt_file(std::path::Path::new(file))?;
+        return Ok(());
+    }
+
+    let options = nu::CliOptions {
+        login: matches.is_present("login"),
+        command: matches.value_of("commands").map(|c| c.to_string()),
+        stdin_format: matches.value_of("stdin-format").map(|f| f.to_string()),
+        script: matches.value_of("script").map(std::path::PathBuf::from),
+        test: matches.is_present("test"),
+        args: matches
+            .values_of("args")
+            .map(|args| args.map(|arg| arg.to_string()).collect())
+            .unwrap_or_default(),
+    };
+
+    futures::executor::block_on(nu::cli(options))?;
     Ok(())
 }
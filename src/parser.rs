@@ -5,6 +5,7 @@ pub(crate) mod parse_command;
 pub(crate) mod registry;
 
 use crate::errors::ShellError;
+use crate::Tag;
 
 pub(crate) use deserializer::ConfigDeserializer;
 pub(crate) use hir::baseline_parse_tokens::baseline_parse_tokens;
@@ -12,8 +13,13 @@ pub(crate) use parse::call_node::CallNode;
 pub(crate) use parse::files::Files;
 pub(crate) use parse::flag::Flag;
 pub(crate) use parse::operator::Operator;
-pub(crate) use parse::parser::{nom_input, pipeline};
+pub(crate) use parse::parser::{
+    closing_delimiter, continue_line, join_continued_lines, join_numbered_lines, nom_input,
+    pipeline, statements, statements_with_recovery, unclosed_delimiter, unclosed_delimiter_at,
+    unescape_bare, without_comment,
+};
 pub(crate) use parse::pipeline::{Pipeline, PipelineElement};
+pub(crate) use parse::reparse::{reparse, Edit};
 pub(crate) use parse::text::Text;
 pub(crate) use parse::token_tree::{DelimitedNode, Delimiter, PathNode, TokenNode};
 pub(crate) use parse::tokens::{RawToken, Token};
@@ -21,11 +27,61 @@ pub(crate) use parse::unit::Unit;
 pub(crate) use parse_command::parse_command;
 pub(crate) use registry::CommandRegistry;
 
+/// The pipelines of a line, which can be separated by `;`
+pub fn parse_statements(input: &str, origin: uuid::Uuid) -> Result<Vec<TokenNode>, ShellError> {
+    let _ = pretty_env_logger::try_init();
+
+    match statements(nom_input(input, origin)) {
+        Ok((_rest, val)) => val.as_statements(),
+        Err(err) => Err(parse_failure(input, origin, err)),
+    }
+}
+
 pub fn parse(input: &str, origin: uuid::Uuid) -> Result<TokenNode, ShellError> {
     let _ = pretty_env_logger::try_init();
 
     match pipeline(nom_input(input, origin)) {
         Ok((_rest, val)) => Ok(val),
-        Err(err) => Err(ShellError::parse_error(err)),
+        Err(err) => Err(parse_failure(input, origin, err)),
+    }
+}
+
+/// The pipelines of a line as far as they can be made out. Unlike `parse_statements` this never
+/// fails: the parts that can't be read are `TokenNode::Error`s, with the rest parsed around them.
+pub fn parse_with_recovery(input: &str, origin: uuid::Uuid) -> TokenNode {
+    statements_with_recovery(nom_input(input, origin))
+}
+
+// A bracket or quote left open is the likeliest reason a line doesn't parse, so that's reported
+// with where it was opened rather than where nom gave up. A number too large to read is
+// reported where it is.
+fn parse_failure(
+    input: &str,
+    origin: uuid::Uuid,
+    error: nom::Err<(parse::parser::NomSpan, nom::error::ErrorKind)>,
+) -> ShellError {
+    if let nom::Err::Failure((span, nom::error::ErrorKind::TooLarge)) = &error {
+        return ShellError::labeled_error(
+            "Number too large",
+            format!(
+                "the exponent can be at most {}",
+                parse::parser::MAX_EXPONENT
+            ),
+            Tag::from((span.offset, span.offset + span.fragment.len(), origin)),
+        );
+    }
+
+    match unclosed_delimiter_at(input) {
+        Some((delimiter, at)) => ShellError::labeled_error_with_secondary(
+            "Unclosed delimiter",
+            "unclosed delimiter opened here",
+            Tag::from((at, at + delimiter.len_utf8(), origin)),
+            format!(
+                "expected a closing {} by here",
+                closing_delimiter(delimiter)
+            ),
+            Tag::from((input.len(), input.len(), origin)),
+        ),
+        None => ShellError::parse_error(error),
     }
 }
@@ -55,6 +55,26 @@ impl Call {
     ) -> Result<registry::EvaluatedArgs, ShellError> {
         registry::evaluate_args(self, registry, scope, source)
     }
+
+    /// The subexpressions in the arguments, which are run before the command is. Those in blocks
+    /// are left alone, as a block is only run later.
+    pub(crate) fn subexpressions(&self) -> Vec<Tagged<Tag>> {
+        let mut subexpressions = vec![];
+
+        for expr in self.positional.iter().flatten() {
+            expr.collect_subexpressions(&mut subexpressions);
+        }
+
+        if let Some(named) = &self.named {
+            for value in named.named.values() {
+                if let named::NamedValue::Value(expr) = value {
+                    expr.collect_subexpressions(&mut subexpressions);
+                }
+            }
+        }
+
+        subexpressions
+    }
 }
 
 impl ToDebug for Call {
@@ -87,9 +107,15 @@ pub enum RawExpression {
     Synthetic(Synthetic),
     Variable(Variable),
     Binary(Box<Binary>),
-    Block(Vec<Expression>),
+    Not(Box<Expression>),
+    Block(Vec<Tagged<String>>, Vec<Expression>),
     List(Vec<Expression>),
     Path(Box<Path>),
+    Interpolation(Vec<Expression>),
+    Table(Vec<Expression>, Vec<Vec<Expression>>),
+    Record(Vec<(Tagged<String>, Expression)>),
+    // A pipeline in parentheses, given as the tag of the pipeline inside
+    Subexpression(Tag),
 
     FilePath(PathBuf),
     ExternalCommand(ExternalCommand),
@@ -120,8 +146,13 @@ impl RawExpression {
             RawExpression::Variable(..) => "variable",
             RawExpression::List(..) => "list",
             RawExpression::Binary(..) => "binary",
+            RawExpression::Not(..) => "boolean",
             RawExpression::Block(..) => "block",
             RawExpression::Path(..) => "path",
+            RawExpression::Interpolation(..) => "string",
+            RawExpression::Table(..) => "table",
+            RawExpression::Record(..) => "row",
+            RawExpression::Subexpression(..) => "subexpression",
             RawExpression::Boolean(..) => "boolean",
             RawExpression::ExternalCommand(..) => "external",
         }
@@ -143,6 +174,14 @@ impl Expression {
         RawExpression::Literal(Literal::Size(i.into(), unit.into())).tagged(tag.into())
     }
 
+    pub(crate) fn boolean(b: bool, tag: impl Into<Tag>) -> Expression {
+        RawExpression::Literal(Literal::Boolean(b)).tagged(tag.into())
+    }
+
+    pub(crate) fn nothing(tag: impl Into<Tag>) -> Expression {
+        RawExpression::Literal(Literal::Nothing).tagged(tag.into())
+    }
+
     pub(crate) fn synthetic_string(s: impl Into<String>) -> Expression {
         RawExpression::Synthetic(Synthetic::String(s.into())).tagged_unknown()
     }
@@ -174,6 +213,34 @@ impl Expression {
     pub(crate) fn it_variable(inner: impl Into<Tag>, outer: impl Into<Tag>) -> Expression {
         RawExpression::Variable(Variable::It(inner.into())).tagged(outer)
     }
+
+    fn collect_subexpressions(&self, out: &mut Vec<Tagged<Tag>>) {
+        match self.item() {
+            RawExpression::Subexpression(pipeline) => out.push((*pipeline).tagged(self.tag())),
+            RawExpression::Binary(binary) => {
+                binary.left().collect_subexpressions(out);
+                binary.right().collect_subexpressions(out);
+            }
+            RawExpression::Not(operand) => operand.collect_subexpressions(out),
+            RawExpression::Path(path) => path.head().collect_subexpressions(out),
+            RawExpression::List(exprs) | RawExpression::Interpolation(exprs) => {
+                for expr in exprs {
+                    expr.collect_subexpressions(out);
+                }
+            }
+            RawExpression::Table(headers, rows) => {
+                for expr in headers.iter().chain(rows.iter().flatten()) {
+                    expr.collect_subexpressions(out);
+                }
+            }
+            RawExpression::Record(fields) => {
+                for (_, expr) in fields {
+                    expr.collect_subexpressions(out);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl ToDebug for Expression {
@@ -186,10 +253,16 @@ impl ToDebug for Expression {
             RawExpression::Variable(Variable::It(_)) => write!(f, "$it"),
             RawExpression::Variable(Variable::Other(s)) => write!(f, "${}", s.slice(source)),
             RawExpression::Binary(b) => write!(f, "{}", b.debug(source)),
+            RawExpression::Not(operand) => write!(f, "not {}", operand.debug(source)),
             RawExpression::ExternalCommand(c) => write!(f, "^{}", c.name().slice(source)),
-            RawExpression::Block(exprs) => {
+            RawExpression::Block(params, exprs) => {
                 write!(f, "{{ ")?;
 
+                if !params.is_empty() {
+                    let names: Vec<_> = params.iter().map(|p| p.item.as_str()).collect();
+                    write!(f, "|{}| ", names.join(" "))?;
+                }
+
                 for expr in exprs {
                     write!(f, "{} ", expr.debug(source))?;
                 }
@@ -206,6 +279,51 @@ impl ToDebug for Expression {
                 write!(f, "]")
             }
             RawExpression::Path(p) => write!(f, "{}", p.debug(source)),
+            RawExpression::Interpolation(parts) => {
+                write!(f, "$\"")?;
+
+                for part in parts {
+                    match part.item() {
+                        RawExpression::Literal(Literal::String(tag)) => {
+                            write!(f, "{}", tag.slice(source))?
+                        }
+                        _ => write!(f, "{{{}}}", part.debug(source))?,
+                    }
+                }
+
+                write!(f, "\"")
+            }
+            RawExpression::Table(headers, rows) => {
+                write!(f, "[[ ")?;
+
+                for header in headers {
+                    write!(f, "{} ", header.debug(source))?;
+                }
+
+                write!(f, "];")?;
+
+                for row in rows {
+                    write!(f, " [ ")?;
+
+                    for cell in row {
+                        write!(f, "{} ", cell.debug(source))?;
+                    }
+
+                    write!(f, "]")?;
+                }
+
+                write!(f, "]")
+            }
+            RawExpression::Record(fields) => {
+                write!(f, "{{ ")?;
+
+                for (key, value) in fields {
+                    write!(f, "{}: {} ", key.item, value.debug(source))?;
+                }
+
+                write!(f, "}}")
+            }
+            RawExpression::Subexpression(pipeline) => write!(f, "({})", pipeline.slice(source)),
             RawExpression::Boolean(true) => write!(f, "$yes"),
             RawExpression::Boolean(false) => write!(f, "$no"),
         }
@@ -227,6 +345,8 @@ impl From<Tagged<Path>> for Expression {
 pub enum Literal {
     Number(Number),
     Size(Number, Unit),
+    Boolean(bool),
+    Nothing,
     String(Tag),
     GlobPattern,
     Bare,
@@ -237,6 +357,8 @@ impl ToDebug for Tagged<&Literal> {
         match self.item() {
             Literal::Number(number) => write!(f, "{:?}", *number),
             Literal::Size(number, unit) => write!(f, "{:?}{:?}", *number, unit),
+            Literal::Boolean(boolean) => write!(f, "{}", boolean),
+            Literal::Nothing => write!(f, "null"),
             Literal::String(tag) => write!(f, "{}", tag.slice(source)),
             Literal::GlobPattern => write!(f, "{}", self.tag().slice(source)),
             Literal::Bare => write!(f, "{}", self.tag().slice(source)),
@@ -249,6 +371,8 @@ impl Literal {
         match self {
             Literal::Number(..) => "number",
             Literal::Size(..) => "size",
+            Literal::Boolean(..) => "boolean",
+            Literal::Nothing => "nothing",
             Literal::String(..) => "string",
             Literal::Bare => "string",
             Literal::GlobPattern => "pattern",
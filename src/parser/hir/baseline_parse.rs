@@ -1,6 +1,6 @@
 use crate::context::Context;
 use crate::errors::ShellError;
-use crate::parser::{hir, RawToken, Token};
+use crate::parser::{hir, unescape_bare, RawToken, Token};
 use crate::TaggedItem;
 use crate::Text;
 use std::path::PathBuf;
@@ -14,6 +14,8 @@ pub fn baseline_parse_single_token(
         RawToken::Size(int, unit) => {
             hir::Expression::size(int.to_number(source), unit, token.tag())
         }
+        RawToken::Boolean(b) => hir::Expression::boolean(b, token.tag()),
+        RawToken::Null => hir::Expression::nothing(token.tag()),
         RawToken::String(tag) => hir::Expression::string(tag, token.tag()),
         RawToken::Variable(tag) if tag.slice(source) == "it" => {
             hir::Expression::it_variable(tag, token.tag())
@@ -41,7 +43,9 @@ pub fn baseline_parse_token_as_number(
         RawToken::Size(number, unit) => {
             hir::Expression::size(number.to_number(source), unit, token.tag())
         }
-        RawToken::Bare => hir::Expression::bare(token.tag()),
+        RawToken::Boolean(_) | RawToken::Null | RawToken::Bare => {
+            hir::Expression::bare(token.tag())
+        }
         RawToken::GlobPattern => {
             return Err(ShellError::type_error(
                 "Number",
@@ -65,7 +69,9 @@ pub fn baseline_parse_token_as_string(
         RawToken::Variable(tag) => hir::Expression::variable(tag, token.tag()),
         RawToken::Number(_) => hir::Expression::bare(token.tag()),
         RawToken::Size(_, _) => hir::Expression::bare(token.tag()),
-        RawToken::Bare => hir::Expression::bare(token.tag()),
+        RawToken::Boolean(_) | RawToken::Null | RawToken::Bare => {
+            hir::Expression::bare(token.tag())
+        }
         RawToken::GlobPattern => {
             return Err(ShellError::type_error(
                 "String",
@@ -90,9 +96,10 @@ pub fn baseline_parse_token_as_path(
         RawToken::Variable(tag) => hir::Expression::variable(tag, token.tag()),
         RawToken::Number(_) => hir::Expression::bare(token.tag()),
         RawToken::Size(_, _) => hir::Expression::bare(token.tag()),
-        RawToken::Bare => {
-            hir::Expression::file_path(expand_path(token.tag().slice(source), context), token.tag())
-        }
+        RawToken::Boolean(_) | RawToken::Null | RawToken::Bare => hir::Expression::file_path(
+            expand_path(&unescape_bare(token.tag().slice(source)), context),
+            token.tag(),
+        ),
         RawToken::GlobPattern => {
             return Err(ShellError::type_error(
                 "Path",
@@ -124,7 +131,7 @@ pub fn baseline_parse_token_as_pattern(
         RawToken::Number(_) => hir::Expression::bare(token.tag()),
         RawToken::Size(_, _) => hir::Expression::bare(token.tag()),
         RawToken::GlobPattern => hir::Expression::pattern(token.tag()),
-        RawToken::Bare => {
+        RawToken::Boolean(_) | RawToken::Null | RawToken::Bare => {
             hir::Expression::file_path(expand_path(token.tag().slice(source), context), token.tag())
         }
         RawToken::String(tag) => {
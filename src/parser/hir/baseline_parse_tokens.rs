@@ -6,7 +6,7 @@ use crate::parser::{
         baseline_parse_single_token, baseline_parse_token_as_number, baseline_parse_token_as_path,
         baseline_parse_token_as_pattern, baseline_parse_token_as_string,
     },
-    DelimitedNode, Delimiter, PathNode, RawToken, TokenNode,
+    DelimitedNode, Delimiter, Operator, PathNode, RawToken, TokenNode,
 };
 use crate::{Tag, Tagged, TaggedItem, Text};
 use derive_new::new;
@@ -78,9 +78,42 @@ pub fn baseline_parse_next_expr(
         .next()
         .ok_or_else(|| ShellError::string("Expected token, found none"))?;
 
+    // `not` before a condition negates it. Where any argument can go, a bare word after it is
+    // more likely just words, as in `echo not yet`.
+    let negates = match (syntax_type, next, tokens.peek()) {
+        (SyntaxShape::Block, TokenNode::Operator(op), Some(_)) => op.is_unary(),
+        (SyntaxShape::Any, TokenNode::Operator(op), Some(operand)) => {
+            op.is_unary() && !operand.is_bare()
+        }
+        _ => false,
+    };
+
+    // `in` at the start of an expression is just the word
+    let word;
+    let next = match next {
+        _ if negates => next,
+        TokenNode::Operator(op) if op.is_word() => {
+            word = TokenNode::Token(RawToken::Bare.tagged(op.tag()));
+            &word
+        }
+        // So is `*`, `/`, `+` or `-`, as in `cp * /tmp` or `cd /`. A `*` is a glob.
+        TokenNode::Operator(op) if op.is_arithmetic() => {
+            let token = match op.item {
+                Operator::Multiply | Operator::Pow => RawToken::GlobPattern,
+                _ => RawToken::Bare,
+            };
+            word = TokenNode::Token(token.tagged(op.tag()));
+            &word
+        }
+        other => other,
+    };
+
     trace!(target: "nu::parser::parse_one_expr", "syntax_type={:?}, token={:?}", syntax_type, next);
 
     match (syntax_type, next) {
+        // Whatever is in parentheses can stand in for any kind of argument
+        (_, TokenNode::Delimited(delimited)) if *delimited.delimiter() == Delimiter::Paren => {}
+
         (SyntaxShape::Path, TokenNode::Token(token)) => {
             return baseline_parse_token_as_path(token, context, source)
         }
@@ -107,6 +140,9 @@ pub fn baseline_parse_next_expr(
             return baseline_parse_token_as_string(token, source);
         }
 
+        // Filled in when the arguments are evaluated
+        (SyntaxShape::String, TokenNode::Interpolation(_)) => {}
+
         (SyntaxShape::String, token) => {
             return Err(ShellError::type_error(
                 "String",
@@ -115,7 +151,10 @@ pub fn baseline_parse_next_expr(
         }
 
         (SyntaxShape::Number, TokenNode::Token(token)) => {
-            return Ok(baseline_parse_token_as_number(token, source)?);
+            let number = baseline_parse_token_as_number(token, source)?;
+            let arithmetic = Operator::Plus.precedence();
+
+            return baseline_parse_binary(number, arithmetic, tokens, context, source);
         }
 
         (SyntaxShape::Number, token) => {
@@ -146,99 +185,247 @@ pub fn baseline_parse_next_expr(
         (SyntaxShape::Boolean, _) => {}
     };
 
-    let first = baseline_parse_semantic_token(next, context, source)?;
-
-    let possible_op = tokens.peek();
+    let first = baseline_parse_operand(next, tokens, context, source)?;
 
-    let op = match possible_op {
-        Some(TokenNode::Operator(op)) => op.clone(),
+    match tokens.peek() {
+        // Arithmetic and the word operators need a value on their left, except where a number
+        // or a condition is expected, so `echo checked in` is just words
+        Some(TokenNode::Operator(op))
+            if (op.is_arithmetic() || op.is_word()) && !computes(syntax_type, &first) =>
+        {
+            return Ok(first)
+        }
+        Some(TokenNode::Operator(_)) => {}
+        // An un-braced `where not ...` is still a block
+        _ if negates => {}
         _ => return Ok(first),
     };
 
-    tokens.next();
+    // We definitely have a binary expression here -- let's see if we should coerce it into a block
+
+    match syntax_type {
+        SyntaxShape::Any => baseline_parse_binary(first, 0, tokens, context, source),
+
+        SyntaxShape::Block => {
+            let binary = baseline_parse_binary(first, 0, tokens, context, source)?;
+            let binary = coerce_block_columns(binary, source)?;
+            let tag = binary.tag();
+
+            let block = hir::RawExpression::Block(vec![], vec![binary]);
+            let block = block.tagged(tag);
+
+            Ok(block)
+        }
+
+        other => Err(ShellError::unimplemented(format!(
+            "coerce hint {:?}",
+            other
+        ))),
+    }
+}
+
+/// Whether an arithmetic or word operator after `left` is an operator, rather than being the
+/// next word as in `echo a - b` or `echo load the mod`
+fn computes(syntax_type: SyntaxShape, left: &hir::Expression) -> bool {
+    match (syntax_type, &left.item) {
+        (SyntaxShape::Block, _) | (SyntaxShape::Number, _) => true,
+        (_, hir::RawExpression::Literal(hir::Literal::Number(_)))
+        | (_, hir::RawExpression::Literal(hir::Literal::Size(..)))
+        | (_, hir::RawExpression::Variable(_))
+        | (_, hir::RawExpression::Path(_))
+        | (_, hir::RawExpression::Binary(_))
+        | (_, hir::RawExpression::Subexpression(_)) => true,
+        _ => false,
+    }
+}
+
+/// In an un-braced block, each condition starts with a column name. Conditions joined with
+/// `&&` and `||` each get their own, so `size > 1kb && type == Directory` checks two columns.
+fn coerce_block_columns(
+    expr: hir::Expression,
+    source: &Text,
+) -> Result<hir::Expression, ShellError> {
+    let tag = expr.tag();
+
+    match expr.item {
+        hir::RawExpression::Binary(binary) => {
+            let (left, op, right) = binary.into_parts();
+
+            let (left, right) = if op.is_logical() {
+                (
+                    coerce_block_columns(left, source)?,
+                    coerce_block_columns(right, source)?,
+                )
+            } else {
+                (coerce_block_columns(left, source)?, right)
+            };
+
+            let binary = hir::Binary::new(left, op, right);
+            Ok(hir::RawExpression::Binary(Box::new(binary)).tagged(tag))
+        }
 
-    let second = match tokens.next() {
-        None => {
+        hir::RawExpression::Not(operand) => {
+            let operand = coerce_block_columns(*operand, source)?;
+            Ok(hir::RawExpression::Not(Box::new(operand)).tagged(tag))
+        }
+
+        item => coerce_column(item.tagged(tag), source),
+    }
+}
+
+fn coerce_column(first: hir::Expression, source: &Text) -> Result<hir::Expression, ShellError> {
+    // A path from a variable, like `$it.name`, already says where it looks
+    if let hir::RawExpression::Path(path) = &first.item {
+        if is_variable(path.head()) {
+            return Ok(first);
+        }
+    }
+
+    match first {
+        Tagged {
+            item: hir::RawExpression::Literal(hir::Literal::Bare),
+            tag,
+        } => {
+            let string = tag.slice(source).to_string().tagged(tag);
+            let path = hir::Path::new(
+                // TODO: Deal with synthetic nodes that have no representation at all in source
+                hir::RawExpression::Variable(hir::Variable::It(Tag::unknown()))
+                    .tagged(Tag::unknown()),
+                vec![string],
+            );
+            let path = hir::RawExpression::Path(Box::new(path));
+            Ok(path.tagged(first.tag()))
+        }
+        Tagged {
+            item: hir::RawExpression::Literal(hir::Literal::String(inner)),
+            tag,
+        } => {
+            let string = inner.slice(source).to_string().tagged(tag);
+            let path = hir::Path::new(
+                // TODO: Deal with synthetic nodes that have no representation at all in source
+                hir::RawExpression::Variable(hir::Variable::It(Tag::unknown())).tagged_unknown(),
+                vec![string],
+            );
+            let path = hir::RawExpression::Path(Box::new(path));
+            Ok(path.tagged(first.tag()))
+        }
+        Tagged {
+            item: hir::RawExpression::Variable(..),
+            ..
+        } => Ok(first),
+        Tagged { tag, item } => Err(ShellError::labeled_error(
+            "The first part of an un-braced block must be a column name",
+            item.type_name(),
+            tag,
+        )),
+    }
+}
+
+fn is_variable(expr: &hir::Expression) -> bool {
+    match expr.item {
+        hir::RawExpression::Variable(..) => true,
+        _ => false,
+    }
+}
+
+/// Read operators and their right-hand sides after `left` for as long as they bind at least as
+/// tightly as `min_precedence`, so that `1 + 2 * 3 > 6` groups as `(1 + (2 * 3)) > 6`
+fn baseline_parse_binary(
+    mut left: hir::Expression,
+    min_precedence: usize,
+    tokens: &mut TokensIterator,
+    context: &Context,
+    source: &Text,
+) -> Result<hir::Expression, ShellError> {
+    loop {
+        let op = match tokens.peek() {
+            Some(TokenNode::Operator(op))
+                if !op.is_unary() && op.precedence() >= min_precedence =>
+            {
+                op.clone()
+            }
+            _ => return Ok(left),
+        };
+
+        if op.item == Operator::Append {
             return Err(ShellError::labeled_error(
-                "Expected something after an operator",
-                "operator",
+                "Unexpected redirection",
+                "only the end of a pipeline can be redirected",
                 op.tag(),
-            ))
+            ));
         }
-        Some(token) => baseline_parse_semantic_token(token, context, source)?,
-    };
 
-    // We definitely have a binary expression here -- let's see if we should coerce it into a block
+        tokens.next();
 
-    match syntax_type {
-        SyntaxShape::Any => {
-            let tag = first.tag().until(second.tag());
-            let binary = hir::Binary::new(first, op, second);
-            let binary = hir::RawExpression::Binary(Box::new(binary));
-            let binary = binary.tagged(tag);
+        let mut right = match tokens.next() {
+            None => {
+                return Err(ShellError::labeled_error(
+                    "Expected something after an operator",
+                    "operator",
+                    op.tag(),
+                ))
+            }
+            Some(token) => baseline_parse_operand(token, tokens, context, source)?,
+        };
 
-            Ok(binary)
+        loop {
+            // A right-associative operator takes the rest of a run of itself as its right side
+            let binds_tighter = match tokens.peek() {
+                Some(TokenNode::Operator(next)) if next.is_unary() => false,
+                Some(TokenNode::Operator(next)) => {
+                    next.precedence() > op.precedence()
+                        || (op.is_right_associative() && next.item == op.item)
+                }
+                _ => false,
+            };
+
+            if !binds_tighter {
+                break;
+            }
+
+            let min_precedence = if op.is_right_associative() {
+                op.precedence()
+            } else {
+                op.precedence() + 1
+            };
+
+            right = baseline_parse_binary(right, min_precedence, tokens, context, source)?;
         }
 
-        SyntaxShape::Block => {
-            let tag = first.tag().until(second.tag());
-
-            let path: Tagged<hir::RawExpression> = match first {
-                Tagged {
-                    item: hir::RawExpression::Literal(hir::Literal::Bare),
-                    tag,
-                } => {
-                    let string = tag.slice(source).to_string().tagged(tag);
-                    let path = hir::Path::new(
-                        // TODO: Deal with synthetic nodes that have no representation at all in source
-                        hir::RawExpression::Variable(hir::Variable::It(Tag::unknown()))
-                            .tagged(Tag::unknown()),
-                        vec![string],
-                    );
-                    let path = hir::RawExpression::Path(Box::new(path));
-                    path.tagged(first.tag())
-                }
-                Tagged {
-                    item: hir::RawExpression::Literal(hir::Literal::String(inner)),
-                    tag,
-                } => {
-                    let string = inner.slice(source).to_string().tagged(tag);
-                    let path = hir::Path::new(
-                        // TODO: Deal with synthetic nodes that have no representation at all in source
-                        hir::RawExpression::Variable(hir::Variable::It(Tag::unknown()))
-                            .tagged_unknown(),
-                        vec![string],
-                    );
-                    let path = hir::RawExpression::Path(Box::new(path));
-                    path.tagged(first.tag())
-                }
-                Tagged {
-                    item: hir::RawExpression::Variable(..),
-                    ..
-                } => first,
-                Tagged { tag, item } => {
+        let tag = left.tag().until(right.tag());
+        let binary = hir::Binary::new(left, op, right);
+        left = hir::RawExpression::Binary(Box::new(binary)).tagged(tag);
+    }
+}
+
+/// A single operand: a token, or `not` and the condition after it, which runs up to the next
+/// `&&` or `||`. So `not size > 10kb && type == File` is `(not (size > 10kb)) && type == File`.
+fn baseline_parse_operand(
+    token: &TokenNode,
+    tokens: &mut TokensIterator,
+    context: &Context,
+    source: &Text,
+) -> Result<hir::Expression, ShellError> {
+    match token {
+        TokenNode::Operator(op) if op.is_unary() => {
+            let operand = match tokens.next() {
+                Some(operand) => baseline_parse_operand(operand, tokens, context, source)?,
+                None => {
                     return Err(ShellError::labeled_error(
-                        "The first part of an un-braced block must be a column name",
-                        item.type_name(),
-                        tag,
+                        "Expected a condition after not",
+                        "not",
+                        op.tag(),
                     ))
                 }
             };
 
-            let binary = hir::Binary::new(path, op, second);
-            let binary = hir::RawExpression::Binary(Box::new(binary));
-            let binary = binary.tagged(tag);
-
-            let block = hir::RawExpression::Block(vec![binary]);
-            let block = block.tagged(tag);
+            let operand =
+                baseline_parse_binary(operand, op.precedence() + 1, tokens, context, source)?;
+            let tag = op.tag().until(operand.tag());
 
-            Ok(block)
+            Ok(hir::RawExpression::Not(Box::new(operand)).tagged(tag))
         }
-
-        other => Err(ShellError::unimplemented(format!(
-            "coerce hint {:?}",
-            other
-        ))),
+        other => baseline_parse_semantic_token(other, context, source),
     }
 }
 
@@ -249,9 +436,16 @@ pub fn baseline_parse_semantic_token(
 ) -> Result<hir::Expression, ShellError> {
     match token {
         TokenNode::Token(token) => baseline_parse_single_token(token, source),
-        TokenNode::Call(_call) => unimplemented!(),
+        TokenNode::Call(call) => Err(ShellError::syntax_error(
+            "Unexpected command".tagged(call.tag()),
+        )),
         TokenNode::Delimited(delimited) => baseline_parse_delimited(delimited, context, source),
-        TokenNode::Pipeline(_pipeline) => unimplemented!(),
+        TokenNode::Pipeline(pipeline) => Err(ShellError::syntax_error(
+            "Unexpected pipeline".tagged(pipeline.tag()),
+        )),
+        TokenNode::Statements(statements) => Err(ShellError::syntax_error(
+            "Unexpected pipeline".tagged(statements.tag()),
+        )),
         TokenNode::Operator(op) => Err(ShellError::syntax_error(
             "Unexpected operator".tagged(op.tag),
         )),
@@ -264,9 +458,80 @@ pub fn baseline_parse_semantic_token(
         )),
         TokenNode::Error(error) => Err(*error.item.clone()),
         TokenNode::Path(path) => baseline_parse_path(path, context, source),
+        TokenNode::Interpolation(parts) => {
+            let mut exprs = vec![];
+
+            for part in parts.iter() {
+                exprs.push(baseline_parse_semantic_token(part, context, source)?);
+            }
+
+            Ok(hir::RawExpression::Interpolation(exprs).tagged(parts.tag()))
+        }
+        TokenNode::Table(parts) => baseline_parse_table(parts, context, source),
+        TokenNode::BlockParams(params) => Err(ShellError::syntax_error(
+            "Block parameters must start a block".tagged(params.tag()),
+        )),
+        TokenNode::Record(entries) => {
+            let mut fields = vec![];
+
+            for (key, value) in entries.iter() {
+                let key = baseline_parse_member(key, source)?;
+                let value = baseline_parse_semantic_token(value, context, source)?;
+                fields.push((key, value));
+            }
+
+            Ok(hir::RawExpression::Record(fields).tagged(entries.tag()))
+        }
     }
 }
 
+fn baseline_parse_table(
+    parts: &Tagged<Vec<TokenNode>>,
+    context: &Context,
+    source: &Text,
+) -> Result<hir::Expression, ShellError> {
+    let mut cells = vec![];
+
+    for part in parts.iter() {
+        let children = match part {
+            TokenNode::Delimited(delimited) => delimited.children(),
+            other => {
+                return Err(ShellError::type_error(
+                    "List",
+                    other.type_name().tagged(other.tag()),
+                ))
+            }
+        };
+
+        let exprs = baseline_parse_tokens(
+            &mut TokensIterator::new(children),
+            context,
+            source,
+            SyntaxShape::Any,
+        )?;
+
+        cells.push(exprs.tagged(part.tag()));
+    }
+
+    let mut cells = cells.into_iter();
+    let headers = cells.next().map(|h| h.item).unwrap_or_else(|| vec![]);
+    let mut rows = vec![];
+
+    for row in cells {
+        if row.len() != headers.len() {
+            return Err(ShellError::labeled_error(
+                format!("Expected {} cells", headers.len()),
+                format!("this row has {}", row.len()),
+                row.tag(),
+            ));
+        }
+
+        rows.push(row.item);
+    }
+
+    Ok(hir::RawExpression::Table(headers, rows).tagged(parts.tag()))
+}
+
 pub fn baseline_parse_delimited(
     token: &Tagged<DelimitedNode>,
     context: &Context,
@@ -274,18 +539,59 @@ pub fn baseline_parse_delimited(
 ) -> Result<hir::Expression, ShellError> {
     match token.delimiter() {
         Delimiter::Brace => {
+            let (params, children) = match token.children().split_first() {
+                Some((TokenNode::BlockParams(params), rest)) => (
+                    params
+                        .iter()
+                        .map(|p| p.slice(source).to_string().tagged(*p))
+                        .collect(),
+                    rest,
+                ),
+                _ => (vec![], &token.children()[..]),
+            };
+
+            // A block holding a pipeline, like `{ ls | first 2 }` or `{ echo big }`, is run as
+            // one by commands like `if` rather than evaluated
+            let exprs = match subexpression(children, context, source) {
+                Some(pipeline) => {
+                    vec![hir::RawExpression::Subexpression(pipeline).tagged(pipeline)]
+                }
+                None => baseline_parse_tokens(
+                    &mut TokensIterator::new(children),
+                    context,
+                    source,
+                    SyntaxShape::Any,
+                )?,
+            };
+
+            let expr = hir::RawExpression::Block(params, exprs);
+            Ok(expr.tagged(token.tag()))
+        }
+        Delimiter::Paren => {
             let children = token.children();
-            let exprs = baseline_parse_tokens(
+
+            if let Some(pipeline) = subexpression(children, context, source) {
+                return Ok(hir::RawExpression::Subexpression(pipeline).tagged(token.tag()));
+            }
+
+            let mut exprs = baseline_parse_tokens(
                 &mut TokensIterator::new(children),
                 context,
                 source,
                 SyntaxShape::Any,
             )?;
 
-            let expr = hir::RawExpression::Block(exprs);
-            Ok(expr.tagged(token.tag()))
+            if exprs.len() != 1 {
+                return Err(ShellError::labeled_error(
+                    "Expected a single expression in parentheses",
+                    "like (3 + 4)",
+                    token.tag(),
+                ));
+            }
+
+            let expr = exprs.remove(0);
+            Ok(expr.item.tagged(token.tag()))
         }
-        Delimiter::Paren => unimplemented!(),
         Delimiter::Square => {
             let children = token.children();
             let exprs = baseline_parse_tokens(
@@ -301,6 +607,30 @@ pub fn baseline_parse_delimited(
     }
 }
 
+/// The pipeline in parentheses, if that's what they hold: more than one command, or a single
+/// one whose name is a command, like `(pwd)`
+fn subexpression(children: &[TokenNode], context: &Context, source: &Text) -> Option<Tag> {
+    let significant: Vec<&TokenNode> = children
+        .iter()
+        .filter(|token| match token {
+            TokenNode::Whitespace(_) => false,
+            _ => true,
+        })
+        .collect();
+
+    let first = significant.first()?;
+    let last = significant.last()?;
+
+    match first {
+        TokenNode::Pipeline(pipeline) => Some(pipeline.tag()),
+        first if first.is_external() => Some(first.tag().until(last.tag())),
+        first if first.is_bare() && context.has_command(first.source(source)) => {
+            Some(first.tag().until(last.tag()))
+        }
+        _ => None,
+    }
+}
+
 pub fn baseline_parse_path(
     token: &Tagged<PathNode>,
     context: &Context,
@@ -311,38 +641,43 @@ pub fn baseline_parse_path(
     let mut tail = vec![];
 
     for part in token.tail() {
-        let string = match part {
-            TokenNode::Token(token) => match token.item() {
-                RawToken::Bare => token.tag().slice(source),
-                RawToken::String(tag) => tag.slice(source),
-                RawToken::Number(_)
-                | RawToken::Size(..)
-                | RawToken::Variable(_)
-                | RawToken::ExternalCommand(_)
-                | RawToken::GlobPattern
-                | RawToken::ExternalWord => {
-                    return Err(ShellError::type_error(
-                        "String",
-                        token.type_name().tagged(part.tag()),
-                    ))
-                }
-            },
+        tail.push(baseline_parse_member(part, source)?);
+    }
 
-            TokenNode::Member(tag) => tag.slice(source),
+    Ok(hir::path(head, tail).tagged(token.tag()).into())
+}
 
-            // TODO: Make this impossible
-            other => {
-                return Err(ShellError::syntax_error(
-                    format!("{} in path", other.type_name()).tagged(other.tag()),
+/// The name of a column, as in a path's tail or a record's keys
+fn baseline_parse_member(part: &TokenNode, source: &Text) -> Result<Tagged<String>, ShellError> {
+    let string = match part {
+        TokenNode::Token(token) => match token.item() {
+            RawToken::Boolean(_) | RawToken::Null | RawToken::Bare => token.tag().slice(source),
+            RawToken::String(tag) => tag.slice(source),
+            RawToken::Number(_)
+            | RawToken::Size(..)
+            | RawToken::Variable(_)
+            | RawToken::ExternalCommand(_)
+            | RawToken::GlobPattern
+            | RawToken::ExternalWord => {
+                return Err(ShellError::type_error(
+                    "String",
+                    token.type_name().tagged(part.tag()),
                 ))
             }
-        }
-        .to_string();
+        },
 
-        tail.push(string.tagged(part.tag()));
+        TokenNode::Member(tag) => tag.slice(source),
+
+        // TODO: Make this impossible
+        other => {
+            return Err(ShellError::syntax_error(
+                format!("{} in path", other.type_name()).tagged(other.tag()),
+            ))
+        }
     }
+    .to_string();
 
-    Ok(hir::path(head, tail).tagged(token.tag()).into())
+    Ok(string.tagged(part.tag()))
 }
 
 #[derive(Debug, new)]
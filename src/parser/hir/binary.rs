@@ -16,6 +16,12 @@ pub struct Binary {
     right: Expression,
 }
 
+impl Binary {
+    pub(crate) fn into_parts(self) -> (Expression, Tagged<Operator>, Expression) {
+        (self.left, self.op, self.right)
+    }
+}
+
 impl ToDebug for Binary {
     fn fmt_debug(&self, f: &mut fmt::Formatter, source: &str) -> fmt::Result {
         write!(f, "{}", self.left.debug(source))?;
@@ -11,6 +11,20 @@ pub enum Operator {
     GreaterThan,
     LessThanOrEqual,
     GreaterThanOrEqual,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    Pow,
+    And,
+    Or,
+    Match,
+    NotMatch,
+    In,
+    NotIn,
+    Not,
+    Append,
 }
 
 impl ToDebug for Operator {
@@ -32,6 +46,88 @@ impl Operator {
             Operator::GreaterThan => ">",
             Operator::LessThanOrEqual => "<=",
             Operator::GreaterThanOrEqual => ">=",
+            Operator::Plus => "+",
+            Operator::Minus => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+            Operator::Modulo => "mod",
+            Operator::Pow => "**",
+            Operator::And => "&&",
+            Operator::Or => "||",
+            Operator::Match => "=~",
+            Operator::NotMatch => "!~",
+            Operator::In => "in",
+            Operator::NotIn => "not-in",
+            Operator::Not => "not",
+            Operator::Append => ">>",
+        }
+    }
+
+    /// How tightly the operator binds: `**` first, then `*`, `/` and `mod`, then `+` and `-`,
+    /// then the comparisons, then `&&` and last of all `||`. `not` takes in everything after it
+    /// up to the next `&&` or `||`. `>>` only ever redirects a pipeline, so it binds nothing.
+    pub fn precedence(&self) -> usize {
+        match *self {
+            Operator::Append => 0,
+            Operator::Not => 2,
+            Operator::Pow => 6,
+            Operator::Multiply | Operator::Divide | Operator::Modulo => 5,
+            Operator::Plus | Operator::Minus => 4,
+            Operator::And => 2,
+            Operator::Or => 1,
+            _ => 3,
+        }
+    }
+
+    /// Whether the operator goes before a single operand rather than between two
+    pub fn is_unary(&self) -> bool {
+        *self == Operator::Not
+    }
+
+    /// Whether a run of the operator groups from the right, so `2 ** 3 ** 2` is `2 ** (3 ** 2)`
+    pub fn is_right_associative(&self) -> bool {
+        *self == Operator::Pow
+    }
+
+    /// Operators spelled with symbols that also turn up in words and globs, like `cp * /tmp`
+    pub fn is_arithmetic(&self) -> bool {
+        match *self {
+            Operator::Plus
+            | Operator::Minus
+            | Operator::Multiply
+            | Operator::Divide
+            | Operator::Pow => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_comparison(&self) -> bool {
+        self.precedence() == 3
+    }
+
+    pub fn is_pattern_match(&self) -> bool {
+        match *self {
+            Operator::Match | Operator::NotMatch => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_membership(&self) -> bool {
+        match *self {
+            Operator::In | Operator::NotIn => true,
+            _ => false,
+        }
+    }
+
+    /// Operators spelled as words, which are plain words again wherever an operator can't go
+    pub fn is_word(&self) -> bool {
+        self.is_membership() || self.is_unary() || *self == Operator::Modulo
+    }
+
+    pub fn is_logical(&self) -> bool {
+        match *self {
+            Operator::And | Operator::Or => true,
+            _ => false,
         }
     }
 }
@@ -52,6 +148,20 @@ impl FromStr for Operator {
             ">" => Ok(Operator::GreaterThan),
             "<=" => Ok(Operator::LessThanOrEqual),
             ">=" => Ok(Operator::GreaterThanOrEqual),
+            "+" => Ok(Operator::Plus),
+            "-" => Ok(Operator::Minus),
+            "*" => Ok(Operator::Multiply),
+            "/" => Ok(Operator::Divide),
+            "mod" | "%" => Ok(Operator::Modulo),
+            "**" => Ok(Operator::Pow),
+            "&&" => Ok(Operator::And),
+            "||" => Ok(Operator::Or),
+            "=~" => Ok(Operator::Match),
+            "!~" => Ok(Operator::NotMatch),
+            "in" => Ok(Operator::In),
+            "not-in" => Ok(Operator::NotIn),
+            "not" => Ok(Operator::Not),
+            ">>" => Ok(Operator::Append),
             _ => Err(()),
         }
     }
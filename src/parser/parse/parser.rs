@@ -31,6 +31,19 @@ pub fn nom_input(s: &str, origin: Uuid) -> NomSpan<'_> {
 }
 
 macro_rules! operator {
+    ($name:tt : $token:literal ) => {
+        pub fn $name(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+            let start = input.offset;
+            let (input, tag) = tag($token)(input)?;
+            let end = input.offset;
+
+            Ok((
+                input,
+                TokenTreeBuilder::tagged_op(tag.fragment, (start, end, input.extra)),
+            ))
+        }
+    };
+
     ($name:tt : $token:tt ) => {
         pub fn $name(input: NomSpan) -> IResult<NomSpan, TokenNode> {
             let start = input.offset;
@@ -45,12 +58,40 @@ macro_rules! operator {
     };
 }
 
+operator! { append: >> }
 operator! { gt:  >  }
 operator! { lt:  <  }
 operator! { gte: >= }
 operator! { lte: <= }
 operator! { eq:  == }
 operator! { neq: != }
+operator! { plus: + }
+operator! { minus: - }
+operator! { multiply: * }
+operator! { divide: / }
+operator! { modulo: % }
+operator! { pow: "**" }
+operator! { and: && }
+operator! { or: || }
+operator! { regex_match: "=~" }
+operator! { regex_not_match: "!~" }
+
+/// The operators spelled as words: `in`, `not-in`, `not` and `mod`
+pub fn word_operator(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, word) = alt((
+        keyword("in"),
+        keyword("not-in"),
+        keyword("not"),
+        keyword("mod"),
+    ))(input)?;
+    let end = input.offset;
+
+    Ok((
+        input,
+        TokenTreeBuilder::tagged_op(word.fragment, (start, end, input.extra)),
+    ))
+}
 
 fn trace_step<'a, T: Debug>(
     input: NomSpan<'a>,
@@ -130,6 +171,120 @@ impl std::ops::Mul for Number {
     }
 }
 
+impl std::ops::Add for Number {
+    type Output = Number;
+
+    fn add(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Number::Int(a + b),
+            (Number::Int(a), Number::Decimal(b)) => Number::Decimal(BigDecimal::from(a) + b),
+            (Number::Decimal(a), Number::Int(b)) => Number::Decimal(a + BigDecimal::from(b)),
+            (Number::Decimal(a), Number::Decimal(b)) => Number::Decimal(a + b),
+        }
+    }
+}
+
+impl std::ops::Sub for Number {
+    type Output = Number;
+
+    fn sub(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Number::Int(a - b),
+            (Number::Int(a), Number::Decimal(b)) => Number::Decimal(BigDecimal::from(a) - b),
+            (Number::Decimal(a), Number::Int(b)) => Number::Decimal(a - BigDecimal::from(b)),
+            (Number::Decimal(a), Number::Decimal(b)) => Number::Decimal(a - b),
+        }
+    }
+}
+
+// Dividing ints stays an int when it comes out even. Callers check for a zero divisor first.
+impl std::ops::Div for Number {
+    type Output = Number;
+
+    fn div(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => {
+                if (&a % &b).is_zero() {
+                    Number::Int(a / b)
+                } else {
+                    Number::Decimal(BigDecimal::from(a) / BigDecimal::from(b))
+                }
+            }
+            (Number::Int(a), Number::Decimal(b)) => Number::Decimal(BigDecimal::from(a) / b),
+            (Number::Decimal(a), Number::Int(b)) => Number::Decimal(a / BigDecimal::from(b)),
+            (Number::Decimal(a), Number::Decimal(b)) => Number::Decimal(a / b),
+        }
+    }
+}
+
+// The remainder takes the sign of the left side, as `%` does in Rust. Callers check for a zero
+// divisor first.
+impl std::ops::Rem for Number {
+    type Output = Number;
+
+    fn rem(self, other: Number) -> Number {
+        match (self, other) {
+            (Number::Int(a), Number::Int(b)) => Number::Int(a % b),
+            (a, b) => {
+                let (a, b) = (a.into_decimal(), b.into_decimal());
+                let whole = (a.clone() / b.clone()).with_scale(0);
+                Number::Decimal(a - b * whole)
+            }
+        }
+    }
+}
+
+impl Number {
+    fn into_decimal(self) -> BigDecimal {
+        match self {
+            Number::Int(int) => BigDecimal::from(int),
+            Number::Decimal(decimal) => decimal,
+        }
+    }
+
+    /// The number raised to a power. An int to a whole power that isn't negative stays an exact
+    /// int; anything else is worked out in floating point, and is None if that doesn't give a
+    /// number, as with the square root of a negative.
+    pub fn pow(self, exponent: Number) -> Option<Number> {
+        if let (Number::Int(base), Number::Int(power)) = (&self, &exponent) {
+            if let Some(power) = power.to_usize() {
+                return Some(Number::Int(num_traits::pow(base.clone(), power)));
+            }
+        }
+
+        let base = self.into_decimal().to_f64()?;
+        let exponent = exponent.into_decimal().to_f64()?;
+
+        BigDecimal::from_f64(base.powf(exponent)).map(Number::Decimal)
+    }
+
+    /// Whether the number raised to a power would be too large to work out: an exact int of more
+    /// than `MAX_POW_BITS` bits, or beyond what floating point can hold.
+    pub fn pow_is_too_large(&self, exponent: &Number) -> bool {
+        if let (Number::Int(base), Number::Int(power)) = (self, exponent) {
+            if let Some(power) = power.to_usize() {
+                // 0, 1 and -1 stay as they are whatever the power
+                return base.bits() > 1 && base.bits().saturating_mul(power) > MAX_POW_BITS;
+            }
+        }
+
+        match (
+            self.clone().into_decimal().to_f64(),
+            exponent.clone().into_decimal().to_f64(),
+        ) {
+            (Some(base), Some(exponent)) => base.powf(exponent).is_infinite(),
+            _ => true,
+        }
+    }
+}
+
+/// The most bits an int raised to a power can have
+const MAX_POW_BITS: usize = 1 << 16;
+
+/// The largest exponent a decimal literal like `1e300` can have. The decimal keeps every digit
+/// the exponent stands for, so a much larger one would be too slow to work with.
+pub const MAX_EXPONENT: u64 = 1 << 12;
+
 // For literals
 impl std::ops::Mul<u32> for Number {
     type Output = Number;
@@ -153,32 +308,85 @@ pub fn raw_number(input: NomSpan) -> IResult<NomSpan, Tagged<RawNumber>> {
     let start = input.offset;
     trace_step(input, "raw_decimal", move |input| {
         let (input, neg) = opt(tag("-"))(input)?;
-        let (input, head) = digit1(input)?;
-        let dot: IResult<NomSpan, NomSpan, (NomSpan, nom::error::ErrorKind)> = tag(".")(input);
+        let (input, head) = digits(input)?;
+        let (input, fraction) = opt(pair(tag("."), digits))(input)?;
+        let (input, exponent) = opt(exponent)(input)?;
 
-        let input = match dot {
-            Ok((input, dot)) => input,
+        let end = input.offset;
 
+        match (fraction, exponent) {
             // it's just an integer
-            Err(_) => return Ok((input, RawNumber::int((start, input.offset, input.extra)))),
-        };
-
-        let (input, tail) = digit1(input)?;
+            (None, None) => Ok((input, RawNumber::int((start, end, input.extra)))),
+            _ => Ok((input, RawNumber::decimal((start, end, input.extra)))),
+        }
+    })
+}
 
-        let end = input.offset;
+/// Digits, which may be grouped with underscores like `1_000_000`
+fn digits(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    recognize(pair(digit1, many0(pair(char('_'), digit1))))(input)
+}
 
-        Ok((input, RawNumber::decimal((start, end, input.extra))))
-    })
+/// The `e9` of `1.5e9` or the `E-3` of `2E-3`. An exponent larger than `MAX_EXPONENT` fails
+/// the parse rather than being read as something else.
+fn exponent(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    let (input, exponent) = recognize(tuple((one_of("eE"), opt(one_of("+-")), digit1)))(input)?;
+
+    let digits = exponent.fragment[1..].trim_start_matches(|c| c == '+' || c == '-');
+    match digits.parse::<u64>() {
+        Ok(power) if power <= MAX_EXPONENT => Ok((input, exponent)),
+        _ => Err(nom::Err::Failure((
+            exponent,
+            nom::error::ErrorKind::TooLarge,
+        ))),
+    }
 }
 
 pub fn operator(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "operator", |input| {
-        let (input, operator) = alt((gte, lte, neq, gt, lt, eq))(input)?;
+        let (input, operator) = alt((
+            append,
+            gte,
+            lte,
+            neq,
+            gt,
+            lt,
+            eq,
+            and,
+            or,
+            regex_match,
+            regex_not_match,
+            word_operator,
+            math_operator,
+        ))(input)?;
 
         Ok((input, operator))
     })
 }
 
+/// `+`, `-`, `*`, `/`, `%` or `**` standing on its own between two operands. Anywhere else
+/// they're part of a word, so `ls *`, `cd -` and `cd /` still mean what they always have.
+pub fn math_operator(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "math_operator", |input| {
+        let (rest, operator) = alt((plus, minus, pow, multiply, divide, modulo))(input)?;
+
+        let after = rest.fragment;
+        let operand = after.trim_start_matches(|c| c == ' ' || c == '\t');
+
+        let spaced = operand.len() < after.len();
+        let operand_follows = match operand.chars().next() {
+            None | Some('|') | Some(';') | Some('#') | Some(')') | Some('\n') | Some('\r') => false,
+            Some(_) => true,
+        };
+
+        if !(spaced && operand_follows) {
+            return Err(nom::Err::Error((input, nom::error::ErrorKind::Tag)));
+        }
+
+        Ok((rest, operator))
+    })
+}
+
 pub fn dq_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "dq_string", |input| {
         let start = input.offset;
@@ -212,12 +420,73 @@ pub fn sq_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     })
 }
 
+/// `r#"..."#` holds whatever is up to the closing quote and as many `#`s as it opened with,
+/// quotes included. With no `#`s, `r"..."` is the same as a double-quoted string.
+pub fn raw_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "raw_string", move |input| {
+        let start = input.offset;
+        let (input, _) = char('r')(input)?;
+        let (input, hashes) = take_while(|c: char| c == '#')(input)?;
+        let (input, _) = char('"')(input)?;
+
+        let closing = format!("\"{}", hashes.fragment);
+
+        let start1 = input.offset;
+        let (input, _) = take_until(closing.as_str())(input)?;
+        let end1 = input.offset;
+        let (input, _) = tag(closing.as_str())(input)?;
+        let end = input.offset;
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_string((start1, end1, input.extra), (start, end, input.extra)),
+        ))
+    })
+}
+
 pub fn string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "string", move |input| {
         alt((sq_string, dq_string))(input)
     })
 }
 
+/// A string with expressions in braces, whose values are filled in: `$"size: {$it.size}"`
+pub fn interpolated_string(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "interpolated_string", move |input| {
+        let start = input.offset;
+        let (input, _) = tag("$\"")(input)?;
+        let (input, parts) = many0(alt((interpolated_text, interpolated_expression)))(input)?;
+        let (input, _) = char('"')(input)?;
+        let end = input.offset;
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_interpolation(parts, (start, end, input.extra)),
+        ))
+    })
+}
+
+fn interpolated_text(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let start = input.offset;
+    let (input, _) = take_while1(|c: char| c != '"' && c != '{')(input)?;
+    let end = input.offset;
+
+    Ok((
+        input,
+        TokenTreeBuilder::tagged_string((start, end, input.extra), (start, end, input.extra)),
+    ))
+}
+
+fn interpolated_expression(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let (input, _) = char('{')(input)?;
+    let (input, _) = opt(space1)(input)?;
+    let (input, expr) = node(input)?;
+    let (input, _) = opt(space1)(input)?;
+    let (input, _) = char('}')(input)?;
+
+    Ok((input, expr))
+}
+
 pub fn external(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "external", move |input| {
         let start = input.offset;
@@ -258,11 +527,62 @@ pub fn pattern(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     })
 }
 
+/// `true` or `false`
+pub fn boolean(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "boolean", move |input| {
+        let start = input.offset;
+        let (input, word) = alt((keyword("true"), keyword("false")))(input)?;
+        let end = input.offset;
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_boolean(word.fragment == "true", (start, end, input.extra)),
+        ))
+    })
+}
+
+/// `null`, the empty value
+pub fn null(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "null", move |input| {
+        let start = input.offset;
+        let (input, _) = keyword("null")(input)?;
+        let end = input.offset;
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_null((start, end, input.extra)),
+        ))
+    })
+}
+
+// The word, as long as it isn't the start of a longer one
+fn keyword<'a>(word: &'static str) -> impl Fn(NomSpan<'a>) -> IResult<NomSpan<'a>, NomSpan<'a>> {
+    move |input: NomSpan<'a>| {
+        let (input, word) = tag(word)(input)?;
+
+        match input.fragment.chars().next() {
+            Some(c) if is_bare_char(c) || is_external_word_char(c) || is_glob_specific_char(c) => {
+                Err(nom::Err::Error((input, nom::error::ErrorKind::Tag)))
+            }
+            _ => Ok((input, word)),
+        }
+    }
+}
+
 pub fn bare(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "bare", move |input| {
+        let word = input.fragment;
         let start = input.offset;
         let (input, _) = take_while1(is_start_bare_char)(input)?;
-        let (input, _) = take_while(is_bare_char)(input)?;
+        let (mut input, _) = take_while(is_bare_char)(input)?;
+
+        // A backslash keeps the space, quote or parenthesis after it in the word, as in
+        // `My\ Documents`
+        while escapes_next(&word[..input.offset - start], input.fragment) {
+            let (rest, _) = take(1usize)(input)?;
+            let (rest, _) = take_while(is_bare_char)(rest)?;
+            input = rest;
+        }
 
         let next_char = &input.fragment.chars().nth(0);
 
@@ -330,16 +650,45 @@ pub fn flag(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "flag", move |input| {
         let start = input.offset;
         let (input, _) = tag("--")(input)?;
-        let (input, bare) = bare(input)?;
+        let name_start = input.offset;
+        let (input, _) = take_while1(is_start_bare_char)(input)?;
+        let (input, _) = take_while(|c: char| is_bare_char(c) && c != '=')(input)?;
         let end = input.offset;
 
+        // Like a bare word, a flag's name can't run into other characters, except the `=` of
+        // `--name=value`
+        if let Some(next_char) = input.fragment.chars().nth(0) {
+            if next_char != '='
+                && (is_external_word_char(next_char) || is_glob_specific_char(next_char))
+            {
+                return Err(nom::Err::Error(nom::error::make_error(
+                    input,
+                    nom::error::ErrorKind::TakeWhile1,
+                )));
+            }
+        }
+
         Ok((
             input,
-            TokenTreeBuilder::tagged_flag(bare.tag(), (start, end, input.extra)),
+            TokenTreeBuilder::tagged_flag(
+                (name_start, end, input.extra),
+                (start, end, input.extra),
+            ),
         ))
     })
 }
 
+/// `--name=value`, which is read as the flag followed by its value, like `--name value`
+pub fn flag_with_value(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    trace_step(input, "flag_with_value", move |input| {
+        let (input, flag) = flag(input)?;
+        let (input, _) = char('=')(input)?;
+        let (input, value) = node(input)?;
+
+        Ok((input, vec![flag, value]))
+    })
+}
+
 pub fn shorthand(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "shorthand", move |input| {
         let start = input.offset;
@@ -358,25 +707,28 @@ pub fn raw_unit(input: NomSpan) -> IResult<NomSpan, Tagged<Unit>> {
     trace_step(input, "raw_unit", move |input| {
         let start = input.offset;
         let (input, unit) = alt((
-            tag("B"),
-            tag("b"),
-            tag("KB"),
-            tag("kb"),
-            tag("Kb"),
-            tag("K"),
-            tag("k"),
-            tag("MB"),
-            tag("mb"),
-            tag("Mb"),
-            tag("GB"),
-            tag("gb"),
-            tag("Gb"),
-            tag("TB"),
-            tag("tb"),
-            tag("Tb"),
-            tag("PB"),
-            tag("pb"),
-            tag("Pb"),
+            alt((
+                tag("B"),
+                tag("b"),
+                tag("KB"),
+                tag("kb"),
+                tag("Kb"),
+                tag("K"),
+                tag("k"),
+                tag("MB"),
+                tag("mb"),
+                tag("Mb"),
+                tag("GB"),
+                tag("gb"),
+                tag("Gb"),
+                tag("TB"),
+                tag("tb"),
+                tag("Tb"),
+                tag("PB"),
+                tag("pb"),
+                tag("Pb"),
+            )),
+            alt((tag("s"), tag("m"), tag("h"), tag("d"))),
         ))(input)?;
         let end = input.offset;
 
@@ -422,14 +774,20 @@ pub fn size(input: NomSpan) -> IResult<NomSpan, TokenNode> {
 
 pub fn leaf(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "leaf", move |input| {
+        // Numbers come before operators and flags, so the `-5` of `seq -5 5` is a negative
+        // number for internal and external commands alike, not a minus or a shorthand flag
         let (input, node) = alt((
             size,
             string,
             operator,
             flag,
             shorthand,
+            interpolated_string,
             var,
             external,
+            raw_string,
+            boolean,
+            null,
             bare,
             pattern,
             external_word,
@@ -439,10 +797,15 @@ pub fn leaf(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     })
 }
 
+/// A node, or the two a `--name=value` flag stands for
+fn list_item(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
+    alt((flag_with_value, map(node, |node| vec![node])))(input)
+}
+
 pub fn token_list(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
     trace_step(input, "token_list", move |input| {
-        let (input, first) = node(input)?;
-        let (input, list) = many0(pair(space1, node))(input)?;
+        let (input, first) = list_item(input)?;
+        let (input, list) = many0(pair(space, list_item))(input)?;
 
         Ok((input, make_token_list(None, first, list, None)))
     })
@@ -450,10 +813,10 @@ pub fn token_list(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
 
 pub fn spaced_token_list(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
     trace_step(input, "spaced_token_list", move |input| {
-        let (input, sp_left) = opt(space1)(input)?;
-        let (input, first) = node(input)?;
-        let (input, list) = many0(pair(space1, node))(input)?;
-        let (input, sp_right) = opt(space1)(input)?;
+        let (input, sp_left) = opt(space)(input)?;
+        let (input, first) = list_item(input)?;
+        let (input, list) = many0(pair(space, list_item))(input)?;
+        let (input, sp_right) = opt(space)(input)?;
 
         Ok((input, make_token_list(sp_left, first, list, sp_right)))
     })
@@ -461,8 +824,8 @@ pub fn spaced_token_list(input: NomSpan) -> IResult<NomSpan, Vec<TokenNode>> {
 
 fn make_token_list(
     sp_left: Option<NomSpan>,
-    first: TokenNode,
-    list: Vec<(NomSpan, TokenNode)>,
+    first: Vec<TokenNode>,
+    list: Vec<(NomSpan, Vec<TokenNode>)>,
     sp_right: Option<NomSpan>,
 ) -> Vec<TokenNode> {
     let mut nodes = vec![];
@@ -471,11 +834,11 @@ fn make_token_list(
         nodes.push(TokenNode::Whitespace(Tag::from(sp_left)));
     }
 
-    nodes.push(first);
+    nodes.extend(first);
 
-    for (ws, token) in list {
+    for (ws, tokens) in list {
         nodes.push(TokenNode::Whitespace(Tag::from(ws)));
-        nodes.push(token);
+        nodes.extend(tokens);
     }
 
     if let Some(sp_right) = sp_right {
@@ -498,8 +861,29 @@ pub fn whitespace(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     })
 }
 
+/// A `\` at the very end of a line, which carries the line on to the next
+pub fn line_continuation(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    trace_step(input, "line_continuation", move |input| {
+        recognize(pair(char('\\'), line_ending))(input)
+    })
+}
+
+/// Spaces between the tokens of a line, along with any line continuations
+pub fn space(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    recognize(many1(alt((space1, line_continuation))))(input)
+}
+
+/// Spaces and newlines, as they can follow a `|`
+pub fn space_or_newline(input: NomSpan) -> IResult<NomSpan, NomSpan> {
+    recognize(many1(alt((multispace1, line_continuation))))(input)
+}
+
 pub fn delimited_paren(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "delimited_paren", move |input| {
+        if let Ok(subexpression) = paren_pipeline(input) {
+            return Ok(subexpression);
+        }
+
         let left = input.offset;
         let (input, _) = char('(')(input)?;
         let (input, ws1) = opt(whitespace)(input)?;
@@ -529,6 +913,24 @@ pub fn delimited_paren(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     })
 }
 
+/// `(ls | length)`: a pipeline of more than one command in parentheses. A single command, like
+/// `(pwd)`, reads the same as an expression, so it's the command registry that tells them apart.
+fn paren_pipeline(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let left = input.offset;
+    let (input, _) = char('(')(input)?;
+    let (input, pipeline) = raw_pipeline(input)?;
+    let (input, _) = char(')')(input)?;
+    let right = input.offset;
+
+    match &pipeline {
+        TokenNode::Pipeline(inner) if inner.parts.len() > 1 => Ok((
+            input,
+            TokenTreeBuilder::tagged_parens(vec![pipeline], (left, right, input.extra)),
+        )),
+        _ => Err(nom::Err::Error((input, nom::error::ErrorKind::Tag))),
+    }
+}
+
 pub fn delimited_square(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "delimited_paren", move |input| {
         let left = input.offset;
@@ -560,22 +962,132 @@ pub fn delimited_square(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     })
 }
 
+/// A table written as its headers and then its rows: `[[name age]; [alice 30] [bob 25]]`
+pub fn table_literal(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "table_literal", move |input| {
+        let left = input.offset;
+        let (input, _) = char('[')(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, headers) = delimited_square(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = char(';')(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, rows) = separated_list(multispace1, delimited_square)(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = char(']')(input)?;
+        let right = input.offset;
+
+        let mut parts = vec![headers];
+        parts.extend(rows);
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_table(parts, (left, right, input.extra)),
+        ))
+    })
+}
+
+/// A row written as its columns and their values: `{name: "alice", age: 30}`
+pub fn record_literal(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "record_literal", move |input| {
+        let left = input.offset;
+        let (input, _) = char('{')(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, fields) = separated_nonempty_list(
+            alt((delimited(space0, char(','), multispace0), multispace1)),
+            record_field,
+        )(input)?;
+        let (input, _) = opt(pair(space0, char(',')))(input)?;
+        let (input, _) = multispace0(input)?;
+        let (input, _) = char('}')(input)?;
+        let right = input.offset;
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_record(fields, (left, right, input.extra)),
+        ))
+    })
+}
+
+fn record_field(input: NomSpan) -> IResult<NomSpan, (TokenNode, TokenNode)> {
+    let (input, key) = alt((member, string))(input)?;
+    let (input, _) = space0(input)?;
+    let (input, _) = char(':')(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, value) = node(input)?;
+
+    Ok((input, (key, value)))
+}
+
 pub fn delimited_brace(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "delimited_brace", move |input| {
+        if let Ok(block) = brace_pipeline(input) {
+            return Ok(block);
+        }
+
         let left = input.offset;
         let (input, _) = char('{')(input)?;
         let (input, _) = opt(space1)(input)?;
+        let (input, params) = opt(block_params)(input)?;
+        let (input, _) = opt(space1)(input)?;
         let (input, items) = opt(token_list)(input)?;
         let (input, _) = opt(space1)(input)?;
         let (input, _) = char('}')(input)?;
         let right = input.offset;
 
+        let mut children = vec![];
+        children.extend(params);
+        children.extend(items.unwrap_or_else(|| vec![]));
+
         Ok((
             input,
-            TokenTreeBuilder::tagged_brace(
-                items.unwrap_or_else(|| vec![]),
-                (left, right, input.extra),
-            ),
+            TokenTreeBuilder::tagged_brace(children, (left, right, input.extra)),
+        ))
+    })
+}
+
+/// `{ ls | first 2 }`: a pipeline of more than one command in braces, for a command like `if` to
+/// run. Like any block, it can start with the names of its parameters.
+fn brace_pipeline(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    let left = input.offset;
+    let (input, _) = char('{')(input)?;
+    let (input, _) = opt(space1)(input)?;
+    let (input, params) = opt(block_params)(input)?;
+    let (input, pipeline) = raw_pipeline(input)?;
+    let (input, _) = char('}')(input)?;
+    let right = input.offset;
+
+    match &pipeline {
+        TokenNode::Pipeline(inner) if inner.parts.len() > 1 => {
+            let mut children = vec![];
+            children.extend(params);
+            children.push(pipeline);
+
+            Ok((
+                input,
+                TokenTreeBuilder::tagged_brace(children, (left, right, input.extra)),
+            ))
+        }
+        _ => Err(nom::Err::Error((input, nom::error::ErrorKind::Tag))),
+    }
+}
+
+/// The names a block gives its arguments: the `|row|` of `{ |row| $row.size > 10 }`
+pub fn block_params(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "block_params", move |input| {
+        let left = input.offset;
+        let (input, _) = char('|')(input)?;
+        let (input, _) = space0(input)?;
+        let (input, names) = separated_list(space1, member)(input)?;
+        let (input, _) = space0(input)?;
+        let (input, _) = char('|')(input)?;
+        let right = input.offset;
+
+        let names = names.iter().map(|name| name.tag()).collect();
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_block_params(names, (left, right, input.extra)),
         ))
     })
 }
@@ -620,24 +1132,47 @@ pub fn node(input: NomSpan) -> IResult<NomSpan, TokenNode> {
             path,
             leaf,
             delimited_paren,
+            record_literal,
             delimited_brace,
+            table_literal,
             delimited_square,
         )),
     )
 }
 
+/// `#` and the rest of the line
+pub fn comment(input: NomSpan) -> IResult<NomSpan, Tag> {
+    trace_step(input, "comment", move |input| {
+        let start = input.offset;
+        let (input, _) = char('#')(input)?;
+        let (input, _) = not_line_ending(input)?;
+        let end = input.offset;
+
+        Ok((input, Tag::from((start, end, input.extra))))
+    })
+}
+
 pub fn pipeline(input: NomSpan) -> IResult<NomSpan, TokenNode> {
     trace_step(input, "pipeline", |input| {
-        let start = input.offset;
-        let (input, head) = opt(tuple((opt(space1), raw_call, opt(space1))))(input)?;
-        let (input, items) = trace_step(
-            input,
-            "many0",
-            many0(tuple((tag("|"), opt(space1), raw_call, opt(space1)))),
-        )?;
+        let (input, pipeline) = raw_pipeline(input)?;
 
-        let (input, tail) = opt(space1)(input)?;
-        let (input, newline) = opt(multispace1)(input)?;
+        if input.input_len() != 0 {
+            return Err(Err::Error(error_position!(
+                input,
+                nom::error::ErrorKind::Eof
+            )));
+        }
+
+        Ok((input, pipeline))
+    })
+}
+
+/// Pipelines separated by `;`, which run one after the other
+pub fn statements(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "statements", |input| {
+        let start = input.offset;
+        let (input, first) = raw_pipeline(input)?;
+        let (input, rest) = many0(preceded(char(';'), raw_pipeline))(input)?;
 
         if input.input_len() != 0 {
             return Err(Err::Error(error_position!(
@@ -648,10 +1183,87 @@ pub fn pipeline(input: NomSpan) -> IResult<NomSpan, TokenNode> {
 
         let end = input.offset;
 
+        let mut pipelines = vec![first];
+        pipelines.extend(rest);
+
+        Ok((
+            input,
+            TokenTreeBuilder::tagged_statements(pipelines, (start, end, input.extra)),
+        ))
+    })
+}
+
+/// Like `statements`, but never failing: whatever can't be parsed becomes an error node, up to
+/// the next space, `|` or `;`, and parsing picks up again after it. Editing tools can make use
+/// of the rest of a line that's only half typed.
+pub fn statements_with_recovery(input: NomSpan) -> TokenNode {
+    let start = input.offset;
+    let mut input = input;
+    let mut nodes = vec![];
+
+    while input.input_len() != 0 {
+        if let Ok((rest, _)) = char::<_, (NomSpan, nom::error::ErrorKind)>(';')(input) {
+            input = rest;
+            continue;
+        }
+
+        match raw_pipeline(input) {
+            Ok((rest, pipeline)) if rest.offset > input.offset => {
+                match &pipeline {
+                    // Nothing but the space after an error
+                    TokenNode::Pipeline(p) if p.parts.is_empty() && p.comment.is_none() => {}
+                    _ => nodes.push(pipeline),
+                }
+                input = rest;
+            }
+            _ => {
+                let first = input.fragment.chars().nth(0).map_or(1, char::len_utf8);
+                let skipped = input.fragment[first..]
+                    .find(|c: char| c.is_whitespace() || c == '|' || c == ';')
+                    .map(|idx| idx + first)
+                    .unwrap_or(input.fragment.len());
+
+                let (rest, bad) = input.take_split(skipped);
+                let tag = Tag::from(bad);
+
+                nodes.push(TokenTreeBuilder::tagged_error(
+                    ShellError::labeled_error("Unexpected input", "nu can't read this", tag),
+                    tag,
+                ));
+                input = rest;
+            }
+        }
+    }
+
+    TokenTreeBuilder::tagged_statements(nodes, (start, input.offset, input.extra))
+}
+
+pub(crate) fn raw_pipeline(input: NomSpan) -> IResult<NomSpan, TokenNode> {
+    trace_step(input, "raw_pipeline", |input| {
+        let start = input.offset;
+        let (input, head) = opt(tuple((opt(space), raw_call, opt(space))))(input)?;
+        // A line ending in `|` carries on to the next
+        let (input, items) = trace_step(
+            input,
+            "many0",
+            many0(tuple((
+                tag("|"),
+                opt(space_or_newline),
+                raw_call,
+                opt(space),
+            ))),
+        )?;
+
+        let (input, tail) = opt(space)(input)?;
+        let (input, comment) = opt(comment)(input)?;
+        let (input, newline) = opt(multispace1)(input)?;
+
+        let end = input.offset;
+
         Ok((
             input,
             TokenTreeBuilder::tagged_pipeline(
-                (make_call_list(head, items), tail.map(Tag::from)),
+                (make_call_list(head, items), tail.map(Tag::from), comment),
                 (start, end, input.extra),
             ),
         ))
@@ -684,6 +1296,191 @@ fn make_call_list(
     out
 }
 
+/// The innermost bracket or quote left open at the end of `input`, if any. A line that ends
+/// with one can't parse yet, so the line editor keeps reading instead of submitting it.
+pub fn unclosed_delimiter(input: &str) -> Option<char> {
+    unclosed_delimiter_at(input).map(|(delimiter, _)| delimiter)
+}
+
+/// Like `unclosed_delimiter`, along with where in `input` the delimiter was opened
+pub fn unclosed_delimiter_at(input: &str) -> Option<(char, usize)> {
+    let mut open: Vec<(char, usize)> = vec![];
+    let mut prev = None;
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        let at = input.len() - rest.len();
+        let in_quotes = match open.last() {
+            Some(('"', _)) | Some(('\'', _)) | Some(('`', _)) => true,
+            _ => false,
+        };
+
+        // A raw string runs to its closing quote and hashes, whatever is in between
+        if !in_quotes && c == 'r' && !prev.map(char::is_alphanumeric).unwrap_or(false) {
+            let hashes = rest[1..].chars().take_while(|c| *c == '#').count();
+
+            if rest[1 + hashes..].starts_with('"') {
+                let closing = format!("\"{}", "#".repeat(hashes));
+
+                match rest[2 + hashes..].find(&closing) {
+                    Some(idx) => {
+                        rest = &rest[2 + hashes + idx + closing.len()..];
+                        prev = Some('"');
+                        continue;
+                    }
+                    None => return Some(('"', at + 1 + hashes)),
+                }
+            }
+        }
+
+        // A comment runs to the end of the line, apostrophes and all
+        if !in_quotes && c == '#' && prev.map(char::is_whitespace).unwrap_or(true) {
+            rest = match rest.find('\n') {
+                Some(idx) => &rest[idx..],
+                None => "",
+            };
+            continue;
+        }
+
+        // An escaped quote or bracket, as in `it\'s`, is part of a bare word
+        if !in_quotes && c == '\\' {
+            if let Some(next) = rest[1..]
+                .chars()
+                .next()
+                .filter(|next| is_escaped_char(*next))
+            {
+                prev = Some(next);
+                rest = &rest[1 + next.len_utf8()..];
+                continue;
+            }
+        }
+
+        prev = Some(c);
+        rest = &rest[c.len_utf8()..];
+
+        match (open.last().map(|(delimiter, _)| *delimiter), c) {
+            (Some('"'), '"') | (Some('\''), '\'') | (Some('`'), '`') => {
+                open.pop();
+            }
+            (Some('"'), _) | (Some('\''), _) | (Some('`'), _) => {}
+            (_, '(') | (_, '[') | (_, '{') | (_, '"') | (_, '\'') | (_, '`') => open.push((c, at)),
+            (Some('('), ')') | (Some('['), ']') | (Some('{'), '}') => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+
+    open.last().cloned()
+}
+
+/// The character that closes an opening bracket or quote
+pub fn closing_delimiter(delimiter: char) -> char {
+    match delimiter {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        other => other,
+    }
+}
+
+/// The part of a line before its comment, if it has one. A `#` starting a word outside of
+/// quotes starts a comment.
+pub fn without_comment(line: &str) -> &str {
+    for (idx, c) in line.char_indices() {
+        let starts_word = line[..idx]
+            .chars()
+            .last()
+            .map(char::is_whitespace)
+            .unwrap_or(true);
+
+        if c == '#' && starts_word {
+            match unclosed_delimiter(&line[..idx]) {
+                Some('"') | Some('\'') | Some('`') => {}
+                _ => return &line[..idx],
+            }
+        }
+    }
+
+    line
+}
+
+/// Whether a line ends with a `|` or a `\` of its own, ignoring any comment, and so goes on to
+/// the next line. A `\` on the end of a word, as in `cd C:\Users\`, is part of the word.
+pub fn continues_on_next_line(line: &str) -> bool {
+    let line = without_comment(line).trim();
+
+    if line.ends_with('|') {
+        return true;
+    }
+
+    let mut ending = line.chars().rev();
+
+    match (ending.next(), ending.next()) {
+        (Some('\\'), None) => true,
+        (Some('\\'), Some(before)) => before.is_whitespace(),
+        _ => false,
+    }
+}
+
+/// Get a line that can't end where it does ready for the next to be added to it, returning
+/// whether it carries on. That's a line with a bracket or quote left open, or a trailing `|` or
+/// `\`. Newlines only survive inside strings and after a `|` or `\`; elsewhere the lines run
+/// together.
+pub fn continue_line(line: &mut String) -> bool {
+    // A comment would run on into the next line, so it's dropped before that's added
+    let code = without_comment(line);
+    let len = code.trim_end().len();
+
+    match unclosed_delimiter(code) {
+        Some('"') | Some('\'') | Some('`') => line.push('\n'),
+        Some(_) => {
+            line.truncate(len);
+            line.push(' ');
+        }
+        None if continues_on_next_line(line) => {
+            line.truncate(len);
+            line.push('\n');
+        }
+        None => return false,
+    }
+
+    true
+}
+
+/// The lines of a script, with those that go on to the next line joined up with it
+pub fn join_continued_lines(script: &str) -> Vec<String> {
+    join_numbered_lines(script)
+        .into_iter()
+        .map(|(_, line)| line)
+        .collect()
+}
+
+/// Like `join_continued_lines`, along with the number of the line in `script` each starts on
+pub fn join_numbered_lines(script: &str) -> Vec<(usize, String)> {
+    let mut lines = vec![];
+    let mut pending = String::new();
+    let mut first = 1;
+
+    for (idx, line) in script.lines().enumerate() {
+        if pending.is_empty() {
+            first = idx + 1;
+        }
+
+        pending.push_str(line);
+
+        if !continue_line(&mut pending) {
+            lines.push((first, std::mem::replace(&mut pending, String::new())));
+        }
+    }
+
+    if !pending.is_empty() {
+        lines.push((first, pending));
+    }
+
+    lines
+}
+
 fn int<T>(frag: &str, neg: Option<T>) -> i64 {
     let int = FromStr::from_str(frag).unwrap();
 
@@ -745,6 +1542,49 @@ fn is_bare_char(c: char) -> bool {
     }
 }
 
+fn is_escaped_char(c: char) -> bool {
+    match c {
+        ' ' | '\'' | '"' | '(' | ')' | '\\' => true,
+        _ => false,
+    }
+}
+
+/// Whether `word` ends in a backslash that escapes the start of `rest`. A backslash escaped
+/// itself, as in `a\\`, escapes nothing. On Windows, a backslash followed by nothing but
+/// whitespace before a `|`, a `;` or the end of the line ends a path, as in `cd C:\Users\ | ls`.
+fn escapes_next(word: &str, rest: &str) -> bool {
+    let backslashes = word.chars().rev().take_while(|c| *c == '\\').count();
+
+    match rest.chars().next() {
+        Some(c) if is_escaped_char(c) && backslashes % 2 == 1 => {
+            let after = rest.trim_start();
+            !(cfg!(windows)
+                && c.is_whitespace()
+                && (after.is_empty() || after.starts_with('|') || after.starts_with(';')))
+        }
+        _ => false,
+    }
+}
+
+/// A bare word with its escapes taken out: `My\ Documents` is `My Documents` and `a\\b` is
+/// `a\b`. Any other backslash, like those in `C:\Users`, is left as it is.
+pub fn unescape_bare(word: &str) -> String {
+    let mut unescaped = String::with_capacity(word.len());
+    let mut chars = word.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next)) if is_escaped_char(next) => {
+                unescaped.push(next);
+                chars.next();
+            }
+            _ => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
 fn is_id_start(c: char) -> bool {
     unicode_xid::UnicodeXID::is_xid_start(c)
 }
@@ -854,6 +1694,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scientific_notation() {
+        assert_leaf! {
+            parsers [ size ]
+            "1.5e9" -> 0..5 { Number(RawNumber::decimal((0, 5, test_uuid())).item) }
+        }
+
+        assert_leaf! {
+            parsers [ size ]
+            "2E-3" -> 0..4 { Number(RawNumber::decimal((0, 4, test_uuid())).item) }
+        }
+    }
+
+    #[test]
+    fn test_digit_separators() {
+        assert_leaf! {
+            parsers [ size ]
+            "1_000_000" -> 0..9 { Number(RawNumber::int((0, 9, test_uuid())).item) }
+        }
+
+        assert_leaf! {
+            parsers [ size ]
+            "1_500.25MB" -> 0..10 { Size(RawNumber::decimal((0, 8, test_uuid())).item, Unit::MB) }
+        }
+    }
+
     #[test]
     fn test_size() {
         assert_leaf! {
@@ -865,11 +1731,22 @@ mod tests {
             parsers [ size ]
             "10GB" -> 0..4 { Size(RawNumber::int((0, 2, test_uuid())).item, Unit::GB) }
         }
+
+        assert_leaf! {
+            parsers [ size ]
+            "5s" -> 0..2 { Size(RawNumber::int((0, 1, test_uuid())).item, Unit::Second) }
+        }
+
+        assert_leaf! {
+            parsers [ size ]
+            "2m" -> 0..2 { Size(RawNumber::int((0, 1, test_uuid())).item, Unit::Minute) }
+        }
     }
 
     #[test]
     fn test_operator() {
         assert_eq!(apply(node, "node", ">"), build_token(b::op(">")));
+        assert_eq!(apply(node, "node", ">>"), build_token(b::op(">>")));
 
         // assert_leaf! {
         //     parsers [ operator ]
@@ -897,6 +1774,61 @@ mod tests {
         // }
     }
 
+    #[test]
+    fn test_math_operator() {
+        assert_eq!(
+            apply(node, "node", "(3 + 4)"),
+            build_token(b::parens(vec![
+                b::int(3),
+                b::sp(),
+                b::op("+"),
+                b::sp(),
+                b::int(4)
+            ]))
+        );
+
+        assert_eq!(apply(node, "node", "&&"), build_token(b::op("&&")));
+        assert_eq!(apply(node, "node", "||"), build_token(b::op("||")));
+        assert_eq!(apply(node, "node", "=~"), build_token(b::op("=~")));
+        assert_eq!(apply(node, "node", "!~"), build_token(b::op("!~")));
+        assert_eq!(apply(node, "node", "in"), build_token(b::op("in")));
+        assert_eq!(apply(node, "node", "not-in"), build_token(b::op("not-in")));
+        assert_eq!(apply(node, "node", "not"), build_token(b::op("not")));
+        assert_eq!(
+            apply(node, "node", "nothing"),
+            build_token(b::bare("nothing"))
+        );
+        assert_eq!(apply(node, "node", "inbox"), build_token(b::bare("inbox")));
+        assert_eq!(apply(node, "node", "mod"), build_token(b::op("mod")));
+        assert_eq!(apply(node, "node", "model"), build_token(b::bare("model")));
+
+        for source in &["- 1", "* $it.size", "/ 2", "% 2", "** 3"] {
+            assert!(math_operator(nom_input(source, uuid::Uuid::nil())).is_ok());
+        }
+
+        for source in &["*", "* | get name", "-", "/tmp", "-la", "**/*.rs"] {
+            assert!(math_operator(nom_input(source, uuid::Uuid::nil())).is_err());
+        }
+    }
+
+    #[test]
+    fn test_boolean() {
+        assert_leaf! {
+            parsers [ boolean ]
+            "true" -> 0..4 { Boolean(true) }
+        }
+
+        assert_leaf! {
+            parsers [ boolean ]
+            "false" -> 0..5 { Boolean(false) }
+        }
+
+        assert_eq!(
+            apply(node, "node", "trueish"),
+            build_token(b::bare("trueish"))
+        );
+    }
+
     #[test]
     fn test_string() {
         assert_leaf! {
@@ -908,6 +1840,11 @@ mod tests {
             parsers [ string sq_string ]
             r"'hello world'" -> 0..13 { String(tag(1, 12)) }
         }
+
+        assert_leaf! {
+            parsers [ raw_string ]
+            r##"r#"say "hi""#"## -> 0..13 { String(tag(3, 11)) }
+        }
     }
 
     #[test]
@@ -931,6 +1868,40 @@ mod tests {
             parsers [ bare ]
             r"C:\Code\-testing\my_tests.js" -> 0..28 { Bare }
         }
+
+        assert_leaf! {
+            parsers [ bare ]
+            r"My\ Documents" -> 0..13 { Bare }
+        }
+
+        assert_leaf! {
+            parsers [ bare ]
+            r"My\\" -> 0..4 { Bare }
+        }
+
+        assert_eq!(
+            unescape_bare(r"My\ Documents\\and\(more\)"),
+            r"My Documents\and(more)"
+        );
+        assert_eq!(unescape_bare(r"C:\Users\"), r"C:\Users\");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_bare_ending_in_a_directory_separator() {
+        assert_leaf! {
+            parsers [ bare ]
+            r"C:\Users\ " -> 0..9 { Bare }
+        }
+
+        assert_eq!(
+            apply(pipeline, "pipeline", r"cd C:\Users\ | ls")
+                .as_pipeline()
+                .unwrap()
+                .parts
+                .len(),
+            2
+        );
     }
 
     #[test]
@@ -946,6 +1917,21 @@ mod tests {
         // }
     }
 
+    #[test]
+    fn test_flag_with_value() {
+        let source = "open utf16.ini --encoding=utf16";
+
+        let call = apply(raw_call, "raw_call", source);
+        let children: Vec<_> = call
+            .children()
+            .iter()
+            .flatten()
+            .map(|node| node.tag().slice(source))
+            .collect();
+
+        assert_eq!(children, vec![" ", "utf16.ini", " ", "--encoding", "utf16"]);
+    }
+
     #[test]
     fn test_shorthand() {
         // assert_leaf! {
@@ -967,6 +1953,141 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_interpolated_string() {
+        let source = r#"$"size: {$it.size} bytes""#;
+
+        match apply(node, "node", source) {
+            TokenNode::Interpolation(parts) => {
+                let parts: Vec<_> = parts.iter().map(|p| p.tag().slice(source)).collect();
+                assert_eq!(parts, vec!["size: ", "$it.size", " bytes"]);
+            }
+            other => panic!("expected an interpolated string, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_table_literal() {
+        let source = "[[name age]; [alice 30] [bob 25]]";
+
+        match apply(node, "node", source) {
+            TokenNode::Table(parts) => {
+                let parts: Vec<_> = parts.iter().map(|p| p.tag().slice(source)).collect();
+                assert_eq!(parts, vec!["[name age]", "[alice 30]", "[bob 25]"]);
+            }
+            other => panic!("expected a table, got {:?}", other),
+        }
+
+        match apply(node, "node", "[[1 2] [3 4]]") {
+            TokenNode::Delimited(_) => {}
+            other => panic!("expected a list, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_record_literal() {
+        let source = r#"{name: "alice", age: 30}"#;
+
+        match apply(node, "node", source) {
+            TokenNode::Record(fields) => {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(k, v)| (k.tag().slice(source), v.tag().slice(source)))
+                    .collect();
+                assert_eq!(fields, vec![("name", r#""alice""#), ("age", "30")]);
+            }
+            other => panic!("expected a record, got {:?}", other),
+        }
+
+        match apply(node, "node", "{ $it.size > 10 }") {
+            TokenNode::Delimited(_) => {}
+            other => panic!("expected a block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_block_params() {
+        let source = "{ |row| $row.size > 10 }";
+
+        match apply(node, "node", source) {
+            TokenNode::Delimited(block) => match &block.children()[0] {
+                TokenNode::BlockParams(params) => {
+                    let names: Vec<_> = params.iter().map(|p| p.slice(source)).collect();
+                    assert_eq!(names, vec!["row"]);
+                }
+                other => panic!("expected block params, got {:?}", other),
+            },
+            other => panic!("expected a block, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unclosed_delimiter() {
+        assert_eq!(unclosed_delimiter("ls | where size > 10"), None);
+        assert_eq!(unclosed_delimiter("echo [1 2"), Some('['));
+        assert_eq!(unclosed_delimiter("where { $it.name"), Some('{'));
+        assert_eq!(unclosed_delimiter("echo \"a ( b"), Some('"'));
+        assert_eq!(unclosed_delimiter("echo \"a ( b\""), None);
+        assert_eq!(unclosed_delimiter("echo ([ 'x' )"), Some('['));
+        assert_eq!(unclosed_delimiter(r##"echo r#"say "hi""#"##), None);
+        assert_eq!(unclosed_delimiter(r##"echo r#"say "hi""##), Some('"'));
+        assert_eq!(unclosed_delimiter("ls # don't list hidden files"), None);
+        assert_eq!(unclosed_delimiter("echo a#'b"), Some('\''));
+    }
+
+    #[test]
+    fn test_unclosed_delimiter_at() {
+        assert_eq!(unclosed_delimiter_at("echo [1 2"), Some(('[', 5)));
+        assert_eq!(unclosed_delimiter_at("echo (1 + [2]"), Some(('(', 5)));
+        assert_eq!(unclosed_delimiter_at("echo [1 2]"), None);
+    }
+
+    #[test]
+    fn test_continued_lines() {
+        let source = "ls |\n  where size > 10 \\\n  | first 2";
+        let parsed = apply(pipeline, "pipeline", source).as_pipeline().unwrap();
+
+        assert_eq!(parsed.parts.len(), 3);
+
+        assert_eq!(
+            join_continued_lines("ls |\nfirst 2\necho done"),
+            vec!["ls |\nfirst 2".to_string(), "echo done".to_string()]
+        );
+
+        assert_eq!(
+            join_continued_lines("ls | # the files\nfirst 2 \\ # two of them\n| echo $it"),
+            vec!["ls |\nfirst 2 \\\n| echo $it".to_string()]
+        );
+
+        assert_eq!(
+            join_continued_lines("echo [1 # one\n2]"),
+            vec!["echo [1 2]".to_string()]
+        );
+
+        assert_eq!(
+            join_continued_lines("echo it\\'s\necho done"),
+            vec!["echo it\\'s".to_string(), "echo done".to_string()]
+        );
+
+        assert!(!continue_line(&mut "echo \\(not a group\\)".to_string()));
+        assert!(continue_line(&mut "echo a\\\\'b".to_string()));
+
+        assert!(!continues_on_next_line("cd C:\\Users\\"));
+        assert!(!continues_on_next_line("echo a\\\\"));
+        assert!(!continues_on_next_line("# ls |"));
+        assert!(continues_on_next_line("ls \\"));
+
+        assert_eq!(
+            join_numbered_lines("echo [1\n2]\necho \"a\nb\"\n\necho done"),
+            vec![
+                (1, "echo [1 2]".to_string()),
+                (3, "echo \"a\nb\"".to_string()),
+                (5, "".to_string()),
+                (6, "echo done".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_external() {
         assert_leaf! {
@@ -1014,6 +2135,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_paren_pipeline() {
+        assert_eq!(
+            apply(node, "node", "(ls | first)"),
+            build_token(b::parens(vec![b::pipeline(vec![
+                (None, b::call(b::bare("ls"), vec![]), Some(" ")),
+                (Some(" "), b::call(b::bare("first"), vec![]), None)
+            ])]))
+        );
+    }
+
+    #[test]
+    fn test_statements_with_recovery() {
+        let source = "ls | where )x | get name";
+        let tree = statements_with_recovery(NomSpan::new_extra(source, uuid::Uuid::nil()));
+
+        let nodes = match tree {
+            TokenNode::Statements(nodes) => nodes.item,
+            other => panic!("expected statements, got {:?}", other),
+        };
+
+        let kinds: Vec<_> = nodes
+            .iter()
+            .map(|node| match node {
+                TokenNode::Pipeline(_) => "pipeline",
+                TokenNode::Error(_) => "error",
+                _ => "other",
+            })
+            .collect();
+
+        assert_eq!(kinds, vec!["pipeline", "error", "pipeline"]);
+        assert_eq!(nodes[1].tag().slice(source), ")x");
+    }
+
+    #[test]
+    fn test_reparse() {
+        use crate::parser::parse::reparse::{reparse, Edit};
+
+        let before = "ls | where size > 10kb | get name; echo done";
+        let previous = statements_with_recovery(NomSpan::new_extra(before, uuid::Uuid::nil()));
+
+        let edits = vec![
+            // Typing in the middle element
+            Edit::new(18, 2, "100".to_string()),
+            // Typing at the end of the line
+            Edit::new(before.len(), 0, "!".to_string()),
+            // Adding an element
+            Edit::new(22, 0, " | first 3".to_string()),
+            // Taking out a `|`, so two elements become one
+            Edit::new(3, 1, "".to_string()),
+            // Taking out the `;`
+            Edit::new(33, 1, " ".to_string()),
+            // Opening a quote that runs on to the end
+            Edit::new(11, 0, "\"".to_string()),
+        ];
+
+        for edit in edits {
+            let after = edit.apply(before);
+
+            assert_eq!(
+                reparse(&previous, &edit, &after, uuid::Uuid::nil()),
+                statements_with_recovery(NomSpan::new_extra(&after, uuid::Uuid::nil())),
+                "after {:?}",
+                edit
+            );
+            assert_eq!(Edit::between(before, &after).apply(before), after);
+        }
+    }
+
     #[test]
     fn test_delimited_square() {
         assert_eq!(
@@ -1159,6 +2349,14 @@ mod tests {
             ))
         );
 
+        assert_eq!(
+            apply(raw_call, "raw_call", "seq -5 5"),
+            build(b::call(
+                b::bare("seq"),
+                vec![b::sp(), b::int(-5), b::sp(), b::int(5)]
+            ))
+        );
+
         assert_eq!(
             apply(raw_call, "raw_call", "config --set tabs 2"),
             build(b::call(
@@ -1282,6 +2480,41 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_comment() {
+        let source = "ls | first 2 # the newest ones";
+        let parsed = apply(pipeline, "pipeline", source).as_pipeline().unwrap();
+
+        assert_eq!(parsed.parts.len(), 2);
+        assert_eq!(
+            parsed.comment.map(|c| c.slice(source)),
+            Some("# the newest ones")
+        );
+
+        let parsed = apply(pipeline, "pipeline", "# only a note")
+            .as_pipeline()
+            .unwrap();
+
+        assert!(parsed.parts.is_empty());
+    }
+
+    #[test]
+    fn test_statements() {
+        let source = "cd foo; ls | where size > 1kb; echo done";
+
+        match apply(statements, "statements", source) {
+            TokenNode::Statements(pipelines) => {
+                let pipelines: Vec<_> = pipelines
+                    .iter()
+                    .map(|p| p.as_pipeline().unwrap().parts.len())
+                    .collect();
+
+                assert_eq!(pipelines, vec![1, 2, 1]);
+            }
+            other => panic!("expected statements, got {:?}", other),
+        }
+    }
+
     fn apply<T>(
         f: impl Fn(NomSpan) -> Result<(NomSpan, T), nom::Err<(NomSpan, nom::error::ErrorKind)>>,
         desc: &str,
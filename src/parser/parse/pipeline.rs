@@ -9,6 +9,8 @@ use std::fmt;
 pub struct Pipeline {
     pub(crate) parts: Vec<PipelineElement>,
     pub(crate) post_ws: Option<Tag>,
+    // A `# comment` at the end of the line. It's kept only so its span is known.
+    pub(crate) comment: Option<Tag>,
 }
 
 impl ToDebug for Pipeline {
@@ -21,6 +23,10 @@ impl ToDebug for Pipeline {
             write!(f, "{}", post_ws.slice(source))?
         }
 
+        if let Some(comment) = self.comment {
+            write!(f, "{}", comment.slice(source))?
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,405 @@
+use crate::data::meta::Span;
+use crate::parser::parse::call_node::CallNode;
+use crate::parser::parse::flag::Flag;
+use crate::parser::parse::parser::{nom_input, raw_pipeline, statements_with_recovery};
+use crate::parser::parse::pipeline::{Pipeline, PipelineElement};
+use crate::parser::parse::token_tree::{DelimitedNode, PathNode, TokenNode};
+use crate::parser::parse::tokens::{RawNumber, RawToken};
+use crate::{Tag, Tagged};
+use derive_new::new;
+use nom::{InputLength, Slice};
+use uuid::Uuid;
+
+// The highlighter sees a line change a keystroke at a time. Rather than lex all of it again,
+// `reparse` parses only the pipeline elements an edit touched and carries the rest of the tree
+// over, moving the nodes after the edit along by however much longer or shorter the line got.
+// Whenever it can't be sure the result is what parsing the whole line would give, like when the
+// edit opens a quote that may now run past the element, it parses the whole line instead.
+
+/// A change to a line: `removed` bytes at `offset` replaced with `inserted`
+#[derive(Debug, Clone, PartialEq, Eq, new)]
+pub struct Edit {
+    pub offset: usize,
+    pub removed: usize,
+    pub inserted: String,
+}
+
+impl Edit {
+    /// The edit that turns `before` into `after`, found by trimming what they have in common at
+    /// either end
+    pub fn between(before: &str, after: &str) -> Edit {
+        let prefix = before
+            .char_indices()
+            .zip(after.chars())
+            .find(|((_, a), b)| a != b)
+            .map(|((idx, _), _)| idx)
+            .unwrap_or_else(|| before.len().min(after.len()));
+
+        let suffix = before[prefix..]
+            .chars()
+            .rev()
+            .zip(after[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum::<usize>();
+
+        Edit {
+            offset: prefix,
+            removed: before.len() - prefix - suffix,
+            inserted: after[prefix..after.len() - suffix].to_string(),
+        }
+    }
+
+    /// The line the edit makes of `source`
+    pub fn apply(&self, source: &str) -> String {
+        let mut out = String::with_capacity(source.len() + self.inserted.len());
+        out.push_str(&source[..self.offset]);
+        out.push_str(&self.inserted);
+        out.push_str(&source[self.offset + self.removed..]);
+        out
+    }
+
+    fn end(&self) -> usize {
+        self.offset + self.removed
+    }
+
+    fn delta(&self) -> isize {
+        self.inserted.len() as isize - self.removed as isize
+    }
+
+    fn touches(&self, start: usize, end: usize) -> bool {
+        start <= self.end() && end >= self.offset
+    }
+}
+
+/// The tree for `source`, the line `edit` left, given `previous`, the tree `parse_with_recovery`
+/// gave for the line before the edit. It's the same tree parsing `source` from scratch gives.
+pub fn reparse(previous: &TokenNode, edit: &Edit, source: &str, origin: Uuid) -> TokenNode {
+    match reparse_statements(previous, edit, source, origin) {
+        Some(tree) => tree,
+        None => statements_with_recovery(nom_input(source, origin)),
+    }
+}
+
+fn reparse_statements(
+    previous: &TokenNode,
+    edit: &Edit,
+    source: &str,
+    origin: Uuid,
+) -> Option<TokenNode> {
+    let statements = match previous {
+        TokenNode::Statements(statements) => statements,
+        _ => return None,
+    };
+
+    let delta = edit.delta();
+    let touched: Vec<usize> = statements
+        .item
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| edit.touches(node.tag().span.start, node.tag().span.end))
+        .map(|(idx, _)| idx)
+        .collect();
+
+    let (first, last) = (*touched.first()?, *touched.last()?);
+    let old_start = statements.item[first].tag().span.start;
+    let old_end = statements.item[last].tag().span.end;
+
+    // The edit can reach past the statements into the `;` around them
+    let start = old_start.min(edit.offset);
+    let end = if old_end >= edit.end() {
+        shift(old_end, delta)
+    } else {
+        edit.offset + edit.inserted.len()
+    };
+
+    // The statements either side are only still there if `;` still divides them from these
+    if !(start == 0 || source[..start].ends_with(';'))
+        || !(end == source.len() || source[end..].starts_with(';'))
+    {
+        return None;
+    }
+
+    let reparsed = match &statements.item[first] {
+        TokenNode::Pipeline(pipeline)
+            if first == last && start == old_start && old_end >= edit.end() =>
+        {
+            vec![reparse_pipeline(pipeline, edit, source, origin)?]
+        }
+        _ => {
+            let region = statements_with_recovery(nom_input(source, origin).slice(start..end));
+            let nodes = region.as_statements().ok()?;
+
+            let has_error = nodes.iter().any(|node| match node {
+                TokenNode::Error(_) => true,
+                _ => false,
+            });
+            if has_error {
+                return None;
+            }
+
+            nodes
+        }
+    };
+
+    let mut nodes = statements.item[..first].to_vec();
+    nodes.extend(reparsed);
+    for node in &statements.item[last + 1..] {
+        nodes.push(shift_node(node, delta)?);
+    }
+
+    Some(TokenNode::Statements(Tagged::new(
+        shift_end(statements.tag, delta),
+        nodes,
+    )))
+}
+
+/// Parse again the elements of `pipeline` the edit touched
+fn reparse_pipeline(
+    pipeline: &Tagged<Pipeline>,
+    edit: &Edit,
+    source: &str,
+    origin: Uuid,
+) -> Option<TokenNode> {
+    let parts = &pipeline.item.parts;
+    let delta = edit.delta();
+
+    let touched: Vec<usize> = parts
+        .iter()
+        .enumerate()
+        .filter(|(_, part)| {
+            let span = element_span(part);
+            edit.touches(span.start, span.end)
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+
+    // An edit to the space or comment after the last element is the last element's business
+    let to_end = match parts.last() {
+        Some(part) => edit.end() >= element_span(part).end,
+        None => return None,
+    };
+
+    let first = touched.first().cloned().unwrap_or(parts.len() - 1);
+    let last = if to_end {
+        parts.len() - 1
+    } else {
+        *touched.last()?
+    };
+
+    let start = if first == 0 {
+        pipeline.tag.span.start
+    } else {
+        element_span(&parts[first]).start
+    };
+    let end = if last == parts.len() - 1 {
+        shift(pipeline.tag.span.end, delta)
+    } else {
+        shift(element_span(&parts[last]).end, delta)
+    };
+
+    // The next element has to start where this text ends, with its `|`
+    if last != parts.len() - 1 && !source[end..].starts_with('|') {
+        return None;
+    }
+
+    let (rest, region) = raw_pipeline(nom_input(source, origin).slice(start..end)).ok()?;
+    if rest.input_len() != 0 {
+        return None;
+    }
+
+    let region = region.as_pipeline().ok()?;
+    let region_end = region.parts.last().map(|part| element_span(part).end);
+
+    // Without its `|` the first element would have run on from the one before
+    if first > 0 && region.parts.first()?.pipe.is_none() {
+        return None;
+    }
+
+    // Anything after the last element belongs to the pipeline, which can only be the case at
+    // its end
+    if last != parts.len() - 1 && region_end != Some(end) {
+        return None;
+    }
+
+    let mut new_parts = parts[..first].to_vec();
+    new_parts.extend(region.parts.iter().cloned());
+    for part in &parts[last + 1..] {
+        new_parts.push(shift_element(part, delta)?);
+    }
+
+    let (post_ws, comment) = if last == parts.len() - 1 {
+        (region.post_ws, region.comment)
+    } else {
+        (
+            pipeline.item.post_ws.map(|tag| shift_tag(tag, delta)),
+            pipeline.item.comment.map(|tag| shift_tag(tag, delta)),
+        )
+    };
+
+    Some(TokenNode::Pipeline(Tagged::new(
+        shift_end(pipeline.tag, delta),
+        Pipeline::new(new_parts, post_ws, comment),
+    )))
+}
+
+fn element_span(part: &PipelineElement) -> Span {
+    let call = part.call().tag.span;
+
+    Span {
+        start: part
+            .pipe
+            .or(part.pre_ws)
+            .map_or(call.start, |tag| tag.span.start),
+        end: part.post_ws.map_or(call.end, |tag| tag.span.end),
+    }
+}
+
+fn shift(offset: usize, delta: isize) -> usize {
+    (offset as isize + delta) as usize
+}
+
+fn shift_tag(tag: Tag, delta: isize) -> Tag {
+    Tag {
+        origin: tag.origin,
+        span: Span {
+            start: shift(tag.span.start, delta),
+            end: shift(tag.span.end, delta),
+        },
+    }
+}
+
+fn shift_end(tag: Tag, delta: isize) -> Tag {
+    Tag {
+        origin: tag.origin,
+        span: Span {
+            start: tag.span.start,
+            end: shift(tag.span.end, delta),
+        },
+    }
+}
+
+fn shift_element(part: &PipelineElement, delta: isize) -> Option<PipelineElement> {
+    Some(PipelineElement::new(
+        part.pipe.map(|tag| shift_tag(tag, delta)),
+        part.pre_ws.map(|tag| shift_tag(tag, delta)),
+        shift_call(part.call(), delta)?,
+        part.post_ws.map(|tag| shift_tag(tag, delta)),
+    ))
+}
+
+fn shift_call(call: &Tagged<CallNode>, delta: isize) -> Option<Tagged<CallNode>> {
+    let children = match call.item.children() {
+        Some(children) => shift_nodes(children, delta)?,
+        None => vec![],
+    };
+
+    Some(Tagged::new(
+        shift_tag(call.tag, delta),
+        CallNode::new(Box::new(shift_node(call.item.head(), delta)?), children),
+    ))
+}
+
+fn shift_nodes(nodes: &[TokenNode], delta: isize) -> Option<Vec<TokenNode>> {
+    nodes.iter().map(|node| shift_node(node, delta)).collect()
+}
+
+fn shift_number(number: RawNumber, delta: isize) -> RawNumber {
+    match number {
+        RawNumber::Int(tag) => RawNumber::Int(shift_tag(tag, delta)),
+        RawNumber::Decimal(tag) => RawNumber::Decimal(shift_tag(tag, delta)),
+    }
+}
+
+/// The node as it is once the text before it has grown or shrunk by `delta`. Errors carry tags
+/// of their own that can't be moved, so a node holding one can't be reused.
+fn shift_node(node: &TokenNode, delta: isize) -> Option<TokenNode> {
+    if delta == 0 {
+        return Some(node.clone());
+    }
+
+    Some(match node {
+        TokenNode::Token(token) => {
+            let item = match token.item {
+                RawToken::Number(number) => RawToken::Number(shift_number(number, delta)),
+                RawToken::Size(number, unit) => RawToken::Size(shift_number(number, delta), unit),
+                RawToken::String(tag) => RawToken::String(shift_tag(tag, delta)),
+                RawToken::Variable(tag) => RawToken::Variable(shift_tag(tag, delta)),
+                RawToken::ExternalCommand(tag) => RawToken::ExternalCommand(shift_tag(tag, delta)),
+                other => other,
+            };
+
+            TokenNode::Token(Tagged::new(shift_tag(token.tag, delta), item))
+        }
+        TokenNode::Call(call) => TokenNode::Call(shift_call(call, delta)?),
+        TokenNode::Delimited(delimited) => TokenNode::Delimited(Tagged::new(
+            shift_tag(delimited.tag, delta),
+            DelimitedNode::new(
+                *delimited.item.delimiter(),
+                shift_nodes(delimited.item.children(), delta)?,
+            ),
+        )),
+        TokenNode::Pipeline(pipeline) => {
+            let parts = pipeline
+                .item
+                .parts
+                .iter()
+                .map(|part| shift_element(part, delta))
+                .collect::<Option<Vec<_>>>()?;
+
+            TokenNode::Pipeline(Tagged::new(
+                shift_tag(pipeline.tag, delta),
+                Pipeline::new(
+                    parts,
+                    pipeline.item.post_ws.map(|tag| shift_tag(tag, delta)),
+                    pipeline.item.comment.map(|tag| shift_tag(tag, delta)),
+                ),
+            ))
+        }
+        TokenNode::Statements(statements) => TokenNode::Statements(Tagged::new(
+            shift_tag(statements.tag, delta),
+            shift_nodes(&statements.item, delta)?,
+        )),
+        TokenNode::Operator(operator) => {
+            TokenNode::Operator(Tagged::new(shift_tag(operator.tag, delta), operator.item))
+        }
+        TokenNode::Flag(flag) => TokenNode::Flag(Tagged::new(
+            shift_tag(flag.tag, delta),
+            Flag::new(*flag.item.kind(), shift_tag(*flag.item.name(), delta)),
+        )),
+        TokenNode::Member(tag) => TokenNode::Member(shift_tag(*tag, delta)),
+        TokenNode::Whitespace(tag) => TokenNode::Whitespace(shift_tag(*tag, delta)),
+        TokenNode::Error(_) => return None,
+        TokenNode::Path(path) => TokenNode::Path(Tagged::new(
+            shift_tag(path.tag, delta),
+            PathNode::new(
+                Box::new(shift_node(path.item.head(), delta)?),
+                shift_nodes(path.item.tail(), delta)?,
+            ),
+        )),
+        TokenNode::Interpolation(parts) => TokenNode::Interpolation(Tagged::new(
+            shift_tag(parts.tag, delta),
+            shift_nodes(&parts.item, delta)?,
+        )),
+        TokenNode::Table(items) => TokenNode::Table(Tagged::new(
+            shift_tag(items.tag, delta),
+            shift_nodes(&items.item, delta)?,
+        )),
+        TokenNode::Record(fields) => TokenNode::Record(Tagged::new(
+            shift_tag(fields.tag, delta),
+            fields
+                .item
+                .iter()
+                .map(|(key, value)| Some((shift_node(key, delta)?, shift_node(value, delta)?)))
+                .collect::<Option<Vec<_>>>()?,
+        )),
+        TokenNode::BlockParams(params) => TokenNode::BlockParams(Tagged::new(
+            shift_tag(params.tag, delta),
+            params
+                .item
+                .iter()
+                .map(|tag| shift_tag(*tag, delta))
+                .collect(),
+        )),
+    })
+}
@@ -1,5 +1,7 @@
 use crate::errors::ShellError;
-use crate::parser::parse::{call_node::*, flag::*, operator::*, pipeline::*, tokens::*};
+use crate::parser::parse::{
+    call_node::*, flag::*, operator::*, parser::unescape_bare, pipeline::*, tokens::*,
+};
 use crate::traits::ToDebug;
 use crate::{Tag, Tagged, Text};
 use derive_new::new;
@@ -14,6 +16,7 @@ pub enum TokenNode {
     Call(Tagged<CallNode>),
     Delimited(Tagged<DelimitedNode>),
     Pipeline(Tagged<Pipeline>),
+    Statements(Tagged<Vec<TokenNode>>),
     Operator(Tagged<Operator>),
     Flag(Tagged<Flag>),
     Member(Tag),
@@ -21,6 +24,10 @@ pub enum TokenNode {
 
     Error(Tagged<Box<ShellError>>),
     Path(Tagged<PathNode>),
+    Interpolation(Tagged<Vec<TokenNode>>),
+    Table(Tagged<Vec<TokenNode>>),
+    Record(Tagged<Vec<(TokenNode, TokenNode)>>),
+    BlockParams(Tagged<Vec<Tag>>),
 }
 
 impl ToDebug for TokenNode {
@@ -78,6 +85,17 @@ impl fmt::Debug for DebugTokenNode<'_> {
                 )
             }
             TokenNode::Pipeline(pipeline) => write!(f, "{}", pipeline.debug(self.source)),
+            TokenNode::Statements(statements) => {
+                for (idx, statement) in statements.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ";")?;
+                    }
+
+                    write!(f, "{:?}", statement.old_debug(self.source))?;
+                }
+
+                Ok(())
+            }
             TokenNode::Error(s) => write!(f, "<error> for {:?}", s.tag().slice(self.source)),
             rest => write!(f, "{}", rest.tag().slice(self.source)),
         }
@@ -97,12 +115,17 @@ impl TokenNode {
             TokenNode::Call(s) => s.tag(),
             TokenNode::Delimited(s) => s.tag(),
             TokenNode::Pipeline(s) => s.tag(),
+            TokenNode::Statements(s) => s.tag(),
             TokenNode::Operator(s) => s.tag(),
             TokenNode::Flag(s) => s.tag(),
             TokenNode::Member(s) => *s,
             TokenNode::Whitespace(s) => *s,
             TokenNode::Error(s) => s.tag(),
             TokenNode::Path(s) => s.tag(),
+            TokenNode::Interpolation(s) => s.tag(),
+            TokenNode::Table(s) => s.tag(),
+            TokenNode::Record(s) => s.tag(),
+            TokenNode::BlockParams(s) => s.tag(),
         }
     }
 
@@ -112,12 +135,17 @@ impl TokenNode {
             TokenNode::Call(_) => "command",
             TokenNode::Delimited(d) => d.type_name(),
             TokenNode::Pipeline(_) => "pipeline",
+            TokenNode::Statements(_) => "statements",
             TokenNode::Operator(_) => "operator",
             TokenNode::Flag(_) => "flag",
             TokenNode::Member(_) => "member",
             TokenNode::Whitespace(_) => "whitespace",
             TokenNode::Error(_) => "error",
             TokenNode::Path(_) => "path",
+            TokenNode::Interpolation(_) => "interpolated string",
+            TokenNode::Table(_) => "table",
+            TokenNode::Record(_) => "record",
+            TokenNode::BlockParams(_) => "block parameters",
         }
         .to_string()
     }
@@ -127,7 +155,13 @@ impl TokenNode {
     }
 
     pub fn as_external_arg(&self, source: &Text) -> String {
-        self.tag().slice(source).to_string()
+        match self {
+            TokenNode::Token(Tagged {
+                item: RawToken::Bare,
+                ..
+            }) => unescape_bare(self.tag().slice(source)),
+            _ => self.tag().slice(source).to_string(),
+        }
     }
 
     pub fn source<'a>(&self, source: &'a Text) -> &'a str {
@@ -140,6 +174,11 @@ impl TokenNode {
                 item: RawToken::Bare,
                 ..
             }) => true,
+            // `true` and `false` still name commands at the head of a pipeline
+            TokenNode::Token(Tagged {
+                item: RawToken::Boolean(_),
+                ..
+            }) => true,
             _ => false,
         }
     }
@@ -154,6 +193,16 @@ impl TokenNode {
         }
     }
 
+    pub fn is_null(&self) -> bool {
+        match self {
+            TokenNode::Token(Tagged {
+                item: RawToken::Null,
+                ..
+            }) => true,
+            _ => false,
+        }
+    }
+
     pub fn expect_external(&self) -> Tag {
         match self {
             TokenNode::Token(Tagged {
@@ -164,17 +213,46 @@ impl TokenNode {
         }
     }
 
-    pub(crate) fn as_flag(&self, value: &str, source: &Text) -> Option<Tagged<Flag>> {
+    /// The flag named `value`, given in full or, after a single `-`, as its `shorthand` letter
+    pub(crate) fn as_flag(
+        &self,
+        value: &str,
+        shorthand: Option<char>,
+        source: &Text,
+    ) -> Option<Tagged<Flag>> {
         match self {
             TokenNode::Flag(
                 flag @ Tagged {
                     item: Flag { .. }, ..
                 },
-            ) if value == flag.name().slice(source) => Some(*flag),
+            ) => {
+                let name = flag.name().slice(source);
+
+                let matches = match flag.kind() {
+                    FlagKind::Longhand => name == value,
+                    FlagKind::Shorthand => {
+                        name == value
+                            || shorthand.map_or(false, |c| name.chars().eq(std::iter::once(c)))
+                    }
+                };
+
+                if matches {
+                    Some(*flag)
+                } else {
+                    None
+                }
+            }
             _ => None,
         }
     }
 
+    pub fn as_statements(&self) -> Result<Vec<TokenNode>, ShellError> {
+        match self {
+            TokenNode::Statements(Tagged { item, .. }) => Ok(item.clone()),
+            _ => Err(ShellError::string("unimplemented")),
+        }
+    }
+
     pub fn as_pipeline(&self) -> Result<Pipeline, ShellError> {
         match self {
             TokenNode::Pipeline(Tagged { item, .. }) => Ok(item.clone()),
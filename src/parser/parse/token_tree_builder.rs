@@ -76,15 +76,39 @@ impl TokenTreeBuilder {
 
             let end = b.pos;
 
-            TokenTreeBuilder::tagged_pipeline((out, None), (start, end, b.origin))
+            TokenTreeBuilder::tagged_pipeline((out, None, None), (start, end, b.origin))
         })
     }
 
     pub fn tagged_pipeline(
-        input: (Vec<PipelineElement>, Option<Tag>),
+        input: (Vec<PipelineElement>, Option<Tag>, Option<Tag>),
         tag: impl Into<Tag>,
     ) -> TokenNode {
-        TokenNode::Pipeline(Pipeline::new(input.0, input.1.into()).tagged(tag.into()))
+        TokenNode::Pipeline(Pipeline::new(input.0, input.1, input.2).tagged(tag.into()))
+    }
+
+    pub fn tagged_interpolation(input: Vec<TokenNode>, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Interpolation(input.tagged(tag.into()))
+    }
+
+    pub fn tagged_table(input: Vec<TokenNode>, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Table(input.tagged(tag.into()))
+    }
+
+    pub fn tagged_record(input: Vec<(TokenNode, TokenNode)>, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Record(input.tagged(tag.into()))
+    }
+
+    pub fn tagged_block_params(input: Vec<Tag>, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::BlockParams(input.tagged(tag.into()))
+    }
+
+    pub fn tagged_statements(input: Vec<TokenNode>, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Statements(input.tagged(tag.into()))
+    }
+
+    pub fn tagged_error(input: ShellError, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Error(Box::new(input).tagged(tag.into()))
     }
 
     pub fn op(input: impl Into<Operator>) -> CurriedToken {
@@ -138,6 +162,14 @@ impl TokenTreeBuilder {
         TokenNode::Token(RawToken::Bare.tagged(tag.into()))
     }
 
+    pub fn tagged_boolean(input: bool, tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Token(RawToken::Boolean(input).tagged(tag.into()))
+    }
+
+    pub fn tagged_null(tag: impl Into<Tag>) -> TokenNode {
+        TokenNode::Token(RawToken::Null.tagged(tag.into()))
+    }
+
     pub fn pattern(input: impl Into<String>) -> CurriedToken {
         let input = input.into();
 
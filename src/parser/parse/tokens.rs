@@ -8,6 +8,8 @@ use std::str::FromStr;
 pub enum RawToken {
     Number(RawNumber),
     Size(RawNumber, Unit),
+    Boolean(bool),
+    Null,
     String(Tag),
     Variable(Tag),
     ExternalCommand(Tag),
@@ -37,19 +39,26 @@ impl RawNumber {
 
     pub(crate) fn to_number(self, source: &Text) -> Number {
         match self {
-            RawNumber::Int(tag) => Number::Int(BigInt::from_str(tag.slice(source)).unwrap()),
+            RawNumber::Int(tag) => Number::Int(BigInt::from_str(&digits(tag, source)).unwrap()),
             RawNumber::Decimal(tag) => {
-                Number::Decimal(BigDecimal::from_str(tag.slice(source)).unwrap())
+                Number::Decimal(BigDecimal::from_str(&digits(tag, source)).unwrap())
             }
         }
     }
 }
 
+// The number's text without the underscores used to group its digits
+fn digits(tag: Tag, source: &Text) -> String {
+    tag.slice(source).replace('_', "")
+}
+
 impl RawToken {
     pub fn type_name(&self) -> &'static str {
         match self {
             RawToken::Number(_) => "Number",
             RawToken::Size(..) => "Size",
+            RawToken::Boolean(_) => "Boolean",
+            RawToken::Null => "Null",
             RawToken::String(_) => "String",
             RawToken::Variable(_) => "Variable",
             RawToken::ExternalCommand(_) => "ExternalCommand",
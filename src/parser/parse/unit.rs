@@ -1,4 +1,4 @@
-use crate::data::base::Value;
+use crate::data::base::{checked_nanos, Value};
 use crate::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
@@ -11,6 +11,12 @@ pub enum Unit {
     GB,
     TB,
     PB,
+
+    // Durations
+    Second,
+    Minute,
+    Hour,
+    Day,
 }
 
 impl Unit {
@@ -22,23 +28,38 @@ impl Unit {
             Unit::GB => "GB",
             Unit::TB => "TB",
             Unit::PB => "PB",
+            Unit::Second => "s",
+            Unit::Minute => "m",
+            Unit::Hour => "h",
+            Unit::Day => "d",
         }
     }
 
-    pub(crate) fn compute(&self, size: &Number) -> Value {
+    /// The value of a number of this unit, or None for a duration too long to hold
+    pub(crate) fn compute(&self, size: &Number) -> Option<Value> {
         let size = size.clone();
 
-        Value::number(match self {
-            Unit::B => size,
-            Unit::KB => size * 1024,
-            Unit::MB => size * 1024 * 1024,
-            Unit::GB => size * 1024 * 1024 * 1024,
-            Unit::TB => size * 1024 * 1024 * 1024 * 1024,
-            Unit::PB => size * 1024 * 1024 * 1024 * 1024 * 1024,
-        })
+        match self {
+            Unit::B => Some(Value::number(size)),
+            Unit::KB => Some(Value::number(size * 1024)),
+            Unit::MB => Some(Value::number(size * 1024 * 1024)),
+            Unit::GB => Some(Value::number(size * 1024 * 1024 * 1024)),
+            Unit::TB => Some(Value::number(size * 1024 * 1024 * 1024 * 1024)),
+            Unit::PB => Some(Value::number(size * 1024 * 1024 * 1024 * 1024 * 1024)),
+            Unit::Second => duration(size, 1_000_000_000),
+            Unit::Minute => duration(size, 60 * 1_000_000_000),
+            Unit::Hour => duration(size, 60 * 60 * 1_000_000_000),
+            Unit::Day => duration(size, 24 * 60 * 60 * 1_000_000_000),
+        }
     }
 }
 
+fn duration(size: Number, unit_nanos: i64) -> Option<Value> {
+    let nanos = checked_nanos(size * Number::from(unit_nanos))?;
+
+    Some(Value::Primitive(Primitive::Duration(nanos)))
+}
+
 impl From<&str> for Unit {
     fn from(input: &str) -> Unit {
         Unit::from_str(input).unwrap()
@@ -55,6 +76,10 @@ impl FromStr for Unit {
             "GB" | "gb" | "Gb" => Ok(Unit::GB),
             "TB" | "tb" | "Tb" => Ok(Unit::TB),
             "PB" | "pb" | "Pb" => Ok(Unit::PB),
+            "s" => Ok(Unit::Second),
+            "m" => Ok(Unit::Minute),
+            "h" => Ok(Unit::Hour),
+            "d" => Ok(Unit::Day),
             _ => Err(()),
         }
     }
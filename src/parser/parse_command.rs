@@ -1,5 +1,7 @@
 use crate::context::Context;
 use crate::errors::{ArgumentError, ShellError};
+use crate::parser::parse::flag::FlagKind;
+use crate::parser::parse::token_tree_builder::TokenTreeBuilder;
 use crate::parser::registry::{NamedType, PositionalType, Signature};
 use crate::parser::{baseline_parse_tokens, CallNode};
 use crate::parser::{
@@ -33,6 +35,11 @@ pub fn parse_command(
             .collect()
     });
 
+    let children = match children {
+        Some(children) => Some(expand_shorthands(config, children, source)?),
+        None => None,
+    };
+
     match parse_command_tail(&config, context, children, source, call.tag())? {
         None => Ok(hir::Call::new(Box::new(head), None, None)),
         Some((positional, named)) => Ok(hir::Call::new(Box::new(head), positional, named)),
@@ -81,7 +88,7 @@ fn parse_command_tail(
 
         match kind {
             NamedType::Switch => {
-                let flag = extract_switch(name, tail, source);
+                let flag = extract_switch(config, name, tail, source)?;
 
                 named.insert_switch(name, flag);
             }
@@ -107,35 +114,46 @@ fn parse_command_tail(
                     }
                 }
             }
-            NamedType::Optional(syntax_type) => match extract_optional(name, tail, source) {
-                Err(err) => return Err(err), // produce a correct diagnostic
-                Ok(Some((pos, flag))) => {
-                    tail.move_to(pos);
-
-                    if tail.at_end() {
-                        return Err(ShellError::argument_error(
-                            config.name.clone(),
-                            ArgumentError::MissingValueForName(name.to_string()),
-                            flag.tag(),
-                        ));
-                    }
+            NamedType::Optional(syntax_type) => {
+                match extract_optional(config, name, tail, source) {
+                    Err(err) => return Err(err), // produce a correct diagnostic
+                    Ok(Some((pos, flag))) => {
+                        tail.move_to(pos);
 
-                    let expr = hir::baseline_parse_next_expr(tail, context, source, *syntax_type)?;
+                        if tail.at_end() {
+                            return Err(ShellError::argument_error(
+                                config.name.clone(),
+                                ArgumentError::MissingValueForName(name.to_string()),
+                                flag.tag(),
+                            ));
+                        }
 
-                    tail.restart();
-                    named.insert_optional(name, Some(expr));
-                }
+                        let expr =
+                            hir::baseline_parse_next_expr(tail, context, source, *syntax_type)?;
 
-                Ok(None) => {
-                    tail.restart();
-                    named.insert_optional(name, None);
+                        tail.restart();
+                        named.insert_optional(name, Some(expr));
+                    }
+
+                    Ok(None) => {
+                        tail.restart();
+                        named.insert_optional(name, None);
+                    }
                 }
-            },
+            }
         };
     }
 
     trace_remaining("after named", tail.clone(), source);
 
+    // Every flag the command knows about has been taken out by now
+    if let Some(flag) = tail.clone().find_map(|node| match node {
+        TokenNode::Flag(flag) => Some(*flag),
+        _ => None,
+    }) {
+        return Err(unknown_flag(config, flag, source));
+    }
+
     let mut positional = vec![];
 
     for arg in &config.positional {
@@ -194,10 +212,99 @@ fn parse_command_tail(
     Ok(Some((positional, named)))
 }
 
-fn extract_switch(name: &str, tokens: &mut hir::TokensIterator<'_>, source: &Text) -> Option<Flag> {
-    tokens
-        .extract(|t| t.as_flag(name, source))
-        .map(|(_pos, flag)| flag.item)
+/// The letter that stands for a flag after a single `-`: its first, unless another of the
+/// command's flags starts with it too
+fn shorthand(config: &Signature, name: &str) -> Option<char> {
+    let first = name.chars().next()?;
+
+    match config.named.keys().filter(|k| k.starts_with(first)).count() {
+        1 => Some(first),
+        _ => None,
+    }
+}
+
+/// Split grouped shorthands like the `-la` of `ls -la` into one flag per letter. A group that's
+/// the whole name of one of the command's flags is left alone.
+fn expand_shorthands(
+    config: &Signature,
+    tokens: Vec<TokenNode>,
+    source: &Text,
+) -> Result<Vec<TokenNode>, ShellError> {
+    let mut expanded = vec![];
+
+    for token in tokens {
+        let flag = match token {
+            TokenNode::Flag(flag) => flag,
+            other => {
+                expanded.push(other);
+                continue;
+            }
+        };
+
+        let name = flag.name().slice(source);
+
+        if *flag.kind() == FlagKind::Longhand || config.named.contains_key(name) {
+            expanded.push(TokenNode::Flag(flag));
+            continue;
+        }
+
+        let start = flag.name().span.start;
+        let origin = flag.name().origin;
+
+        for (idx, letter) in name.char_indices() {
+            let tag = Tag::from((start + idx, start + idx + letter.len_utf8(), origin));
+
+            let candidates: Vec<_> = config
+                .named
+                .keys()
+                .filter(|k| k.starts_with(letter))
+                .map(|k| format!("--{}", k))
+                .collect();
+
+            if candidates.len() > 1 && !config.named.contains_key(&letter.to_string()) {
+                return Err(ShellError::labeled_error(
+                    "Ambiguous flag",
+                    format!("-{} could be {}", letter, candidates.join(" or ")),
+                    tag,
+                ));
+            }
+
+            expanded.push(TokenTreeBuilder::tagged_shorthand(tag, tag));
+        }
+    }
+
+    Ok(expanded)
+}
+
+fn extract_switch(
+    config: &Signature,
+    name: &str,
+    tokens: &mut hir::TokensIterator<'_>,
+    source: &Text,
+) -> Result<Option<Flag>, ShellError> {
+    let (pos, flag) = match tokens.extract(|t| t.as_flag(name, shorthand(config, name), source)) {
+        Some(found) => found,
+        None => return Ok(None),
+    };
+
+    // `--name=value` gives the flag and its value as two tokens with an `=` between them. A
+    // switch has no value, and the one given would otherwise be left over as a positional.
+    let mut after = tokens.clone();
+    after.move_to(pos);
+
+    if let Some(value) = after.peek() {
+        let flag_end = flag.tag().span.end;
+
+        if value.tag().span.start == flag_end + 1 && source[flag_end..].starts_with('=') {
+            return Err(ShellError::labeled_error(
+                "Unexpected value",
+                format!("--{} is a switch, and doesn't take a value", name),
+                value.tag(),
+            ));
+        }
+    }
+
+    Ok(Some(flag.item))
 }
 
 fn extract_mandatory(
@@ -207,7 +314,7 @@ fn extract_mandatory(
     source: &Text,
     tag: Tag,
 ) -> Result<(usize, Tagged<Flag>), ShellError> {
-    let flag = tokens.extract(|t| t.as_flag(name, source));
+    let flag = tokens.extract(|t| t.as_flag(name, shorthand(config, name), source));
 
     match flag {
         None => Err(ShellError::argument_error(
@@ -224,11 +331,12 @@ fn extract_mandatory(
 }
 
 fn extract_optional(
+    config: &Signature,
     name: &str,
     tokens: &mut hir::TokensIterator<'_>,
     source: &Text,
 ) -> Result<(Option<(usize, Tagged<Flag>)>), ShellError> {
-    let flag = tokens.extract(|t| t.as_flag(name, source));
+    let flag = tokens.extract(|t| t.as_flag(name, shorthand(config, name), source));
 
     match flag {
         None => Ok(None),
@@ -239,6 +347,28 @@ fn extract_optional(
     }
 }
 
+fn unknown_flag(config: &Signature, flag: Tagged<Flag>, source: &Text) -> ShellError {
+    let name = flag.name().slice(source);
+
+    let closest = config
+        .named
+        .keys()
+        .map(|known| (natural::distance::levenshtein_distance(known, name), known))
+        .min();
+
+    let help = match closest {
+        Some((_, known)) => format!("did you mean --{}?", known),
+        None => format!("{} doesn't take any flags", config.name),
+    };
+
+    ShellError::labeled_error(
+        "Unknown flag",
+        format!("{} has no flag named {}", config.name, name),
+        flag.tag(),
+    )
+    .with_help(help)
+}
+
 pub fn trace_remaining(desc: &'static str, tail: hir::TokensIterator<'_>, source: &Text) {
     trace!(
         "{} = {:?}",
@@ -71,6 +71,9 @@ pub struct Signature {
     pub named: IndexMap<String, NamedType>,
     #[new(value = "false")]
     pub is_filter: bool,
+    #[new(default)]
+    #[serde(default)]
+    pub completions: IndexMap<String, Vec<String>>,
 }
 
 impl Signature {
@@ -121,6 +124,16 @@ impl Signature {
         self
     }
 
+    /// The values an argument, positional or named, can take, for tab completion to offer
+    pub fn completions(mut self, name: impl Into<String>, values: &[&str]) -> Signature {
+        self.completions.insert(
+            name.into(),
+            values.iter().map(|value| value.to_string()).collect(),
+        );
+
+        self
+    }
+
     pub fn filter(mut self) -> Signature {
         self.is_filter = true;
         self
@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    SyntaxShape, Tagged, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, SyntaxShape,
+    Tagged, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 struct Add {
     field: Option<String>,
@@ -41,6 +42,8 @@ impl Add {
 }
 
 impl Plugin for Add {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("add")
             .desc("Add a new field to the table.")
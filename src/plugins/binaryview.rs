@@ -1,8 +1,8 @@
 use crossterm::{cursor, terminal, Attribute, RawScreen};
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ShellError, Signature, SpanSource, Tagged, Value,
+    hex_dump, CallInfo, NuValues, Primitive, ShellError, Signature, SpanSource, Tagged, Value,
 };
-use pretty_hex::*;
+use nu_plugin::{serve_plugin, Plugin};
 
 struct BinaryView;
 
@@ -13,6 +13,8 @@ impl BinaryView {
 }
 
 impl Plugin for BinaryView {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("binaryview")
             .desc("Autoview of binary data.")
@@ -265,7 +267,7 @@ pub fn view_contents(
 
     if raw_image_buffer.is_none() {
         //Not yet supported
-        println!("{:?}", buffer.hex_dump());
+        println!("{}", hex_dump(buffer, 0));
         return Ok(());
     }
     let raw_image_buffer = raw_image_buffer.unwrap();
@@ -323,7 +325,7 @@ pub fn view_contents(
         }
         _ => {
             //Not yet supported
-            println!("{:?}", buffer.hex_dump());
+            println!("{}", hex_dump(buffer, 0));
             return Ok(());
         }
     }
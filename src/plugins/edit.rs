@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    SyntaxShape, Tagged, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, SyntaxShape,
+    Tagged, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 struct Edit {
     field: Option<String>,
@@ -40,6 +41,8 @@ impl Edit {
 }
 
 impl Plugin for Edit {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("edit")
             .desc("Edit an existing column to have a new value.")
@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    SyntaxShape, Tag, Tagged, TaggedDictBuilder, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, SyntaxShape,
+    Tag, Tagged, TaggedDictBuilder, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 struct Embed {
     field: Option<String>,
@@ -34,6 +35,8 @@ impl Embed {
 }
 
 impl Plugin for Embed {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("embed")
             .desc("Embeds a new field to the table.")
@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    SyntaxShape, Tagged, TaggedItem, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, SyntaxShape,
+    Tagged, TaggedItem, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 enum Action {
     SemVerAction(SemVerAction),
@@ -114,6 +115,8 @@ impl Inc {
 }
 
 impl Plugin for Inc {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("inc")
             .desc("Increment a value or version. Optionally use the column of a table.")
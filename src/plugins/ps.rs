@@ -6,9 +6,10 @@ use heim::units::{ratio, Ratio};
 use std::usize;
 
 use nu::{
-    serve_plugin, CallInfo, Plugin, ReturnSuccess, ReturnValue, ShellError, Signature, Tag, Tagged,
+    CallInfo, NuValues, ReturnSuccess, ReturnValue, ShellError, Signature, Tag, Tagged,
     TaggedDictBuilder, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 use std::time::Duration;
 
 struct Ps;
@@ -57,6 +58,8 @@ async fn ps(tag: Tag) -> Vec<Tagged<Value>> {
 }
 
 impl Plugin for Ps {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("ps")
             .desc("View information about system processes.")
@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, CoerceInto, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError,
-    Signature, SyntaxShape, Tagged, TaggedItem, Value,
+    CallInfo, CoerceInto, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
+    SyntaxShape, Tagged, TaggedItem, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 struct Skip {
     skip_amount: i64,
@@ -14,6 +15,8 @@ impl Skip {
 }
 
 impl Plugin for Skip {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("skip")
             .desc("Skip a number of rows")
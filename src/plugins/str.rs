@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    SyntaxShape, Tagged, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, SyntaxShape,
+    Tagged, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 #[derive(Debug, Eq, PartialEq)]
 enum Action {
@@ -121,6 +122,8 @@ impl Str {
 }
 
 impl Plugin for Str {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("str")
             .desc("Apply string function. Optional use the field of a table")
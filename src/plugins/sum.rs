@@ -1,7 +1,8 @@
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    Tagged, TaggedItem, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, Tagged,
+    TaggedItem, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 struct Sum {
     total: Option<Tagged<Value>>,
@@ -61,6 +62,8 @@ impl Sum {
 }
 
 impl Plugin for Sum {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("sum")
             .desc("Sum a column of values.")
@@ -5,9 +5,10 @@ use futures::stream::StreamExt;
 use heim::units::{frequency, information, thermodynamic_temperature, time};
 use heim::{disk, host, memory, net, sensors};
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature,
-    Tag, Tagged, TaggedDictBuilder, Value,
+    CallInfo, NuValues, Primitive, ReturnSuccess, ReturnValue, ShellError, Signature, Tag, Tagged,
+    TaggedDictBuilder, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 struct Sys;
 impl Sys {
@@ -308,6 +309,8 @@ async fn sysinfo(tag: Tag) -> Vec<Tagged<Value>> {
 }
 
 impl Plugin for Sys {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("sys")
             .desc("View information about the current system.")
@@ -1,12 +1,12 @@
 use crossterm::{cursor, terminal, RawScreen};
 use crossterm::{InputEvent, KeyEvent};
 use nu::{
-    serve_plugin, CallInfo, Plugin, Primitive, ShellError, Signature, SourceMap, SpanSource,
-    Tagged, Value,
+    CallInfo, NuValues, Primitive, ShellError, Signature, SourceMap, SpanSource, Tag, Tagged, Value,
 };
+use nu_plugin::{serve_plugin, Plugin};
 
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, ThemeSet};
+use syntect::highlighting::{Color, Style, ThemeSet};
 use syntect::parsing::SyntaxSet;
 
 use std::io::Write;
@@ -18,6 +18,41 @@ enum DrawCommand {
 }
 struct TextView;
 
+struct TextViewConfig {
+    line_numbers: bool,
+    paging: bool,
+    theme: String,
+}
+
+impl TextViewConfig {
+    fn load() -> TextViewConfig {
+        let mut config = TextViewConfig {
+            line_numbers: true,
+            paging: true,
+            theme: "OneHalfDark".to_string(),
+        };
+
+        if let Ok(values) = nu::config(Tag::unknown()) {
+            for (key, value) in values.iter() {
+                match (key.as_str(), &value.item) {
+                    ("textview_line_numbers", Value::Primitive(Primitive::Boolean(b))) => {
+                        config.line_numbers = *b
+                    }
+                    ("textview_paging", Value::Primitive(Primitive::Boolean(b))) => {
+                        config.paging = *b
+                    }
+                    ("textview_theme", Value::Primitive(Primitive::String(s))) => {
+                        config.theme = s.clone()
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        config
+    }
+}
+
 impl TextView {
     fn new() -> TextView {
         TextView
@@ -25,6 +60,8 @@ impl TextView {
 }
 
 impl Plugin for TextView {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("textview").desc("Autoview of text data."))
     }
@@ -125,7 +162,43 @@ fn paint_textview(
     num_frame_buffer_rows
 }
 
-fn scroll_view_lines_if_needed(draw_commands: Vec<DrawCommand>, use_color_buffer: bool) {
+fn print_textview(draw_commands: &[DrawCommand], use_color_buffer: bool) {
+    let mut line = vec![];
+
+    for command in draw_commands {
+        match command {
+            DrawCommand::DrawString(style, string) => {
+                if use_color_buffer {
+                    line.push(
+                        ansi_term::Colour::RGB(
+                            style.foreground.r,
+                            style.foreground.g,
+                            style.foreground.b,
+                        )
+                        .paint(string.clone()),
+                    );
+                } else {
+                    line.push(ansi_term::Style::default().paint(string.clone()));
+                }
+            }
+            DrawCommand::NextLine => {
+                println!("{}", ansi_term::ANSIStrings(&line));
+                line.clear();
+            }
+        }
+    }
+}
+
+fn scroll_view_lines_if_needed(
+    draw_commands: Vec<DrawCommand>,
+    use_color_buffer: bool,
+    config: &TextViewConfig,
+) {
+    if !config.paging {
+        print_textview(&draw_commands, use_color_buffer);
+        return;
+    }
+
     let mut starting_row = 0;
 
     if let Ok(_raw) = RawScreen::into_raw_mode() {
@@ -206,16 +279,36 @@ fn scroll_view_lines_if_needed(draw_commands: Vec<DrawCommand>, use_color_buffer
     println!("");
 }
 
-fn scroll_view(s: &str) {
+fn line_number_gutter(line: usize, total_lines: usize) -> DrawCommand {
+    let width = total_lines.to_string().len();
+    let style = Style {
+        foreground: Color {
+            r: 0x80,
+            g: 0x80,
+            b: 0x80,
+            a: 0xff,
+        },
+        ..Style::default()
+    };
+
+    DrawCommand::DrawString(style, format!("{:>width$} │ ", line, width = width))
+}
+
+fn scroll_view(s: &str, config: &TextViewConfig) {
+    let total_lines = s.lines().count();
     let mut v = vec![];
-    for line in s.lines() {
+    for (idx, line) in s.lines().enumerate() {
+        if config.line_numbers {
+            v.push(line_number_gutter(idx + 1, total_lines));
+        }
         v.push(DrawCommand::DrawString(Style::default(), line.to_string()));
         v.push(DrawCommand::NextLine);
     }
-    scroll_view_lines_if_needed(v, false);
+    scroll_view_lines_if_needed(v, false, config);
 }
 
 fn view_text_value(value: &Tagged<Value>, source_map: &SourceMap) {
+    let config = TextViewConfig::load();
     let value_origin = value.origin();
     match value.item {
         Value::Primitive(Primitive::String(ref s)) => {
@@ -260,10 +353,19 @@ fn view_text_value(value: &Tagged<Value>, source_map: &SourceMap) {
                             let ts: ThemeSet = syntect::dumps::from_binary(include_bytes!(
                                 "../../assets/themes.bin"
                             ));
-                            let mut h = HighlightLines::new(syntax, &ts.themes["OneHalfDark"]);
+                            let theme = ts
+                                .themes
+                                .get(&config.theme)
+                                .unwrap_or(&ts.themes["OneHalfDark"]);
+                            let mut h = HighlightLines::new(syntax, theme);
 
+                            let total_lines = s.lines().count();
                             let mut v = vec![];
-                            for line in s.lines() {
+                            for (idx, line) in s.lines().enumerate() {
+                                if config.line_numbers {
+                                    v.push(line_number_gutter(idx + 1, total_lines));
+                                }
+
                                 let ranges: Vec<(Style, &str)> = h.highlight(line, &ps);
 
                                 for range in ranges {
@@ -272,17 +374,17 @@ fn view_text_value(value: &Tagged<Value>, source_map: &SourceMap) {
 
                                 v.push(DrawCommand::NextLine);
                             }
-                            scroll_view_lines_if_needed(v, true);
+                            scroll_view_lines_if_needed(v, true, &config);
                         } else {
-                            scroll_view(s);
+                            scroll_view(s, &config);
                         }
                     }
                     _ => {
-                        scroll_view(s);
+                        scroll_view(s, &config);
                     }
                 }
             } else {
-                scroll_view(s);
+                scroll_view(s, &config);
             }
         }
         _ => {}
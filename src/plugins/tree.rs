@@ -1,5 +1,6 @@
 use derive_new::new;
-use nu::{serve_plugin, CallInfo, Plugin, ShellError, Signature, Tagged, Value};
+use nu::{CallInfo, NuValues, ShellError, Signature, Tagged, Value};
+use nu_plugin::{serve_plugin, Plugin};
 use ptree::item::StringItem;
 use ptree::output::print_tree_with;
 use ptree::print_config::PrintConfig;
@@ -78,6 +79,8 @@ impl TreeView {
 struct TreeViewer;
 
 impl Plugin for TreeViewer {
+    type Values = NuValues;
+
     fn config(&mut self) -> Result<Signature, ShellError> {
         Ok(Signature::build("tree").desc("View the contents of the pipeline as a tree."))
     }
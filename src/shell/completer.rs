@@ -1,75 +1,949 @@
+use crate::cli::classify_pipeline;
+use crate::commands::classified::{ClassifiedCommand, ClassifiedInputStream};
+use crate::parser::parse::flag::FlagKind;
+use crate::parser::registry::NamedType;
+use crate::parser::{
+    nom_input, statements_with_recovery, unclosed_delimiter_at, unescape_bare, CallNode, TokenNode,
+};
 use crate::prelude::*;
 use derive_new::new;
-use rustyline::completion::{Completer, FilenameCompleter};
+use lazy_static::lazy_static;
+use nom::Slice;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-#[derive(new)]
+#[derive(Clone, new)]
 pub(crate) struct NuCompleter {
-    pub file_completer: FilenameCompleter,
     pub commands: CommandRegistry,
 }
 
+/// What the word under the cursor is, as far as completion is concerned
+#[derive(Debug, PartialEq)]
+pub(crate) enum CompletionLocation {
+    // The head of a pipeline element
+    Command,
+    // A `--flag` for the given command
+    Flag(String),
+    // The value for a named argument, or the nth positional argument
+    Named(String, String),
+    Positional(String, usize),
+    // We couldn't make sense of the line, so fall back to whatever is sensible
+    Unknown,
+}
+
 impl NuCompleter {
+    /// Complete the word ending at `pos`. Paths are looked up through `paths`, which gives the
+    /// candidates for a partially typed path in whichever shell is active.
     pub fn complete(
         &self,
         line: &str,
         pos: usize,
-        context: &rustyline::Context,
+        paths: &dyn Fn(&str) -> Vec<String>,
     ) -> rustyline::Result<(usize, Vec<rustyline::completion::Pair>)> {
-        let commands: Vec<String> = self.commands.names();
+        let replace_pos = word_start(line, pos);
+        let partial = &line[replace_pos..pos];
+
+        if let Some(completions) = custom_completions(line, &line[..replace_pos], partial) {
+            return Ok((replace_pos, completions));
+        }
 
-        let mut completions = self.file_completer.complete(line, pos, context)?.1;
+        let location = completion_location(&self.commands, &line[..replace_pos], partial);
 
-        for completion in &mut completions {
-            if completion.replacement.contains("\\ ") {
-                completion.replacement = completion.replacement.replace("\\ ", " ");
+        let completions = match location {
+            CompletionLocation::Command => self.command_completions(partial),
+            CompletionLocation::Flag(ref command) if !self.commands.has(command) => {
+                let words = current_element(&line[..replace_pos])
+                    .map(element_words)
+                    .unwrap_or_default();
+                pairs_matching(external_flags(&words), partial)
             }
-            if completion.replacement.contains("\\(") {
-                completion.replacement = completion.replacement.replace("\\(", "(");
+            CompletionLocation::Flag(command) => self.flag_completions(&command, partial),
+            CompletionLocation::Named(command, name) => {
+                match self.signature_completions(&command, &name, partial) {
+                    Some(completions) => completions,
+                    None => path_completions(partial, paths),
+                }
+            }
+            CompletionLocation::Positional(ref command, idx)
+                if COLUMN_COMMANDS.contains(&command.as_str())
+                    && (command != "where" || idx == 0) =>
+            {
+                match cursor_element(&line[..replace_pos]).and_then(|element| element.upstream) {
+                    Some(upstream) => {
+                        pairs_matching(upstream_columns(&self.commands, upstream), partial)
+                    }
+                    None => vec![],
+                }
             }
+            CompletionLocation::Positional(command, idx) => {
+                let name = self.commands.get_command(&command).and_then(|cmd| {
+                    cmd.signature()
+                        .positional
+                        .get(idx)
+                        .map(|p| p.name().to_string())
+                });
 
-            if completion.replacement.contains(" ") || completion.replacement.contains("(") {
-                if !completion.replacement.starts_with("\"") {
-                    completion.replacement = format!("\"{}", completion.replacement);
+                match name.and_then(|name| self.signature_completions(&command, &name, partial)) {
+                    Some(completions) => completions,
+                    None => path_completions(partial, paths),
                 }
-                if !completion.replacement.ends_with("\"") {
-                    completion.replacement = format!("{}\"", completion.replacement);
+            }
+            CompletionLocation::Unknown => {
+                let mut completions = path_completions(partial, paths);
+                completions.extend(self.command_completions(partial));
+                completions
+            }
+        };
+
+        Ok((replace_pos, completions))
+    }
+
+    fn command_completions(&self, partial: &str) -> Vec<rustyline::completion::Pair> {
+        pairs_matching(self.commands.names(), partial)
+    }
+
+    fn flag_completions(&self, command: &str, partial: &str) -> Vec<rustyline::completion::Pair> {
+        match self.commands.get_command(command) {
+            Some(cmd) => pairs_matching(
+                cmd.signature()
+                    .named
+                    .keys()
+                    .map(|name| format!("--{}", name))
+                    .collect(),
+                partial,
+            ),
+            None => vec![],
+        }
+    }
+
+    fn signature_completions(
+        &self,
+        command: &str,
+        name: &str,
+        partial: &str,
+    ) -> Option<Vec<rustyline::completion::Pair>> {
+        let signature = self.commands.get_command(command)?.signature();
+        let values = signature.completions.get(name)?;
+
+        Some(pairs_matching(values.clone(), partial))
+    }
+}
+
+fn pairs_matching(candidates: Vec<String>, partial: &str) -> Vec<rustyline::completion::Pair> {
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(partial))
+        .map(|candidate| rustyline::completion::Pair {
+            display: candidate.clone(),
+            replacement: candidate,
+        })
+        .collect()
+}
+
+fn path_completions(
+    partial: &str,
+    paths: &dyn Fn(&str) -> Vec<String>,
+) -> Vec<rustyline::completion::Pair> {
+    let unquoted = partial.trim_start_matches(|c| c == '"' || c == '\'');
+
+    paths(&unescape_bare(unquoted))
+        .into_iter()
+        .map(|path| rustyline::completion::Pair {
+            display: path.clone(),
+            replacement: quote_path(&path, partial != unquoted),
+        })
+        .collect()
+}
+
+/// Quote a completed path if it wouldn't survive the parser as a bare word
+fn quote_path(path: &str, already_quoted: bool) -> String {
+    let needs_quotes = already_quoted
+        || path
+            .chars()
+            .any(|c| c.is_whitespace() || "()[]{}|;#$\"'`".contains(c));
+
+    if !needs_quotes {
+        path.to_string()
+    } else if path.contains('"') {
+        format!("'{}'", path)
+    } else {
+        format!("\"{}\"", path)
+    }
+}
+
+/// Paths on disk that complete `partial`, relative to `cwd`. Directories get a trailing
+/// separator so completion can carry on into them.
+pub(crate) fn filesystem_completions(cwd: &str, partial: &str) -> Vec<String> {
+    let (dir_part, file_prefix) =
+        match partial.rfind(|c| c == '/' || c == std::path::MAIN_SEPARATOR) {
+            Some(idx) => (&partial[..=idx], &partial[idx + 1..]),
+            None => ("", partial),
+        };
+
+    let search_dir = if dir_part.starts_with("~/") {
+        match dirs::home_dir() {
+            Some(home) => home.join(&dir_part[1..].trim_start_matches('/')),
+            None => return vec![],
+        }
+    } else if Path::new(dir_part).is_absolute() {
+        PathBuf::from(dir_part)
+    } else {
+        Path::new(cwd).join(dir_part)
+    };
+
+    let entries = match std::fs::read_dir(&search_dir) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut completions: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            // Hidden files only show up once the user asks for them
+            if !name.starts_with(file_prefix) || (name.starts_with('.') && file_prefix.is_empty()) {
+                return None;
+            }
+
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            Some(format!(
+                "{}{}{}",
+                dir_part,
+                name,
+                if is_dir { "/" } else { "" }
+            ))
+        })
+        .collect();
+
+    completions.sort();
+    completions
+}
+
+/// The byte offset where the word ending at `pos` begins. A quote that hasn't been closed yet
+/// keeps the whitespace after it inside the word.
+pub(crate) fn word_start(line: &str, pos: usize) -> usize {
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (idx, c) in line[..pos].char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') if !escaped => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            // An escaped space, as in `My\ Documents`, is part of the word
+            (None, c) if c.is_whitespace() && !escaped => start = idx + c.len_utf8(),
+            _ => {}
+        }
+
+        escaped = c == '\\' && !escaped;
+    }
+
+    start
+}
+
+/// The pipeline element the cursor is in, as the parser sees the line up to the cursor
+struct CursorElement<'a> {
+    // The element so far, from the start of its command. Empty when a new element is starting.
+    text: &'a str,
+    // Its command and arguments, unless a new element is starting
+    call: Option<Tagged<CallNode>>,
+    // The elements before it in its pipeline
+    upstream: Option<&'a str>,
+}
+
+/// Find the element the cursor is in from the token tree of the line before it. A `(` or `{`
+/// left open starts a pipeline of its own, so in `where { $it.size > (sys | get ` the element
+/// is `get`, fed by `sys`. None inside a string, where the usual rules don't apply, or when the
+/// parser can't make the element out.
+fn cursor_element(prefix: &str) -> Option<CursorElement> {
+    let start = match unclosed_delimiter_at(prefix) {
+        None => 0,
+        Some(('(', at)) | Some(('{', at)) => at + 1,
+        Some(_) => return None,
+    };
+
+    let scope = prefix[start..].trim();
+    if scope.is_empty() || scope.ends_with('|') || scope.ends_with(';') {
+        return Some(CursorElement {
+            text: "",
+            call: None,
+            upstream: None,
+        });
+    }
+
+    let statements = statements_with_recovery(nom_input(prefix, uuid::Uuid::nil()).slice(start..));
+    let pipeline = match statements.as_statements().ok()?.pop()? {
+        TokenNode::Pipeline(pipeline) => pipeline,
+        _ => return None,
+    };
+
+    let part = pipeline.item.parts.last()?;
+    let upstream = part
+        .pipe
+        .map(|pipe| prefix[pipeline.tag.span.start..pipe.span.start].trim());
+
+    Some(CursorElement {
+        text: &prefix[part.call().tag.span.start..],
+        call: Some(part.call().clone()),
+        upstream,
+    })
+}
+
+fn current_element(prefix: &str) -> Option<&str> {
+    cursor_element(prefix).map(|element| element.text)
+}
+
+/// Commands whose arguments name columns of their input
+const COLUMN_COMMANDS: &[&str] = &["get", "pick", "reject", "sort-by", "where"];
+
+/// Commands that only read, and so are safe to run while the user is still typing
+const SPECULATABLE_COMMANDS: &[&str] = &[
+    "date",
+    "env",
+    "first",
+    "get",
+    "last",
+    "lines",
+    "ls",
+    "nth",
+    "open",
+    "pick",
+    "pivot",
+    "ps",
+    "reject",
+    "reverse",
+    "size",
+    "skip-while",
+    "sort-by",
+    "split-column",
+    "split-row",
+    "sys",
+    "tags",
+    "trim",
+    "version",
+    "where",
+];
+
+fn is_speculatable(name: &str) -> bool {
+    SPECULATABLE_COMMANDS.contains(&name) || name.starts_with("from-")
+}
+
+lazy_static! {
+    // Whether a speculative run is still going. Only one runs at a time, so a slow upstream
+    // pipeline and a user pressing Tab repeatedly don't pile up threads.
+    static ref SPECULATING: AtomicBool = AtomicBool::new(false);
+}
+
+/// The columns of the rows that `upstream` produces, found by running it in a scratch context.
+/// Gives up (returning nothing) if any stage might have side effects or it takes too long, in
+/// which case the run is cancelled as it would be by Ctrl-C.
+fn upstream_columns(registry: &CommandRegistry, upstream: &str) -> Vec<String> {
+    if SPECULATING.compare_and_swap(false, true, Ordering::SeqCst) {
+        return vec![];
+    }
+
+    let registry = registry.clone();
+    let upstream = upstream.to_string();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let cancelled = cancel.clone();
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    // The line editor is already running inside the main executor, so run on our own thread
+    std::thread::spawn(move || {
+        let columns = futures::executor::block_on(speculate_columns(registry, upstream, cancelled));
+        SPECULATING.store(false, Ordering::SeqCst);
+        let _ = tx.send(columns);
+    });
+
+    match rx.recv_timeout(Duration::from_millis(500)) {
+        Ok(columns) => columns,
+        Err(_) => {
+            cancel.store(true, Ordering::SeqCst);
+            vec![]
+        }
+    }
+}
+
+lazy_static! {
+    // Flags scraped from `--help` output, by command line. Running `--help` is slow enough that
+    // it should happen once per command a session.
+    static ref HELP_FLAGS: Mutex<HashMap<String, Vec<String>>> = Mutex::new(HashMap::new());
+}
+
+// Flags for an external command are read from what it prints for `--help`, which means running
+// it. Not every command takes `--help` to mean only that, so it's only done with
+// `help_flags = true` in the config.
+
+/// The settings completion reads from the config
+#[derive(Clone, Default)]
+struct CompletionConfig {
+    help_flags: bool,
+    // The `[completions]` section: shell commands to run for a command or flag's arguments
+    completers: Vec<(String, String)>,
+}
+
+lazy_static! {
+    // The settings, along with when the config file they came from was last changed. They're
+    // read again only once it changes, rather than on every Tab.
+    static ref COMPLETION_CONFIG: Mutex<Option<(Option<std::time::SystemTime>, CompletionConfig)>> =
+        Mutex::new(None);
+}
+
+fn completion_config() -> CompletionConfig {
+    let modified = crate::data::config::default_path()
+        .ok()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok());
+
+    let mut cached = COMPLETION_CONFIG.lock().unwrap();
+    if let Some((when, settings)) = &*cached {
+        if *when == modified {
+            return settings.clone();
+        }
+    }
+
+    let settings = match crate::data::config::config(Tag::unknown()) {
+        Ok(config) => CompletionConfig {
+            help_flags: config
+                .get("help_flags")
+                .map(|f| f.is_true())
+                .unwrap_or(false),
+            completers: match config.get("completions") {
+                Some(Tagged {
+                    item: Value::Row(completers),
+                    ..
+                }) => completers
+                    .entries
+                    .iter()
+                    .filter_map(|(key, completer)| Some((key.clone(), completer.as_string().ok()?)))
+                    .collect(),
+                _ => vec![],
+            },
+        },
+        Err(_) => CompletionConfig::default(),
+    };
+
+    *cached = Some((modified, settings.clone()));
+    settings
+}
+
+/// Flags for an external command, read from what it prints for `--help`. If the second word
+/// looks like a subcommand (`git checkout`, `cargo build`), its help is tried first.
+fn external_flags(words: &[&str]) -> Vec<String> {
+    let command = match words.first() {
+        Some(command) => command.trim_start_matches('^'),
+        None => return vec![],
+    };
+
+    if !completion_config().help_flags {
+        return vec![];
+    }
+
+    let mut attempts = vec![];
+    if let Some(subcommand) = words.get(1).filter(|word| !word.starts_with('-')) {
+        attempts.push(vec![command, *subcommand]);
+    }
+    attempts.push(vec![command]);
+
+    for attempt in attempts {
+        let key = attempt.join(" ");
+
+        if let Some(flags) = HELP_FLAGS.lock().unwrap().get(&key) {
+            if !flags.is_empty() {
+                return flags.clone();
+            }
+            continue;
+        }
+
+        let flags = help_flags(&attempt).unwrap_or_default();
+        HELP_FLAGS.lock().unwrap().insert(key, flags.clone());
+
+        if !flags.is_empty() {
+            return flags;
+        }
+    }
+
+    vec![]
+}
+
+fn help_flags(command: &[&str]) -> Option<Vec<String>> {
+    let mut help_command = std::process::Command::new(command[0]);
+    help_command
+        .args(&command[1..])
+        .arg("--help")
+        // Some commands hand `--help` to man, which shouldn't wait for a keypress
+        .env("PAGER", "cat")
+        .env("MANPAGER", "cat")
+        .env("GIT_PAGER", "cat");
+
+    // Plenty of commands print their help on stderr
+    let (stdout, stderr) = output_within(help_command, Duration::from_millis(1000))?;
+    let mut help = String::from_utf8_lossy(&stdout).to_string();
+    help.push_str(&String::from_utf8_lossy(&stderr));
+
+    Some(parse_help_flags(&help))
+}
+
+/// What a command writes to stdout and stderr. Completion blocks the prompt, so a command still
+/// running after `limit` is killed, along with anything it started, and gives None.
+fn output_within(
+    mut command: std::process::Command,
+    limit: Duration,
+) -> Option<(Vec<u8>, Vec<u8>)> {
+    command
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+
+    // A process group of its own, so a shell's children go when it does
+    #[cfg(unix)]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+
+        command.pre_exec(|| {
+            libc::setpgid(0, 0);
+            Ok(())
+        });
+    }
+
+    let mut child = command.spawn().ok()?;
+
+    let stdout = read_in_background(child.stdout.take()?);
+    let stderr = read_in_background(child.stderr.take()?);
+
+    let deadline = Instant::now() + limit;
+
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+            _ => {
+                #[cfg(unix)]
+                unsafe {
+                    libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
                 }
+
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
             }
         }
+    }
+
+    let wait = Duration::from_millis(100);
+    Some((
+        stdout.recv_timeout(wait).ok()?,
+        stderr.recv_timeout(wait).ok()?,
+    ))
+}
 
-        let line_chars: Vec<_> = line[..pos].chars().collect();
-        let mut replace_pos = line_chars.len();
-        while replace_pos > 0 {
-            if line_chars[replace_pos - 1] == ' ' {
-                break;
+/// Read all of `pipe` on a thread of its own, so a command writing more than a pipe holds can
+/// finish while it's waited for
+fn read_in_background(
+    mut pipe: impl std::io::Read + Send + 'static,
+) -> std::sync::mpsc::Receiver<Vec<u8>> {
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let mut contents = vec![];
+        let _ = pipe.read_to_end(&mut contents);
+        let _ = tx.send(contents);
+    });
+
+    rx
+}
+
+/// The flags listed in help text. Options are taken from lines that start with a flag, up to
+/// the gap before the description, so `-v, --verbose <LEVEL>  Be noisy` gives `-v` and
+/// `--verbose`.
+fn parse_help_flags(help: &str) -> Vec<String> {
+    let mut flags = vec![];
+
+    for line in help.lines() {
+        let line = line.trim_start();
+        if !line.starts_with('-') {
+            continue;
+        }
+
+        let options = match line.find("  ") {
+            Some(idx) => &line[..idx],
+            None => line,
+        };
+
+        for word in options.split(|c: char| c.is_whitespace() || c == ',') {
+            let flag: String = word
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                .collect();
+
+            let is_flag = flag.starts_with('-')
+                && flag
+                    .trim_start_matches('-')
+                    .chars()
+                    .next()
+                    .map(|c| c.is_alphanumeric())
+                    .unwrap_or(false);
+
+            if is_flag && !flags.contains(&flag) {
+                flags.push(flag);
             }
-            replace_pos -= 1;
         }
+    }
 
-        for command in commands.iter() {
-            let mut pos = replace_pos;
-            let mut matched = true;
-            if pos < line_chars.len() {
-                for chr in command.chars() {
-                    if line_chars[pos] != chr {
-                        matched = false;
-                        break;
-                    }
-                    pos += 1;
-                    if pos == line_chars.len() {
-                        break;
-                    }
+    flags
+}
+
+// Custom completions are read from the config file. Each key is a command (optionally followed
+// by one of its flags) and each value is a shell command whose output lines are the candidates:
+//
+//   [completions]
+//   "git checkout" = "git branch --format='%(refname:short)'"
+//   "docker run --network" = "docker network ls --format '{{.Name}}'"
+//
+// The completer runs with NU_COMPLETE_LINE and NU_COMPLETE_WORD set to the line being edited
+// and the word under the cursor.
+
+/// Candidates from the most specific configured completer that applies to the word after
+/// `prefix`, or None to fall back to the built-in completions
+fn custom_completions(
+    line: &str,
+    prefix: &str,
+    partial: &str,
+) -> Option<Vec<rustyline::completion::Pair>> {
+    let completers = completion_config().completers;
+    let words = element_words(current_element(prefix)?);
+
+    let (_, completer) = completers
+        .iter()
+        .filter(|(key, _)| completer_applies(key, &words))
+        .max_by_key(|(key, _)| key.split_whitespace().count())?;
+
+    let mut command = if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = std::process::Command::new("sh");
+        command.arg("-c");
+        command
+    };
+
+    command
+        .arg(completer)
+        .env("NU_COMPLETE_LINE", line)
+        .env("NU_COMPLETE_WORD", partial);
+
+    let (stdout, _) = output_within(command, Duration::from_millis(1000))?;
+
+    let candidates = String::from_utf8_lossy(&stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    Some(pairs_matching(candidates, partial))
+}
+
+/// Whether a `[completions]` key covers the next word of a pipeline element that has `words`
+/// typed so far. Command keys apply anywhere in the command's arguments; flag keys only to the
+/// word right after the flag.
+fn completer_applies(key: &str, words: &[&str]) -> bool {
+    let key: Vec<&str> = key.split_whitespace().collect();
+
+    match key.split_last() {
+        Some((flag, command)) if flag.starts_with('-') => {
+            words.len() > command.len() && words.starts_with(command) && words.last() == Some(flag)
+        }
+        Some(_) => words.len() >= key.len() && words.starts_with(&key),
+        None => false,
+    }
+}
+
+/// The whitespace-separated words of a pipeline element, with quoted words kept whole
+fn element_words(element: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut start = None;
+    let mut quote = None;
+
+    for (idx, c) in element.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => {
+                quote = Some(c);
+                start.get_or_insert(idx);
+            }
+            (Some(q), c) if q == c => quote = None,
+            (None, c) if c.is_whitespace() => {
+                if let Some(s) = start.take() {
+                    words.push(&element[s..idx]);
                 }
             }
+            _ => {
+                start.get_or_insert(idx);
+            }
+        }
+    }
 
-            if matched {
-                completions.push(rustyline::completion::Pair {
-                    display: command.clone(),
-                    replacement: command.clone(),
-                });
+    if let Some(s) = start {
+        words.push(&element[s..]);
+    }
+
+    words
+}
+
+async fn speculate_columns(
+    registry: CommandRegistry,
+    upstream: String,
+    cancel: Arc<AtomicBool>,
+) -> Vec<String> {
+    let mut context = match Context::with_registry(registry) {
+        Ok(context) => context,
+        Err(_) => return vec![],
+    };
+    context.ctrl_c = cancel.clone();
+
+    let source = Text::from(upstream.clone());
+
+    let pipeline = match crate::parser::parse(&upstream, uuid::Uuid::nil())
+        .and_then(|tokens| classify_pipeline(&tokens, &context, &source))
+    {
+        Ok(pipeline) => pipeline,
+        Err(_) => return vec![],
+    };
+
+    let mut input = ClassifiedInputStream::new();
+    let mut is_first_command = true;
+
+    for command in pipeline.commands {
+        if cancel.load(Ordering::SeqCst) {
+            return vec![];
+        }
+
+        match command {
+            // A subexpression in the arguments would run whatever pipeline it holds
+            ClassifiedCommand::Internal(command)
+                if is_speculatable(command.command.name())
+                    && command.args.subexpressions().is_empty() =>
+            {
+                input = match command
+                    .run(&mut context, input, source.clone(), is_first_command)
+                    .await
+                {
+                    Ok(output) => ClassifiedInputStream::from_input_stream(output),
+                    Err(_) => return vec![],
+                };
             }
+            _ => return vec![],
         }
 
-        Ok((replace_pos, completions))
+        is_first_command = false;
+    }
+
+    // Stop pulling rows once the completer has stopped waiting for them
+    let rows: Vec<Tagged<Value>> = input
+        .objects
+        .values
+        .take(100)
+        .take_while(move |_| futures::future::ready(!cancel.load(Ordering::SeqCst)))
+        .collect()
+        .await;
+
+    let mut columns = vec![];
+    for row in rows {
+        for desc in row.data_descriptors() {
+            if !columns.contains(&desc) {
+                columns.push(desc);
+            }
+        }
+    }
+
+    columns
+}
+
+/// Work out what kind of word `partial` is, given everything on the line before it
+pub(crate) fn completion_location(
+    registry: &CommandRegistry,
+    prefix: &str,
+    partial: &str,
+) -> CompletionLocation {
+    let call = match cursor_element(prefix) {
+        Some(CursorElement {
+            call: Some(call), ..
+        }) => call,
+        Some(CursorElement { call: None, .. }) => return CompletionLocation::Command,
+        None => return CompletionLocation::Unknown,
+    };
+
+    let command = call.head().tag().slice(prefix).to_string();
+
+    if partial.starts_with('-') {
+        return CompletionLocation::Flag(command);
+    }
+
+    let signature = registry.get_command(&command).map(|cmd| cmd.signature());
+    let takes_value = |flag: &str| match signature.as_ref().and_then(|s| s.named.get(flag)) {
+        Some(NamedType::Switch) | None => false,
+        Some(_) => true,
+    };
+
+    let mut positional = 0;
+    let mut pending_flag: Option<String> = None;
+
+    if let Some(children) = call.children() {
+        for child in children {
+            match child {
+                TokenNode::Whitespace(_) => continue,
+                TokenNode::Flag(flag) => {
+                    let name = flag.name().slice(prefix);
+                    pending_flag = match flag.kind() {
+                        FlagKind::Longhand if takes_value(name) => Some(name.to_string()),
+                        _ => None,
+                    };
+                    continue;
+                }
+                _ => {}
+            }
+
+            if pending_flag.take().is_none() {
+                positional += 1;
+            }
+        }
+    }
+
+    match pending_flag {
+        Some(flag) => CompletionLocation::Named(command, flag),
+        None => CompletionLocation::Positional(command, positional),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{completion_location, cursor_element, CompletionLocation, NuCompleter};
+    use crate::commands::{
+        per_item_command, whole_stream_command, FromTOML, Get, HashCommand, Open, Post, LS,
+    };
+    use crate::context::CommandRegistry;
+
+    fn registry() -> CommandRegistry {
+        let registry = CommandRegistry::new();
+
+        registry.add_command(whole_stream_command(LS));
+        registry.add_command(whole_stream_command(Get));
+        registry.add_command(whole_stream_command(FromTOML));
+        registry.add_command(whole_stream_command(HashCommand));
+        registry.add_command(per_item_command(Open));
+        registry.add_command(per_item_command(Post));
+
+        registry
+    }
+
+    fn replacements(line: &str, paths: &dyn Fn(&str) -> Vec<String>) -> Vec<String> {
+        let (_, pairs) = NuCompleter::new(registry())
+            .complete(line, line.len(), paths)
+            .unwrap();
+
+        pairs.into_iter().map(|pair| pair.replacement).collect()
+    }
+
+    #[test]
+    fn completes_commands_at_the_head_of_an_element() {
+        let registry = registry();
+
+        assert_eq!(
+            completion_location(&registry, "", "l"),
+            CompletionLocation::Command
+        );
+        assert_eq!(
+            completion_location(&registry, "ls | ", "g"),
+            CompletionLocation::Command
+        );
+
+        assert_eq!(
+            replacements("ls | g", &|_: &str| vec![]),
+            vec!["get".to_string()]
+        );
+    }
+
+    #[test]
+    fn completes_flags_from_the_signature() {
+        let registry = registry();
+
+        assert_eq!(
+            completion_location(&registry, "post ", "--u"),
+            CompletionLocation::Flag("post".to_string())
+        );
+        assert_eq!(
+            completion_location(&registry, "post --user ", ""),
+            CompletionLocation::Named("post".to_string(), "user".to_string())
+        );
+        assert_eq!(
+            completion_location(&registry, "post --raw ", ""),
+            CompletionLocation::Positional("post".to_string(), 0)
+        );
+
+        assert_eq!(
+            replacements("post --u", &|_: &str| vec![]),
+            vec!["--user".to_string()]
+        );
+    }
+
+    #[test]
+    fn completes_the_values_an_argument_can_take() {
+        assert_eq!(
+            replacements("hash s", &|_: &str| vec!["src/".to_string()]),
+            vec!["sha256".to_string()]
+        );
+    }
+
+    #[test]
+    fn completes_paths_for_arguments() {
+        let paths = |partial: &str| {
+            vec!["My Documents/", "Music/"]
+                .into_iter()
+                .filter(|path| path.starts_with(partial))
+                .map(String::from)
+                .collect()
+        };
+
+        assert_eq!(
+            replacements("ls M", &paths),
+            vec!["\"My Documents/\"".to_string(), "Music/".to_string()]
+        );
+        assert_eq!(
+            replacements("ls My\\ D", &paths),
+            vec!["\"My Documents/\"".to_string()]
+        );
+    }
+
+    #[test]
+    fn completes_columns_from_the_upstream_pipeline() {
+        assert_eq!(
+            completion_location(&registry(), "ls | get ", "na"),
+            CompletionLocation::Positional("get".to_string(), 0)
+        );
+
+        assert_eq!(
+            replacements(
+                "open tests/fixtures/formats/cargo_sample.toml | get pa",
+                &|_: &str| vec![]
+            ),
+            vec!["package".to_string()]
+        );
+    }
+
+    #[test]
+    fn an_open_bracket_starts_a_pipeline_of_its_own() {
+        let element = cursor_element("where { $it.size > (sys | get ").unwrap();
+        assert_eq!(element.text, "get ");
+        assert_eq!(element.upstream, Some("sys"));
+
+        let element = cursor_element("ls | where { ").unwrap();
+        assert_eq!(element.text, "");
+        assert_eq!(element.upstream, None);
+
+        assert!(cursor_element("echo \"ls | ").is_none());
     }
 }
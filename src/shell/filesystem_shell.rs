@@ -6,10 +6,9 @@ use crate::commands::rm::RemoveArgs;
 use crate::context::SourceMap;
 use crate::data::dir_entry_dict;
 use crate::prelude::*;
-use crate::shell::completer::NuCompleter;
+use crate::shell::completer::{filesystem_completions, NuCompleter};
 use crate::shell::shell::Shell;
 use crate::utils::FileStructure;
-use rustyline::completion::FilenameCompleter;
 use rustyline::hint::{Hinter, HistoryHinter};
 use std::path::{Path, PathBuf};
 
@@ -31,10 +30,7 @@ impl Clone for FilesystemShell {
         FilesystemShell {
             path: self.path.clone(),
             last_path: self.path.clone(),
-            completer: NuCompleter {
-                file_completer: FilenameCompleter::new(),
-                commands: self.completer.commands.clone(),
-            },
+            completer: self.completer.clone(),
             hinter: HistoryHinter {},
         }
     }
@@ -47,10 +43,7 @@ impl FilesystemShell {
         Ok(FilesystemShell {
             path: path.to_string_lossy().to_string(),
             last_path: path.to_string_lossy().to_string(),
-            completer: NuCompleter {
-                file_completer: FilenameCompleter::new(),
-                commands,
-            },
+            completer: NuCompleter::new(commands),
             hinter: HistoryHinter {},
         })
     }
@@ -63,10 +56,7 @@ impl FilesystemShell {
         Ok(FilesystemShell {
             path,
             last_path,
-            completer: NuCompleter {
-                file_completer: FilenameCompleter::new(),
-                commands,
-            },
+            completer: NuCompleter::new(commands),
             hinter: HistoryHinter {},
         })
     }
@@ -197,6 +187,18 @@ impl Shell for FilesystemShell {
 
                     match dunce::canonicalize(path.join(&target)) {
                         Ok(p) => p,
+                        Err(_) if fuzzy_cd_enabled() => {
+                            match pick_directory(fuzzy_directories(&path, &target)) {
+                                Ok(dir) => dir,
+                                Err(label) => {
+                                    return Err(ShellError::labeled_error(
+                                        "Can not change to directory",
+                                        label,
+                                        v.tag().clone(),
+                                    ))
+                                }
+                            }
+                        }
                         Err(_) => {
                             return Err(ShellError::labeled_error(
                                 "Can not change to directory",
@@ -991,12 +993,105 @@ impl Shell for FilesystemShell {
         &self,
         line: &str,
         pos: usize,
-        ctx: &rustyline::Context<'_>,
+        _ctx: &rustyline::Context<'_>,
     ) -> Result<(usize, Vec<rustyline::completion::Pair>), rustyline::error::ReadlineError> {
-        self.completer.complete(line, pos, ctx)
+        self.completer.complete(line, pos, &|partial| {
+            filesystem_completions(&self.path, partial)
+        })
     }
 
     fn hint(&self, line: &str, pos: usize, ctx: &rustyline::Context<'_>) -> Option<String> {
         self.hinter.hint(line, pos, ctx)
     }
 }
+
+// With `cd_fuzzy = true` in the config, a `cd` to a directory that doesn't exist looks for one
+// with a close enough name in the same place: the same name in a different case, then names
+// it's the start of, then names a typo or two away.
+
+fn fuzzy_cd_enabled() -> bool {
+    crate::data::config::config(Tag::unknown())
+        .ok()
+        .and_then(|config| config.get("cd_fuzzy").map(|f| f.is_true()))
+        .unwrap_or(false)
+}
+
+/// Directories whose names are close to the last part of `target`, keeping only the closest
+/// kind of match there is
+fn fuzzy_directories(cwd: &Path, target: &Path) -> Vec<PathBuf> {
+    let full = cwd.join(target);
+
+    let wanted = match full.file_name() {
+        Some(name) => name.to_string_lossy().to_lowercase(),
+        None => return vec![],
+    };
+
+    let parent = match full.parent().map(dunce::canonicalize) {
+        Some(Ok(parent)) => parent,
+        _ => return vec![],
+    };
+
+    let dirs: Vec<(String, PathBuf)> = match std::fs::read_dir(&parent) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| {
+                (
+                    entry.file_name().to_string_lossy().to_lowercase(),
+                    entry.path(),
+                )
+            })
+            .collect(),
+        Err(_) => return vec![],
+    };
+
+    let same_but_case = |name: &str| name == wanted;
+    let starts_with = |name: &str| name.starts_with(&wanted);
+    let typo = |name: &str| {
+        let distance = natural::distance::levenshtein_distance(name, &wanted);
+        distance <= 2 && distance * 2 < wanted.len()
+    };
+    let kinds: [&dyn Fn(&str) -> bool; 3] = [&same_but_case, &starts_with, &typo];
+
+    for matches in kinds.iter() {
+        let mut found: Vec<PathBuf> = dirs
+            .iter()
+            .filter(|(name, _)| matches(name.as_str()))
+            .map(|(_, path)| path.clone())
+            .collect();
+
+        if !found.is_empty() {
+            found.sort();
+            return found;
+        }
+    }
+
+    vec![]
+}
+
+/// The one directory to go to, asking the user to choose if there's more than one. The error
+/// is a label saying why there isn't one.
+fn pick_directory(candidates: Vec<PathBuf>) -> Result<PathBuf, &'static str> {
+    match candidates.len() {
+        0 => Err("directory not found"),
+        1 => Ok(candidates[0].clone()),
+        _ => {
+            let names: Vec<String> = candidates
+                .iter()
+                .map(|dir| dir.to_string_lossy().to_string())
+                .collect();
+            let lines = names.iter().map(|name| name.as_str()).collect();
+
+            match crate::fuzzysearch::interactive_fuzzy_search(&lines, 5) {
+                crate::fuzzysearch::SelectionResult::Selected(choice)
+                | crate::fuzzysearch::SelectionResult::Edit(choice) => candidates
+                    .into_iter()
+                    .find(|dir| dir.to_string_lossy() == choice)
+                    .ok_or("directory not found"),
+                crate::fuzzysearch::SelectionResult::NoSelection => {
+                    Err("more than one directory matches")
+                }
+            }
+        }
+    }
+}
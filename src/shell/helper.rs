@@ -1,23 +1,66 @@
-use crate::parser::nom_input;
+use crate::context::CommandRegistry;
+use crate::ide::{highlight_tree, HighlightKind};
 use crate::parser::parse::token_tree::TokenNode;
-use crate::parser::parse::tokens::RawToken;
-use crate::parser::{Pipeline, PipelineElement};
+use crate::parser::{parse_with_recovery, reparse, Edit};
 use crate::shell::shell_manager::ShellManager;
-use crate::Tagged;
+use crate::Tag;
 use ansi_term::Color;
 use rustyline::completion::Completer;
 use rustyline::error::ReadlineError;
 use rustyline::highlight::Highlighter;
 use rustyline::hint::Hinter;
 use std::borrow::Cow::{self, Owned};
+use std::cell::RefCell;
 
 pub(crate) struct Helper {
     helper: ShellManager,
+    registry: CommandRegistry,
+    autosuggest: bool,
+    // Whether the prompt ends with the mark of where the command line starts
+    terminal_integration: bool,
+    // The line last highlighted and its tree, so the next keystroke only reparses what it changed
+    last_parse: RefCell<Option<(String, TokenNode)>>,
 }
 
 impl Helper {
-    pub(crate) fn new(helper: ShellManager) -> Helper {
-        Helper { helper }
+    pub(crate) fn new(
+        helper: ShellManager,
+        registry: CommandRegistry,
+        terminal_integration: bool,
+    ) -> Helper {
+        // Suggestions from history are on unless `autosuggest = false` is set in the config. The
+        // right arrow at the end of the line takes the one shown, as does any key bound to
+        // `complete-hint`.
+        let autosuggest = match crate::data::config::config(Tag::unknown()) {
+            Ok(config) => config
+                .get("autosuggest")
+                .map(|v| v.is_true())
+                .unwrap_or(true),
+            Err(_) => true,
+        };
+
+        Helper {
+            helper,
+            registry,
+            autosuggest,
+            terminal_integration,
+            last_parse: RefCell::new(None),
+        }
+    }
+
+    fn parse(&self, line: &str) -> TokenNode {
+        let mut last_parse = self.last_parse.borrow_mut();
+
+        let tree = match last_parse.take() {
+            Some((previous_line, previous)) => {
+                let edit = Edit::between(&previous_line, line);
+                reparse(&previous, &edit, line, uuid::Uuid::nil())
+            }
+            None => parse_with_recovery(line, uuid::Uuid::nil()),
+        };
+
+        *last_parse = Some((line.to_string(), tree.clone()));
+        tree
     }
 }
 
@@ -52,51 +95,53 @@ impl Completer for Helper {
 
 impl Hinter for Helper {
     fn hint(&self, line: &str, pos: usize, ctx: &rustyline::Context<'_>) -> Option<String> {
+        if !self.autosuggest {
+            return None;
+        }
+
         self.helper.hint(line, pos, ctx)
     }
 }
 
 impl Highlighter for Helper {
     fn highlight_prompt<'b, 's: 'b, 'p: 'b>(&'s self, prompt: &'p str, _: bool) -> Cow<'b, str> {
-        Owned("\x1b[32m".to_owned() + &prompt[0..prompt.len() - 2] + "\x1b[m> ")
+        let mut highlighted = if prompt.ends_with("> ") {
+            "\x1b[32m".to_owned() + &prompt[0..prompt.len() - 2] + "\x1b[m> "
+        } else {
+            // Continuation lines
+            "\x1b[2m".to_owned() + prompt + "\x1b[m"
+        };
+
+        // The line editor works out how wide the prompt is before it's highlighted, so the mark
+        // added here doesn't take up room
+        if self.terminal_integration && prompt != "::: " {
+            highlighted.push_str(crate::env::terminal_integration::PROMPT_END);
+        }
+
+        Owned(highlighted)
     }
 
     fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
-        Owned("\x1b[1m".to_owned() + hint + "\x1b[m")
+        Owned("\x1b[2m".to_owned() + hint + "\x1b[m")
     }
 
     fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
-        let tokens = crate::parser::pipeline(nom_input(line, uuid::Uuid::nil()));
-
-        match tokens {
-            Err(_) => Cow::Borrowed(line),
-            Ok((_rest, v)) => {
-                let mut out = String::new();
-                let pipeline = match v.as_pipeline() {
-                    Err(_) => return Cow::Borrowed(line),
-                    Ok(v) => v,
-                };
-
-                let Pipeline { parts, post_ws } = pipeline;
-                let mut iter = parts.into_iter();
-
-                loop {
-                    match iter.next() {
-                        None => {
-                            if let Some(ws) = post_ws {
-                                out.push_str(ws.slice(line));
-                            }
-
-                            return Cow::Owned(out);
-                        }
-                        Some(token) => {
-                            let styled = paint_pipeline_element(&token, line);
-                            out.push_str(&styled.to_string());
-                        }
-                    }
-                }
+        let tree = self.parse(line);
+        let mut out = String::new();
+        let mut painted = 0;
+
+        for (span, kind) in highlight_tree(&tree, line, &self.registry) {
+            if span.start < painted {
+                continue;
             }
+
+            out.push_str(&line[painted..span.start]);
+            out.push_str(&paint(kind, span.slice(line)));
+            painted = span.end;
         }
+
+        out.push_str(&line[painted..]);
+        Cow::Owned(out)
     }
 
     fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
@@ -104,83 +149,27 @@ impl Highlighter for Helper {
     }
 }
 
-fn paint_token_node(token_node: &TokenNode, line: &str) -> String {
-    let styled = match token_node {
-        TokenNode::Call(..) => Color::Cyan.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Whitespace(..) => Color::White.normal().paint(token_node.tag().slice(line)),
-        TokenNode::Flag(..) => Color::Black.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Member(..) => Color::Yellow.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Path(..) => Color::Green.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Error(..) => Color::Red.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Delimited(..) => Color::White.paint(token_node.tag().slice(line)),
-        TokenNode::Operator(..) => Color::White.normal().paint(token_node.tag().slice(line)),
-        TokenNode::Pipeline(..) => Color::Blue.normal().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::Number(..),
-            ..
-        }) => Color::Purple.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::Size(..),
-            ..
-        }) => Color::Purple.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::GlobPattern,
-            ..
-        }) => Color::Cyan.normal().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::String(..),
-            ..
-        }) => Color::Green.normal().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::Variable(..),
-            ..
-        }) => Color::Yellow.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::Bare,
-            ..
-        }) => Color::Green.normal().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::ExternalCommand(..),
-            ..
-        }) => Color::Cyan.bold().paint(token_node.tag().slice(line)),
-        TokenNode::Token(Tagged {
-            item: RawToken::ExternalWord,
-            ..
-        }) => Color::Black.bold().paint(token_node.tag().slice(line)),
+fn paint(kind: HighlightKind, text: &str) -> String {
+    let style = match kind {
+        HighlightKind::InternalCommand => Color::Cyan.bold(),
+        HighlightKind::ExternalCommand => Color::Cyan.normal(),
+        HighlightKind::Flag => Color::Black.bold(),
+        HighlightKind::String => Color::Green.normal(),
+        HighlightKind::Number => Color::Purple.bold(),
+        HighlightKind::Literal => Color::Purple.bold(),
+        HighlightKind::Variable => Color::Yellow.bold(),
+        HighlightKind::Member => Color::Yellow.bold(),
+        HighlightKind::Operator => Color::White.normal(),
+        HighlightKind::Pipe => Color::Purple.normal(),
+        HighlightKind::Bare => Color::Green.normal(),
+        HighlightKind::Comment => Color::Black.bold(),
+        HighlightKind::Error => Color::Red.bold(),
     };
 
-    styled.to_string()
+    style.paint(text).to_string()
 }
 
-fn paint_pipeline_element(pipeline_element: &PipelineElement, line: &str) -> String {
-    let mut styled = String::new();
-
-    if let Some(_) = pipeline_element.pipe {
-        styled.push_str(&Color::Purple.paint("|"));
-    }
-
-    if let Some(ws) = pipeline_element.pre_ws {
-        styled.push_str(&Color::White.normal().paint(ws.slice(line)));
-    }
-
-    styled.push_str(
-        &Color::Cyan
-            .bold()
-            .paint(pipeline_element.call().head().tag().slice(line))
-            .to_string(),
-    );
-
-    if let Some(children) = pipeline_element.call().children() {
-        for child in children {
-            styled.push_str(&paint_token_node(child, line));
-        }
-    }
-
-    if let Some(ws) = pipeline_element.post_ws {
-        styled.push_str(&Color::White.normal().paint(ws.slice(line)));
-    }
-
-    styled.to_string()
-}
+// Lines that carry on to the next are joined by the REPL loop, so every line is taken as it is
+impl rustyline::validate::Validator for Helper {}
 
 impl rustyline::Helper for Helper {}
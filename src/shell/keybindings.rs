@@ -0,0 +1,131 @@
+use crate::prelude::*;
+use rustyline::{At, Cmd, KeyPress, Movement, Word};
+
+// Key bindings are read from the config file, mapping a key to one of the line editor's
+// actions. They apply on top of the bindings for the current edit_mode:
+//
+//   edit_mode = "vi"
+//
+//   [keybindings]
+//   ctrl-a = "beginning-of-line"
+//   ctrl-e = "end-of-line"
+//   alt-f = "forward-word"
+//   f2 = "clear-screen"
+
+/// The configured key bindings, with an error message for each one that couldn't be understood
+pub(crate) fn keybindings() -> Vec<Result<(KeyPress, Cmd), String>> {
+    let config = match crate::data::config::config(Tag::unknown()) {
+        Ok(config) => config,
+        Err(_) => return vec![],
+    };
+
+    match config.get("keybindings") {
+        Some(Tagged {
+            item: Value::Row(bindings),
+            ..
+        }) => bindings
+            .entries
+            .iter()
+            .map(|(key, action)| {
+                let action = action
+                    .as_string()
+                    .map_err(|_| format!("the action for '{}' should be a string", key))?;
+
+                match (key_press(key), command(&action)) {
+                    (Some(key), Some(action)) => Ok((key, action)),
+                    (None, _) => Err(format!("'{}' is not a key nu knows about", key)),
+                    (_, None) => Err(format!("'{}' is not an editor action", action)),
+                }
+            })
+            .collect(),
+        Some(_) => vec![Err("keybindings should be a table".to_string())],
+        None => vec![],
+    }
+}
+
+fn key_press(name: &str) -> Option<KeyPress> {
+    let name = name.to_lowercase();
+
+    let single_char = |s: &str| {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    };
+
+    if name.starts_with("ctrl-") {
+        return single_char(&name[5..]).map(|c| KeyPress::Ctrl(c.to_ascii_uppercase()));
+    }
+
+    if name.starts_with("alt-") {
+        return single_char(&name[4..]).map(KeyPress::Meta);
+    }
+
+    if name.starts_with('f') && name.len() > 1 {
+        if let Ok(n) = name[1..].parse() {
+            return Some(KeyPress::F(n));
+        }
+    }
+
+    Some(match name.as_str() {
+        "backspace" => KeyPress::Backspace,
+        "backtab" | "shift-tab" => KeyPress::BackTab,
+        "delete" => KeyPress::Delete,
+        "down" => KeyPress::Down,
+        "end" => KeyPress::End,
+        "enter" => KeyPress::Enter,
+        "esc" | "escape" => KeyPress::Esc,
+        "home" => KeyPress::Home,
+        "insert" => KeyPress::Insert,
+        "left" => KeyPress::Left,
+        "pagedown" => KeyPress::PageDown,
+        "pageup" => KeyPress::PageUp,
+        "right" => KeyPress::Right,
+        "tab" => KeyPress::Tab,
+        "up" => KeyPress::Up,
+        other => KeyPress::Char(single_char(other)?),
+    })
+}
+
+fn command(action: &str) -> Option<Cmd> {
+    Some(match action {
+        "accept-line" => Cmd::AcceptLine,
+        "backward-char" => Cmd::Move(Movement::BackwardChar(1)),
+        "backward-delete-char" => Cmd::Kill(Movement::BackwardChar(1)),
+        "backward-kill-word" => Cmd::Kill(Movement::BackwardWord(1, Word::Emacs)),
+        "backward-word" => Cmd::Move(Movement::BackwardWord(1, Word::Emacs)),
+        "beginning-of-history" => Cmd::BeginningOfHistory,
+        "beginning-of-line" => Cmd::Move(Movement::BeginningOfLine),
+        "capitalize-word" => Cmd::CapitalizeWord,
+        "clear-screen" => Cmd::ClearScreen,
+        "complete" => Cmd::Complete,
+        "complete-hint" => Cmd::CompleteHint,
+        "delete-char" => Cmd::Kill(Movement::ForwardChar(1)),
+        "downcase-word" => Cmd::DowncaseWord,
+        "end-of-history" => Cmd::EndOfHistory,
+        "end-of-line" => Cmd::Move(Movement::EndOfLine),
+        "forward-char" => Cmd::Move(Movement::ForwardChar(1)),
+        "forward-search-history" => Cmd::ForwardSearchHistory,
+        "forward-word" => Cmd::Move(Movement::ForwardWord(1, At::AfterEnd, Word::Emacs)),
+        "history-search-backward" => Cmd::HistorySearchBackward,
+        "history-search-forward" => Cmd::HistorySearchForward,
+        "interrupt" => Cmd::Interrupt,
+        "kill-line" => Cmd::Kill(Movement::EndOfLine),
+        "kill-whole-line" => Cmd::Kill(Movement::WholeLine),
+        "kill-word" => Cmd::Kill(Movement::ForwardWord(1, At::AfterEnd, Word::Emacs)),
+        "next-history" => Cmd::NextHistory,
+        "noop" => Cmd::Noop,
+        "previous-history" => Cmd::PreviousHistory,
+        "quoted-insert" => Cmd::QuotedInsert,
+        "reverse-search-history" => Cmd::ReverseSearchHistory,
+        "transpose-chars" => Cmd::TransposeChars,
+        "transpose-words" => Cmd::TransposeWords(1),
+        "undo" => Cmd::Undo(1),
+        "unix-line-discard" => Cmd::Kill(Movement::BeginningOfLine),
+        "upcase-word" => Cmd::UpcaseWord,
+        "yank" => Cmd::Yank(1, rustyline::Anchor::Before),
+        "yank-pop" => Cmd::YankPop,
+        _ => return None,
+    })
+}
@@ -3,19 +3,35 @@ use crate::commands::cp::CopyArgs;
 use crate::commands::mkdir::MkdirArgs;
 use crate::commands::mv::MoveArgs;
 use crate::commands::rm::RemoveArgs;
+use crate::env::DirectoryEnv;
 use crate::errors::ShellError;
 use crate::prelude::*;
 use crate::shell::filesystem_shell::FilesystemShell;
 use crate::shell::shell::Shell;
 use crate::stream::OutputStream;
+use indexmap::IndexMap;
 use std::error::Error;
+use std::ffi::OsString;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+/// The environment that belongs to one shell in the ring. It's applied to the process while
+/// that shell is the current one, and taken back off when another shell takes over.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ShellEnv {
+    pub(crate) vars: IndexMap<String, String>,
+    pub(crate) config: IndexMap<String, Tagged<Value>>,
+    pub(crate) directory_env: DirectoryEnv,
+}
+
 #[derive(Clone, Debug)]
 pub struct ShellManager {
     pub(crate) current_shell: usize,
     pub(crate) shells: Arc<Mutex<Vec<Box<dyn Shell + Send>>>>,
+    // One for each shell, in the same order
+    pub(crate) envs: Arc<Mutex<Vec<ShellEnv>>>,
+    // What each variable a shell has set was before any shell set it
+    base_env: Arc<Mutex<IndexMap<String, Option<OsString>>>>,
 }
 
 impl ShellManager {
@@ -25,21 +41,28 @@ impl ShellManager {
             shells: Arc::new(Mutex::new(vec![Box::new(FilesystemShell::basic(
                 commands,
             )?)])),
+            envs: Arc::new(Mutex::new(vec![ShellEnv::default()])),
+            base_env: Arc::new(Mutex::new(IndexMap::new())),
         })
     }
 
     pub fn insert_at_current(&mut self, shell: Box<dyn Shell + Send>) {
+        self.unapply_env();
         self.shells.lock().unwrap().push(shell);
+        self.envs.lock().unwrap().push(ShellEnv::default());
         self.current_shell = self.shells.lock().unwrap().len() - 1;
         self.set_path(self.path());
     }
 
     pub fn remove_at_current(&mut self) {
+        self.unapply_env();
         {
             let mut shells = self.shells.lock().unwrap();
+            let mut envs = self.envs.lock().unwrap();
             if shells.len() > 0 {
                 if self.current_shell == shells.len() - 1 {
                     shells.pop();
+                    envs.pop();
                     let new_len = shells.len();
                     if new_len > 0 {
                         self.current_shell = new_len - 1;
@@ -48,9 +71,11 @@ impl ShellManager {
                     }
                 } else {
                     shells.remove(self.current_shell);
+                    envs.remove(self.current_shell);
                 }
             }
         }
+        self.apply_env();
         self.set_path(self.path());
     }
 
@@ -86,6 +111,7 @@ impl ShellManager {
     }
 
     pub fn next(&mut self) {
+        self.unapply_env();
         {
             let shell_len = self.shells.lock().unwrap().len();
             if self.current_shell == (shell_len - 1) {
@@ -94,10 +120,12 @@ impl ShellManager {
                 self.current_shell += 1;
             }
         }
+        self.apply_env();
         self.set_path(self.path());
     }
 
     pub fn prev(&mut self) {
+        self.unapply_env();
         {
             let shell_len = self.shells.lock().unwrap().len();
             if self.current_shell == 0 {
@@ -106,9 +134,76 @@ impl ShellManager {
                 self.current_shell -= 1;
             }
         }
+        self.apply_env();
         self.set_path(self.path());
     }
 
+    /// Set an environment variable for the current shell only
+    pub(crate) fn set_env(&mut self, key: &str, value: &str) {
+        self.base_env
+            .lock()
+            .unwrap()
+            .entry(key.to_string())
+            .or_insert_with(|| std::env::var_os(key));
+
+        if let Some(env) = self.envs.lock().unwrap().get_mut(self.current_shell) {
+            env.vars.insert(key.to_string(), value.to_string());
+        }
+
+        std::env::set_var(key, value);
+    }
+
+    /// Take back a variable the current shell set, returning it to what it was outside the shell
+    pub(crate) fn unset_env(&mut self, key: &str) {
+        if let Some(env) = self.envs.lock().unwrap().get_mut(self.current_shell) {
+            env.vars.swap_remove(key);
+        }
+
+        restore_var(&self.base_env.lock().unwrap(), key);
+    }
+
+    /// Layer settings over the config for the current shell only
+    pub(crate) fn set_config_overlay(&mut self, settings: IndexMap<String, Tagged<Value>>) {
+        if let Some(env) = self.envs.lock().unwrap().get_mut(self.current_shell) {
+            env.config = settings.clone();
+        }
+
+        crate::data::config::set_overlay(settings);
+    }
+
+    /// Load or unload `.nu-env` files to match the current shell's directory
+    pub(crate) fn update_directory_env(&mut self, cwd: &std::path::Path, host: &mut dyn Host) {
+        let mut directory_env = match self.envs.lock().unwrap().get(self.current_shell) {
+            Some(env) => env.directory_env.clone(),
+            None => return,
+        };
+
+        directory_env.enter(cwd, host, self);
+
+        if let Some(env) = self.envs.lock().unwrap().get_mut(self.current_shell) {
+            env.directory_env = directory_env;
+        }
+    }
+
+    fn apply_env(&self) {
+        if let Some(env) = self.envs.lock().unwrap().get(self.current_shell) {
+            for (key, value) in env.vars.iter() {
+                std::env::set_var(key, value);
+            }
+            crate::data::config::set_overlay(env.config.clone());
+        }
+    }
+
+    fn unapply_env(&self) {
+        if let Some(env) = self.envs.lock().unwrap().get(self.current_shell) {
+            let base_env = self.base_env.lock().unwrap();
+            for key in env.vars.keys() {
+                restore_var(&base_env, key);
+            }
+            crate::data::config::set_overlay(IndexMap::new());
+        }
+    }
+
     pub fn homedir(&self) -> Option<PathBuf> {
         let env = self.shells.lock().unwrap();
 
@@ -211,3 +306,10 @@ impl ShellManager {
         }
     }
 }
+
+fn restore_var(base_env: &IndexMap<String, Option<OsString>>, key: &str) {
+    match base_env.get(key) {
+        Some(Some(value)) => std::env::set_var(key, value),
+        _ => std::env::remove_var(key),
+    }
+}
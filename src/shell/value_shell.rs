@@ -5,6 +5,7 @@ use crate::commands::mv::MoveArgs;
 use crate::commands::rm::RemoveArgs;
 use crate::context::SourceMap;
 use crate::prelude::*;
+use crate::shell::completer::NuCompleter;
 use crate::shell::shell::Shell;
 use crate::utils::ValueStructure;
 use std::ffi::OsStr;
@@ -15,6 +16,7 @@ pub struct ValueShell {
     pub(crate) path: String,
     pub(crate) last_path: String,
     pub(crate) value: Tagged<Value>,
+    completer: NuCompleter,
 }
 
 impl std::fmt::Debug for ValueShell {
@@ -24,11 +26,12 @@ impl std::fmt::Debug for ValueShell {
 }
 
 impl ValueShell {
-    pub fn new(value: Tagged<Value>) -> ValueShell {
+    pub fn new(value: Tagged<Value>, commands: CommandRegistry) -> ValueShell {
         ValueShell {
             path: "/".to_string(),
             last_path: "/".to_string(),
             value,
+            completer: NuCompleter::new(commands),
         }
     }
 
@@ -66,8 +69,28 @@ impl ValueShell {
         shell_entries
     }
 
-    fn members(&self) -> VecDeque<Tagged<Value>> {
-        self.members_under(Path::new("."))
+    fn value_completions(&self, partial: &str) -> Vec<String> {
+        let (dir_part, prefix) = match partial.rfind('/') {
+            Some(idx) => (&partial[..=idx], &partial[idx + 1..]),
+            None => ("", partial),
+        };
+
+        let mut under = PathBuf::from(self.path());
+        under.push(dir_part);
+
+        let mut completions = vec![];
+        for member in self.members_under(under.as_path()) {
+            for desc in member.item.data_descriptors() {
+                if desc.starts_with(prefix) {
+                    let completion = format!("{}{}", dir_part, desc);
+                    if !completions.contains(&completion) {
+                        completions.push(completion);
+                    }
+                }
+            }
+        }
+
+        completions
     }
 }
 
@@ -231,53 +254,8 @@ impl Shell for ValueShell {
         pos: usize,
         _ctx: &rustyline::Context<'_>,
     ) -> Result<(usize, Vec<rustyline::completion::Pair>), rustyline::error::ReadlineError> {
-        let mut completions = vec![];
-
-        let mut possible_completion = vec![];
-        let members = self.members();
-        for member in members {
-            match member {
-                Tagged { item, .. } => {
-                    for desc in item.data_descriptors() {
-                        possible_completion.push(desc);
-                    }
-                }
-            }
-        }
-
-        let line_chars: Vec<_> = line.chars().collect();
-        let mut replace_pos = pos;
-        while replace_pos > 0 {
-            if line_chars[replace_pos - 1] == ' ' {
-                break;
-            }
-            replace_pos -= 1;
-        }
-
-        for command in possible_completion.iter() {
-            let mut pos = replace_pos;
-            let mut matched = true;
-            if pos < line_chars.len() {
-                for chr in command.chars() {
-                    if line_chars[pos] != chr {
-                        matched = false;
-                        break;
-                    }
-                    pos += 1;
-                    if pos == line_chars.len() {
-                        break;
-                    }
-                }
-            }
-
-            if matched {
-                completions.push(rustyline::completion::Pair {
-                    display: command.to_string(),
-                    replacement: command.to_string(),
-                });
-            }
-        }
-        Ok((replace_pos, completions))
+        self.completer
+            .complete(line, pos, &|partial| self.value_completions(partial))
     }
 
     fn hint(&self, _line: &str, _pos: usize, _ctx: &rustyline::Context<'_>) -> Option<String> {
@@ -113,3 +113,66 @@ fn removes_configuration_value() {
 
     h::delete_file_at(nu::config_path().unwrap().join("test_5.toml"));
 }
+
+#[test]
+fn sets_nested_configuration_values_by_path() {
+    Playground::setup("config_test_6", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "test_6.toml",
+            r#"
+                    [table]
+                    header_color = "green"
+                "#,
+        )]);
+
+        nu!(
+            cwd: dirs.test(),
+            "config --load test_6.toml set table.mode light"
+        );
+
+        let actual = nu!(
+            cwd: dirs.root(),
+            r#"open "{}/test_6.toml" | get table.mode table.header_color | echo $it"#,
+            dirs.config_path()
+        );
+
+        assert_eq!(actual, "lightgreen");
+    });
+
+    h::delete_file_at(nu::config_path().unwrap().join("test_6.toml"));
+}
+
+#[test]
+fn merges_settings_from_another_file() {
+    Playground::setup("config_test_7", |dirs, sandbox| {
+        sandbox.with_files(vec![
+            FileWithContent(
+                "test_7.toml",
+                r#"
+                    caballero_1 = "Andrés N. Robalino"
+                "#,
+            ),
+            FileWithContent(
+                "more.toml",
+                r#"
+                    caballero_2 = "Jonathan Turner"
+                "#,
+            ),
+        ]);
+
+        nu!(
+            cwd: dirs.test(),
+            "config --load test_7.toml merge more.toml"
+        );
+
+        let actual = nu!(
+            cwd: dirs.root(),
+            r#"open "{}/test_7.toml" | get caballero_1 caballero_2 | echo $it"#,
+            dirs.config_path()
+        );
+
+        assert_eq!(actual, "Andrés N. RobalinoJonathan Turner");
+    });
+
+    h::delete_file_at(nu::config_path().unwrap().join("test_7.toml"));
+}
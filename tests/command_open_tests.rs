@@ -227,3 +227,27 @@ fn errors_if_file_not_found() {
 
     assert!(actual.contains("File could not be opened"));
 }
+
+#[test]
+fn keeps_errors_as_rows_when_asked() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "open i_dont_exist.txt --keep-errors | get error | echo $it"
+    );
+
+    assert_eq!(actual, "File could not be opened");
+}
+
+#[test]
+fn opens_a_path_with_escaped_spaces() {
+    Playground::setup("open_test_2", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent("my notes.txt", "hello")]);
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            r"open my\ notes.txt | echo $it"
+        );
+
+        assert_eq!(actual, "hello");
+    })
+}
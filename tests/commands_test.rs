@@ -42,6 +42,17 @@ fn first_requires_an_amount() {
     })
 }
 
+#[test]
+fn missing_parameter_errors_point_to_help() {
+    Playground::setup("first_test_4", |dirs, _| {
+        let actual = nu_error!(
+            cwd: dirs.test(), "ls | first"
+        );
+
+        assert!(actual.contains("help first"));
+    })
+}
+
 #[test]
 fn get() {
     Playground::setup("get_test_1", |dirs, sandbox| {
@@ -116,6 +127,34 @@ fn get_requires_at_least_one_member() {
     })
 }
 
+#[test]
+fn get_suggests_the_closest_column() {
+    Playground::setup("get_test_3", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "sample.toml",
+            r#"
+                nu_party_venue = "zion"
+            "#,
+        )]);
+
+        let actual = nu_error!(
+            cwd: dirs.test(), "open sample.toml | get nu_party_venu"
+        );
+
+        assert!(actual.contains("did you mean 'nu_party_venue'?"));
+    })
+}
+
+#[test]
+fn unknown_flags_suggest_the_closest_flag() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats", "open cargo_sample.toml --rwa"
+    );
+
+    assert!(actual.contains("Unknown flag"));
+    assert!(actual.contains("did you mean --raw?"));
+}
+
 #[test]
 fn lines() {
     let actual = nu!(
@@ -136,6 +175,63 @@ fn lines() {
     assert_eq!(actual, "rustyline");
 }
 
+#[test]
+fn group_by_gathers_the_rows_with_each_value() {
+    let actual = nu!(
+        cwd: ".", h::pipeline(
+        r#"
+            echo [[name type]; [andres File] [jonathan Dir] [yehuda File]]
+            | group-by type
+            | get File
+            | get name
+            | echo $it
+        "#
+    ));
+
+    assert_eq!(actual, "andresyehuda");
+}
+
+#[test]
+fn take_while_stops_at_the_first_row_that_fails_the_condition() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [1 2 5 1 7] | take-while $it < 3 | echo $it"
+    );
+
+    assert_eq!(actual, "12");
+}
+
+#[cfg(unix)]
+#[test]
+fn take_while_stops_an_endless_stream() {
+    let actual = nu!(
+        cwd: ".",
+        r#"^sh -c "i=1; while echo $i; do i=$((i+1)); done" | lines | take-while $it != "3" | echo $it"#
+    );
+
+    assert_eq!(actual, "12");
+}
+
+#[test]
+fn uniq_keeps_the_first_of_each_value() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [a b a c a] | uniq | echo $it"
+    );
+
+    assert_eq!(actual, "abc");
+}
+
+#[test]
+fn uniq_counts_how_many_times_each_value_was_seen() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [a b a c a] | uniq --count | sort-by value | get count | echo $it"
+    );
+
+    assert_eq!(actual, "311");
+}
+
 #[test]
 fn save_figures_out_intelligently_where_to_write_out_with_metadata() {
     Playground::setup("save_test_1", |dirs, sandbox| {
@@ -211,3 +307,414 @@ fn save_can_write_out_bson() {
         );
     })
 }
+
+#[test]
+fn hexdump_windows_bytes_with_offsets_and_ascii_gutter() {
+    Playground::setup("hexdump_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent("sample.txt", "nushell")]);
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            "open sample.txt --raw | hexdump --skip 2 --length 5 | echo $it"
+        );
+
+        assert!(actual.starts_with("00000002  73 68 65 6c 6c"));
+        assert!(actual.ends_with("|shell|"));
+    })
+}
+
+#[test]
+fn grouped_shorthand_flags_stand_for_each_flag() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "open cargo_sample.toml -rk | lines | first 1 | echo $it"
+    );
+
+    assert_eq!(actual, "[package]");
+}
+
+#[test]
+fn ambiguous_shorthand_flags_are_reported() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            def greet [--verbose --version] { echo hello }
+            greet -v
+        "#
+    );
+
+    assert!(actual.contains("Ambiguous flag"));
+}
+
+#[test]
+fn flags_can_be_given_their_values_with_equals() {
+    Playground::setup("hexdump_test_2", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent("sample.txt", "nushell")]);
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            "open sample.txt --raw | hexdump --skip=2 --length=5 | echo $it"
+        );
+
+        assert!(actual.starts_with("00000002  73 68 65 6c 6c"));
+        assert!(actual.ends_with("|shell|"));
+    })
+}
+
+#[test]
+fn switches_can_not_be_given_a_value_with_equals() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats",
+        "ls --full=false | echo $it"
+    );
+
+    assert!(actual.contains("--full is a switch, and doesn't take a value"));
+}
+
+#[test]
+fn alias_expands_to_its_pipeline() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            alias package = open cargo_sample.toml | get package
+            package | get name | echo $it
+        "#
+    );
+
+    assert_eq!(actual, "nu");
+}
+
+#[test]
+fn alias_substitutes_its_arguments() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            alias field = open $2 | get $1
+            field package cargo_sample.toml | get name | echo $it
+        "#
+    );
+
+    assert_eq!(actual, "nu");
+}
+
+#[test]
+fn def_defines_a_command_with_its_parameters() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            def field [column: string path --upper] { open $path | get $column | get name | echo $it $upper }
+            field package cargo_sample.toml --upper
+        "#
+    );
+
+    assert_eq!(actual, "nu true");
+}
+
+#[test]
+fn def_body_output_is_the_commands_output() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "def f [x] { echo $x }; f 3 | echo $it"
+    );
+
+    assert_eq!(actual, "3");
+}
+
+#[test]
+fn def_can_be_followed_by_a_comment() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            def f [x] { echo $x } # echoes x
+            f 3 | echo $it
+        "#
+    );
+
+    assert_eq!(actual, "3");
+}
+
+#[test]
+fn source_keeps_the_aliases_a_script_defines() {
+    Playground::setup("source_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "defs.nu",
+            "# shared aliases\nalias greet = echo hello\n",
+        )]);
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            r#"
+                source defs.nu
+                greet
+            "#
+        );
+
+        assert_eq!(actual, "hello");
+    })
+}
+
+#[test]
+fn source_keeps_the_commands_a_script_defines() {
+    Playground::setup("source_test_2", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "defs.nu",
+            "def twice [n: int] { echo ($n * 2) }\n",
+        )]);
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            r#"
+                source defs.nu
+                twice 21
+            "#
+        );
+
+        assert_eq!(actual, "42");
+    })
+}
+
+#[test]
+fn tags_shows_the_file_a_value_was_opened_from() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "open cargo_sample.toml | tags | get origin | echo $it"
+    );
+
+    assert!(actual.contains("cargo_sample.toml"));
+}
+
+#[test]
+fn error_make_reports_the_message_it_is_given() {
+    Playground::setup("error_make_test_1", |dirs, _| {
+        let actual = nu_error!(
+            cwd: dirs.test(), r#"error make "disk is full" --label "no room left""#
+        );
+
+        assert!(actual.contains("disk is full"));
+        assert!(actual.contains("no room left"));
+    })
+}
+
+#[test]
+fn assert_passes_quietly_when_the_condition_holds() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats", "assert eq 4 4.0"
+    );
+
+    assert_eq!(actual, "");
+}
+
+#[test]
+fn assert_eq_shows_both_values_when_they_differ() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats", "assert eq 4 5"
+    );
+
+    assert!(actual.contains("Assertion failed"));
+    assert!(actual.contains("this is 4"));
+    assert!(actual.contains("but 5 was expected"));
+}
+
+#[test]
+fn assert_fails_when_the_condition_is_false() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats", "assert 4 > 5"
+    );
+
+    assert!(actual.contains("Assertion failed"));
+}
+
+#[cfg(unix)]
+#[test]
+fn a_line_ending_in_ampersand_runs_as_a_background_job() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", "echo hello &"
+    );
+
+    assert!(actual.starts_with("[1] "));
+}
+
+#[test]
+fn an_ampersand_in_a_comment_does_not_start_a_job() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", "echo hello # and goodbye &"
+    );
+
+    assert_eq!(actual, "hello");
+}
+
+#[cfg(unix)]
+#[test]
+fn bg_reports_when_there_are_no_jobs() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats", "bg"
+    );
+
+    assert!(actual.contains("No such job"));
+}
+
+#[test]
+fn bytes_length_counts_binary_data() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", h::pipeline(
+        r#"
+            open sample.bson --raw
+            | bytes length
+            | echo $it
+        "#
+    ));
+
+    assert_eq!(actual, "561");
+}
+
+#[test]
+fn bytes_slice_renders_as_hex() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", h::pipeline(
+        r#"
+            open sample.bson --raw
+            | bytes slice 0 4
+            | bytes hex
+            | echo $it
+        "#
+    ));
+
+    assert_eq!(actual, "31020000");
+}
+
+#[test]
+fn hash_md5_of_text() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", "echo hello | hash md5 | echo $it"
+    );
+
+    assert_eq!(actual, "5d41402abc4b2a76b9719d911017c592");
+}
+
+#[test]
+fn save_writes_binary_data_back_unchanged() {
+    Playground::setup("save_test_4", |dirs, _| {
+        let expected_file = dirs.test().join("copy.bin");
+
+        nu!(
+            cwd: dirs.root(),
+            "open {}/sample.bson --raw | save save_test_4/copy.bin",
+            dirs.formats()
+        );
+
+        let actual = h::file_contents_binary(expected_file);
+        let original = h::file_contents_binary(dirs.formats().join("sample.bson"));
+        assert!(actual == original);
+    })
+}
+
+#[test]
+fn redirection_writes_the_output_of_a_pipeline() {
+    Playground::setup("save_test_5", |dirs, _| {
+        nu!(
+            cwd: dirs.test(),
+            "open {}/cargo_sample.toml | get package > package.json",
+            dirs.formats()
+        );
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            "open package.json | where name > mu | get name | echo $it"
+        );
+
+        assert_eq!(actual, "nu");
+    })
+}
+
+#[test]
+fn redirection_appends_external_output_with_double_angle() {
+    Playground::setup("save_test_6", |dirs, _| {
+        let expected_file = dirs.test().join("lines.txt");
+
+        nu!(cwd: dirs.test(), "^echo one > lines.txt; ^echo two >> lines.txt");
+
+        let actual = h::file_contents(&expected_file);
+        assert_eq!(actual.lines().collect::<Vec<_>>(), vec!["one", "two"]);
+    })
+}
+
+#[test]
+fn redirection_refuses_to_append_a_table_to_a_structured_file() {
+    Playground::setup("save_test_7", |dirs, _| {
+        nu!(
+            cwd: dirs.test(),
+            "open {}/cargo_sample.toml | get package > package.json",
+            dirs.formats()
+        );
+
+        let actual = nu_error!(
+            cwd: dirs.test(),
+            "open {}/cargo_sample.toml | get package >> package.json",
+            dirs.formats()
+        );
+
+        assert!(actual.contains("Can't append to a structured file"));
+
+        let name = nu!(
+            cwd: dirs.test(),
+            "open package.json | get name | echo $it"
+        );
+
+        assert_eq!(name, "nu");
+    })
+}
+
+#[test]
+fn redirection_appends_text_to_a_structured_file_as_lines() {
+    Playground::setup("save_test_8", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent("names.csv", "name\nandres\n")]);
+
+        nu!(cwd: dirs.test(), "echo jonathan >> names.csv");
+
+        let actual = h::file_contents(&dirs.test().join("names.csv"));
+        assert_eq!(actual, "name\nandres\njonathan");
+    })
+}
+
+#[test]
+fn benchmark_returns_a_duration() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", r#"benchmark "cd ." | to-json | echo $it"#
+    );
+
+    let nanos: i64 = actual
+        .parse()
+        .expect("expected the duration in nanoseconds");
+    assert!(nanos > 0);
+}
+
+#[test]
+fn format_filesize_shows_sizes_in_the_given_unit() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", h::pipeline(
+        r#"
+            echo 1536
+            | into filesize
+            | format filesize KiB
+            | echo $it
+        "#
+    ));
+
+    assert_eq!(actual, "1.5 KiB");
+}
+
+#[test]
+fn into_filesize_reads_sizes_written_as_text() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", h::pipeline(
+        r#"
+            echo "5MB"
+            | into filesize
+            | format filesize KB
+            | echo $it
+        "#
+    ));
+
+    assert!(actual.starts_with("5000"));
+    assert!(actual.ends_with("KB"));
+}
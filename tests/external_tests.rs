@@ -9,3 +9,23 @@ fn external_command() {
 
     assert!(actual.contains("1"));
 }
+
+#[test]
+fn external_command_gets_negative_numbers_intact() {
+    let actual = nu!(
+        cwd: "tests/fixtures",
+        "^echo -5 5"
+    );
+
+    assert_eq!(actual, "-5 5");
+}
+
+#[test]
+fn external_command_gets_flags_with_values_intact() {
+    let actual = nu!(
+        cwd: "tests/fixtures",
+        "^echo --color=never -x"
+    );
+
+    assert_eq!(actual, "--color=never -x");
+}
@@ -145,6 +145,22 @@ fn can_convert_table_to_json_text_and_from_json_text_back_into_table() {
     assert_eq!(actual, "markup");
 }
 
+#[test]
+fn can_convert_table_to_nu_text_and_from_nu_text_back_into_table() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats", h::pipeline(
+        r#"
+            open sgml_description.json
+            | to-nu
+            | from-nu
+            | get glossary.GlossDiv.GlossList.GlossEntry.GlossSee
+            | echo $it
+        "#
+    ));
+
+    assert_eq!(actual, "markup");
+}
+
 #[test]
 fn converts_from_json_text_to_structured_table() {
     Playground::setup("filter_from_json_test_1", |dirs, sandbox| {
@@ -505,6 +521,245 @@ fn can_filter_by_unit_size_comparison() {
     assert_eq!(actual, "cargo_sample.toml");
 }
 
+#[test]
+fn can_filter_by_size_arithmetic() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "ls | where { $it.size * 4 > 4kb } | sort-by size | get name | first 1 | trim | echo $it"
+    );
+
+    assert_eq!(actual, "cargo_sample.toml");
+}
+
+#[test]
+fn can_filter_every_other_row_with_mod() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [1 2 3 4 5 6] | where { $it mod 2 == 0 } | echo $it"
+    );
+
+    assert_eq!(actual, "246");
+}
+
+#[test]
+fn can_filter_by_the_opposite_of_a_condition() {
+    let actual = nu!(
+        cwd: ".",
+        r#"echo [[name]; [.git] [src] [.env]] | where not ($it.name =~ "^\.") | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "src");
+}
+
+#[test]
+fn not_takes_in_comparisons_but_not_logical_operators() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [[name size]; [a 5] [b 20] [c 30]] | where not size > 10 || name == c | get name | echo $it"
+    );
+
+    assert_eq!(actual, "ac");
+}
+
+#[test]
+fn sort_by_sorts_the_column_given_with_reverse_largest_first() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [[name type]; [b File] [A Dir] [c File] [a File]] | sort-by type --reverse name | get name | echo $it"
+    );
+
+    assert_eq!(actual, "Acba");
+}
+
+#[test]
+fn sort_by_reverses_the_same_column_wherever_the_flag_goes() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [[name type]; [b File] [A Dir] [c File] [a File]] | sort-by -r name type | get name | echo $it"
+    );
+
+    assert_eq!(actual, "Acba");
+}
+
+#[test]
+fn sort_by_keeps_the_place_of_a_reversed_column_among_the_others() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [[name type]; [b File] [A Dir] [c File] [a File]] | sort-by name type -r name | get type | echo $it"
+    );
+
+    assert_eq!(actual, "FileFileFileDir");
+}
+
+#[test]
+fn sort_by_can_ignore_case() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [[name]; [B] [a] [c]] | sort-by name --insensitive | get name | echo $it"
+    );
+
+    assert_eq!(actual, "aBc");
+}
+
+#[test]
+fn can_filter_by_boolean() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "open appveyor.yml | where build == false | get build | echo $it"
+    );
+
+    assert_eq!(actual, "false");
+}
+
+#[test]
+fn missing_cells_equal_null() {
+    Playground::setup("filter_null_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContentToBeTrimmed(
+            "katz.txt",
+            r#"
+                {
+                    "katz": [
+                        {"name":   "Yehuda", "parent": "Andres"},
+                        {"name":   "Andres"}
+                    ]
+                }
+            "#,
+        )]);
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            "open katz.txt | from-json | get katz | where parent == null | get name | echo $it"
+        );
+
+        assert_eq!(actual, "Andres");
+
+        let actual = nu!(
+            cwd: dirs.test(),
+            "open katz.txt | from-json | get katz | where null != parent | get name | echo $it"
+        );
+
+        assert_eq!(actual, "Yehuda");
+    })
+}
+
+#[test]
+fn null_is_not_a_command() {
+    let actual = nu_error!(cwd: ".", "null");
+
+    assert!(actual.contains("null is a value, not a command"));
+}
+
+#[test]
+fn can_filter_by_several_conditions() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where size > 1kb && name == "caco3_plastics.csv" | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "caco3_plastics.csv");
+
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name == "sample.url" || size > 10kb | sort-by size | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "sample.urlsample.db");
+}
+
+#[test]
+fn can_filter_by_regex() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name =~ "^caco3.*\.csv$" | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "caco3_plastics.csv");
+
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name !~ "\.(csv|tsv|toml|xml|json|yml|bson|db|ini)$" | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "sample.url");
+}
+
+#[test]
+fn reports_invalid_regex() {
+    let actual = nu_error!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name =~ "(unclosed""#
+    );
+
+    assert!(actual.contains("Invalid regular expression"));
+}
+
+#[test]
+fn can_filter_by_membership() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name in ["sample.url" "sample.ini"] | sort-by name | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "sample.inisample.url");
+
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where size > 1kb | where name not-in ["sample.db" "caco3_plastics.csv" "caco3_plastics.tsv"] | get name | echo $it"#
+    );
+
+    assert_eq!(actual, "cargo_sample.toml");
+}
+
+#[test]
+fn can_filter_with_named_block_params() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "ls | where { |file| $file.size > 1500 && $file.size < 2kb } | sort-by name | get name | echo $it"
+    );
+
+    assert_eq!(actual, "caco3_plastics.csvcaco3_plastics.tsv");
+}
+
+#[test]
+fn if_gives_the_value_of_the_branch_taken() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name in ["sample.url" "cargo_sample.toml"] | sort-by name | if $it.size > 1kb { big } { small } | echo $it"#
+    );
+
+    assert_eq!(actual, "bigsmall");
+}
+
+#[test]
+fn if_runs_the_commands_of_the_branch_taken_for_each_row() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name in ["sample.url" "cargo_sample.toml"] | sort-by name | if $it.size > 1kb { get name } { echo small } | echo $it"#
+    );
+
+    assert_eq!(actual, "cargo_sample.tomlsmall");
+}
+
+#[test]
+fn if_can_start_a_pipeline() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "if 2 > 1 { echo yes } { echo no } | echo $it"
+    );
+
+    assert_eq!(actual, "yes");
+}
+
+#[test]
+fn if_runs_no_branch_when_the_commands_before_it_produce_nothing() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"ls | where name == "no_such_file" | if 2 > 1 { echo yes } { echo no } | echo $it"#
+    );
+
+    assert_eq!(actual, "");
+}
+
 #[test]
 fn can_get_last() {
     let actual = nu!(
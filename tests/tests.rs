@@ -1,6 +1,8 @@
 mod helpers;
 
 use helpers as h;
+use helpers::{Playground, Stub::*};
+use std::process::Command;
 
 #[test]
 fn pipeline_helper() {
@@ -31,6 +33,283 @@ fn external_num() {
     assert_eq!(actual, "10");
 }
 
+#[test]
+fn comments_run_to_the_end_of_the_line() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            # what's the package called?
+            open cargo_sample.toml | get package.name | echo $it # the name
+        "#
+    );
+
+    assert_eq!(actual, "nu");
+}
+
+#[test]
+fn pipelines_continue_after_a_trailing_pipe() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"
+            open cargo_sample.toml |
+                get package.name |
+                echo $it
+        "#
+    );
+
+    assert_eq!(actual, "nu");
+}
+
+#[test]
+fn semicolons_run_pipelines_one_after_the_other() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "echo first; open cargo_sample.toml | get package.name | echo $it; echo last"
+    );
+
+    assert_eq!(actual, "firstnulast");
+}
+
+#[test]
+fn a_failing_statement_stops_the_rest_of_the_line() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "open cargo_sample.toml | get no_such_column; echo unreachable"
+    );
+
+    assert_eq!(actual, "");
+}
+
+#[test]
+fn interpolated_strings_fill_in_their_expressions() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        r#"open cargo_sample.toml | get package | echo $"{$it.name} is at {$it.version}""#
+    );
+
+    assert_eq!(actual, "nu is at 0.1.1");
+}
+
+#[test]
+fn raw_strings_keep_quotes_and_backslashes() {
+    let actual = nu!(
+        cwd: ".",
+        r##"echo r#"say "hi" from C:\nu"# | echo $it"##
+    );
+
+    assert_eq!(actual, r#"say "hi" from C:\nu"#);
+}
+
+#[test]
+fn a_doubled_backslash_in_a_bare_word_is_a_backslash() {
+    let actual = nu!(
+        cwd: ".",
+        r"echo back\\ | echo $it"
+    );
+
+    assert_eq!(actual, r"back\");
+}
+
+#[test]
+fn arithmetic_follows_precedence() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (3 + 4 * 2 - 1)"
+    );
+
+    assert_eq!(actual, "10");
+}
+
+#[test]
+fn arithmetic_operators_after_words_are_words() {
+    let actual = nu!(
+        cwd: ".",
+        "echo a - b + c"
+    );
+
+    assert_eq!(actual, "a - b + c");
+}
+
+#[test]
+fn word_operators_after_words_are_words() {
+    let actual = nu!(
+        cwd: ".",
+        "echo checked in; echo load the mod"
+    );
+
+    assert_eq!(actual, "checked inload the mod");
+}
+
+#[test]
+fn duration_literals_out_of_range_are_an_error() {
+    let actual = nu_error!(
+        cwd: ".",
+        "echo 1000000d"
+    );
+
+    assert!(actual.contains("Duration out of range"));
+}
+
+#[test]
+fn duration_arithmetic_out_of_range_is_an_error() {
+    let actual = nu_error!(
+        cwd: ".",
+        "echo (100000d + 100000d)"
+    );
+
+    assert!(actual.contains("Result out of range"));
+}
+
+#[test]
+fn powers_too_large_to_work_out_are_an_error() {
+    let actual = nu_error!(
+        cwd: ".",
+        "echo (10 ** 100000000)"
+    );
+
+    assert!(actual.contains("the number this gives is too large"));
+}
+
+#[test]
+fn decimals_with_too_large_an_exponent_are_an_error() {
+    let actual = nu_error!(
+        cwd: ".",
+        "echo 1e99999999999999999999"
+    );
+
+    assert!(actual.contains("Number too large"));
+}
+
+#[test]
+fn dividing_ints_unevenly_gives_a_decimal() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (7 / 2)"
+    );
+
+    assert_eq!(actual, "3.5");
+}
+
+#[test]
+fn mod_gives_the_remainder_and_powers_group_from_the_right() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (17 mod 5) (17 % 5 + 1) (2 ** 3 ** 2) (2 * 3 ** 2)"
+    );
+
+    assert_eq!(actual, "2 3 512 18");
+}
+
+#[test]
+fn durations_add_up() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (2m + 30s * 3)"
+    );
+
+    assert_eq!(actual, "3min 30s");
+}
+
+#[test]
+fn numbers_can_be_written_in_scientific_notation() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (1.5e9 > 1000000000) (2E-3 < 0.001)"
+    );
+
+    assert_eq!(actual, "true false");
+}
+
+#[test]
+fn underscores_group_the_digits_of_numbers() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (1_000_000 + 1)"
+    );
+
+    assert_eq!(actual, "1000001");
+}
+
+#[test]
+fn table_literals_become_rows() {
+    let actual = nu!(
+        cwd: ".",
+        "echo [[name age]; [alice 30] [bob 25]] | where age > 26 | get name | echo $it"
+    );
+
+    assert_eq!(actual, "alice");
+}
+
+#[test]
+fn negative_numbers_are_not_flags() {
+    let actual = nu!(
+        cwd: ".",
+        "echo (-5 + 3) -2"
+    );
+
+    assert_eq!(actual, "-2 -2");
+}
+
+#[test]
+fn subexpressions_give_the_value_of_their_pipeline() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "echo (open cargo_sample.toml | get package.name) is here"
+    );
+
+    assert_eq!(actual, "nu is here");
+}
+
+#[test]
+fn subexpressions_can_stand_in_for_paths() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "open (echo cargo_sample.toml) | get package.name | echo $it"
+    );
+
+    assert_eq!(actual, "nu");
+}
+
+#[test]
+fn for_runs_its_block_for_each_value() {
+    let actual = nu!(
+        cwd: ".",
+        "for n in [1 2 3] { $n * 2 } | echo $it"
+    );
+
+    assert_eq!(actual, "246");
+}
+
+#[test]
+fn for_runs_the_commands_of_its_block_with_the_value() {
+    let actual = nu!(
+        cwd: "tests/fixtures/formats",
+        "for file in [cargo_sample.toml] { open $file | get package.name } | echo $it"
+    );
+
+    assert_eq!(actual, "nu");
+}
+
+#[test]
+fn for_runs_its_block_for_each_value_a_subexpression_produces() {
+    let actual = nu!(
+        cwd: ".",
+        r#"for n in (echo "1 2 3" | split-row " ") { echo $n } | first 2 | echo $it"#
+    );
+
+    assert_eq!(actual, "12");
+}
+
+#[test]
+fn record_literals_become_a_row() {
+    let actual = nu!(
+        cwd: ".",
+        r#"echo {name: "alice", age: 30} | get age | echo $it"#
+    );
+
+    assert_eq!(actual, "30");
+}
+
 #[test]
 fn external_has_correct_quotes() {
     let actual = nu!(
@@ -70,3 +349,519 @@ fn edit_plugin() {
 
     assert_eq!(actual, "7");
 }
+
+#[test]
+fn ide_check_reports_problems_without_running_the_script() {
+    Playground::setup("ide_check_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "script.nu",
+            r#"
+                # nothing here should run
+                mkdir should_not_exist
+                ls | first 1 --rwa
+            "#,
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg("--ide-check")
+            .arg(dirs.test().join("script.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let diagnostics = String::from_utf8_lossy(&output.stdout);
+
+        assert!(!output.status.success());
+        assert!(diagnostics.contains(r#""line":4"#));
+        assert!(diagnostics.contains(r#""message":"Unknown flag""#));
+        assert!(!dirs.test().join("should_not_exist").exists());
+    })
+}
+
+#[test]
+fn ide_check_reports_the_line_a_problem_is_on_within_a_continued_line() {
+    Playground::setup("ide_check_test_2", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "script.nu",
+            "echo [\n  1\n  2\n] | first 1 --rwa\nls",
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg("--ide-check")
+            .arg(dirs.test().join("script.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let diagnostics = String::from_utf8_lossy(&output.stdout);
+
+        assert_eq!(diagnostics.lines().count(), 1);
+        assert!(diagnostics.contains(r#""line":4"#));
+        assert!(diagnostics.contains(r#""column":13"#));
+    })
+}
+
+#[test]
+fn fmt_makes_the_spacing_of_a_script_regular() {
+    Playground::setup("fmt_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "script.nu",
+            "ls   |where size >   10kb|  get name   # big files\nopen \"a  b.txt\";echo [ 1  2 ]",
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg("--fmt")
+            .arg(dirs.test().join("script.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let formatted = h::file_contents(&dirs.test().join("script.nu"));
+
+        assert!(output.status.success());
+        assert_eq!(
+            formatted,
+            "ls | where size > 10kb | get name # big files\nopen \"a  b.txt\"; echo [1 2]"
+        );
+    })
+}
+
+#[test]
+fn fmt_keeps_the_pipe_starting_a_continued_line() {
+    Playground::setup("fmt_test_2", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "script.nu",
+            "ls \\\n    |  first 2 \\\n    |get name\necho a\\ ",
+        )]);
+
+        Command::new(h::executable_path())
+            .arg("--fmt")
+            .arg(dirs.test().join("script.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let formatted = h::file_contents(&dirs.test().join("script.nu"));
+
+        assert_eq!(
+            formatted,
+            "ls \\\n    | first 2 \\\n    | get name\necho a\\ "
+        );
+    })
+}
+
+#[test]
+fn fmt_copies_a_string_running_over_several_lines_as_it_is() {
+    Playground::setup("fmt_test_3", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "script.nu",
+            "echo   \"a  |  b\n   c ;  d\"   |  lines\nls   |  first 1\n",
+        )]);
+
+        Command::new(h::executable_path())
+            .arg("--fmt")
+            .arg(dirs.test().join("script.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let formatted = h::file_contents(&dirs.test().join("script.nu"));
+
+        assert_eq!(
+            formatted,
+            "echo \"a  |  b\n   c ;  d\" | lines\nls | first 1\n"
+        );
+    })
+}
+
+#[test]
+fn test_flag_runs_the_test_commands_of_a_script() {
+    Playground::setup("test_runner_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "script.nu",
+            r#"
+                def test_addition [] { assert eq (1 + 1) 2 }
+                def test_subtraction [] { assert eq (3 - 1) 1 }
+                def helper [] { mkdir should_not_exist }
+                mkdir should_not_exist
+            "#,
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg("--test")
+            .arg(dirs.test().join("script.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let report = String::from_utf8_lossy(&output.stdout);
+
+        assert!(!output.status.success());
+        assert!(report.contains("test_addition"));
+        assert!(report.contains("test_subtraction"));
+        assert!(report.contains("Assertion failed"));
+        assert!(!dirs.test().join("should_not_exist").exists());
+    })
+}
+
+#[test]
+fn lsp_publishes_diagnostics_for_an_opened_script() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let messages = vec![
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///tmp/script.nu","languageId":"nu","version":1,"text":"def greet [] { echo hi }\nls | first 1 --rwa"}}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"textDocument/documentSymbol","params":{"textDocument":{"uri":"file:///tmp/script.nu"}}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ];
+
+    let mut lsp = Command::new(h::executable_path())
+        .arg("--lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("couldn't run nu");
+
+    {
+        let stdin = lsp.stdin.as_mut().expect("couldn't write to nu");
+        for message in messages {
+            write!(
+                stdin,
+                "Content-Length: {}\r\n\r\n{}",
+                message.len(),
+                message
+            )
+            .expect("couldn't write to nu");
+        }
+    }
+
+    let output = lsp.wait_with_output().expect("couldn't run nu");
+    let replies = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(replies.contains(r#""hoverProvider":true"#));
+    assert!(replies.contains("textDocument/publishDiagnostics"));
+    assert!(replies.contains("Unknown flag"));
+    assert!(replies.contains(r#""name":"greet""#));
+}
+
+#[test]
+fn lsp_answers_a_malformed_message_with_an_error_and_carries_on() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let messages = vec![
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize""#,
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///tmp/my%20script.nu","languageId":"nu","version":1,"text":"echo \"😀\" | first 1 --rwa"}}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"shutdown"}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ];
+
+    let mut lsp = Command::new(h::executable_path())
+        .arg("--lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("couldn't run nu");
+
+    {
+        let stdin = lsp.stdin.as_mut().expect("couldn't write to nu");
+        for message in messages {
+            write!(
+                stdin,
+                "Content-Length: {}\r\n\r\n{}",
+                message.len(),
+                message
+            )
+            .expect("couldn't write to nu");
+        }
+    }
+
+    let output = lsp.wait_with_output().expect("couldn't run nu");
+    let replies = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(replies.contains(r#""code":-32700"#));
+    assert!(replies.contains("Unknown flag"));
+    // The emoji before the flag is two UTF-16 code units
+    assert!(replies.contains(r#""character":20"#));
+}
+
+#[test]
+fn highlight_tells_internal_and_external_commands_apart() {
+    use nu::HighlightKind::*;
+
+    let registry = nu::command_registry().expect("couldn't load the commands");
+    let line = "ls --full | where size > 10 | ^grep \"nu\" $it";
+    let pieces: Vec<_> = nu::highlight(line, &registry)
+        .into_iter()
+        .map(|(span, kind)| (span.slice(line), kind))
+        .collect();
+
+    assert_eq!(
+        pieces,
+        vec![
+            ("ls", InternalCommand),
+            ("--full", Flag),
+            ("|", Pipe),
+            ("where", InternalCommand),
+            ("size", Bare),
+            (">", Operator),
+            ("10", Number),
+            ("|", Pipe),
+            ("^grep", ExternalCommand),
+            ("\"nu\"", String),
+            ("$it", Variable),
+        ]
+    );
+}
+
+#[test]
+fn commands_flag_runs_a_pipeline_and_exits() {
+    let output = Command::new(h::executable_path())
+        .arg("-c")
+        .arg("echo nushell")
+        .current_dir("tests/fixtures/formats")
+        .output()
+        .expect("couldn't run nu");
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("nushell"));
+}
+
+#[test]
+fn commands_flag_exits_with_an_error_code_when_the_pipeline_fails() {
+    let output = Command::new(h::executable_path())
+        .arg("-c")
+        .arg("open does_not_exist.txt")
+        .current_dir("tests/fixtures/formats")
+        .output()
+        .expect("couldn't run nu");
+
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn commands_flag_exits_with_an_error_code_when_a_sourced_script_fails() {
+    Playground::setup("source_exit_code_test", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "broken.nu",
+            "open does_not_exist.txt\nmkdir should_not_exist\n",
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg("-c")
+            .arg("source broken.nu")
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(!dirs.test().join("should_not_exist").exists());
+    })
+}
+
+#[cfg(unix)]
+#[test]
+fn commands_flag_exits_with_the_exit_code_of_an_external_command() {
+    let output = Command::new(h::executable_path())
+        .arg("-c")
+        .arg(r#"^sh -c "echo out; exit 3""#)
+        .output()
+        .expect("couldn't run nu");
+
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "out\n");
+}
+
+#[test]
+fn unclosed_delimiters_are_reported_where_they_open() {
+    let output = Command::new(h::executable_path())
+        .arg("-c")
+        .arg("echo [1 2 | first 1")
+        .current_dir("tests/fixtures/formats")
+        .output()
+        .expect("couldn't run nu");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert!(stderr.contains("unclosed delimiter opened here"));
+}
+
+#[test]
+fn env_variables_are_values() {
+    let output = Command::new(h::executable_path())
+        .arg("-c")
+        .arg(r#"echo $nu.env.NU_TEST_GREETING $"{$env.NU_TEST_GREETING} again""#)
+        .env("NU_TEST_GREETING", "hello")
+        .output()
+        .expect("couldn't run nu");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert_eq!(stdout.trim(), "hello hello again");
+}
+
+fn nu_with_stdin(args: &[&str], stdin: &str) -> String {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut process = Command::new(h::executable_path())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("couldn't run nu");
+
+    process
+        .stdin
+        .take()
+        .expect("couldn't open stdin")
+        .write_all(stdin.as_bytes())
+        .expect("couldn't write to stdin");
+
+    let output = process.wait_with_output().expect("couldn't read stdout");
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+#[test]
+fn commands_flag_reads_stdin_as_text() {
+    let actual = nu_with_stdin(
+        &["-c", "lines | nth 1 | echo $it"],
+        "andres\njonathan\nyehuda\n",
+    );
+
+    assert_eq!(actual, "jonathan");
+}
+
+#[cfg(unix)]
+#[test]
+fn commands_flag_leaves_stdin_to_an_external_command() {
+    let actual = nu_with_stdin(&["-c", "^cat"], "andres\njonathan\n");
+
+    assert_eq!(actual, "andres\njonathan");
+}
+
+#[test]
+fn commands_flag_converts_stdin_with_stdin_format() {
+    let actual = nu_with_stdin(
+        &["--stdin-format", "json", "-c", "get name | echo $it"],
+        r#"{"name": "nushell", "stars": 4000}"#,
+    );
+
+    assert_eq!(actual, "nushell");
+}
+
+#[test]
+fn pipelines_piped_into_nu_run_one_line_at_a_time() {
+    let actual = nu_with_stdin(
+        &[],
+        "echo andres\n# a comment\n\necho [jonathan\n yehuda] | nth 1\nno-such-command\necho done\n",
+    );
+
+    assert_eq!(actual, "andres\nyehuda\ndone");
+}
+
+#[test]
+fn an_escaped_quote_piped_into_nu_does_not_swallow_the_next_line() {
+    let actual = nu_with_stdin(&[], "echo it\\'s\necho done\n");
+
+    assert_eq!(actual, "it's\ndone");
+}
+
+#[test]
+fn input_piped_into_nu_ending_partway_through_a_line_is_an_error() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut process = Command::new(h::executable_path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("couldn't run nu");
+
+    process
+        .stdin
+        .take()
+        .expect("couldn't open stdin")
+        .write_all(b"echo andres\necho [jonathan\n")
+        .expect("couldn't write to stdin");
+
+    let output = process.wait_with_output().expect("couldn't read output");
+
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "andres");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unexpected end of input"));
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn scripts_see_their_arguments_as_args() {
+    Playground::setup("script_args_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "greet.nu",
+            r#"
+                #!/usr/bin/env nu
+                echo hello $args
+            "#,
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg(dirs.test().join("greet.nu"))
+            .arg("jonathan")
+            .arg("--loud")
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "hello jonathan --loud"
+        );
+    })
+}
+
+#[test]
+fn scripts_stop_at_the_first_line_that_fails() {
+    Playground::setup("script_error_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "fails.nu",
+            "#!/usr/bin/env nu\n# a comment\necho before\nopen does_not_exist.txt\necho after\n",
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg(dirs.test().join("fails.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stdout.contains("before"));
+        assert!(!stdout.contains("after"));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("line 4 of"));
+    })
+}
+
+#[test]
+fn scripts_carry_lines_left_open_on_to_the_next() {
+    Playground::setup("script_continued_lines_test_1", |dirs, sandbox| {
+        sandbox.with_files(vec![FileWithContent(
+            "continued.nu",
+            "echo [jonathan\n yehuda] | nth 1\nopen does_not_exist.txt\n",
+        )]);
+
+        let output = Command::new(h::executable_path())
+            .arg(dirs.test().join("continued.nu"))
+            .current_dir(dirs.test())
+            .output()
+            .expect("couldn't run nu");
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "yehuda");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("line 3 of"));
+    })
+}